@@ -0,0 +1,166 @@
+use crate::Cache;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A cache that holds values in-process.
+///
+/// Once the cache holds `max_entries` values, expired values are removed and, if it is still full, the value that expires first is evicted to make room for a new one.
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+    max_entries: usize,
+}
+
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+impl MemoryCache {
+    /// Creates an empty cache that holds up to `max_entries` values.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries,
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Ok(Some(entry.value.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set_bytes(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> Result<(), anyhow::Error> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= self.max_entries {
+                let first_to_expire = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(first_to_expire) = first_to_expire {
+                    entries.remove(&first_to_expire);
+                }
+            }
+        }
+        if self.max_entries > 0 {
+            entries.insert(
+                String::from(key),
+                Entry {
+                    value,
+                    expires_at: now + ttl,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        self.entries.lock().unwrap().remove(key);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_set_remove() {
+        let cache: Arc<dyn Cache> = Arc::new(MemoryCache::new(10));
+
+        cache
+            .set("greeting", "hello", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_that!(
+            cache.get::<String>("greeting").await.unwrap(),
+            some(eq("hello"))
+        );
+
+        cache.remove("greeting").await.unwrap();
+        assert_that!(cache.get::<String>("greeting").await.unwrap(), none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expiry() {
+        let cache = MemoryCache::new(10);
+        cache
+            .set_bytes("key", vec![1], Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        tokio::time::advance(Duration::from_secs(59)).await;
+        assert_that!(cache.get_bytes("key").await.unwrap(), some(eq(&vec![1])));
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_that!(cache.get_bytes("key").await.unwrap(), none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_eviction() {
+        let cache = MemoryCache::new(2);
+        cache
+            .set_bytes("short", vec![1], Duration::from_secs(10))
+            .await
+            .unwrap();
+        cache
+            .set_bytes("long", vec![2], Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        cache
+            .set_bytes("new", vec![3], Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        assert_that!(cache.get_bytes("short").await.unwrap(), none());
+        assert_that!(cache.get_bytes("long").await.unwrap(), some(eq(&vec![2])));
+        assert_that!(cache.get_bytes("new").await.unwrap(), some(eq(&vec![3])));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_or_compute() {
+        let cache: Arc<dyn Cache> = Arc::new(MemoryCache::new(10));
+        let ttl = Duration::from_secs(60);
+
+        let value: std::result::Result<u32, anyhow::Error> = cache
+            .fetch_or_compute("answer", ttl, || async { Ok(42) })
+            .await;
+        assert_that!(value, ok(eq(&42)));
+
+        let value: std::result::Result<u32, anyhow::Error> = cache
+            .fetch_or_compute("answer", ttl, || async { panic!("the value is cached") })
+            .await;
+        assert_that!(value, ok(eq(&42)));
+
+        let value: std::result::Result<u32, &str> = cache
+            .fetch_or_compute("question", ttl, || async { Err("failed") })
+            .await;
+        assert_that!(value, err(eq("failed")));
+        assert_that!(cache.get_bytes("question").await.unwrap(), none());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::Cache;
+use anyhow::Context;
+use async_trait::async_trait;
+use redis::{aio::MultiplexedConnection, AsyncCommands};
+use std::time::Duration;
+
+/// The prefix of the keys of cached values so that the cache can share a Redis server with other data, e.g. rate limits
+const KEY_PREFIX: &str = "cache:";
+
+/// A cache that holds values on a Redis server that is shared by all nodes of the application.
+pub struct RedisCache {
+    connection: MultiplexedConnection,
+}
+
+impl RedisCache {
+    /// Connects to the Redis server at `url`, e.g. "redis://localhost:6379".
+    pub async fn connect(url: &str) -> Result<Self, anyhow::Error> {
+        let client = redis::Client::open(url).context("Invalid Redis URL!")?;
+        let connection = client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis!")?;
+
+        Ok(Self { connection })
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.connection
+            .clone()
+            .get(format!("{}{}", KEY_PREFIX, key))
+            .await
+            .context(format!("Failed to read cached value {}!", key))
+    }
+
+    async fn set_bytes(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> Result<(), anyhow::Error> {
+        // Redis rejects expiry times of 0
+        let ttl = ttl.as_millis().max(1) as u64;
+        self.connection
+            .clone()
+            .pset_ex(format!("{}{}", KEY_PREFIX, key), value, ttl)
+            .await
+            .context(format!("Failed to cache value {}!", key))
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), anyhow::Error> {
+        self.connection
+            .clone()
+            .del(format!("{}{}", KEY_PREFIX, key))
+            .await
+            .context(format!("Failed to remove cached value {}!", key))
+    }
+}
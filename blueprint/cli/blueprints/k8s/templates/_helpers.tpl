@@ -0,0 +1,46 @@
+{{/* The name of the release's resources – the release name, suffixed with the chart name unless it contains it already */}}
+{{- define "app.fullname" -}}
+{{- if contains .Chart.Name .Release.Name }}
+{{- .Release.Name | trunc 63 | trimSuffix "-" }}
+{{- else }}
+{{- printf "%s-%s" .Release.Name .Chart.Name | trunc 63 | trimSuffix "-" }}
+{{- end }}
+{{- end }}
+
+{{/* The labels of all resources */}}
+{{- define "app.labels" -}}
+helm.sh/chart: {{ printf "%s-%s" .Chart.Name .Chart.Version }}
+{{ include "app.selectorLabels" . }}
+app.kubernetes.io/version: {{ .Chart.AppVersion | quote }}
+app.kubernetes.io/managed-by: {{ .Release.Service }}
+{{- end }}
+
+{{/* The labels that select the release's pods – combined with app.kubernetes.io/component to select a process's pods */}}
+{{- define "app.selectorLabels" -}}
+app.kubernetes.io/name: {{ .Chart.Name }}
+app.kubernetes.io/instance: {{ .Release.Name }}
+{{- end }}
+
+{{/* The image all processes run */}}
+{{- define "app.image" -}}
+{{ .Values.image.repository }}:{{ .Values.image.tag | default .Chart.AppVersion }}
+{{- end }}
+
+{{/* The name of the Secret with the secret settings */}}
+{{- define "app.secretName" -}}
+{{ .Values.existingSecret | default (include "app.fullname" .) }}
+{{- end }}
+
+{{/* Passes the settings from the ConfigMap and the Secret to a container as env vars */}}
+{{- define "app.envFrom" -}}
+- configMapRef:
+    name: {{ include "app.fullname" . }}
+- secretRef:
+    name: {{ include "app.secretName" . }}
+{{- end }}
+
+{{/* Restarts a process's pods when the settings change */}}
+{{- define "app.checksums" -}}
+checksum/config: {{ include (print .Template.BasePath "/configmap.yaml") . | sha256sum }}
+checksum/secret: {{ include (print .Template.BasePath "/secret.yaml") . | sha256sum }}
+{{- end }}
@@ -0,0 +1,197 @@
+#![allow(missing_docs)]
+{% unless template_type == "minimal" -%}
+use anyhow::{anyhow, Context};
+{% endunless -%}
+use clap::{Parser, Subcommand};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{
+    environments, load_config_for, load_env_files, masked_config, parse_env, Config, Environment,
+};
+{% unless template_type == "minimal" -%}
+use {{crate_name}}_config::{DatabaseBackend, DatabaseConfig};
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+{% endunless -%}
+use std::path::PathBuf;
+{% unless template_type == "minimal" -%}
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+{% endunless %}
+#[tokio::main]
+async fn main() {
+    cli().await;
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to inspect the project's configuration.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Print the merged configuration with secret values masked")]
+    Show {
+        #[arg(short, long, help = "Choose the environment (development, test, production, or a custom one configured in config/environments).", value_parser = parse_env, default_value = "development")]
+        env: Environment,
+
+        #[arg(
+            long,
+            help = "Load env vars from this file instead of the environment's default .env file."
+        )]
+        env_file: Option<PathBuf>,
+    },
+    #[command(about = "Load and validate the configuration for every environment")]
+    Check {
+        #[arg(short, long = "env", help = "Only check this environment (can be passed several times).", value_parser = parse_env)]
+        envs: Vec<Environment>,
+        {%- unless template_type == "minimal" %}
+        #[arg(
+            long,
+            help = "Check that the configured PostgreSQL databases are reachable."
+        )]
+        connect: bool,
+        {%- endunless %}
+    },
+}
+
+#[allow(missing_docs)]
+async fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    match cli.command {
+        Commands::Show { env, env_file } => {
+            let config = load_env_files(&env, env_file.as_deref()).and_then(|env_files| {
+                if env_files.is_empty() {
+                    ui.debug("Not loading any env file.");
+                }
+                for env_file in env_files {
+                    ui.debug(&format!("Loaded env file {}.", env_file.display()));
+                }
+                masked_config(&env)
+            });
+            match config {
+                Ok(config) => ui.log(config.trim_end()),
+                Err(e) => {
+                    ui.error("Could not read configuration!", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        {% if template_type == "minimal" -%}
+        Commands::Check { envs } => {
+        {%- else -%}
+        Commands::Check { envs, connect } => {
+        {%- endif %}
+            let envs = if envs.is_empty() {
+                environments()
+            } else {
+                envs
+            };
+            let mut invalid = 0;
+            for env in &envs {
+                ui.info(&format!("Checking {} configuration…", env));
+                ui.indent();
+                {% if template_type == "minimal" -%}
+                let problems = check(env).await;
+                {%- else -%}
+                let problems = check(env, connect).await;
+                {%- endif %}
+                for problem in &problems {
+                    ui.log(problem);
+                }
+                ui.outdent();
+                if problems.is_empty() {
+                    ui.success(&format!("The {} configuration is valid.", env));
+                } else {
+                    invalid += 1;
+                }
+            }
+            if invalid > 0 {
+                ui.error(
+                    "Invalid configuration!",
+                    anyhow::anyhow!("{} of {} environments have problems.", invalid, envs.len()),
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Loads the configuration for an environment and checks it, returning a description of every problem that was found.
+///
+/// The configuration is loaded with the environment's `.env` file (see [`load_config_for`]) which checks that all required settings are present and all `${NAME}` placeholders can be resolved. On top of that, the server port{% unless template_type == "minimal" %} and the database URLs are checked – if `connect` is `true`, it is also checked that the PostgreSQL databases are reachable{% endunless %}.
+{% if template_type == "minimal" -%}
+async fn check(env: &Environment) -> Vec<String> {
+{%- else -%}
+async fn check(env: &Environment, connect: bool) -> Vec<String> {
+{%- endif %}
+    let config: Config = match load_config_for(env) {
+        Ok(config) => config,
+        Err(e) => return vec![format!("{:#}", e)],
+    };
+
+    let mut problems = vec![];
+    if config.server.port == 0 {
+        problems.push(String::from("server.port: must be between 1 and 65535"));
+    }
+    {%- unless template_type == "minimal" %}
+
+    let mut databases = vec![(String::from("database"), &config.database)];
+    let mut named_databases: Vec<_> = config
+        .databases
+        .iter()
+        .map(|(name, database)| (format!("databases.{}", name), database))
+        .collect();
+    named_databases.sort_by(|(a, _), (b, _)| a.cmp(b));
+    databases.append(&mut named_databases);
+
+    for (name, database) in databases {
+        if let Err(e) = Url::parse(&database.url) {
+            problems.push(format!("{}.url: not a valid URL ({})", name, e));
+            continue;
+        }
+        match database.backend() {
+            Ok(DatabaseBackend::Postgres) if connect => {
+                if let Err(e) = check_connection(database).await {
+                    problems.push(format!("{}: not reachable ({:#})", name, e));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(format!("{}.url: {:#}", name, e)),
+        }
+    }
+    {%- endunless %}
+
+    problems
+}
+{%- unless template_type == "minimal" %}
+
+/// Connects to a PostgreSQL database, using the configured connect timeout or 5 seconds.
+async fn check_connection(config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let options = PgConnectOptions::from_str(&config.url).context("Invalid database URL!")?;
+    let secs = config.timeouts.connect.unwrap_or(5);
+    let connection = tokio::time::timeout(Duration::from_secs(secs), options.connect())
+        .await
+        .map_err(|_| anyhow!("Timed out connecting to database after {}s!", secs))?
+        .context("Failed to connect to database!")?;
+    connection
+        .close()
+        .await
+        .context("Failed to close connection!")?;
+
+    Ok(())
+}
+{%- endunless %}
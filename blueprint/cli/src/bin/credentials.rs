@@ -0,0 +1,186 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{CredentialsFile, MASTER_KEY_ENV_VAR};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The contents of a newly created credentials file
+const CREDENTIALS_TEMPLATE: &str = r#"# Secrets in this file can be referenced from the configuration files via
+# placeholders, e.g. "${DB_PASSWORD}" or "${stripe.api_key}".
+#
+# DB_PASSWORD = "…"
+#
+# [stripe]
+# api_key = "…"
+"#;
+
+fn main() {
+    cli();
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to manage the project's encrypted credentials.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(
+        long,
+        global = true,
+        default_value = "config/credentials.enc",
+        help = "The credentials file, relative to the project root."
+    )]
+    file: PathBuf,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(
+        about = "Edit the credentials in $EDITOR, creating the file and the master key if necessary"
+    )]
+    Edit,
+    #[command(about = "Print the decrypted credentials")]
+    Show,
+}
+
+#[allow(missing_docs)]
+fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    let path = match project_root() {
+        Ok(root) => root.join(&cli.file),
+        Err(e) => {
+            ui.error("Could not find the project root!", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command {
+        Commands::Edit => match edit(&mut ui, &path) {
+            Ok(true) => ui.success(&format!("Saved {}.", path.display())),
+            Ok(false) => ui.info("Credentials unchanged."),
+            Err(e) => {
+                ui.error("Could not edit credentials!", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Show => match show(&path) {
+            Ok(credentials) => ui.log(credentials.trim_end()),
+            Err(e) => {
+                ui.error("Could not show credentials!", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Decrypts the credentials file into a temporary file, opens it in the editor, and encrypts the result back into the credentials file. Returns whether the credentials were changed.
+fn edit(ui: &mut UI, path: &Path) -> Result<bool, anyhow::Error> {
+    let key_path = CredentialsFile::key_path(path);
+    if std::env::var(MASTER_KEY_ENV_VAR).is_err() && !key_path.exists() {
+        create_private_file(&key_path, &CredentialsFile::generate_key())?;
+        ui.success(&format!(
+            "Created master key {} – do not check it into version control!",
+            key_path.display()
+        ));
+    }
+    let key = CredentialsFile::read_key(path)?;
+
+    let original = if path.exists() {
+        show(path)?
+    } else {
+        String::from(CREDENTIALS_TEMPLATE)
+    };
+
+    let temp_path = std::env::temp_dir().join(format!("credentials-{}.toml", std::process::id()));
+    create_private_file(&temp_path, &original)?;
+    let edited = run_editor(&temp_path).and_then(|_| {
+        fs::read_to_string(&temp_path)
+            .context(format!(r#"Failed to read "{}"!"#, temp_path.display()))
+    });
+    let _ = fs::remove_file(&temp_path);
+    let edited = edited?;
+
+    if path.exists() && edited == original {
+        return Ok(false);
+    }
+    CredentialsFile::parse(&edited)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(format!(r#"Failed to create "{}"!"#, dir.display()))?;
+    }
+    let encrypted = CredentialsFile::encrypt(&key, &edited)?;
+    fs::write(path, format!("{}\n", encrypted))
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(true)
+}
+
+fn show(path: &Path) -> Result<String, anyhow::Error> {
+    let key = CredentialsFile::read_key(path)?;
+    let encrypted =
+        fs::read_to_string(path).context(format!(r#"Failed to read "{}"!"#, path.display()))?;
+
+    CredentialsFile::decrypt(&key, &encrypted)
+}
+
+/// Opens the file in `$VISUAL` or `$EDITOR`, falling back to `vi`, and waits for the editor to exit.
+fn run_editor(path: &Path) -> Result<(), anyhow::Error> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    // the editor may be configured with arguments, e.g. "code --wait"
+    let mut args = editor.split_whitespace();
+    let program = args.next().ok_or(anyhow!("No editor configured!"))?;
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .context(format!(r#"Failed to run "{}"!"#, editor))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}.", editor, status));
+    }
+
+    Ok(())
+}
+
+/// Creates a file that is only readable by the current user.
+fn create_private_file(path: &Path, contents: &str) -> Result<(), anyhow::Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options
+        .open(path)
+        .context(format!(r#"Failed to create "{}"!"#, path.display()))?;
+    file.write_all(contents.as_bytes())
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(())
+}
+
+/// Find the root of the gerust workspace.
+fn project_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
+    )
+    .join("..")
+    .canonicalize()?)
+}
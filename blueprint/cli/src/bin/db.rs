@@ -1,20 +1,36 @@
+#![allow(missing_docs)]
 use anyhow::{anyhow, Context};
-use clap::{Parser, Subcommand};
-use {{crate_name}}_cli::util::ui::UI;
-use {{crate_name}}_config::DatabaseConfig;
-use {{crate_name}}_config::{load_config, parse_env, Config, Environment};
+use clap::{Parser, Subcommand, ValueEnum};
+use {{crate_name}}_cli::util::{
+    diff::unified_diff,
+    schema::{baseline_migration, introspect, Table},
+    sql::{
+        format_table, quote_identifier, quote_literal, read_with_includes, split_statements,
+        StatementReader,
+    },
+    ui::UI,
+};
+use {{crate_name}}_config::{DatabaseBackend, DatabaseConfig, TenancyConfig};
+use {{crate_name}}_config::{load_env_files, parse_env, read_config, Config, Environment};
+use futures_util::TryStreamExt;
 use guppy::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::postgres::{PgConnectOptions, PgConnection};
+use sqlx::types::chrono::{DateTime, Utc};
 use sqlx::{
-    migrate::{Migrate, Migrator},
-    ConnectOptions, Connection, Executor,
+    migrate::{Migrate, MigrateError, Migration, Migrator},
+    Acquire, Column, ConnectOptions, Connection, Either, Executor, Postgres, Row,
 };
 use tokio::io::{stdin, AsyncBufReadExt};
 
 use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::time::{Duration, Instant};
 use url::Url;
 
 #[tokio::main]
@@ -29,9 +45,58 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    #[arg(short, long, global = true, help = "Choose the environment (development, test, production).", value_parser = parse_env, default_value = "development")]
+    #[arg(short, long, global = true, help = "Choose the environment (development, test, production, or a custom one configured in config/environments).", value_parser = parse_env, default_value = "development")]
     env: Environment,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Load env vars from this file instead of the environment's default .env file."
+    )]
+    env_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Run against the database with this name as configured in a [databases.<name>] section instead of the primary database."
+    )]
+    database: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Run against N copies of the test database for parallel test workers (e.g. my_app_test_1 to my_app_test_N); supported by create, drop, migrate, reset, truncate, and schema load."
+    )]
+    test_workers: Option<u32>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME=SECS",
+        value_parser = parse_timeout,
+        help = "Override a configured timeout, e.g. statement=30 (connect, statement, lock, migration, migration_lock, install, notify)."
+    )]
+    timeout: Vec<(String, u64)>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Skip the confirmation of destructive commands run against the production (or another deployed) database (e.g. in CI)."
+    )]
+    force: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = Format::Text,
+        help = "The output format; json prints every message and result as a JSON object on a line of its own."
+    )]
+    format: Format,
+
     #[arg(long, global = true, help = "Disable colored output.")]
     no_color: bool,
 
@@ -39,37 +104,376 @@ struct Cli {
     quiet: bool,
 }
 
+/// The output format of the CLI (see [`UI::json`]).
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Drop the database")]
     Drop,
     #[command(about = "Create the database")]
     Create,
+    #[command(about = "Create a database user with all privileges on the database")]
+    CreateUser {
+        #[arg(help = "The name of the user (role) to create.")]
+        name: String,
+        #[arg(long, help = "The user's password.")]
+        password: String,
+        #[arg(
+            long,
+            help = "Print the statements that would be run and check for an existing role instead of creating it."
+        )]
+        dry_run: bool,
+    },
+    #[command(about = "Create a tenant's schema and apply all migrations to it")]
+    CreateTenant {
+        #[arg(help = "The name of the tenant, e.g. acme for the schema tenant_acme.")]
+        name: String,
+    },
     #[command(about = "Migrate the database")]
-    Migrate,
+    Migrate {
+        #[arg(
+            long,
+            value_name = "SECS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Cancel any migration that runs for longer than this many seconds."
+        )]
+        max_duration: Option<u64>,
+        #[arg(
+            long,
+            conflicts_with = "max_duration",
+            help = "Apply all migrations to a throwaway database and print the resulting schema instead of migrating."
+        )]
+        print_ddl_plan: bool,
+        #[arg(
+            long,
+            value_name = "URL",
+            conflicts_with = "print_ddl_plan",
+            help = "Send the migration results as JSON to this webhook URL when done."
+        )]
+        notify: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "print_ddl_plan",
+            help = "If no migrations are recorded but the schema matches the one all migrations produce, mark them as applied instead of running them."
+        )]
+        baseline_on_empty: bool,
+        #[arg(
+            long,
+            conflicts_with = "print_ddl_plan",
+            help = "Refuse to migrate if there are uncommitted changes to migration files (enabled via config for production by default)."
+        )]
+        require_clean_git: bool,
+        #[arg(
+            long,
+            conflicts_with = "require_clean_git",
+            help = "Skip checking for uncommitted changes to migration files even if enabled via config."
+        )]
+        allow_dirty: bool,
+        #[arg(
+            long,
+            alias = "out-of-band-lock-key",
+            value_name = "KEY",
+            allow_negative_numbers = true,
+            conflicts_with = "print_ddl_plan",
+            help = "The key of the advisory lock that serializes migration runs (defaults to the configured key or one derived from the database name)."
+        )]
+        lock_key: Option<i64>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "print_ddl_plan",
+            help = "Write the highest applied migration version and its checksum to this file after migrating successfully."
+        )]
+        report_schema_version_to: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "print_ddl_plan",
+            help = "Append a dated section listing the applied migrations to this changelog file (if any were applied)."
+        )]
+        changelog: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "print_ddl_plan",
+            help = "Dump the schema to the schema file (see `db schema dump`) after migrating successfully."
+        )]
+        dump_schema: bool,
+        #[arg(
+            long,
+            conflicts_with = "print_ddl_plan",
+            help = "Only warn instead of failing if applied migrations have changed since they were applied."
+        )]
+        skip_checksum: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["print_ddl_plan", "max_duration", "notify", "baseline_on_empty", "report_schema_version_to", "changelog", "dump_schema"],
+            help = "List the migrations that would be applied and validate them by applying them in a transaction that is rolled back."
+        )]
+        dry_run: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["print_ddl_plan", "dry_run"],
+            help = "After migrating the database, apply the migrations to every tenant's schema as well (see the [tenancy] section of the configuration)."
+        )]
+        all_tenants: bool,
+    },
+    #[command(about = "Run the pending data migrations in db/src/data_migrations")]
+    MigrateData {
+        #[arg(
+            long,
+            default_value_t = 1000,
+            value_parser = clap::value_parser!(i64).range(1..),
+            help = "The number of records to migrate per batch (and transaction)."
+        )]
+        batch_size: i64,
+        #[arg(
+            long,
+            value_name = "VERSION",
+            help = "Only run the data migration with this version."
+        )]
+        only: Option<i64>,
+    },
+    #[command(about = "Generate a baseline migration from the existing database's schema")]
+    BaselineMigration {
+        #[arg(default_value = "baseline", help = "The name of the migration.")]
+        name: String,
+        #[arg(
+            long,
+            help = "Only generate the migration without marking it as applied."
+        )]
+        no_stamp: bool,
+    },
+    #[command(about = "Create a new migration in the migrations directory")]
+    NewMigration {
+        #[arg(help = "The name of the migration.")]
+        name: String,
+        #[arg(
+            long,
+            help = "Create a pair of up and down migrations so that the migration can be rolled back."
+        )]
+        reversible: bool,
+    },
+    #[command(about = "Show which migrations are applied and which are pending")]
+    Status,
     #[command(about = "Reset (drop, create, migrate) the database")]
     Reset,
+    #[command(about = "Delete all data by truncating all tables")]
+    Truncate {
+        #[arg(
+            long,
+            value_name = "TABLE",
+            help = "Don't truncate this table (can be passed multiple times)."
+        )]
+        except: Vec<String>,
+        #[arg(
+            long,
+            help = "Truncate the migrations tables as well so that all migrations are considered pending."
+        )]
+        include_migrations: bool,
+    },
+    #[command(about = "Roll back the most recently applied migrations")]
+    Rollback {
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "The number of migrations to roll back."
+        )]
+        steps: u64,
+    },
+    #[command(about = "Roll back all migrations newer than a version")]
+    ResetTo {
+        #[arg(
+            id = "target_version",
+            value_name = "VERSION",
+            help = "The version of the migration to roll the database back to."
+        )]
+        version: i64,
+    },
     #[command(about = "Seed the database")]
-    Seed,
+    Seed {
+        #[arg(
+            long,
+            help = "Execute the seeds and report their effects but roll back instead of committing."
+        )]
+        dry_run: bool,
+        #[arg(
+            long,
+            help = "Execute the seeds from this file instead of the configured seeds file."
+        )]
+        file: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "file",
+            help = "Run the Rust seeds in db/src/seeds.rs instead of SQL seeds."
+        )]
+        rust: bool,
+    },
     #[command(about = "Generate query metadata to support offline compile-time verification")]
-    Prepare,
+    Prepare {
+        #[arg(
+            long,
+            help = "Verify that the query metadata in db/.sqlx is up to date instead of writing it."
+        )]
+        locked: bool,
+    },
+    #[command(about = "Check that query metadata for offline compile-time verification exists")]
+    CheckOffline,
+    #[command(about = "Dump the database to a SQL file")]
+    Dump {
+        #[arg(
+            long,
+            help = "The file to write the dump to (defaults to db/dumps/<env>-<timestamp>.sql)."
+        )]
+        output: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Only dump the data (except for the migrations tables), not the schema."
+        )]
+        data_only: bool,
+    },
+    #[command(about = "Open a SQL console (psql) connected to the database")]
+    Console {
+        #[arg(
+            long,
+            help = "Use the embedded SQL console even if psql is installed."
+        )]
+        embedded: bool,
+    },
+    #[command(about = "Dump or load the database schema")]
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
+    #[command(about = "Restore the database from a SQL file")]
+    Restore {
+        #[arg(help = "The SQL file to restore the database from.")]
+        file: PathBuf,
+        #[arg(
+            long,
+            help = "Continue an interrupted restore after the last committed batch."
+        )]
+        resume: bool,
+        #[arg(
+            long,
+            default_value_t = 1000,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "The number of statements to commit per transaction."
+        )]
+        batch_size: u64,
+        #[arg(
+            long,
+            conflicts_with = "resume",
+            help = "Restore in a single transaction so that either all or no statements are applied."
+        )]
+        single_transaction: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommands {
+    #[command(about = "Dump the schema (and applied migrations) to db/schema.sql")]
+    Dump {
+        #[arg(
+            long,
+            help = "The file to write the schema to instead of the schema file next to the migrations directory."
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "Load the schema from db/schema.sql into an empty database")]
+    Load {
+        #[arg(
+            long,
+            help = "The file to load the schema from instead of the schema file next to the migrations directory."
+        )]
+        file: Option<PathBuf>,
+    },
 }
 
-#[allow(missing_docs)]
 async fn cli() {
     let cli = Cli::parse();
 
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
     let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+    if cli.format == Format::Json {
+        ui = ui.json();
+    }
 
-    let config: Result<Config, anyhow::Error> = load_config(&cli.env);
+    let config: Result<Config, anyhow::Error> = load_env_files(&cli.env, cli.env_file.as_deref())
+        .and_then(|env_files| {
+            if env_files.is_empty() {
+                ui.debug("Not loading any env file.");
+            }
+            for env_file in env_files {
+                ui.debug(&format!("Loaded env file {}.", env_file.display()));
+            }
+            let mut config: Config = read_config(&cli.env)?;
+            if cli.database.is_some() {
+                config.database = config.database_named(cli.database.as_deref())?.clone();
+            }
+            for (name, secs) in &cli.timeout {
+                config.database.timeouts.set(name, *secs)?;
+            }
+            Ok(config)
+        });
     match config {
         Ok(config) => match cli.command {
+            Commands::NewMigration { name, reversible } => {
+                ui.info("Creating migration…");
+                ui.indent();
+                match new_migration(&config.database, &name, reversible) {
+                    Ok(paths) => {
+                        for path in &paths {
+                            ui.log(&format!("Created {}.", path.display()));
+                        }
+                        ui.outdent();
+                        ui.success("Created migration successfully.");
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not create migration!", e);
+                    }
+                }
+            }
+            command if cli.test_workers.is_some() => {
+                let workers = cli.test_workers.unwrap_or_default();
+                test_workers_cli(&mut ui, &cli.env, workers, command, &config.database).await
+            }
+            command
+                if matches!(
+                    config.database.backend(),
+                    Ok(DatabaseBackend::MySql | DatabaseBackend::Sqlite)
+                ) =>
+            {
+                backend_cli(&mut ui, &cli.env, cli.force, command, &config.database).await
+            }
             Commands::Drop => {
+                if cli.env.is_deployed() && !cli.force {
+                    let db_name = get_db_config(&config.database)
+                        .get_database()
+                        .map(String::from)
+                        .context("Failed to get database name!");
+                    let confirmation = match db_name {
+                        Ok(db_name) => {
+                            confirm_database_name(&mut ui, &cli.env, "drop", &db_name).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = confirmation {
+                        ui.error("Could not drop database!", e);
+                        return;
+                    }
+                }
                 ui.info(&format!("Dropping {} database…", &cli.env));
                 match drop(&config.database).await {
                     Ok(db_name) => {
+                        event(&mut ui, "database_dropped", &json!({ "database": db_name }));
                         ui.success(&format!("Dropped database {} successfully.", &db_name))
                     }
                     Err(e) => ui.error("Could not drop database!", e),
@@ -79,33 +483,308 @@ async fn cli() {
                 ui.info(&format!("Creating {} database…", &cli.env));
                 match create(&config.database).await {
                     Ok(db_name) => {
+                        event(&mut ui, "database_created", &json!({ "database": db_name }));
                         ui.success(&format!("Created database {} successfully.", &db_name))
                     }
                     Err(e) => ui.error("Could not create database!", e),
                 }
             }
-            Commands::Migrate => {
+            Commands::Migrate {
+                print_ddl_plan: true,
+                ..
+            } => {
+                ui.info(&format!("Planning schema of {} database…", &cli.env));
+                ui.indent();
+                match plan_schema(&mut ui, &config.database).await {
+                    Ok(tables) => {
+                        ui.outdent();
+                        for table in &tables {
+                            for line in table.to_ddl().lines() {
+                                ui.log(line);
+                            }
+                        }
+                        ui.success(&format!(
+                            "Schema after applying all migrations ({} tables).",
+                            tables.len()
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not plan schema!", e);
+                    }
+                }
+            }
+            Commands::Migrate {
+                dry_run: true,
+                require_clean_git,
+                allow_dirty,
+                lock_key,
+                skip_checksum,
+                ..
+            } => {
+                ui.info(&format!("Migrating {} database (dry run)…", &cli.env));
+                ui.indent();
+                if !allow_dirty && (require_clean_git || config.database.require_clean_git) {
+                    if let Err(e) = check_clean_git(&mut ui, &config.database).await {
+                        ui.outdent();
+                        ui.error("Refusing to migrate with uncommitted changes to migration files!", e);
+                        return;
+                    }
+                }
+                let mut pending = vec![];
+                let result = migrate_dry_run(
+                    &mut ui,
+                    &config.database,
+                    lock_key,
+                    skip_checksum,
+                    &mut pending,
+                )
+                .await;
+                event(&mut ui, "migrations_pending", &pending);
+                ui.outdent();
+                match result {
+                    Ok(_) => ui.success(&format!(
+                        "{} migrations would be applied; all changes were rolled back.",
+                        pending.len()
+                    )),
+                    Err(e) => ui.error("Migrations would fail!", e),
+                }
+            }
+            Commands::Migrate {
+                max_duration,
+                notify,
+                baseline_on_empty,
+                require_clean_git,
+                allow_dirty,
+                lock_key,
+                report_schema_version_to,
+                changelog,
+                dump_schema,
+                skip_checksum,
+                all_tenants,
+                ..
+            } => {
                 ui.info(&format!("Migrating {} database…", &cli.env));
                 ui.indent();
-                match migrate(&mut ui, &config.database).await {
-                    Ok(migrations) => {
+                if !allow_dirty && (require_clean_git || config.database.require_clean_git) {
+                    if let Err(e) = check_clean_git(&mut ui, &config.database).await {
+                        ui.outdent();
+                        ui.error("Refusing to migrate with uncommitted changes to migration files!", e);
+                        return;
+                    }
+                }
+                let max_duration = max_duration
+                    .or(config.database.timeouts.migration)
+                    .map(Duration::from_secs);
+                let started = Instant::now();
+                let mut applied = vec![];
+                let result = migrate(
+                    &mut ui,
+                    &config.database,
+                    max_duration,
+                    baseline_on_empty,
+                    lock_key,
+                    skip_checksum,
+                    &mut applied,
+                )
+                .await;
+                let notification = MigrationNotification {
+                    text: match &result {
+                        Ok(_) => format!(
+                            "Migrated {} database: {} migrations applied.",
+                            &cli.env,
+                            applied.len()
+                        ),
+                        Err(e) => format!("Could not migrate {} database: {:#}", &cli.env, e),
+                    },
+                    environment: cli.env.to_string(),
+                    success: result.is_ok(),
+                    error: result.as_ref().err().map(|e| format!("{:#}", e)),
+                    duration_ms: started.elapsed().as_millis(),
+                    migrations: &applied,
+                };
+                event(&mut ui, "migrations_applied", &applied);
+                match result {
+                    Ok(_) => {
                         ui.outdent();
-                        ui.success(&format!("{} migrations applied.", migrations));
+                        ui.success(&format!("{} migrations applied.", applied.len()));
+                        if all_tenants {
+                            ui.info("Migrating tenant schemas…");
+                            ui.indent();
+                            let mut tenant_applied = vec![];
+                            match migrate_tenants(
+                                &mut ui,
+                                &config.database,
+                                &config.tenancy,
+                                None,
+                                max_duration,
+                                lock_key,
+                                skip_checksum,
+                                &mut tenant_applied,
+                            )
+                            .await
+                            {
+                                Ok(schemas) => {
+                                    ui.outdent();
+                                    ui.success(&format!(
+                                        "{} migrations applied to {} tenant schemas.",
+                                        tenant_applied.len(),
+                                        schemas
+                                    ));
+                                }
+                                Err(e) => {
+                                    ui.outdent();
+                                    ui.error("Could not migrate tenant schemas!", e);
+                                }
+                            }
+                        }
+                        if let Some(path) = &report_schema_version_to {
+                            match report_schema_version(&config.database, path).await {
+                                Ok(Some(version)) => ui.log(&format!("Wrote schema version {} to {}.", version, path.display())),
+                                Ok(None) => ui.log(&format!("No migrations applied; wrote empty schema version to {}.", path.display())),
+                                Err(e) => ui.error("Could not report schema version!", e),
+                            }
+                        }
+                        if let Some(path) = changelog.as_ref().filter(|_| !applied.is_empty()) {
+                            match append_changelog(path, &cli.env, &applied) {
+                                Ok(_) => ui.log(&format!("Added applied migrations to {}.", path.display())),
+                                Err(e) => ui.error("Could not write changelog!", e),
+                            }
+                        }
+                        if dump_schema {
+                            match schema_dump(&config.database, None).await {
+                                Ok(path) => ui.log(&format!("Dumped schema to {}.", path.display())),
+                                Err(e) => ui.error("Could not dump schema!", e),
+                            }
+                        }
                     }
                     Err(e) => {
                         ui.outdent();
                         ui.error("Could not migrate database!", e);
                     }
                 }
+                if let Some(url) = notify {
+                    let timeout = config.database.timeouts.notify.map(Duration::from_secs);
+                    if let Err(e) = send_notification(&url, &notification, timeout).await {
+                        ui.warning(&format!(
+                            "Could not send migration notification to {}: {:#}",
+                            url, e
+                        ));
+                    }
+                }
+            }
+            Commands::Seed {
+                dry_run,
+                file,
+                rust,
+            } => {
+                if dry_run {
+                    ui.info(&format!("Seeding {} database (dry run)…", &cli.env));
+                } else {
+                    ui.info(&format!("Seeding {} database…", &cli.env));
+                }
+                ui.indent();
+                let result = if rust {
+                    seed_rust(&mut ui, &config.database, dry_run).await
+                } else {
+                    seed(&mut ui, &config.database, &cli.env, file.as_deref(), dry_run).await
+                };
+                match result {
+                    Ok(_) if dry_run => {
+                        ui.outdent();
+                        ui.success("Seeds are valid; all changes were rolled back.");
+                    }
+                    Ok(_) => {
+                        ui.outdent();
+                        ui.success("Seeded database successfully.");
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not seed database!", e);
+                    }
+                }
+            }
+            Commands::MigrateData { batch_size, only } => {
+                ui.info(&format!("Migrating data in {} database…", &cli.env));
+                ui.indent();
+                match migrate_data(&mut ui, &config.database, batch_size, only).await {
+                    Ok(0) => {
+                        ui.outdent();
+                        ui.success("No pending data migrations.");
+                    }
+                    Ok(completed) => {
+                        ui.outdent();
+                        ui.success(&format!("{} data migrations completed.", completed));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not migrate data!", e);
+                    }
+                }
+            }
+            Commands::CreateTenant { name } => {
+                ui.info(&format!("Creating tenant {} in {} database…", name, &cli.env));
+                ui.indent();
+                match create_tenant(&mut ui, &config.database, &config.tenancy, &name).await {
+                    Ok(schema) => {
+                        ui.outdent();
+                        ui.success(&format!(
+                            "Created tenant {} with schema {} successfully.",
+                            name, schema
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not create tenant!", e);
+                    }
+                }
             }
-            Commands::Seed => {
-                ui.info(&format!("Seeding {} database…", &cli.env));
-                match seed(&config.database).await {
-                    Ok(_) => ui.success("Seeded database successfully."),
-                    Err(e) => ui.error("Could not seed database!", e),
+            Commands::CreateUser {
+                name,
+                password,
+                dry_run,
+            } => {
+                if dry_run {
+                    ui.info(&format!(
+                        "Creating user {} for {} database (dry run)…",
+                        name, &cli.env
+                    ));
+                } else {
+                    ui.info(&format!("Creating user {} for {} database…", name, &cli.env));
+                }
+                ui.indent();
+                match create_user(&mut ui, &config.database, &name, &password, dry_run).await {
+                    Ok(_) if dry_run => {
+                        ui.outdent();
+                        ui.success("Dry run complete; nothing was changed.");
+                    }
+                    Ok(_) => {
+                        ui.outdent();
+                        ui.success(&format!("Created user {} successfully.", name));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not create user!", e);
+                    }
                 }
             }
             Commands::Reset => {
+                if cli.env.is_deployed() && !cli.force {
+                    let db_name = get_db_config(&config.database)
+                        .get_database()
+                        .map(String::from)
+                        .context("Failed to get database name!");
+                    let confirmation = match db_name {
+                        Ok(db_name) => {
+                            confirm_database_name(&mut ui, &cli.env, "reset", &db_name).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = confirmation {
+                        ui.error("Could not reset database!", e);
+                        return;
+                    }
+                }
                 ui.info(&format!("Resetting {} database…", &cli.env));
                 ui.indent();
                 match reset(&mut ui, &config.database).await {
@@ -119,8 +798,210 @@ async fn cli() {
                     }
                 }
             }
-            Commands::Prepare => {
-                if let Err(e) = ensure_sqlx_cli_installed(&mut ui).await {
+            Commands::Truncate {
+                except,
+                include_migrations,
+            } => {
+                if cli.env.is_deployed() && !cli.force {
+                    let db_name = get_db_config(&config.database)
+                        .get_database()
+                        .map(String::from)
+                        .context("Failed to get database name!");
+                    let confirmation = match db_name {
+                        Ok(db_name) => {
+                            confirm_database_name(&mut ui, &cli.env, "truncate", &db_name).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = confirmation {
+                        ui.error("Could not truncate database!", e);
+                        return;
+                    }
+                }
+                ui.info(&format!("Truncating {} database…", &cli.env));
+                match truncate(&config.database, &except, include_migrations).await {
+                    Ok(tables) => {
+                        event(&mut ui, "tables_truncated", &tables);
+                        ui.success(&format!("Truncated {} tables.", tables.len()))
+                    }
+                    Err(e) => ui.error("Could not truncate database!", e),
+                }
+            }
+            Commands::BaselineMigration { name, no_stamp } => {
+                ui.info(&format!(
+                    "Generating baseline migration from {} database…",
+                    &cli.env
+                ));
+                ui.indent();
+                match generate_baseline_migration(&mut ui, &config.database, &name, !no_stamp).await {
+                    Ok(path) => {
+                        ui.outdent();
+                        ui.success(&format!(
+                            "Generated baseline migration {}; please review it before checking it in.",
+                            path.display()
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not generate baseline migration!", e);
+                    }
+                }
+            }
+            Commands::Status => {
+                ui.info(&format!("Migration status of {} database…", &cli.env));
+                ui.indent();
+                match status(&mut ui, &config.database).await {
+                    Ok((applied, pending)) => {
+                        ui.outdent();
+                        ui.success(&format!(
+                            "{} migrations applied, {} pending.",
+                            applied, pending
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not determine migration status!", e);
+                    }
+                }
+            }
+            Commands::Rollback { steps } => {
+                if cli.env.is_deployed() && !cli.force {
+                    ui.info(&format!(
+                        "This will roll back the last {} migrations in the {} database. Are you sure? [y/N]",
+                        steps, cli.env
+                    ));
+                    match confirm(&mut ui, false).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            ui.error("Could not roll back database!", anyhow!("Rollback canceled."));
+                            return;
+                        }
+                        Err(e) => {
+                            ui.error("Could not roll back database!", e);
+                            return;
+                        }
+                    }
+                }
+                ui.info(&format!("Rolling back {} database…", &cli.env));
+                ui.indent();
+                match rollback(&mut ui, &config.database, steps as usize).await {
+                    Ok(migrations) => {
+                        ui.outdent();
+                        ui.success(&format!("{} migrations rolled back.", migrations));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not roll back database!", e);
+                    }
+                }
+            }
+            Commands::ResetTo { version } => {
+                if cli.env.is_deployed() && !cli.force {
+                    ui.info(&format!(
+                        "This will roll back all migrations newer than {} in the {} database. Are you sure? [y/N]",
+                        version, cli.env
+                    ));
+                    match confirm(&mut ui, false).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            ui.error("Could not reset database!", anyhow!("Reset canceled."));
+                            return;
+                        }
+                        Err(e) => {
+                            ui.error("Could not reset database!", e);
+                            return;
+                        }
+                    }
+                }
+                ui.info(&format!(
+                    "Resetting {} database to version {}…",
+                    &cli.env, version
+                ));
+                ui.indent();
+                match reset_to(&mut ui, &config.database, version).await {
+                    Ok(migrations) => {
+                        ui.outdent();
+                        ui.success(&format!("{} migrations rolled back.", migrations));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not reset database!", e);
+                    }
+                }
+            }
+            Commands::Dump { output, data_only } => {
+                ui.info(&format!("Dumping {} database…", &cli.env));
+                match dump(&config.database, &cli.env, output, data_only).await {
+                    Ok(path) => ui.success(&format!("Dumped database to {}.", path.display())),
+                    Err(e) => ui.error("Could not dump database!", e),
+                }
+            }
+            Commands::Console { embedded } => {
+                ui.info(&format!("Connecting to {} database…", &cli.env));
+                let result = if embedded {
+                    embedded_console(&mut ui, &config.database).await
+                } else {
+                    console(&mut ui, &config.database).await
+                };
+                if let Err(e) = result {
+                    ui.error("Could not run console!", e);
+                }
+            }
+            Commands::Schema {
+                command: SchemaCommands::Dump { output },
+            } => {
+                ui.info(&format!("Dumping schema of {} database…", &cli.env));
+                match schema_dump(&config.database, output).await {
+                    Ok(path) => ui.success(&format!("Dumped schema to {}.", path.display())),
+                    Err(e) => ui.error("Could not dump schema!", e),
+                }
+            }
+            Commands::Schema {
+                command: SchemaCommands::Load { file },
+            } => {
+                ui.info(&format!("Loading schema into {} database…", &cli.env));
+                match schema_load(&config.database, file).await {
+                    Ok(migrations) => ui.success(&format!(
+                        "Loaded schema successfully ({} migrations marked as applied).",
+                        migrations
+                    )),
+                    Err(e) => ui.error("Could not load schema!", e),
+                }
+            }
+            Commands::Restore {
+                file,
+                resume,
+                batch_size,
+                single_transaction,
+            } => {
+                ui.info(&format!(
+                    "Restoring {} database from {}…",
+                    &cli.env,
+                    file.display()
+                ));
+                ui.indent();
+                let batch_size = if single_transaction {
+                    None
+                } else {
+                    Some(batch_size as usize)
+                };
+                match restore(&mut ui, &config.database, &file, resume, batch_size).await {
+                    Ok(statements) => {
+                        ui.outdent();
+                        ui.success(&format!(
+                            "Restored database successfully ({} statements).",
+                            statements
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Could not restore database!", e);
+                    }
+                }
+            }
+            Commands::Prepare { locked } => {
+                let install_timeout = config.database.timeouts.install.map(Duration::from_secs);
+                if let Err(e) = ensure_sqlx_cli_installed(&mut ui, install_timeout).await {
                     ui.error("Error ensuring sqlx-cli is installed!", e);
                     return;
                 }
@@ -130,7 +1011,11 @@ async fn cli() {
                 let mut sqlx_prepare_command = {
                     let mut cmd = tokio::process::Command::new(&cargo);
 
-                    cmd.args(["sqlx", "prepare", "--", "--all-targets", "--all-features"]);
+                    cmd.args(["sqlx", "prepare"]);
+                    if locked {
+                        cmd.arg("--check");
+                    }
+                    cmd.args(["--", "--all-targets", "--all-features"]);
 
                     let cmd_cwd = match db_package_root() {
                         Ok(cwd) => cwd,
@@ -153,14 +1038,51 @@ async fn cli() {
                     }
                 };
                 if !o.status.success() {
-                    ui.error(
-                        "Error generating query metadata. Are you sure the database is running and all migrations are applied?",
-                        anyhow!(String::from_utf8_lossy(&o.stdout).to_string()),
-                    );
+                    // sqlx-cli might have been uninstalled since it was last detected
+                    if let Some(cache_path) = sqlx_cli_cache_path() {
+                        let _ = fs::remove_file(cache_path);
+                    }
+                    if locked {
+                        ui.error(
+                            "Query metadata in db/.sqlx is missing or out of date. Run `cargo db prepare` and check in the changes.",
+                            anyhow!(String::from_utf8_lossy(&o.stderr).to_string()),
+                        );
+                    } else {
+                        ui.error(
+                            "Error generating query metadata. Are you sure the database is running and all migrations are applied?",
+                            anyhow!(String::from_utf8_lossy(&o.stdout).to_string()),
+                        );
+                    }
                     return;
                 }
 
-                ui.success("Query data written to db/.sqlx directory; please check this into version control.");
+                match check_offline_data() {
+                    Ok(_) if locked => ui.success("Query data in db/.sqlx directory is up to date."),
+                    Ok(_) => ui.success("Query data written to db/.sqlx directory; please check this into version control."),
+                    Err(e) => ui.error("Query data missing after running sqlx prepare!", e),
+                }
+            }
+            Commands::CheckOffline => {
+                ui.info("Checking query data for offline compile-time verification…");
+                ui.indent();
+                let result = check_offline_data();
+                let offline = matches!(std::env::var("SQLX_OFFLINE").as_deref(), Ok("1") | Ok("true"));
+                if std::env::var_os("CI").is_some() && !offline {
+                    ui.warning("Running in CI but SQLX_OFFLINE is not enabled – building the db crate will require a database connection. Set SQLX_OFFLINE=1 to build against db/.sqlx.");
+                }
+                match result {
+                    Ok(queries) => {
+                        ui.outdent();
+                        ui.success(&format!(
+                            "Query data for {} queries found in db/.sqlx directory.",
+                            queries
+                        ));
+                    }
+                    Err(e) => {
+                        ui.outdent();
+                        ui.error("Query data for offline compile-time verification is missing! Run `cargo db prepare` and check in db/.sqlx.", e);
+                    }
+                }
             }
         },
         Err(e) => ui.error("Could not load config!", e),
@@ -174,13 +1096,23 @@ async fn drop(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
         .context("Failed to get database name!")?;
     let mut root_connection = get_root_db_client(config).await;
 
+    drop_database(&mut root_connection, db_name).await?;
+
+    Ok(String::from(db_name))
+}
+
+/// Drops the database `db_name` using any executor connected to the database server, e.g. a connection or a pool.
+async fn drop_database<'e, E>(executor: E, db_name: &str) -> Result<(), anyhow::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
     let query = format!("DROP DATABASE {}", db_name);
-    root_connection
+    executor
         .execute(query.as_str())
         .await
         .context("Failed to drop database!")?;
 
-    Ok(String::from(db_name))
+    Ok(())
 }
 
 async fn create(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
@@ -190,80 +1122,2100 @@ async fn create(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
         .context("Failed to get database name!")?;
     let mut root_connection = get_root_db_client(config).await;
 
+    create_database(&mut root_connection, db_name).await?;
+
+    Ok(String::from(db_name))
+}
+
+/// Creates the database `db_name` using any executor connected to the database server, e.g. a connection or a pool.
+async fn create_database<'e, E>(executor: E, db_name: &str) -> Result<(), anyhow::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
     let query = format!("CREATE DATABASE {}", db_name);
-    root_connection
+    executor
         .execute(query.as_str())
         .await
         .context("Failed to create database!")?;
 
-    Ok(String::from(db_name))
+    Ok(())
 }
 
-async fn migrate(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<i32, anyhow::Error> {
+/// Runs all pending migrations while holding the advisory lock that serializes migration runs.
+///
+/// See [`migration_lock_key`] for the lock's key.
+async fn migrate(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    max_duration: Option<Duration>,
+    baseline_on_empty: bool,
+    lock_key: Option<i64>,
+    skip_checksum: bool,
+    applied: &mut Vec<AppliedMigration>,
+) -> Result<(), anyhow::Error> {
     let db_config = get_db_config(config);
-    let migrations_path = db_package_root()?.join("migrations");
+    let migrations_path = project_root()?.join(&config.migrations_dir);
     let migrator = Migrator::new(Path::new(&migrations_path))
         .await
         .context("Failed to create migrator!")?;
-    let mut connection = db_config
-        .connect()
-        .await
-        .context("Failed to connect to database!")?;
+    let mut connection = connect(config, &db_config).await?;
 
-    connection
-        .ensure_migrations_table()
+    let lock_key = migration_lock_key(config, &db_config, lock_key)?;
+    let lock_timeout = config.timeouts.migration_lock.map(Duration::from_secs);
+    acquire_migration_lock(ui, &mut connection, lock_key, lock_timeout).await?;
+
+    let result = if baseline_on_empty {
+        match baseline(ui, config, &mut connection, &migrator).await {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let deadline = max_duration.map(|max_duration| (&db_config, max_duration));
+                run_migrations(
+                    ui,
+                    &mut connection,
+                    &migrator,
+                    deadline,
+                    false,
+                    skip_checksum,
+                    applied,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        let deadline = max_duration.map(|max_duration| (&db_config, max_duration));
+        run_migrations(
+            ui,
+            &mut connection,
+            &migrator,
+            deadline,
+            false,
+            skip_checksum,
+            applied,
+        )
         .await
-        .context("Failed to ensure migrations table!")?;
+    };
 
-    let applied_migrations: HashMap<_, _> = connection
-        .list_applied_migrations()
+    // the lock is released when the connection is closed anyway so failing to release it explicitly is not an error
+    let _ = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(lock_key)
+        .execute(&mut connection)
+        .await;
+
+    result
+}
+
+/// Applies all pending migrations in a transaction that is rolled back, collecting the migrations that would be applied in `pending`.
+///
+/// This validates that the pending migrations can be applied on top of the database's current schema without changing anything. Migrations that cannot be run in a transaction are listed but not validated. Like a regular migration run, the dry run holds the advisory lock that serializes migration runs (see [`migrate`]).
+async fn migrate_dry_run(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    lock_key: Option<i64>,
+    skip_checksum: bool,
+    pending: &mut Vec<AppliedMigration>,
+) -> Result<(), anyhow::Error> {
+    let db_config = get_db_config(config);
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
         .await
-        .context("Failed to list applied migrations!")?
-        .into_iter()
-        .map(|m| (m.version, m))
-        .collect();
+        .context("Failed to create migrator!")?;
+    let mut connection = connect(config, &db_config).await?;
 
-    let mut applied = 0;
-    for migration in migrator.iter() {
-        if !applied_migrations.contains_key(&migration.version) {
-            connection
-                .apply(migration)
+    let lock_key = migration_lock_key(config, &db_config, lock_key)?;
+    let lock_timeout = config.timeouts.migration_lock.map(Duration::from_secs);
+    acquire_migration_lock(ui, &mut connection, lock_key, lock_timeout).await?;
+
+    let result = match Connection::begin(&mut connection).await {
+        Ok(mut transaction) => {
+            let result = run_migrations(
+                ui,
+                &mut transaction,
+                &migrator,
+                None,
+                true,
+                skip_checksum,
+                pending,
+            )
+            .await;
+            transaction
+                .rollback()
                 .await
-                .context("Failed to apply migration {}!")?;
-            ui.log(&format!("Applied migration {}.", migration.version));
-            applied += 1;
+                .context("Failed to roll back transaction!")
+                .and(result)
         }
-    }
+        Err(e) => Err(anyhow::Error::from(e).context("Failed to start transaction!")),
+    };
 
-    Ok(applied)
+    // the lock is released when the connection is closed anyway so failing to release it explicitly is not an error
+    let _ = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(lock_key)
+        .execute(&mut connection)
+        .await;
+
+    result
 }
 
-async fn seed(config: &DatabaseConfig) -> Result<(), anyhow::Error> {
-    let mut connection = get_db_client(config).await;
+/// Applies all pending migrations to the tenants' schemas while holding the advisory lock that serializes migration runs, collecting the applied migrations of all schemas in `applied`.
+///
+/// All schemas whose names start with the configured prefix are migrated (see [`TenancyConfig`]) or only `schema` if passed. The schemas are migrated one after another on a single connection, with each schema put first on the connection's `search_path` so that the migrations' unqualified names, including those of the migrations tables, refer to the tenant's schema. Returns the number of migrated schemas.
+#[allow(clippy::too_many_arguments)]
+async fn migrate_tenants(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    tenancy: &TenancyConfig,
+    schema: Option<&str>,
+    max_duration: Option<Duration>,
+    lock_key: Option<i64>,
+    skip_checksum: bool,
+    applied: &mut Vec<AppliedMigration>,
+) -> Result<usize, anyhow::Error> {
+    let db_config = get_db_config(config);
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+    let mut connection = connect(config, &db_config).await?;
 
-    let statements = fs::read_to_string("./db/seeds.sql")
-        .expect("Could not read seeds – make sure db/seeds.sql exists!");
+    let lock_key = migration_lock_key(config, &db_config, lock_key)?;
+    let lock_timeout = config.timeouts.migration_lock.map(Duration::from_secs);
+    acquire_migration_lock(ui, &mut connection, lock_key, lock_timeout).await?;
 
-    let mut transaction = connection
-        .begin()
+    let schemas: Vec<String> = match schema {
+        Some(schema) => vec![schema.to_string()],
+        None => sqlx::query_scalar(
+            "SELECT nspname::text FROM pg_namespace WHERE starts_with(nspname, $1) ORDER BY nspname",
+        )
+        .bind(&tenancy.schema_prefix)
+        .fetch_all(&mut connection)
         .await
-        .context("Failed to start transaction!")?;
-    transaction
-        .execute(statements.as_str())
+        .context("Failed to list tenant schemas!")?,
+    };
+    let deadline = max_duration.map(|max_duration| (&db_config, max_duration));
+    let mut result = Ok(schemas.len());
+    for schema in &schemas {
+        ui.log(&format!("Migrating schema {}…", schema));
+        ui.indent();
+        let schema_result = migrate_schema(
+            ui,
+            &mut connection,
+            schema,
+            &migrator,
+            deadline,
+            skip_checksum,
+            applied,
+        )
+        .await;
+        ui.outdent();
+        if let Err(e) = schema_result {
+            result = Err(e.context(format!("Failed to migrate schema {}!", schema)));
+            break;
+        }
+    }
+
+    // the lock is released when the connection is closed anyway so failing to release it explicitly is not an error
+    let _ = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(lock_key)
+        .execute(&mut connection)
+        .await;
+
+    result
+}
+
+/// Applies all pending migrations to a schema by putting it first on the connection's `search_path` (see [`migrate_tenants`]).
+async fn migrate_schema(
+    ui: &mut UI<'_>,
+    connection: &mut PgConnection,
+    schema: &str,
+    migrator: &Migrator,
+    deadline: Option<(&PgConnectOptions, Duration)>,
+    skip_checksum: bool,
+    applied: &mut Vec<AppliedMigration>,
+) -> Result<(), anyhow::Error> {
+    connection
+        .execute(format!("SET search_path TO {}, public", quote_identifier(schema)).as_str())
         .await
-        .context("Failed to execute seeds!")?;
+        .context("Failed to set the search path!")?;
 
-    Ok(())
+    run_migrations(
+        ui,
+        &mut *connection,
+        migrator,
+        deadline,
+        false,
+        skip_checksum,
+        applied,
+    )
+    .await
 }
 
-async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<String, anyhow::Error> {
-    ui.log("Dropping database…");
-    drop(config).await?;
-    ui.log("Recreating database…");
+/// Creates the schema of a new tenant and applies all migrations to it (see [`migrate_tenants`]), returning the schema's name.
+async fn create_tenant(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    tenancy: &TenancyConfig,
+    name: &str,
+) -> Result<String, anyhow::Error> {
+    let schema = tenancy.schema(name).ok_or_else(|| {
+        anyhow!(
+            "Invalid tenant name {} – tenant names consist of letters, digits, -, and _ and start with a letter or digit!",
+            name
+        )
+    })?;
+    let mut connection = get_db_client(config).await;
+
+    let schema_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)")
+            .bind(&schema)
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for existing schema!")?;
+    if schema_exists {
+        return Err(anyhow!("Schema {} already exists!", schema));
+    }
+
+    ui.log(&format!("Creating schema {}…", schema));
+    connection
+        .execute(format!("CREATE SCHEMA {}", quote_identifier(&schema)).as_str())
+        .await
+        .context("Failed to create schema!")?;
+
+    let max_duration = config.timeouts.migration.map(Duration::from_secs);
+    let mut applied = vec![];
+    migrate_tenants(
+        ui,
+        config,
+        tenancy,
+        Some(&schema),
+        max_duration,
+        None,
+        false,
+        &mut applied,
+    )
+    .await?;
+
+    Ok(schema)
+}
+
+/// Returns the key of the advisory lock that serializes migration runs: `lock_key` if passed, the configured key (see [`DatabaseConfig`]) if set, or a key derived from the database name otherwise (see [`default_lock_key`]).
+fn migration_lock_key(
+    config: &DatabaseConfig,
+    db_config: &PgConnectOptions,
+    lock_key: Option<i64>,
+) -> Result<i64, anyhow::Error> {
+    match lock_key.or(config.lock_key) {
+        Some(lock_key) => Ok(lock_key),
+        None => Ok(default_lock_key(
+            db_config
+                .get_database()
+                .context("Failed to get database name!")?,
+        )),
+    }
+}
+
+/// Writes the highest applied migration version and its checksum to `path`.
+///
+/// The file is formatted as `key=value` lines, e.g.:
+///
+/// ```text
+/// version=1674214687
+/// checksum=5f0a…
+/// ```
+///
+/// Both values are empty if no migrations were applied. The file is written to a temporary file next to `path` first and then renamed so that it is never left partially written.
+async fn report_schema_version(
+    config: &DatabaseConfig,
+    path: &Path,
+) -> Result<Option<i64>, anyhow::Error> {
+    let mut connection = connect(config, &get_db_config(config)).await?;
+    let latest: Option<(i64, Vec<u8>)> = sqlx::query_as(
+        "SELECT version, checksum FROM _sqlx_migrations WHERE success ORDER BY version DESC LIMIT 1",
+    )
+    .fetch_optional(&mut connection)
+    .await
+    .context("Failed to read the applied migrations!")?;
+
+    let (version, checksum) = match &latest {
+        Some((version, checksum)) => (
+            version.to_string(),
+            checksum
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        ),
+        None => (String::new(), String::new()),
+    };
+    let report = format!("version={}\nchecksum={}\n", version, checksum);
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, report).context(format!(
+        r#"Failed to write "{}"!"#,
+        Path::new(&tmp_path).display()
+    ))?;
+    fs::rename(&tmp_path, path).context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(latest.map(|(version, _)| version))
+}
+
+/// Appends a section listing the applied migrations to the changelog file at `path`, creating the file if necessary.
+///
+/// The section is formatted in Markdown, e.g.:
+///
+/// ```text
+/// ## 2024-01-20 12:00 UTC (production)
+///
+/// - 1674214685: create tasks table
+/// ```
+fn append_changelog(
+    path: &Path,
+    env: &Environment,
+    applied: &[AppliedMigration],
+) -> Result<(), anyhow::Error> {
+    let mut section = format!(
+        "## {} ({})\n\n",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        env
+    );
+    for migration in applied {
+        section.push_str(&format!(
+            "- {}: {}\n",
+            migration.version, migration.description
+        ));
+    }
+    section.push('\n');
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!(r#"Failed to open "{}"!"#, path.display()))?;
+    file.write_all(section.as_bytes())
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(())
+}
+
+/// Derives the key of the advisory lock that serializes migration runs from the database name.
+///
+/// The key is the 64-bit FNV-1a hash of the name so that it is stable across runs and migration runs against different databases in the same cluster don't block each other.
+fn default_lock_key(db_name: &str) -> i64 {
+    let hash = db_name.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+
+    i64::from_ne_bytes(hash.to_ne_bytes())
+}
+
+/// How often to check whether the migration lock has been released while waiting for it
+const MIGRATION_LOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often to report that the migration lock is still held by another migration run while waiting for it
+const MIGRATION_LOCK_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Acquires the session-level advisory lock with the given key, waiting for it to be released if another migration run holds it.
+///
+/// While waiting, the session holding the lock is reported (see [`migration_lock_holder`]) along with how long the run has been waiting. If a timeout is passed (see the `migration_lock` timeout in [`DatabaseConfig`]), the function gives up once it has waited for that long.
+async fn acquire_migration_lock(
+    ui: &mut UI<'_>,
+    connection: &mut PgConnection,
+    lock_key: i64,
+    timeout: Option<Duration>,
+) -> Result<(), anyhow::Error> {
+    ui.debug(&format!("Acquiring migration lock {}…", lock_key));
+    let started = Instant::now();
+    let mut reported: Option<Instant> = None;
+    loop {
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(lock_key)
+            .fetch_one(&mut *connection)
+            .await
+            .context("Failed to acquire migration lock!")?;
+        if acquired {
+            if reported.is_some() {
+                ui.log(&format!(
+                    "Acquired migration lock {} after {}s.",
+                    lock_key,
+                    started.elapsed().as_secs()
+                ));
+            }
+            return Ok(());
+        }
+
+        let waited = started.elapsed();
+        if let Some(timeout) = timeout.filter(|timeout| waited >= *timeout) {
+            return Err(anyhow!(
+                "Timed out after {}s waiting for another migration run to release migration lock {}!",
+                timeout.as_secs(),
+                lock_key
+            ));
+        }
+        match reported {
+            None => {
+                let holder = migration_lock_holder(&mut *connection, lock_key).await?;
+                ui.log(&format!(
+                    "Waiting for another migration run holding lock {} to finish{}…",
+                    lock_key,
+                    holder
+                        .map(|holder| format!(" ({})", holder))
+                        .unwrap_or_default()
+                ));
+                reported = Some(Instant::now());
+            }
+            Some(at) if at.elapsed() >= MIGRATION_LOCK_REPORT_INTERVAL => {
+                ui.log(&format!(
+                    "Still waiting for migration lock {} ({}s)…",
+                    lock_key,
+                    waited.as_secs()
+                ));
+                reported = Some(Instant::now());
+            }
+            Some(_) => {}
+        }
+        tokio::time::sleep(MIGRATION_LOCK_POLL_INTERVAL).await;
+    }
+}
+
+/// Describes the session holding the advisory lock with the given key, e.g. `held by PID 4711 connected from 10.0.0.5 since 2024-01-01 12:00:00 UTC`.
+///
+/// Returns `None` if the lock isn't held (anymore) or the holder isn't visible to the current user.
+async fn migration_lock_holder(
+    connection: &mut PgConnection,
+    lock_key: i64,
+) -> Result<Option<String>, anyhow::Error> {
+    // advisory locks on bigint keys are listed in pg_locks with the key's upper and lower 32 bits in classid and objid
+    let holder: Option<(i32, Option<String>, Option<DateTime<Utc>>)> = sqlx::query_as(
+        "SELECT a.pid, host(a.client_addr), a.backend_start
+        FROM pg_locks l JOIN pg_stat_activity a ON a.pid = l.pid
+        WHERE l.locktype = 'advisory' AND l.granted AND l.objsubid = 1
+        AND ((l.classid::bigint << 32) | l.objid::bigint) = $1
+        LIMIT 1",
+    )
+    .bind(lock_key)
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to determine the holder of the migration lock!")?;
+
+    Ok(holder.map(|(pid, client_addr, backend_start)| {
+        let mut description = format!("held by PID {}", pid);
+        if let Some(client_addr) = client_addr {
+            description.push_str(&format!(" connected from {}", client_addr));
+        }
+        if let Some(backend_start) = backend_start {
+            description.push_str(&format!(
+                " since {}",
+                backend_start.format("%Y-%m-%d %H:%M:%S UTC")
+            ));
+        }
+        description
+    }))
+}
+
+/// Creates a role that can log in with `password` and has all privileges on the database and its `public` schema.
+///
+/// In a dry run, the statements are printed with the password masked instead of being run, and it is reported whether the role and its privileges exist already. Otherwise, the statements are run in a transaction; an existing role is never changed.
+async fn create_user(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    name: &str,
+    password: &str,
+    dry_run: bool,
+) -> Result<(), anyhow::Error> {
+    let db_config = get_db_config(config);
+    let db_name = db_config
+        .get_database()
+        .context("Failed to get database name!")?;
+    let mut connection = get_db_client(config).await;
+
+    let role_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = $1)")
+            .bind(name)
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for existing role!")?;
+
+    if dry_run {
+        for statement in create_user_statements(name, "********", db_name) {
+            ui.log(&format!("{};", statement));
+        }
+        if !role_exists {
+            ui.log(&format!("Role {} does not exist yet.", name));
+            return Ok(());
+        }
+
+        let has_privileges: bool = sqlx::query_scalar(
+            "SELECT has_database_privilege($1, $2, 'CREATE')
+                AND has_database_privilege($1, $2, 'CONNECT')
+                AND has_database_privilege($1, $2, 'TEMPORARY')
+                AND has_schema_privilege($1, 'public', 'CREATE')
+                AND has_schema_privilege($1, 'public', 'USAGE')",
+        )
+        .bind(name)
+        .bind(db_name)
+        .fetch_one(&mut connection)
+        .await
+        .context("Failed to check existing privileges!")?;
+        if has_privileges {
+            ui.log(&format!(
+                "Role {} already exists and has all privileges on database {}; creating it would fail.",
+                name, db_name
+            ));
+        } else {
+            ui.log(&format!(
+                "Role {} already exists but does not have all privileges on database {}; creating it would fail.",
+                name, db_name
+            ));
+        }
+        return Ok(());
+    }
+
+    if role_exists {
+        return Err(anyhow!("Role {} already exists!", name));
+    }
+
+    let mut transaction = Connection::begin(&mut connection)
+        .await
+        .context("Failed to start transaction!")?;
+    for statement in create_user_statements(name, password, db_name) {
+        transaction
+            .execute(statement.as_str())
+            .await
+            .context("Failed to create user!")?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction!")?;
+
+    Ok(())
+}
+
+/// Returns the statements that create a role with all privileges on a database.
+fn create_user_statements(name: &str, password: &str, db_name: &str) -> Vec<String> {
+    let role = quote_identifier(name);
+    vec![
+        format!(
+            "CREATE ROLE {} WITH LOGIN PASSWORD {}",
+            role,
+            quote_literal(password)
+        ),
+        format!(
+            "GRANT ALL PRIVILEGES ON DATABASE {} TO {}",
+            quote_identifier(db_name),
+            role
+        ),
+        format!("GRANT ALL ON SCHEMA public TO {}", role),
+    ]
+}
+
+/// Marks all migrations as applied without running them if no migrations are recorded but the database's schema matches the one that results from applying all migrations (see [`plan_schema`]).
+///
+/// Returns whether the migrations were marked as applied. If any migrations are recorded already, the database is empty, or the schema does not match, nothing is changed.
+async fn baseline(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    connection: &mut PgConnection,
+    migrator: &Migrator,
+) -> Result<bool, anyhow::Error> {
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+    let applied_migrations = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?;
+    if !applied_migrations.is_empty() {
+        return Ok(false);
+    }
+
+    let tables = introspect(&mut *connection)
+        .await
+        .context("Failed to introspect schema!")?;
+    if tables.is_empty() {
+        ui.log("Database is empty; migrating normally.");
+        return Ok(false);
+    }
+
+    if plan_schema(ui, config).await? != tables {
+        ui.log("Schema does not match the one all migrations produce; migrating normally.");
+        return Ok(false);
+    }
+
+    let migrations: Vec<&Migration> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .collect();
+    stamp_migrations(&mut *connection, &migrations).await?;
+
+    ui.log(&format!(
+        "Schema matches the one all migrations produce; marked {} migrations as applied.",
+        migrations.len()
+    ));
+
+    Ok(true)
+}
+
+/// Marks migrations as applied without running them.
+///
+/// The migrations are recorded in a transaction along with their sources (see [`record_migration_source`]).
+async fn stamp_migrations(
+    connection: &mut PgConnection,
+    migrations: &[&Migration],
+) -> Result<(), anyhow::Error> {
+    ensure_migration_sources_table(&mut *connection).await?;
+    let mut transaction = Connection::begin(&mut *connection)
+        .await
+        .context("Failed to start transaction!")?;
+    for migration in migrations {
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+            VALUES ($1, $2, TRUE, $3, 0)",
+        )
+        .bind(migration.version)
+        .bind(migration.description.as_ref())
+        .bind(migration.checksum.as_ref())
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to record migration!")?;
+        record_migration_source(&mut *transaction, migration).await?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction!")?;
+
+    Ok(())
+}
+
+/// Generates a migration that reproduces the existing database's schema (see [`baseline_migration`]) and, if `stamp` is true, marks it as applied.
+///
+/// This is meant for adopting the migrations on top of an existing database, so neither the migrations directory nor the database may contain any migrations yet.
+async fn generate_baseline_migration(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    name: &str,
+    stamp: bool,
+) -> Result<PathBuf, anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    fs::create_dir_all(&migrations_path).context(format!(
+        r#"Failed to create "{}"!"#,
+        migrations_path.display()
+    ))?;
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+    if migrator.iter().next().is_some() {
+        return Err(anyhow!(
+            r#""{}" contains migrations already!"#,
+            migrations_path.display()
+        ));
+    }
+
+    let mut connection = get_db_client(config).await;
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+    let applied_migrations = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?;
+    if !applied_migrations.is_empty() {
+        return Err(anyhow!("The database has migrations applied already!"));
+    }
+
+    let tables = introspect(&mut connection)
+        .await
+        .context("Failed to introspect schema!")?;
+    if tables.is_empty() {
+        return Err(anyhow!("The database does not contain any tables!"));
+    }
+    ui.log(&format!("Introspected {} tables.", tables.len()));
+
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let path = migrations_path.join(format!("{}_{}.sql", timestamp.as_secs(), name));
+    fs::write(&path, baseline_migration(&tables))
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    if stamp {
+        let migrator = Migrator::new(Path::new(&migrations_path))
+            .await
+            .context("Failed to create migrator!")?;
+        let migrations: Vec<&Migration> = migrator.iter().collect();
+        stamp_migrations(&mut connection, &migrations).await?;
+        ui.log("Marked the migration as applied.");
+    }
+
+    Ok(path)
+}
+
+/// Creates a new, empty migration in the configured migrations directory and returns the paths of the created files.
+///
+/// The migration's version is the current Unix timestamp (or the version following the latest existing migration's if that is newer). If `reversible` is set, a pair of `<version>_<name>.up.sql` and `<version>_<name>.down.sql` files is created, otherwise a single `<version>_<name>.sql` file. The name is normalized to lowercase words separated by underscores.
+fn new_migration(
+    config: &DatabaseConfig,
+    name: &str,
+    reversible: bool,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let name = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() {
+        return Err(anyhow!(
+            "The migration name must contain letters or digits!"
+        ));
+    }
+
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    fs::create_dir_all(&migrations_path).context(format!(
+        r#"Failed to create "{}"!"#,
+        migrations_path.display()
+    ))?;
+
+    // make sure the version is newer than all existing migrations even if several are created within a second
+    let latest_version = fs::read_dir(&migrations_path)
+        .context(format!(
+            r#"Failed to read "{}"!"#,
+            migrations_path.display()
+        ))?
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name();
+            let (version, _) = file_name.to_str()?.split_once('_')?;
+            version.parse::<u64>().ok()
+        })
+        .max();
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let version = latest_version.map_or(timestamp.as_secs(), |latest| {
+        timestamp.as_secs().max(latest + 1)
+    });
+    let files: &[(&str, &str)] = if reversible {
+        &[
+            ("up.sql", "-- Add migration script here\n"),
+            ("down.sql", "-- Add down migration script here\n"),
+        ]
+    } else {
+        &[("sql", "-- Add migration script here\n")]
+    };
+    let mut paths = vec![];
+    for (extension, content) in files {
+        let path = migrations_path.join(format!("{}_{}.{}", version, name, extension));
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+            .context(format!(r#"Failed to create "{}"!"#, path.display()))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Determines the schema that results from applying all migrations.
+///
+/// The migrations are applied to a throwaway database that is introspected (see [`introspect`]) and dropped afterwards.
+async fn plan_schema(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+) -> Result<Vec<Table>, anyhow::Error> {
+    let db_config = get_db_config(config);
+    let db_name = db_config
+        .get_database()
+        .context("Failed to get database name!")?;
+    let plan_db_name = format!("{}_ddl_plan_{}", db_name, std::process::id());
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+
+    let mut root_connection = get_root_db_client(config).await;
+    ui.log(&format!("Creating throwaway database {}…", plan_db_name));
+    create_database(&mut root_connection, &plan_db_name).await?;
+
+    let tables = async {
+        let mut connection = connect(config, &db_config.clone().database(&plan_db_name))
+            .await
+            .context("Failed to connect to throwaway database!")?;
+        run_migrations(
+            ui,
+            &mut connection,
+            &migrator,
+            None,
+            false,
+            false,
+            &mut vec![],
+        )
+        .await?;
+        let tables = introspect(&mut connection)
+            .await
+            .context("Failed to introspect schema!")?;
+        connection
+            .close()
+            .await
+            .context("Failed to close connection!")?;
+        Ok::<_, anyhow::Error>(tables)
+    }
+    .await;
+
+    ui.log(&format!("Dropping throwaway database {}…", plan_db_name));
+    drop_database(&mut root_connection, &plan_db_name).await?;
+
+    tables
+}
+
+/// Applies all pending migrations using any connection source, e.g. a connection, a pool, or a transaction.
+///
+/// If a deadline is passed, any migration that does not complete within the deadline's duration is canceled via a separate connection established with the deadline's connect options (see [`apply_with_deadline`]). In a dry run (which is expected to run in a transaction that is rolled back, see [`migrate_dry_run`]), migrations that cannot be run in a transaction are skipped.
+///
+/// Before applying anything, the applied migrations are verified to not have changed since they were applied (showing a diff of the changes if they have) – unless `skip_checksum` is set, changed migrations fail the run.
+async fn run_migrations<'a, A>(
+    ui: &mut UI<'_>,
+    connection: A,
+    migrator: &Migrator,
+    deadline: Option<(&PgConnectOptions, Duration)>,
+    dry_run: bool,
+    skip_checksum: bool,
+    applied: &mut Vec<AppliedMigration>,
+) -> Result<(), anyhow::Error>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut connection = connection
+        .acquire()
+        .await
+        .context("Failed to acquire connection!")?;
+
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+
+    let applied_migrations: HashMap<_, _> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?
+        .into_iter()
+        .map(|m| (m.version, m))
+        .collect();
+
+    ensure_migration_sources_table(&mut *connection).await?;
+
+    let mut changed = 0;
+    for migration in migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        let Some(applied_migration) = applied_migrations.get(&migration.version) else {
+            continue;
+        };
+        if applied_migration.checksum != migration.checksum {
+            ui.log(&format!(
+                "Migration {} ({}) has changed since it was applied:",
+                migration.version, migration.description
+            ));
+            let recorded_sql: Option<String> =
+                sqlx::query_scalar("SELECT sql FROM _gerust_migration_sources WHERE version = $1")
+                    .bind(migration.version)
+                    .fetch_optional(&mut *connection)
+                    .await
+                    .context("Failed to read recorded migration source!")?;
+            match recorded_sql {
+                Some(recorded_sql) => {
+                    let diff = unified_diff(
+                        &recorded_sql,
+                        &migration.sql,
+                        "applied",
+                        "current",
+                        ui.color(),
+                    );
+                    for line in diff.lines() {
+                        ui.log(line);
+                    }
+                }
+                None => ui.log(
+                    "The applied version of the migration was not recorded (it was applied before migration sources were recorded), so no diff can be shown.",
+                ),
+            }
+            changed += 1;
+        }
+    }
+    if changed > 0 && skip_checksum {
+        ui.warning(&format!(
+            "{} applied migration(s) have changed – checksum mismatch! Continuing as --skip-checksum was passed.",
+            changed
+        ));
+    } else if changed > 0 {
+        return Err(anyhow!(
+            "{} applied migration(s) have changed – checksum mismatch! Restore the applied version or pass --skip-checksum to migrate anyway.",
+            changed
+        ));
+    }
+
+    for migration in migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        if !applied_migrations.contains_key(&migration.version) {
+            if dry_run && migration.no_tx {
+                ui.log(&format!(
+                    "Would apply migration {} (not validated as it cannot run in a transaction).",
+                    migration.version
+                ));
+                applied.push(AppliedMigration {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    duration_ms: 0,
+                });
+                continue;
+            }
+            let started = Instant::now();
+            match deadline {
+                Some((db_config, max_duration)) => {
+                    apply_with_deadline(&mut connection, db_config, migration, max_duration).await?
+                }
+                None => connection.apply(migration).await.map(|_| ()),
+            }
+            .with_context(|| format!("Failed to apply migration {}!", migration.version))?;
+            record_migration_source(&mut *connection, migration).await?;
+            if dry_run {
+                ui.log(&format!("Would apply migration {}.", migration.version));
+            } else {
+                ui.log(&format!("Applied migration {}.", migration.version));
+            }
+            applied.push(AppliedMigration {
+                version: migration.version,
+                description: migration.description.to_string(),
+                duration_ms: started.elapsed().as_millis(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a table of all migrations with their versions, descriptions, and when they were applied.
+///
+/// Migrations that were applied but do not exist in the migrations directory (anymore) are included as well. Nothing is changed in the database, i.e. the migrations table is not created if it doesn't exist. Returns the numbers of applied and pending migrations.
+async fn status(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(usize, usize), anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+    let mut connection = get_db_client(config).await;
+
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for migrations table!")?;
+    let applied_migrations: Vec<(i64, String, DateTime<Utc>)> = if has_migrations_table {
+        sqlx::query_as(
+            "SELECT version, description, installed_on FROM _sqlx_migrations WHERE success ORDER BY version",
+        )
+        .fetch_all(&mut connection)
+        .await
+        .context("Failed to list applied migrations!")?
+    } else {
+        vec![]
+    };
+
+    Ok(print_status(ui, &migrator, &applied_migrations))
+}
+
+/// Prints a table of all migrations in `migrator` and the applied migrations (see [`status`]) and returns the numbers of applied and pending migrations.
+fn print_status(
+    ui: &mut UI<'_>,
+    migrator: &Migrator,
+    applied_migrations: &[(i64, String, DateTime<Utc>)],
+) -> (usize, usize) {
+    let mut rows: Vec<(&str, i64, String, Option<DateTime<Utc>>)> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|migration| {
+            match applied_migrations
+                .iter()
+                .find(|(version, ..)| *version == migration.version)
+            {
+                Some((_, _, installed_on)) => (
+                    "applied",
+                    migration.version,
+                    migration.description.to_string(),
+                    Some(*installed_on),
+                ),
+                None => (
+                    "pending",
+                    migration.version,
+                    migration.description.to_string(),
+                    None,
+                ),
+            }
+        })
+        .collect();
+    for (version, description, installed_on) in applied_migrations {
+        if !rows.iter().any(|(_, v, ..)| v == version) {
+            rows.push((
+                "missing",
+                *version,
+                description.clone(),
+                Some(*installed_on),
+            ));
+        }
+    }
+    rows.sort_by_key(|(_, version, ..)| *version);
+
+    let version_width = rows
+        .iter()
+        .map(|(_, version, ..)| version.to_string().len())
+        .chain(std::iter::once("Version".len()))
+        .max()
+        .unwrap_or_default();
+    let description_width = rows
+        .iter()
+        .map(|(_, _, description, _)| description.chars().count())
+        .chain(std::iter::once("Description".len()))
+        .max()
+        .unwrap_or_default();
+    ui.log(&format!(
+        "{:<7}  {:<version_width$}  {:<description_width$}  Applied at",
+        "Status", "Version", "Description"
+    ));
+    for (status, version, description, installed_on) in &rows {
+        let installed_on = installed_on
+            .map(|installed_on| installed_on.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        ui.log(
+            format!(
+                "{:<7}  {:<version_width$}  {:<description_width$}  {}",
+                status, version, description, installed_on
+            )
+            .trim_end(),
+        );
+    }
+    let migrations: Vec<_> = rows
+        .iter()
+        .map(|(status, version, description, installed_on)| {
+            json!({
+                "status": status,
+                "version": version,
+                "description": description,
+                "applied_at": installed_on.map(|installed_on| installed_on.to_rfc3339()),
+            })
+        })
+        .collect();
+    event(ui, "migration_status", &migrations);
+
+    let applied = rows
+        .iter()
+        .filter(|(status, ..)| *status != "pending")
+        .count();
+    (applied, rows.len() - applied)
+}
+
+/// Rolls back all applied migrations that are newer than `version` by running their down scripts.
+///
+/// Before anything is rolled back, all of those migrations are checked to have a down script. Each down script runs in its own transaction.
+async fn reset_to(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    version: i64,
+) -> Result<i32, anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+    let mut connection = get_db_client(config).await;
+
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+
+    let mut applied_versions: Vec<i64> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    if !applied_versions.contains(&version) {
+        return Err(anyhow!("Migration {} has not been applied!", version));
+    }
+    applied_versions.retain(|applied_version| *applied_version > version);
+    applied_versions.sort_unstable_by(|a, b| b.cmp(a));
+
+    revert_migrations(ui, &mut connection, &migrator, &applied_versions).await
+}
+
+/// Rolls back the `steps` most recently applied migrations by running their down scripts.
+///
+/// If fewer migrations than `steps` have been applied, all of them are rolled back. Just like with [`reset_to`], all of the migrations are checked to have a down script before anything is rolled back.
+async fn rollback(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    steps: usize,
+) -> Result<i32, anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let migrator = Migrator::new(Path::new(&migrations_path))
+        .await
+        .context("Failed to create migrator!")?;
+    let mut connection = get_db_client(config).await;
+
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+
+    let mut applied_versions: Vec<i64> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    applied_versions.sort_unstable_by(|a, b| b.cmp(a));
+    applied_versions.truncate(steps);
+
+    revert_migrations(ui, &mut connection, &migrator, &applied_versions).await
+}
+
+/// Runs the down scripts of the applied migrations with the given versions in the given order.
+///
+/// If any of the migrations does not have a down script, nothing is rolled back. Each down script runs in its own transaction.
+async fn revert_migrations(
+    ui: &mut UI<'_>,
+    connection: &mut PgConnection,
+    migrator: &Migrator,
+    applied_versions: &[i64],
+) -> Result<i32, anyhow::Error> {
+    let down_migrations: Vec<&Migration> = applied_versions
+        .iter()
+        .filter_map(|applied_version| {
+            migrator
+                .iter()
+                .find(|m| m.version == *applied_version && m.migration_type.is_down_migration())
+        })
+        .collect();
+    if down_migrations.len() != applied_versions.len() {
+        let irreversible: Vec<String> = applied_versions
+            .iter()
+            .filter(|applied_version| {
+                !down_migrations
+                    .iter()
+                    .any(|m| m.version == **applied_version)
+            })
+            .map(i64::to_string)
+            .collect();
+        return Err(anyhow!(
+            "Cannot roll back; these migrations do not have a down script: {}",
+            irreversible.join(", ")
+        ));
+    }
+
+    for migration in &down_migrations {
+        connection
+            .revert(migration)
+            .await
+            .with_context(|| format!("Failed to roll back migration {}!", migration.version))?;
+        sqlx::query("DELETE FROM _gerust_migration_sources WHERE version = $1")
+            .bind(migration.version)
+            .execute(&mut *connection)
+            .await
+            .context("Failed to remove recorded migration source!")?;
+        ui.log(&format!("Rolled back migration {}.", migration.version));
+    }
+
+    Ok(down_migrations.len() as i32)
+}
+
+/// Creates the table that records the SQL of applied migrations if it does not exist yet.
+///
+/// The recorded SQL is used to show what changed when an applied migration's checksum does not match anymore.
+async fn ensure_migration_sources_table<'e, E>(executor: E) -> Result<(), anyhow::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    executor
+        .execute(
+            "CREATE TABLE IF NOT EXISTS _gerust_migration_sources (version BIGINT PRIMARY KEY, sql TEXT NOT NULL)",
+        )
+        .await
+        .context("Failed to ensure migration sources table!")?;
+
+    Ok(())
+}
+
+/// Records the SQL of an applied migration (see [`ensure_migration_sources_table`]).
+async fn record_migration_source<'e, E>(
+    executor: E,
+    migration: &Migration,
+) -> Result<(), anyhow::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO _gerust_migration_sources (version, sql) VALUES ($1, $2)
+        ON CONFLICT (version) DO UPDATE SET sql = EXCLUDED.sql",
+    )
+    .bind(migration.version)
+    .bind(migration.sql.as_ref())
+    .execute(executor)
+    .await
+    .context("Failed to record migration source!")?;
+
+    Ok(())
+}
+
+/// A migration that was applied by [`run_migrations`].
+#[derive(Serialize)]
+struct AppliedMigration {
+    version: i64,
+    description: String,
+    duration_ms: u128,
+}
+
+/// The payload that is posted to the webhook passed via `migrate --notify`.
+///
+/// The `text` field contains a human-readable summary so that the payload can be posted to Slack-compatible webhooks directly.
+#[derive(Serialize)]
+struct MigrationNotification<'a> {
+    text: String,
+    environment: String,
+    success: bool,
+    error: Option<String>,
+    duration_ms: u128,
+    migrations: &'a [AppliedMigration],
+}
+
+/// Posts a migration notification to a webhook.
+async fn send_notification(
+    url: &str,
+    notification: &MigrationNotification<'_>,
+    timeout: Option<Duration>,
+) -> Result<(), anyhow::Error> {
+    let mut client = reqwest::Client::builder();
+    if let Some(timeout) = timeout {
+        client = client.timeout(timeout);
+    }
+    let client = client.build().context("Failed to create HTTP client!")?;
+    client
+        .post(url)
+        .json(notification)
+        .send()
+        .await
+        .context("Failed to send notification!")?
+        .error_for_status()
+        .context("Webhook rejected notification!")?;
+
+    Ok(())
+}
+
+/// Applies a migration, canceling it if it does not complete within `max_duration`.
+///
+/// The migration's query is canceled from a second connection via `pg_cancel_backend` so that the migration's transaction is rolled back cleanly. The outer result is an error if the migration was canceled, the inner result is the result of applying the migration otherwise.
+async fn apply_with_deadline(
+    connection: &mut PgConnection,
+    db_config: &PgConnectOptions,
+    migration: &Migration,
+    max_duration: Duration,
+) -> Result<Result<(), MigrateError>, anyhow::Error> {
+    let backend_pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+        .fetch_one(&mut *connection)
+        .await
+        .context("Failed to determine the migration connection's backend process!")?;
+
+    let apply = connection.apply(migration);
+    tokio::pin!(apply);
+
+    tokio::select! {
+        result = &mut apply => Ok(result.map(|_| ())),
+        _ = tokio::time::sleep(max_duration) => {
+            let mut cancel_connection = db_config
+                .connect()
+                .await
+                .context("Failed to connect to database to cancel migration!")?;
+            sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_pid)
+                .execute(&mut cancel_connection)
+                .await
+                .context("Failed to cancel migration!")?;
+
+            // wait for the migration to be rolled back
+            let _ = apply.await;
+
+            Err(anyhow!(
+                "Migration {} ({}) exceeded the maximum duration of {}s and was canceled!",
+                migration.version,
+                migration.description,
+                max_duration.as_secs()
+            ))
+        }
+    }
+}
+
+/// Executes the seeds from `file` or, if no file is passed, from the environment's seeds file (see [`read_seeds`]).
+async fn seed(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    env: &Environment,
+    file: Option<&Path>,
+    dry_run: bool,
+) -> Result<(), anyhow::Error> {
+    let mut connection = get_db_client(config).await;
+    let statements = read_seeds(ui, config, env, file)?;
+
+    run_seeds(ui, &mut connection, &statements, dry_run).await
+}
+
+/// Runs the Rust seeds in the db crate's `seeds` module via the db crate's `seed` binary.
+///
+/// The binary is built and run with `DATABASE_URL` pointing to the configured database so that sqlx verifies the queries against that database at compile time (unless `SQLX_OFFLINE` is enabled).
+async fn seed_rust(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    dry_run: bool,
+) -> Result<(), anyhow::Error> {
+    let cargo = get_cargo_path()?;
+    let mut command = tokio::process::Command::new(&cargo);
+    command
+        .args(["run", "--quiet", "--features", "seeds", "--bin", "seed"])
+        .current_dir(db_package_root()?)
+        .env("DATABASE_URL", &config.url);
+    if dry_run {
+        command.args(["--", "--dry-run"]);
+    }
+
+    ui.debug("Running seeds from db/src/seeds.rs…");
+    let status = command
+        .status()
+        .await
+        .context(format!("Could not run {cargo} run!"))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Running the seeds failed (see the output above for details)!"
+        ));
+    }
+
+    Ok(())
+}
+
+/// The progress of a data migration run as printed by the db crate's `migrate_data` binary, one JSON object per line.
+#[derive(Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum DataMigrationProgress {
+    Started {
+        version: i64,
+        name: String,
+        processed: i64,
+    },
+    Batch {
+        processed: i64,
+        total: Option<i64>,
+    },
+    Completed {
+        version: i64,
+        name: String,
+        processed: i64,
+    },
+}
+
+/// Runs the data migrations in the db crate's `data_migrations` module via the db crate's `migrate_data` binary and returns the number of migrations that were completed.
+///
+/// Like [`seed_rust`], the binary is built and run with `DATABASE_URL` pointing to the configured database. The binary reports its progress on stdout which is displayed after every batch; migrations that were interrupted resume after their last committed batch.
+async fn migrate_data(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    batch_size: i64,
+    only: Option<i64>,
+) -> Result<usize, anyhow::Error> {
+    let cargo = get_cargo_path()?;
+    let mut command = tokio::process::Command::new(&cargo);
+    command
+        .args([
+            "run",
+            "--quiet",
+            "--features",
+            "data-migrations",
+            "--bin",
+            "migrate_data",
+            "--",
+            "--batch-size",
+            &batch_size.to_string(),
+        ])
+        .current_dir(db_package_root()?)
+        .env("DATABASE_URL", &config.url)
+        .stdout(Stdio::piped());
+    if let Some(version) = only {
+        command.args(["--version", &version.to_string()]);
+    }
+
+    ui.debug("Running data migrations from db/src/data_migrations…");
+    let mut child = command
+        .spawn()
+        .context(format!("Could not run {cargo} run!"))?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("Failed to read the data migrations' progress!")?;
+    let mut lines = tokio::io::BufReader::new(stdout).lines();
+    let mut completed = 0;
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .context("Failed to read the data migrations' progress!")?
+    {
+        let Ok(progress) = serde_json::from_str::<DataMigrationProgress>(&line) else {
+            // anything else the migrations print is passed through
+            ui.log(&line);
+            continue;
+        };
+        match progress {
+            DataMigrationProgress::Started {
+                version,
+                name,
+                processed: 0,
+            } => ui.log(&format!("Running data migration {} ({})…", version, name)),
+            DataMigrationProgress::Started {
+                version,
+                name,
+                processed,
+            } => ui.log(&format!(
+                "Resuming data migration {} ({}) after {} records…",
+                version, name, processed
+            )),
+            DataMigrationProgress::Batch {
+                processed,
+                total: Some(total),
+            } => ui.log(&format!(
+                "{} {}/{} records",
+                progress_bar(processed.max(0) as usize, total.max(0) as usize),
+                processed,
+                total
+            )),
+            DataMigrationProgress::Batch {
+                processed,
+                total: None,
+            } => ui.log(&format!("{} records", processed)),
+            DataMigrationProgress::Completed {
+                version,
+                name,
+                processed,
+            } => {
+                completed += 1;
+                ui.log(&format!(
+                    "Completed data migration {} ({}), {} records migrated.",
+                    version, name, processed
+                ));
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .context(format!("Could not run {cargo} run!"))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Running the data migrations failed (see the output above for details); run the command again to resume!"
+        ));
+    }
+
+    Ok(completed)
+}
+
+/// Reads the seeds from `file` or, if no file is passed, from the environment's seeds file.
+///
+/// The environment's seeds file is located next to the configured seeds file (see [`DatabaseConfig`]) in a directory named like it without the extension, e.g. `db/seeds/test.sql` for `db/seeds.sql`. If the environment doesn't have a seeds file, the configured seeds file is used.
+fn read_seeds(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    env: &Environment,
+    file: Option<&Path>,
+) -> Result<String, anyhow::Error> {
+    let seeds_path = match file {
+        Some(file) => file.to_path_buf(),
+        None => {
+            let seeds_path = project_root()?.join(&config.seeds_path);
+            let env_seeds_path = seeds_path.with_extension("").join(format!("{}.sql", env));
+            if env_seeds_path.is_file() {
+                env_seeds_path
+            } else {
+                seeds_path
+            }
+        }
+    };
+    ui.debug(&format!("Using seeds from {}.", seeds_path.display()));
+
+    read_with_includes(&seeds_path).context(format!(
+        r#"Could not read seeds from "{}"!"#,
+        seeds_path.display()
+    ))
+}
+
+/// Executes seed statements in a transaction using any connection source, e.g. a connection, a pool, or a transaction (in which case a savepoint is used).
+///
+/// In a dry run, the number of affected rows is reported for each statement and the transaction is rolled back instead of committed.
+async fn run_seeds<'a, A>(
+    ui: &mut UI<'_>,
+    connection: A,
+    statements: &str,
+    dry_run: bool,
+) -> Result<(), anyhow::Error>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut transaction = connection
+        .begin()
+        .await
+        .context("Failed to start transaction!")?;
+    for statement in split_statements(statements) {
+        let result = transaction
+            .execute(statement.as_str())
+            .await
+            .context(format!(
+                "Failed to execute seed statement: {}",
+                summarize_statement(&statement)
+            ))?;
+        if dry_run {
+            ui.log(&format!(
+                "{} row(s) affected by: {}",
+                result.rows_affected(),
+                summarize_statement(&statement)
+            ));
+        }
+    }
+
+    if dry_run {
+        transaction
+            .rollback()
+            .await
+            .context("Failed to roll back transaction!")?;
+    } else {
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction!")?;
+    }
+
+    Ok(())
+}
+
+/// Returns a single-line, shortened version of a SQL statement suitable for displaying it in the UI.
+fn summarize_statement(statement: &str) -> String {
+    const MAX_LENGTH: usize = 60;
+
+    let statement = statement
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("--"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if statement.chars().count() > MAX_LENGTH {
+        let shortened: String = statement.chars().take(MAX_LENGTH).collect();
+        format!("{}…", shortened)
+    } else {
+        statement
+    }
+}
+
+/// Dumps the database to a SQL file via `pg_dump` and returns the file's path.
+///
+/// Unless an output file is passed, the dump is written to `db/dumps/<env>-<timestamp>.sql`. The dump consists of plain SQL statements (data is dumped as `INSERT` statements rather than `COPY` blocks and psql meta-commands are left out) so that it can be restored via [`restore`]. Ownership and privileges are not dumped so that the dump can be restored by any user. A data-only dump leaves out the data of the migrations tables so that it can be restored into a migrated database.
+async fn dump(
+    config: &DatabaseConfig,
+    env: &Environment,
+    output: Option<PathBuf>,
+    data_only: bool,
+) -> Result<PathBuf, anyhow::Error> {
+    let path = match output {
+        Some(path) => path,
+        None => project_root()?.join("db/dumps").join(format!(
+            "{}-{}.sql",
+            env,
+            Utc::now().format("%Y%m%d%H%M%S")
+        )),
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .context(format!(r#"Failed to create "{}"!"#, parent.display()))?;
+    }
+
+    let mut args = vec!["--inserts"];
+    if data_only {
+        args.extend([
+            "--data-only",
+            "--exclude-table-data=_sqlx_migrations",
+            "--exclude-table-data=_gerust_migration_sources",
+            "--exclude-table-data=_gerust_data_migrations",
+        ]);
+    }
+    let sql = pg_dump(config, &args).await?;
+    fs::write(&path, sql).context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(path)
+}
+
+/// Runs `pg_dump` with the passed arguments (in addition to `--no-owner` and `--no-privileges`) against the configured database and returns the dumped SQL without psql meta-commands.
+async fn pg_dump(config: &DatabaseConfig, args: &[&str]) -> Result<String, anyhow::Error> {
+    let mut command = pg_command("pg_dump", config)?;
+    command.args(["--no-owner", "--no-privileges"]).args(args);
+    let output = command
+        .output()
+        .await
+        .context("Failed to run pg_dump – make sure it is installed!")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "pg_dump failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let sql = String::from_utf8(output.stdout)
+        .context("pg_dump returned invalid UTF-8!")?
+        .lines()
+        .filter(|line| !line.starts_with('\\'))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+
+    Ok(sql)
+}
+
+/// Prepares a command for running one of PostgreSQL's client programs (e.g. `psql`) against the configured database.
+fn pg_command(
+    program: &str,
+    config: &DatabaseConfig,
+) -> Result<tokio::process::Command, anyhow::Error> {
+    let mut command = tokio::process::Command::new(program);
+    // pass the password via the environment so it doesn't show up in the process list
+    let mut url = Url::parse(&config.url).context("Invalid database URL!")?;
+    if let Some(password) = url.password() {
+        command.env(
+            "PGPASSWORD",
+            percent_encoding::percent_decode_str(password)
+                .decode_utf8_lossy()
+                .as_ref(),
+        );
+        let _ = url.set_password(None);
+    }
+    command.arg(format!("--dbname={}", url));
+
+    Ok(command)
+}
+
+/// Opens `psql` connected to the configured database, falling back to the embedded console (see [`embedded_console`]) if `psql` isn't installed.
+async fn console(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let mut child = match pg_command("psql", config)?.spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            ui.log("psql is not installed; using the embedded console.");
+            return embedded_console(ui, config).await;
+        }
+        Err(e) => return Err(anyhow::Error::from(e).context("Failed to run psql!")),
+    };
+
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status.context("Failed to run psql!")?,
+            // psql handles Ctrl+C itself (canceling the running query) so it must not terminate the CLI
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    };
+    if !status.success() {
+        return Err(anyhow!("psql exited with {}.", status));
+    }
+
+    Ok(())
+}
+
+/// Runs a minimal SQL console connected to the configured database that reads statements from stdin and prints their results.
+///
+/// Statements are executed once a line ends with `;`; `\q` (or the end of the input) quits the console. Results are printed as tables with values in PostgreSQL's text representation.
+async fn embedded_console(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let mut connection = connect(config, &get_db_config(config)).await?;
+    ui.log(r"Connected. Enter SQL statements terminated by ; or \q to quit.");
+
+    let mut lines = tokio::io::BufReader::new(stdin()).lines();
+    let mut buffer = String::new();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .context("Failed to read from stdin!")?
+    {
+        if buffer.trim().is_empty() && matches!(line.trim(), r"\q" | "exit" | "quit") {
+            break;
+        }
+        buffer.push_str(&line);
+        buffer.push('\n');
+        if !line.trim_end().ends_with(';') {
+            continue;
+        }
+
+        for statement in split_statements(&buffer) {
+            if let Err(e) = run_console_statement(ui, &mut connection, &statement).await {
+                ui.warning(&format!("{}", e));
+            }
+        }
+        buffer.clear();
+    }
+
+    Ok(())
+}
+
+/// Executes a statement entered in the embedded console and prints the returned rows or the number of affected rows.
+async fn run_console_statement(
+    ui: &mut UI<'_>,
+    connection: &mut PgConnection,
+    statement: &str,
+) -> Result<(), anyhow::Error> {
+    let mut columns: Vec<String> = vec![];
+    let mut rows: Vec<Vec<Option<String>>> = vec![];
+    let mut rows_affected = 0;
+    // without arguments, the statement is run via the simple query protocol so all values are returned in their text representation
+    let mut results = connection.fetch_many(statement);
+    while let Some(result) = results.try_next().await? {
+        match result {
+            Either::Left(result) => rows_affected += result.rows_affected(),
+            Either::Right(row) => {
+                if columns.is_empty() {
+                    columns = row
+                        .columns()
+                        .iter()
+                        .map(|column| column.name().to_string())
+                        .collect();
+                }
+                rows.push(
+                    (0..row.len())
+                        .map(|i| row.try_get_unchecked::<Option<String>, _>(i))
+                        .collect::<Result<_, _>>()?,
+                );
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        ui.log(&format!("({} rows affected)", rows_affected));
+    } else {
+        for line in format_table(&columns, &rows) {
+            ui.log(&line);
+        }
+        ui.log(&format!("({} rows)", rows.len()));
+    }
+
+    Ok(())
+}
+
+/// Returns the path of the schema file, `schema.sql` next to the migrations directory (e.g. `db/schema.sql` for `db/migrations`).
+fn schema_path(config: &DatabaseConfig) -> Result<PathBuf, anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+    let parent = migrations_path
+        .parent()
+        .context("Failed to determine the directory containing the migrations directory!")?;
+
+    Ok(parent.join("schema.sql"))
+}
+
+/// Dumps the database's schema to the schema file (see [`schema_path`]) or the passed output file and returns the file's path.
+///
+/// The schema is dumped via `pg_dump --schema-only`, followed by statements recording the applied migrations so that a database the schema is loaded into (see [`schema_load`]) is considered migrated. The lines `pg_dump` adds with its own and the server's version are left out so that the file only changes when the schema does – this makes the schema file a diffable snapshot of the cumulative effect of all migrations.
+async fn schema_dump(
+    config: &DatabaseConfig,
+    output: Option<PathBuf>,
+) -> Result<PathBuf, anyhow::Error> {
+    let path = match output {
+        Some(path) => path,
+        None => schema_path(config)?,
+    };
+
+    let mut connection = get_db_client(config).await;
+    let (migrations_table,): (bool,) =
+        sqlx::query_as("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for migrations table!")?;
+    let migrations: Vec<(i64, String, Vec<u8>)> = if migrations_table {
+        sqlx::query_as(
+            "SELECT version, description, checksum FROM _sqlx_migrations WHERE success ORDER BY version",
+        )
+        .fetch_all(&mut connection)
+        .await
+        .context("Failed to list applied migrations!")?
+    } else {
+        vec![]
+    };
+
+    let mut sql: String = pg_dump(config, &["--schema-only"])
+        .await?
+        .lines()
+        .filter(|line| !line.starts_with("-- Dumped from") && !line.starts_with("-- Dumped by"))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    if !migrations.is_empty() {
+        sql.push_str("--\n-- Applied migrations\n--\n\n");
+        for (version, description, checksum) in migrations {
+            let checksum: String = checksum
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            sql.push_str(&format!(
+                "INSERT INTO public._sqlx_migrations (version, description, success, checksum, execution_time) VALUES ({}, {}, true, '\\x{}', 0);\n",
+                version,
+                quote_literal(&description),
+                checksum
+            ));
+        }
+    }
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .context(format!(r#"Failed to create "{}"!"#, parent.display()))?;
+    }
+    fs::write(&path, sql).context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(path)
+}
+
+/// Loads the schema from the schema file (see [`schema_path`]) or the passed file into the database and returns the number of migrations it marks as applied.
+///
+/// This is much faster than running all migrations, e.g. for setting up a test database. The database must not contain any tables yet; the schema is loaded in a single transaction.
+async fn schema_load(
+    config: &DatabaseConfig,
+    file: Option<PathBuf>,
+) -> Result<usize, anyhow::Error> {
+    let path = match file {
+        Some(path) => path,
+        None => schema_path(config)?,
+    };
+    let sql =
+        fs::read_to_string(&path).context(format!(r#"Failed to read "{}"!"#, path.display()))?;
+
+    let mut connection = get_db_client(config).await;
+    let (tables,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'public'")
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for existing tables!")?;
+    if tables > 0 {
+        return Err(anyhow!(
+            "The database is not empty ({} tables); reset it first, e.g. with `db drop` and `db create`.",
+            tables
+        ));
+    }
+
+    let mut transaction = Connection::begin(&mut connection)
+        .await
+        .context("Failed to start transaction!")?;
+    // without arguments, the whole file is sent as one simple query which may contain any number of statements
+    transaction
+        .execute(sql.as_str())
+        .await
+        .context("Failed to load schema!")?;
+    // the schema file clears the search path so the migrations table must be schema-qualified
+    let (migrations_table,): (bool,) =
+        sqlx::query_as("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut *transaction)
+            .await
+            .context("Failed to check for migrations table!")?;
+    let migrations: i64 = if migrations_table {
+        sqlx::query_scalar("SELECT COUNT(*) FROM public._sqlx_migrations")
+            .fetch_one(&mut *transaction)
+            .await
+            .context("Failed to count applied migrations!")?
+    } else {
+        0
+    };
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction!")?;
+
+    Ok(migrations as usize)
+}
+
+/// Restores the database from a SQL file.
+///
+/// The statements in the file are read and executed one by one so that only the current one is held in memory, and they are committed in batches of `batch_size` statements (or all in one transaction if no batch size is passed). After each committed batch, the progress is recorded in a marker file next to the SQL file so that an interrupted restore can be continued with `resume`. The marker file is removed once the restore completes.
+async fn restore(
+    ui: &mut UI<'_>,
+    config: &DatabaseConfig,
+    file: &Path,
+    resume: bool,
+    batch_size: Option<usize>,
+) -> Result<usize, anyhow::Error> {
+    let total = StatementReader::open(file)?.try_fold(0, |total, statement| {
+        statement.map(|_| total + 1)
+    })?;
+    let marker_path = restore_marker_path(file);
+
+    let mut restored = 0;
+    if resume {
+        restored = read_restore_marker(&marker_path, total)?;
+        ui.log(&format!(
+            "Resuming after {} of {} statements.",
+            restored, total
+        ));
+    }
+
+    let mut connection = get_db_client(config).await;
+    restore_statements(
+        ui,
+        &mut connection,
+        StatementReader::open(file)?,
+        total,
+        restored,
+        batch_size.unwrap_or(total),
+        &marker_path,
+    )
+    .await?;
+
+    if marker_path.exists() {
+        fs::remove_file(&marker_path).context("Failed to remove restore progress marker!")?;
+    }
+
+    Ok(total)
+}
+
+/// Executes the `total` statements read from `statements` after the first `restored` ones in batches of `batch_size` statements using any connection source, e.g. a connection, a pool, or a transaction (in which case savepoints are used).
+///
+/// Statements are executed as they are read so that only the current one is held in memory. After each committed batch, the progress is recorded in the marker file at `marker_path`.
+async fn restore_statements<'a, A>(
+    ui: &mut UI<'_>,
+    connection: A,
+    statements: impl Iterator<Item = Result<String, anyhow::Error>>,
+    total: usize,
+    restored: usize,
+    batch_size: usize,
+    marker_path: &Path,
+) -> Result<(), anyhow::Error>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut connection = connection
+        .acquire()
+        .await
+        .context("Failed to acquire connection!")?;
+
+    let mut statements = statements.skip(restored);
+    for batch in restore_batches(total, restored, batch_size) {
+        let mut transaction = Connection::begin(&mut *connection)
+            .await
+            .context("Failed to start transaction!")?;
+        for (index, statement) in batch.clone().zip(statements.by_ref()) {
+            let statement = statement?;
+            transaction
+                .execute(statement.as_str())
+                .await
+                .context(format!(
+                    "Failed to execute statement {}: {}",
+                    index + 1,
+                    summarize_statement(&statement)
+                ))?;
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction!")?;
+
+        write_restore_marker(marker_path, batch.end, total)?;
+        ui.log(&format!(
+            "{} {}/{} statements",
+            progress_bar(batch.end, total),
+            batch.end,
+            total
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the ranges of the indices of the statements in the batches that remain to be restored after the first `restored` of `total` statements; a `batch_size` of 0 is treated as 1.
+fn restore_batches(
+    total: usize,
+    restored: usize,
+    batch_size: usize,
+) -> impl Iterator<Item = Range<usize>> {
+    let batch_size = batch_size.max(1);
+    (restored..total)
+        .step_by(batch_size)
+        .map(move |start| start..total.min(start + batch_size))
+}
+
+/// Returns the path of the marker file that records the progress of restoring `file`.
+fn restore_marker_path(file: &Path) -> PathBuf {
+    let mut marker_path = file.as_os_str().to_owned();
+    marker_path.push(".restore-progress");
+    PathBuf::from(marker_path)
+}
+
+/// Records that `restored` of `total` statements have been restored in the restore progress marker at `marker_path`.
+fn write_restore_marker(
+    marker_path: &Path,
+    restored: usize,
+    total: usize,
+) -> Result<(), anyhow::Error> {
+    fs::write(marker_path, format!("{}/{}", restored, total))
+        .context("Failed to record restore progress!")
+}
+
+/// Reads the number of already restored statements from a restore progress marker.
+fn read_restore_marker(marker_path: &Path, total: usize) -> Result<usize, anyhow::Error> {
+    let marker = fs::read_to_string(marker_path).context(format!(
+        r#"No interrupted restore found – "{}" does not exist!"#,
+        marker_path.display()
+    ))?;
+    let (restored, recorded_total) = marker
+        .trim()
+        .split_once('/')
+        .and_then(|(restored, recorded_total)| {
+            Some((
+                restored.parse::<usize>().ok()?,
+                recorded_total.parse::<usize>().ok()?,
+            ))
+        })
+        .context(format!(
+            r#"Invalid restore progress marker "{}"!"#,
+            marker_path.display()
+        ))?;
+
+    if recorded_total != total || restored > total {
+        return Err(anyhow!(
+            "The SQL file has changed since the interrupted restore; cannot resume!"
+        ));
+    }
+
+    Ok(restored)
+}
+
+/// Renders a simple textual progress bar, e.g. `[#####-----] 50%`.
+fn progress_bar(done: usize, total: usize) -> String {
+    const WIDTH: usize = 20;
+
+    let percent = (done * 100).checked_div(total).unwrap_or(100);
+    let filled = percent * WIDTH / 100;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        percent
+    )
+}
+
+async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    ui.log("Dropping database…");
+    drop(config).await?;
+    ui.log("Recreating database…");
     let db_name = create(config).await?;
     ui.log("Migrating database…");
     ui.indent();
-    let migration_result = migrate(ui, config).await;
+    let migration_result = migrate(ui, config, None, false, None, false, &mut vec![]).await;
     ui.outdent();
 
     match migration_result {
@@ -272,14 +3224,78 @@ async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<String, anyho
     }
 }
 
+/// Truncates all tables in the database's `public` schema except for the ones in `except` and returns the names of the truncated tables.
+///
+/// All tables are truncated in a single `TRUNCATE … CASCADE` statement so that foreign keys between them don't matter, and sequences are reset. The migrations tables are only truncated if `include_migrations` is set. This is much faster than resetting the database (see [`reset`]), e.g. for cleaning up between integration test runs.
+async fn truncate(
+    config: &DatabaseConfig,
+    except: &[String],
+    include_migrations: bool,
+) -> Result<Vec<String>, anyhow::Error> {
+    let mut connection = get_db_client(config).await;
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT c.relname FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'public' AND c.relkind IN ('r', 'p') AND NOT c.relispartition
+        ORDER BY c.relname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to list tables!")?;
+    for table in except {
+        if !tables.contains(table) {
+            return Err(anyhow!(r#"Unknown table "{}"!"#, table));
+        }
+    }
+
+    let tables: Vec<String> = tables
+        .into_iter()
+        .filter(|table| !except.contains(table))
+        .filter(|table| {
+            include_migrations
+                || !matches!(
+                    table.as_str(),
+                    "_sqlx_migrations" | "_gerust_migration_sources" | "_gerust_data_migrations"
+                )
+        })
+        .collect();
+    if tables.is_empty() {
+        return Ok(tables);
+    }
+
+    let statement = format!(
+        "TRUNCATE TABLE {} RESTART IDENTITY CASCADE",
+        tables
+            .iter()
+            .map(|table| format!("public.{}", quote_identifier(table)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    connection
+        .execute(statement.as_str())
+        .await
+        .context("Failed to truncate tables!")?;
+
+    Ok(tables)
+}
+
 fn get_db_config(config: &DatabaseConfig) -> PgConnectOptions {
     let db_url = Url::parse(&config.url).expect("Invalid DATABASE_URL!");
-    ConnectOptions::from_url(&db_url).expect("Invalid DATABASE_URL!")
+    let mut db_config: PgConnectOptions =
+        ConnectOptions::from_url(&db_url).expect("Invalid DATABASE_URL!");
+
+    if let Some(secs) = config.timeouts.statement {
+        db_config = db_config.options([("statement_timeout", format!("{}s", secs))]);
+    }
+    if let Some(secs) = config.timeouts.lock {
+        db_config = db_config.options([("lock_timeout", format!("{}s", secs))]);
+    }
+
+    db_config
 }
 
 async fn get_db_client(config: &DatabaseConfig) -> PgConnection {
     let db_config = get_db_config(config);
-    let connection: PgConnection = Connection::connect_with(&db_config).await.unwrap();
+    let connection: PgConnection = connect(config, &db_config).await.unwrap();
 
     connection
 }
@@ -287,11 +3303,38 @@ async fn get_db_client(config: &DatabaseConfig) -> PgConnection {
 async fn get_root_db_client(config: &DatabaseConfig) -> PgConnection {
     let db_config = get_db_config(config);
     let root_db_config = db_config.clone().database("postgres");
-    let connection: PgConnection = Connection::connect_with(&root_db_config).await.unwrap();
+    let connection: PgConnection = connect(config, &root_db_config).await.unwrap();
 
     connection
 }
 
+/// Connects to the database, giving up once the configured connect timeout has passed.
+async fn connect(
+    config: &DatabaseConfig,
+    db_config: &PgConnectOptions,
+) -> Result<PgConnection, anyhow::Error> {
+    let connection = match config.timeouts.connect {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), db_config.connect())
+            .await
+            .map_err(|_| anyhow!("Timed out connecting to database after {}s!", secs))?,
+        None => db_config.connect().await,
+    };
+
+    connection.context("Failed to connect to database!")
+}
+
+/// Parses a timeout override passed as `<name>=<secs>`, e.g. `statement=30`.
+fn parse_timeout(value: &str) -> Result<(String, u64), String> {
+    let (name, secs) = value
+        .split_once('=')
+        .ok_or_else(|| String::from("expected <name>=<secs>"))?;
+    let secs = secs
+        .parse()
+        .map_err(|_| format!(r#"invalid number of seconds "{}""#, secs))?;
+
+    Ok((String::from(name), secs))
+}
+
 fn get_cargo_path() -> Result<String, anyhow::Error> {
     std::env::var("CARGO")
         .map_err(|_| anyhow!("Please invoke me using Cargo, e.g.: `cargo db <ARGS>`"))
@@ -299,7 +3342,10 @@ fn get_cargo_path() -> Result<String, anyhow::Error> {
 
 /// Ensure that the correct version of sqlx-cli is installed,
 /// and install it if it isn't.
-async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error> {
+async fn ensure_sqlx_cli_installed(
+    ui: &mut UI<'_>,
+    install_timeout: Option<Duration>,
+) -> Result<(), anyhow::Error> {
     /// The version of sqlx-cli required
     const SQLX_CLI_VERSION: &str = "0.8";
     let sqlx_version_req = VersionReq::parse(SQLX_CLI_VERSION)
@@ -354,10 +3400,23 @@ async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error>
 
     let cargo = get_cargo_path()?;
 
+    let cache_path = sqlx_cli_cache_path();
+    if let Some(cache_path) = &cache_path {
+        if read_sqlx_cli_cache(cache_path, SQLX_CLI_VERSION)
+            .is_some_and(|version| sqlx_version_req.matches(&version))
+        {
+            // the correct version of sqlx-cli was confirmed recently, skip running it
+            return Ok(());
+        }
+    }
+
     let current_version = installed_sqlx_cli_version(&cargo).await?;
     if let Some(version) = &current_version {
-        if sqlx_version_req.matches(&version) {
+        if sqlx_version_req.matches(version) {
             // sqlx-cli is already installed and of the correct version, nothing to do
+            if let Some(cache_path) = &cache_path {
+                write_sqlx_cli_cache(cache_path, SQLX_CLI_VERSION, version);
+            }
             return Ok(());
         }
     }
@@ -372,24 +3431,10 @@ async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error>
         Would you like to install the latest compatible version now? [Y/n]"
     ));
 
-    // Read user answer
-    {
-        let mut buf = String::new();
-        let mut reader = tokio::io::BufReader::new(stdin());
-        loop {
-            reader.read_line(&mut buf).await?;
-            let line = buf.to_ascii_lowercase();
-            let line = line.trim_end();
-            if matches!(line, "" | "y" | "yes") {
-                ui.info("Starting installation of sqlx-cli...");
-                break;
-            } else if matches!(line, "n" | "no") {
-                return Err(anyhow!("Installation of sqlx-cli canceled."));
-            };
-            ui.info("Please enter y or n");
-            buf.clear();
-        }
+    if !confirm(ui, true).await? {
+        return Err(anyhow!("Installation of sqlx-cli canceled."));
     }
+    ui.info("Starting installation of sqlx-cli...");
 
     let mut cargo_install_command = {
         let mut cmd = tokio::process::Command::new(&cargo);
@@ -412,7 +3457,19 @@ async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error>
 
     let mut child = cargo_install_command.spawn()?;
 
-    let status = child.wait().await?;
+    let status = match install_timeout {
+        Some(install_timeout) => match tokio::time::timeout(install_timeout, child.wait()).await {
+            Ok(status) => status?,
+            Err(_) => {
+                child.kill().await?;
+                return Err(anyhow!(
+                    "Installing sqlx-cli timed out after {}s",
+                    install_timeout.as_secs()
+                ));
+            }
+        },
+        None => child.wait().await?,
+    };
     if !status.success() {
         return Err(anyhow!(
             "Something went wrong when installing sqlx-cli. Please check output"
@@ -421,6 +3478,9 @@ async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error>
 
     match installed_sqlx_cli_version(&cargo).await {
         Ok(Some(v)) if sqlx_version_req.matches(&v) => {
+            if let Some(cache_path) = &cache_path {
+                write_sqlx_cli_cache(cache_path, SQLX_CLI_VERSION, &v);
+            }
             ui.success(&format!("Successfully installed sqlx-cli {v}"));
             Ok(())
         }
@@ -430,13 +3490,739 @@ async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error>
     }
 }
 
-/// Find the root of the db package in the gerust workspace.
-fn db_package_root() -> Result<PathBuf, anyhow::Error> {
+/// How long a confirmed sqlx-cli installation is trusted before `cargo sqlx --version` is run again.
+const SQLX_CLI_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The file the result of detecting the installed sqlx-cli version is cached in.
+///
+/// The file is stored in the workspace's target directory so that it is removed by `cargo clean`.
+fn sqlx_cli_cache_path() -> Option<PathBuf> {
+    let target_dir = match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(target_dir) => PathBuf::from(target_dir),
+        None => PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR")?)
+            .join("..")
+            .join("target"),
+    };
+
+    Some(target_dir.join("gerust").join("sqlx-cli-version"))
+}
+
+/// Read the sqlx-cli version that was confirmed to be installed from the cache.
+///
+/// The cached version is only returned if it was detected for the same required version and the cache has not expired.
+fn read_sqlx_cli_cache(cache_path: &Path, required_version: &str) -> Option<Version> {
+    let age = fs::metadata(cache_path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > SQLX_CLI_CACHE_TTL {
+        return None;
+    }
+
+    let contents = fs::read_to_string(cache_path).ok()?;
+    let (cached_required_version, version) = contents.trim().split_once(' ')?;
+    if cached_required_version != required_version {
+        return None;
+    }
+
+    Version::parse(version).ok()
+}
+
+/// Cache the sqlx-cli version that was confirmed to be installed.
+///
+/// Failing to write the cache is not an error; the version will just be detected again next time.
+fn write_sqlx_cli_cache(cache_path: &Path, required_version: &str, version: &Version) {
+    if let Some(cache_dir) = cache_path.parent() {
+        let _ = fs::create_dir_all(cache_dir);
+    }
+    let _ = fs::write(cache_path, format!("{required_version} {version}\n"));
+}
+
+/// Read the user's answer to a yes/no question from stdin.
+///
+/// An empty answer is interpreted as `default`.
+async fn confirm(ui: &mut UI<'_>, default: bool) -> Result<bool, anyhow::Error> {
+    let mut buf = String::new();
+    let mut reader = tokio::io::BufReader::new(stdin());
+    loop {
+        reader.read_line(&mut buf).await?;
+        let line = buf.to_ascii_lowercase();
+        match line.trim_end() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => {}
+        }
+        ui.info("Please enter y or n");
+        buf.clear();
+    }
+}
+
+/// Prints structured data about a command's result with JSON output (see [`UI::event`]).
+fn event<T: Serialize>(ui: &mut UI<'_>, event: &str, data: &T) {
+    let data = serde_json::to_string(data).expect("Event data can always be serialized!");
+    ui.event(event, &data);
+}
+
+/// Asks the user to confirm a destructive command (e.g. dropping the database) by typing the name of the database it is run against.
+///
+/// Fails if the entered name doesn't match.
+async fn confirm_database_name(
+    ui: &mut UI<'_>,
+    env: &Environment,
+    action: &str,
+    db_name: &str,
+) -> Result<(), anyhow::Error> {
+    ui.info(&format!(
+        "This will {} the {} database {}. Type the name of the database to confirm:",
+        action, env, db_name
+    ));
+    let mut answer = String::new();
+    tokio::io::BufReader::new(stdin())
+        .read_line(&mut answer)
+        .await
+        .context("Failed to read confirmation!")?;
+    if answer.trim() != db_name {
+        return Err(anyhow!(
+            "The entered name does not match the database name; canceled."
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verifies that the db package's `.sqlx` directory exists and contains query metadata.
+///
+/// Returns the number of queries there is metadata for.
+fn check_offline_data() -> Result<usize, anyhow::Error> {
+    let sqlx_dir = db_package_root()?.join(".sqlx");
+    let entries =
+        fs::read_dir(&sqlx_dir).context(format!(r#"Could not read "{}"!"#, sqlx_dir.display()))?;
+
+    let queries = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            file_name.starts_with("query-") && file_name.ends_with(".json")
+        })
+        .count();
+    if queries == 0 {
+        return Err(anyhow!(
+            r#""{}" does not contain any query metadata!"#,
+            sqlx_dir.display()
+        ));
+    }
+
+    Ok(queries)
+}
+
+/// Checks that there are no uncommitted changes (including untracked files) in the configured migrations directory (see [`DatabaseConfig`]).
+///
+/// The check is based on the porcelain output of `git status`. If git isn't available or the migrations directory isn't part of a git repository, a warning is printed and the check passes.
+async fn check_clean_git(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let migrations_path = project_root()?.join(&config.migrations_dir);
+
+    let output = tokio::process::Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=all", "--"])
+        .arg(&migrations_path)
+        .current_dir(&migrations_path)
+        .output()
+        .await;
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            ui.warning(&format!(
+                "Could not check for uncommitted changes to migration files: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+            return Ok(());
+        }
+        Err(e) => {
+            ui.warning(&format!(
+                "Could not check for uncommitted changes to migration files: {}",
+                e
+            ));
+            return Ok(());
+        }
+    };
+
+    // porcelain lines are formatted as `XY <path>`, e.g. ` M db/migrations/1674214685_create_tasks_table.sql`
+    let changed: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(String::from)
+        .collect();
+    if !changed.is_empty() {
+        return Err(anyhow!(
+            "Uncommitted changes to migration files: {}",
+            changed.join(", ")
+        ));
+    }
+
+    ui.debug("No uncommitted changes to migration files.");
+    Ok(())
+}
+
+/// Runs a command against a MySQL or SQLite database (see [`{{crate_name}}_cli::util::backend`]).
+///
+/// Only dropping, creating, migrating, resetting, seeding, rolling back, and showing the migration status are supported for those databases – all other commands and options are PostgreSQL-specific.
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+async fn backend_cli(
+    ui: &mut UI<'_>,
+    env: &Environment,
+    force: bool,
+    command: Commands,
+    config: &DatabaseConfig,
+) {
+    use {{crate_name}}_cli::util::backend;
+
+    async fn migrator(config: &DatabaseConfig) -> Result<Migrator, anyhow::Error> {
+        let migrations_path = project_root()?.join(&config.migrations_dir);
+        Migrator::new(Path::new(&migrations_path))
+            .await
+            .context("Failed to create migrator!")
+    }
+
+    async fn migrate(
+        ui: &mut UI<'_>,
+        config: &DatabaseConfig,
+    ) -> Result<Vec<(i64, String)>, anyhow::Error> {
+        let applied = backend::migrate(config, &migrator(config).await?).await?;
+        for (version, description) in &applied {
+            ui.log(&format!("Applied migration {} ({}).", version, description));
+        }
+        Ok(applied)
+    }
+
+    match command {
+        Commands::Drop => {
+            if env.is_deployed() && !force {
+                let confirmation = match backend::database_name(config) {
+                    Ok(db_name) => confirm_database_name(ui, env, "drop", &db_name).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = confirmation {
+                    ui.error("Could not drop database!", e);
+                    return;
+                }
+            }
+            ui.info(&format!("Dropping {} database…", env));
+            match backend::drop_database(config).await {
+                Ok(db_name) => {
+                    event(ui, "database_dropped", &json!({ "database": db_name }));
+                    ui.success(&format!("Dropped database {} successfully.", &db_name))
+                }
+                Err(e) => ui.error("Could not drop database!", e),
+            }
+        }
+        Commands::Create => {
+            ui.info(&format!("Creating {} database…", env));
+            match backend::create_database(config).await {
+                Ok(db_name) => {
+                    event(ui, "database_created", &json!({ "database": db_name }));
+                    ui.success(&format!("Created database {} successfully.", &db_name))
+                }
+                Err(e) => ui.error("Could not create database!", e),
+            }
+        }
+        Commands::Migrate {
+            max_duration: None,
+            print_ddl_plan: false,
+            notify: None,
+            baseline_on_empty: false,
+            require_clean_git,
+            allow_dirty,
+            lock_key: None,
+            report_schema_version_to: None,
+            changelog,
+            dump_schema: false,
+            skip_checksum: false,
+            dry_run: false,
+            all_tenants: false,
+        } => {
+            ui.info(&format!("Migrating {} database…", env));
+            ui.indent();
+            if !allow_dirty && (require_clean_git || config.require_clean_git) {
+                if let Err(e) = check_clean_git(ui, config).await {
+                    ui.outdent();
+                    ui.error(
+                        "Refusing to migrate with uncommitted changes to migration files!",
+                        e,
+                    );
+                    return;
+                }
+            }
+            match migrate(ui, config).await {
+                Ok(applied) => {
+                    let applied: Vec<AppliedMigration> = applied
+                        .into_iter()
+                        .map(|(version, description)| AppliedMigration {
+                            version,
+                            description,
+                            duration_ms: 0,
+                        })
+                        .collect();
+                    event(ui, "migrations_applied", &applied);
+                    ui.outdent();
+                    ui.success(&format!("{} migrations applied.", applied.len()));
+                    if let Some(path) = changelog.as_ref().filter(|_| !applied.is_empty()) {
+                        match append_changelog(path, env, &applied) {
+                            Ok(_) => {
+                                ui.log(&format!("Added applied migrations to {}.", path.display()))
+                            }
+                            Err(e) => ui.error("Could not write changelog!", e),
+                        }
+                    }
+                }
+                Err(e) => {
+                    ui.outdent();
+                    ui.error("Could not migrate database!", e);
+                }
+            }
+        }
+        Commands::Reset => {
+            if env.is_deployed() && !force {
+                let confirmation = match backend::database_name(config) {
+                    Ok(db_name) => confirm_database_name(ui, env, "reset", &db_name).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = confirmation {
+                    ui.error("Could not reset database!", e);
+                    return;
+                }
+            }
+            ui.info(&format!("Resetting {} database…", env));
+            ui.indent();
+            ui.log("Dropping database…");
+            let result = match backend::drop_database(config).await {
+                Ok(_) => {
+                    ui.log("Recreating database…");
+                    backend::create_database(config).await
+                }
+                Err(e) => Err(e),
+            };
+            let result = match result {
+                Ok(db_name) => {
+                    ui.log("Migrating database…");
+                    ui.indent();
+                    let migration_result = migrate(ui, config).await;
+                    ui.outdent();
+                    migration_result.map(|_| db_name)
+                }
+                Err(e) => Err(e),
+            };
+            ui.outdent();
+            match result {
+                Ok(db_name) => ui.success(&format!("Reset database {} successfully.", db_name)),
+                Err(e) => ui.error("Could not reset database!", e),
+            }
+        }
+        Commands::Seed {
+            dry_run,
+            file,
+            rust: false,
+        } => {
+            if dry_run {
+                ui.info(&format!("Seeding {} database (dry run)…", env));
+            } else {
+                ui.info(&format!("Seeding {} database…", env));
+            }
+            ui.indent();
+            let statements =
+                read_seeds(ui, config, env, file.as_deref()).map(|sql| split_statements(&sql));
+            let result = match &statements {
+                Ok(statements) => backend::run_seeds(config, statements, dry_run).await,
+                Err(e) => Err(anyhow!("{:#}", e)),
+            };
+            match (statements, result) {
+                (Ok(statements), Ok(rows_affected)) => {
+                    if dry_run {
+                        for (statement, rows_affected) in statements.iter().zip(rows_affected) {
+                            ui.log(&format!(
+                                "{} row(s) affected by: {}",
+                                rows_affected,
+                                summarize_statement(statement)
+                            ));
+                        }
+                    }
+                    ui.outdent();
+                    if dry_run {
+                        ui.success("Seeds are valid; all changes were rolled back.");
+                    } else {
+                        ui.success("Seeded database successfully.");
+                    }
+                }
+                (_, Err(e)) | (Err(e), _) => {
+                    ui.outdent();
+                    ui.error("Could not seed database!", e);
+                }
+            }
+        }
+        Commands::Rollback { steps } => {
+            if env.is_deployed() && !force {
+                ui.info(&format!(
+                    "This will roll back the last {} migrations in the {} database. Are you sure? [y/N]",
+                    steps, env
+                ));
+                match confirm(ui, false).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        ui.error(
+                            "Could not roll back database!",
+                            anyhow!("Rollback canceled."),
+                        );
+                        return;
+                    }
+                    Err(e) => {
+                        ui.error("Could not roll back database!", e);
+                        return;
+                    }
+                }
+            }
+            ui.info(&format!("Rolling back {} database…", env));
+            ui.indent();
+            let result = match migrator(config).await {
+                Ok(migrator) => backend::rollback(config, &migrator, steps as usize).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(versions) => {
+                    for version in &versions {
+                        ui.log(&format!("Rolled back migration {}.", version));
+                    }
+                    ui.outdent();
+                    ui.success(&format!("{} migrations rolled back.", versions.len()));
+                }
+                Err(e) => {
+                    ui.outdent();
+                    ui.error("Could not roll back database!", e);
+                }
+            }
+        }
+        Commands::Status => {
+            ui.info(&format!("Migration status of {} database…", env));
+            ui.indent();
+            let result = match migrator(config).await {
+                Ok(migrator) => backend::applied_migrations(config)
+                    .await
+                    .map(|applied_migrations| print_status(ui, &migrator, &applied_migrations)),
+                Err(e) => Err(e),
+            };
+            ui.outdent();
+            match result {
+                Ok((applied, pending)) => ui.success(&format!(
+                    "{} migrations applied, {} pending.",
+                    applied, pending
+                )),
+                Err(e) => ui.error("Could not determine migration status!", e),
+            }
+        }
+        _ => ui.error(
+            "Could not run command!",
+            anyhow!("This command or option is only supported for PostgreSQL databases."),
+        ),
+    }
+}
+
+/// Reports that MySQL and SQLite databases are not supported unless the cli crate's `mysql` or `sqlite` feature is enabled.
+#[cfg(not(any(feature = "mysql", feature = "sqlite")))]
+async fn backend_cli(
+    ui: &mut UI<'_>,
+    _env: &Environment,
+    _force: bool,
+    _command: Commands,
+    _config: &DatabaseConfig,
+) {
+    ui.error(
+        "Could not run command!",
+        anyhow!("MySQL and SQLite databases are only supported if the `mysql` or `sqlite` feature of the cli crate is enabled respectively."),
+    );
+}
+
+/// Runs a command against the databases of parallel test workers (see [`DatabaseConfig::for_test_worker`]).
+///
+/// The command is run for one worker database after the other and stops at the first failure.
+async fn test_workers_cli(
+    ui: &mut UI<'_>,
+    env: &Environment,
+    workers: u32,
+    command: Commands,
+    config: &DatabaseConfig,
+) {
+    if *env != Environment::Test {
+        ui.error(
+            "Could not run command for test workers!",
+            anyhow!("--test-workers can only be used with the test environment (-e test)."),
+        );
+        return;
+    }
+    if !matches!(config.backend(), Ok(DatabaseBackend::Postgres)) {
+        ui.error(
+            "Could not run command for test workers!",
+            anyhow!("--test-workers is only supported for PostgreSQL databases."),
+        );
+        return;
+    }
+    let supported = matches!(
+        command,
+        Commands::Create
+            | Commands::Drop
+            | Commands::Reset
+            | Commands::Truncate { .. }
+            | Commands::Migrate {
+                max_duration: None,
+                print_ddl_plan: false,
+                notify: None,
+                baseline_on_empty: false,
+                require_clean_git: false,
+                allow_dirty: false,
+                lock_key: None,
+                report_schema_version_to: None,
+                changelog: None,
+                dump_schema: false,
+                skip_checksum: false,
+                dry_run: false,
+                all_tenants: false,
+            }
+            | Commands::Schema {
+                command: SchemaCommands::Load { .. },
+            }
+    );
+    if !supported {
+        ui.error(
+            "Could not run command for test workers!",
+            anyhow!("--test-workers is only supported by create, drop, migrate (without options), reset, truncate, and schema load."),
+        );
+        return;
+    }
+
+    for worker in 1..=workers {
+        let worker_config = match config.for_test_worker(worker) {
+            Ok(worker_config) => worker_config,
+            Err(e) => {
+                ui.error("Could not determine test worker database!", e);
+                return;
+            }
+        };
+        let db_name = get_db_config(&worker_config)
+            .get_database()
+            .unwrap_or_default()
+            .to_string();
+        match &command {
+            Commands::Create => {
+                ui.info(&format!("Creating test database {}…", db_name));
+                match create(&worker_config).await {
+                    Ok(db_name) => {
+                        event(ui, "database_created", &json!({ "database": db_name }));
+                        ui.success(&format!("Created database {} successfully.", &db_name))
+                    }
+                    Err(e) => {
+                        ui.error("Could not create database!", e);
+                        return;
+                    }
+                }
+            }
+            Commands::Drop => {
+                ui.info(&format!("Dropping test database {}…", db_name));
+                match drop(&worker_config).await {
+                    Ok(db_name) => {
+                        event(ui, "database_dropped", &json!({ "database": db_name }));
+                        ui.success(&format!("Dropped database {} successfully.", &db_name))
+                    }
+                    Err(e) => {
+                        ui.error("Could not drop database!", e);
+                        return;
+                    }
+                }
+            }
+            Commands::Reset => {
+                ui.info(&format!("Resetting test database {}…", db_name));
+                ui.indent();
+                let result = reset(ui, &worker_config).await;
+                ui.outdent();
+                match result {
+                    Ok(db_name) => ui.success(&format!("Reset database {} successfully.", db_name)),
+                    Err(e) => {
+                        ui.error("Could not reset database!", e);
+                        return;
+                    }
+                }
+            }
+            Commands::Truncate {
+                except,
+                include_migrations,
+            } => {
+                ui.info(&format!("Truncating test database {}…", db_name));
+                match truncate(&worker_config, except, *include_migrations).await {
+                    Ok(tables) => {
+                        event(ui, "tables_truncated", &tables);
+                        ui.success(&format!("Truncated {} tables.", tables.len()))
+                    }
+                    Err(e) => {
+                        ui.error("Could not truncate database!", e);
+                        return;
+                    }
+                }
+            }
+            Commands::Migrate { .. } => {
+                ui.info(&format!("Migrating test database {}…", db_name));
+                ui.indent();
+                let mut applied = vec![];
+                let result =
+                    migrate(ui, &worker_config, None, false, None, false, &mut applied).await;
+                ui.outdent();
+                event(ui, "migrations_applied", &applied);
+                match result {
+                    Ok(_) => ui.success(&format!("{} migrations applied.", applied.len())),
+                    Err(e) => {
+                        ui.error("Could not migrate database!", e);
+                        return;
+                    }
+                }
+            }
+            Commands::Schema {
+                command: SchemaCommands::Load { file },
+            } => {
+                ui.info(&format!("Loading schema into test database {}…", db_name));
+                match schema_load(&worker_config, file.clone()).await {
+                    Ok(migrations) => ui.success(&format!(
+                        "Loaded schema successfully ({} migrations marked as applied).",
+                        migrations
+                    )),
+                    Err(e) => {
+                        ui.error("Could not load schema!", e);
+                        return;
+                    }
+                }
+            }
+            _ => unreachable!("unsupported commands are rejected above"),
+        }
+    }
+}
+
+/// Find the root of the gerust workspace.
+///
+/// The configured migrations directory and seeds file (see [`DatabaseConfig`]) are resolved relative to this.
+fn project_root() -> Result<PathBuf, anyhow::Error> {
     Ok(PathBuf::from(
         std::env::var("CARGO_MANIFEST_DIR")
             .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
     )
     .join("..")
-    .join("db")
     .canonicalize()?)
 }
+
+/// Find the root of the db package in the gerust workspace.
+fn db_package_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(project_root()?.join("db"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        read_restore_marker, restore_batches, restore_marker_path, write_restore_marker, Cli,
+        Commands,
+    };
+    use clap::Parser;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_restore_marker() {
+        let dir = create_temp_dir("restore-marker");
+        let marker_path = restore_marker_path(&dir.join("dump.sql"));
+        assert_eq!(marker_path, dir.join("dump.sql.restore-progress"));
+
+        write_restore_marker(&marker_path, 2000, 4321).unwrap();
+        assert_eq!(fs::read_to_string(&marker_path).unwrap(), "2000/4321");
+        assert_eq!(read_restore_marker(&marker_path, 4321).unwrap(), 2000);
+
+        write_restore_marker(&marker_path, 4321, 4321).unwrap();
+        assert_eq!(read_restore_marker(&marker_path, 4321).unwrap(), 4321);
+    }
+
+    #[test]
+    fn test_restore_marker_errors() {
+        let dir = create_temp_dir("restore-marker-errors");
+        let marker_path = restore_marker_path(&dir.join("dump.sql"));
+
+        let error = read_restore_marker(&marker_path, 10).unwrap_err();
+        assert!(error.to_string().contains("No interrupted restore found"));
+
+        write_restore_marker(&marker_path, 5, 10).unwrap();
+        let error = read_restore_marker(&marker_path, 12).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The SQL file has changed since the interrupted restore; cannot resume!"
+        );
+
+        fs::write(&marker_path, "11/10").unwrap();
+        assert!(read_restore_marker(&marker_path, 10).is_err());
+
+        fs::write(&marker_path, "five of ten").unwrap();
+        let error = read_restore_marker(&marker_path, 10).unwrap_err();
+        assert!(error.to_string().contains("Invalid restore progress marker"));
+    }
+
+    #[test]
+    fn test_restore_batches() {
+        assert_eq!(
+            restore_batches(10, 0, 4).collect::<Vec<_>>(),
+            vec![0..4, 4..8, 8..10]
+        );
+        assert_eq!(restore_batches(10, 0, 10).collect::<Vec<_>>(), vec![0..10]);
+        assert_eq!(restore_batches(10, 0, 1000).collect::<Vec<_>>(), vec![0..10]);
+        assert_eq!(restore_batches(0, 0, 1000).count(), 0);
+    }
+
+    #[test]
+    fn test_restore_batches_resume() {
+        assert_eq!(
+            restore_batches(10, 4, 3).collect::<Vec<_>>(),
+            vec![4..7, 7..10]
+        );
+        assert_eq!(restore_batches(10, 9, 3).collect::<Vec<_>>(), vec![9..10]);
+        assert_eq!(restore_batches(10, 10, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_restore_batches_edge_sizes() {
+        assert_eq!(
+            restore_batches(3, 0, 1).collect::<Vec<_>>(),
+            vec![0..1, 1..2, 2..3]
+        );
+        assert_eq!(
+            restore_batches(3, 1, 0).collect::<Vec<_>>(),
+            vec![1..2, 2..3]
+        );
+    }
+
+    #[test]
+    fn test_restore_batch_size_arg() {
+        let parse = |batch_size: &str| {
+            Cli::try_parse_from(["db", "restore", "dump.sql", "--batch-size", batch_size])
+        };
+
+        assert!(parse("0").is_err());
+        match parse("1").unwrap().command {
+            Commands::Restore {
+                file, batch_size, ..
+            } => {
+                assert_eq!(file, Path::new("dump.sql"));
+                assert_eq!(batch_size, 1);
+            }
+            _ => panic!("expected the restore command"),
+        }
+    }
+
+    fn create_temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gerust-db-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+}
@@ -0,0 +1,173 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{referenced_secrets, Environment};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+static BLUEPRINTS_DIR: include_dir::Dir =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprints/k8s");
+
+/// The secret settings the chart passes to the application regardless of the configuration files
+const SECRET_SETTINGS: [&str; 3] = ["APP_DATABASE__URL", "APP_SESSION__KEYS", "APP_MASTER_KEY"];
+
+fn main() {
+    cli();
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to generate the files for deploying the application.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Generate a Helm chart that deploys the application to Kubernetes")]
+    K8s {
+        #[arg(
+            long,
+            default_value = "deploy/k8s",
+            help = "The directory to generate the chart in, relative to the project root."
+        )]
+        dir: PathBuf,
+        #[arg(long, help = "Overwrite existing files.")]
+        force: bool,
+    },
+}
+
+#[allow(missing_docs)]
+fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    let root = match project_root() {
+        Ok(root) => root,
+        Err(e) => {
+            ui.error("Could not find the project root!", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command {
+        Commands::K8s { dir, force } => {
+            if let Err(e) = k8s(&mut ui, &root, &dir, force) {
+                ui.error("Could not generate the Helm chart!", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Renders the Helm chart into `dir`, skipping files that exist already unless `force` is set.
+///
+/// The chart deploys the image built from the Dockerfile generated with `cargo docker init`. Besides the settings every deployment needs (see [`SECRET_SETTINGS`]), the secrets that the production configuration references via `${NAME}` placeholders are added to the chart's values so that they can be passed to the application as env vars.
+fn k8s(ui: &mut UI, root: &Path, dir: &Path, force: bool) -> Result<(), anyhow::Error> {
+    // the configuration files are read relative to the working directory
+    std::env::set_current_dir(root).context("Failed to change to the project root!")?;
+    let secrets: Vec<String> = referenced_secrets(&Environment::Production)?
+        .into_iter()
+        .filter(|name| is_env_var_name(name) && !SECRET_SETTINGS.contains(&name.as_str()))
+        .collect();
+
+    let variables = liquid::object!({
+        "project_name": "{{project-name}}",
+        "has_db": {% if template_type != "minimal" %}true{% else %}false{% endif %},
+        "has_worker": {% if template_type == "full" %}true{% else %}false{% endif %},
+        "has_grpc": {% if grpc %}true{% else %}false{% endif %},
+        "referenced_secrets": secrets,
+    });
+
+    let templates = [
+        "_helpers.tpl",
+        "configmap.yaml",
+        "secret.yaml",
+        "web-deployment.yaml",
+        "web-service.yaml",
+        "web-hpa.yaml",
+        {%- unless template_type == "minimal" %}
+        "migrate-job.yaml",
+        {%- endunless %}
+        {%- if template_type == "full" %}
+        "worker-deployment.yaml",
+        {%- endif %}
+        {%- if grpc %}
+        "grpc-deployment.yaml",
+        "grpc-service.yaml",
+        {%- endif %}
+    ];
+
+    let mut files = vec![];
+    for file in ["Chart.yaml", "values.yaml"] {
+        let output = get_liquid_template(file)?
+            .render(&variables)
+            .context("Failed to render Liquid template")?;
+        files.push((PathBuf::from(file), output));
+    }
+    for template in templates {
+        let path = PathBuf::from("templates").join(template);
+        let contents = BLUEPRINTS_DIR
+            .get_file(&path)
+            .and_then(|file| file.contents_utf8())
+            .context(format!("Failed to read blueprint {}!", path.display()))?;
+        files.push((path, String::from(contents)));
+    }
+
+    fs::create_dir_all(dir.join("templates"))
+        .context(format!(r#"Failed to create "{}"!"#, dir.display()))?;
+    for (file, contents) in files {
+        let path = dir.join(file);
+        if path.exists() && !force {
+            ui.info(&format!(
+                "Skipped {} as it exists already – pass --force to overwrite it.",
+                path.display()
+            ));
+            continue;
+        }
+        fs::write(&path, contents).context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+        ui.success(&format!("Generated {}.", path.display()));
+    }
+
+    Ok(())
+}
+
+/// Whether the secret can be passed as an env var – secrets with nested names like `stripe.api_key` can only be read from the credentials file.
+fn is_env_var_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn get_liquid_template(path: &str) -> Result<liquid::Template, anyhow::Error> {
+    let blueprint = BLUEPRINTS_DIR
+        .get_file(path)
+        .context(format!("Failed to get blueprint {}!", path))?;
+    let template_source = blueprint
+        .contents_utf8()
+        .context(format!("Failed to read blueprint {}!", path))?;
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template_source)
+        .context("Failed to parse blueprint as Liquid template")?;
+
+    Ok(template)
+}
+
+fn project_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
+    )
+    .join("..")
+    .canonicalize()?)
+}
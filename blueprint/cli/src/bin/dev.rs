@@ -0,0 +1,374 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::Parser;
+use guppy::{graph::PackageGraph, MetadataCommand};
+use {{crate_name}}_cli::util::dev::{free_port, prefix_line, requires_restart, wait_for_port, Proxy};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{load_config, Config, Environment};
+{% unless template_type == "minimal" -%}
+use {{crate_name}}_config::{DatabaseBackend, DatabaseConfig};
+{% endunless -%}
+use notify::{EventKind, RecursiveMode, Watcher};
+{% unless template_type == "minimal" -%}
+use sqlx::{migrate::Migrator, postgres::PgConnection, Connection};
+{% endunless -%}
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpListener;
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// The ANSI color of the application's output, cyan.
+const APP_COLOR: u8 = 36;
+
+/// How long changes are collected after the first change before the application is rebuilt so that e.g. saving several files at once only triggers one rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How long to wait for a new instance of the application to accept connections.
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for requests in flight to a previous instance of the application to finish before it is stopped.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+async fn main() {
+    cli().await;
+}
+
+#[derive(Parser)]
+#[command(
+    author,
+    version,
+    about = "A CLI tool to run the application in development, rebuilding and restarting it whenever files change.",
+    long_about = None
+)]
+struct Cli {
+    {% unless template_type == "minimal" -%}
+    #[arg(long, help = "Do not run pending migrations on start.")]
+    no_migrate: bool,
+
+    {% endunless -%}
+    #[arg(long, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[allow(missing_docs)]
+async fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    {% unless template_type == "minimal" -%}
+    if let Err(e) = dev(&mut ui, cli.no_migrate).await {
+    {%- else -%}
+    if let Err(e) = dev(&mut ui).await {
+    {%- endunless %}
+        ui.error("Could not run the application!", e);
+        std::process::exit(1);
+    }
+}
+
+/// Runs the application behind a [`Proxy`] that listens on the configured interface and port, and rebuilds and restarts the application whenever files in the project change.
+///
+/// Every instance of the application runs on a random port. When a new instance accepts connections, the proxy is switched over to it and the previous instance is stopped once the requests in flight to it have finished. If the build fails or the new instance does not start, the previous instance keeps running until the next change.
+{%- unless template_type == "minimal" %}
+///
+/// Unless `no_migrate` is `true`, pending migrations are run before the application is started for the first time.
+async fn dev(ui: &mut UI<'_>, no_migrate: bool) -> Result<(), anyhow::Error> {
+{%- else %}
+async fn dev(ui: &mut UI<'_>) -> Result<(), anyhow::Error> {
+{%- endunless %}
+    let root = project_root()?;
+    std::env::set_current_dir(&root).context("Failed to change to the project root!")?;
+    let config: Config = load_config(&Environment::Development).context("Cannot load config!")?;
+    let cargo = get_cargo_path()?;
+    let (package, binary) = web_binary()?;
+    {%- unless template_type == "minimal" %}
+
+    if !no_migrate {
+        migrate_if_pending(ui, &cargo, &root, &config.database).await?;
+    }
+    {%- endunless %}
+
+    let addr = SocketAddr::new(config.server.ip, config.server.port);
+    let listener = TcpListener::bind(addr)
+        .await
+        .context(format!("Failed to listen on {}!", addr))?;
+    let proxy = Proxy::new();
+    tokio::spawn(proxy.clone().serve(listener));
+
+    let (sender, mut changes) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // reading files (e.g. by the compiler) does not change them
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                for path in event.paths {
+                    let _ = sender.send(path);
+                }
+            }
+            _ => {}
+        }
+    })
+    .context("Failed to watch files!")?;
+    watch(&mut watcher, &root)?;
+
+    ui.success(&format!(
+        "Serving the application at http://{} – it is restarted whenever files change.",
+        addr
+    ));
+
+    let mut generation = 0;
+    let mut running: Option<Child> = None;
+    loop {
+        generation += 1;
+        match start(ui, &cargo, &package, &binary, generation).await {
+            Ok((child, port)) => {
+                let drain = proxy.switch(port);
+                if let (Some(drain), Some(mut previous)) = (drain, running.take()) {
+                    tokio::spawn(async move {
+                        drain.wait(DRAIN_TIMEOUT).await;
+                        let _ = previous.kill().await;
+                    });
+                }
+                running = Some(child);
+                ui.success(&format!("Started the application (web#{}).", generation));
+            }
+            Err(e) => {
+                if running.is_some() {
+                    ui.warning(&format!(
+                        "{:#} – the previous version keeps running until the next change.",
+                        e
+                    ));
+                } else {
+                    ui.warning(&format!(
+                        "{:#} – the application is started again on the next change.",
+                        e
+                    ));
+                }
+            }
+        }
+
+        tokio::select! {
+            changed = wait_for_changes(&mut changes, &root) => {
+                for path in changed {
+                    ui.debug(&format!("Changed {}.", path.display()));
+                }
+                ui.info("Files changed, restarting the application…");
+            }
+            _ = tokio::signal::ctrl_c() => {
+                if let Some(mut running) = running.take() {
+                    let _ = running.kill().await;
+                }
+                ui.info("Stopped the application.");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Builds the web crate and starts the application on a random port, returning the process and the port once the application accepts connections.
+///
+/// The output of the build is printed as it is while the application's output is prefixed with `web#<generation>`.
+async fn start(
+    ui: &mut UI<'_>,
+    cargo: &str,
+    package: &str,
+    binary: &Path,
+    generation: usize,
+) -> Result<(Child, u16), anyhow::Error> {
+    ui.info("Building the application…");
+    let status = Command::new(cargo)
+        .args(["build", "--package", package, "--bin", package])
+        .status()
+        .await
+        .context("Failed to run cargo build!")?;
+    if !status.success() {
+        return Err(anyhow!("The build failed"));
+    }
+
+    let port = free_port().context("Failed to find a free port!")?;
+    let mut child = Command::new(binary)
+        .env("APP_SERVER__IP", "127.0.0.1")
+        .env("APP_SERVER__PORT", port.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(format!("Failed to start {}!", binary.display()))?;
+    let name = format!("web#{}", generation);
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(print_lines(stdout, name.clone(), ui.color()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(print_lines(stderr, name, ui.color()));
+    }
+
+    tokio::select! {
+        ready = wait_for_port(port, START_TIMEOUT) => {
+            if ready {
+                Ok((child, port))
+            } else {
+                Err(anyhow!("The application did not accept connections within {}s", START_TIMEOUT.as_secs()))
+            }
+        }
+        status = child.wait() => {
+            let status = status.context("Failed to wait for the application!")?;
+            Err(anyhow!("The application exited during start ({})", status))
+        }
+    }
+}
+
+async fn print_lines(output: impl AsyncRead + Unpin, name: String, color: bool) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        println!("{}", prefix_line(&name, &line, APP_COLOR, color));
+    }
+}
+
+/// Watches the project root and all directories in it except the ones that changes in do not require a restart, e.g. `target` (see [`requires_restart`]).
+fn watch(watcher: &mut impl Watcher, root: &Path) -> Result<(), anyhow::Error> {
+    watcher
+        .watch(root, RecursiveMode::NonRecursive)
+        .context("Failed to watch the project root!")?;
+    let entries = std::fs::read_dir(root).context("Failed to read the project root!")?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative_path = path.strip_prefix(root).unwrap_or(&path);
+        if path.is_dir() && requires_restart(&relative_path.join("_")) {
+            watcher
+                .watch(&path, RecursiveMode::Recursive)
+                .context(format!("Failed to watch {}!", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits for changes to files that require a restart (see [`requires_restart`]) and returns the changed files (relative to `root`) once no more changes happened for [`DEBOUNCE`].
+async fn wait_for_changes(changes: &mut UnboundedReceiver<PathBuf>, root: &Path) -> Vec<PathBuf> {
+    let relevant = |path: PathBuf| {
+        let path = path
+            .strip_prefix(root)
+            .map(Path::to_path_buf)
+            .unwrap_or(path);
+        requires_restart(&path).then_some(path)
+    };
+
+    let mut changed = vec![];
+    while changed.is_empty() {
+        match changes.recv().await {
+            Some(path) => changed.extend(relevant(path)),
+            None => std::future::pending::<()>().await,
+        }
+    }
+    while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, changes.recv()).await {
+        changed.extend(relevant(path));
+    }
+    changed.sort();
+    changed.dedup();
+
+    changed
+}
+{%- unless template_type == "minimal" %}
+
+/// Runs `cargo db migrate` if there are migrations that have not been applied to the development database yet.
+///
+/// Pending migrations are only detected for PostgreSQL databases – for other databases, `cargo db migrate` is always run.
+async fn migrate_if_pending(
+    ui: &mut UI<'_>,
+    cargo: &str,
+    root: &Path,
+    config: &DatabaseConfig,
+) -> Result<(), anyhow::Error> {
+    if let Ok(DatabaseBackend::Postgres) = config.backend() {
+        let pending = pending_migrations(root, config).await?;
+        if pending == 0 {
+            ui.debug("No pending migrations.");
+            return Ok(());
+        }
+        ui.info(&format!("Running {} pending migrations…", pending));
+    }
+
+    let status = Command::new(cargo)
+        .args(["db", "migrate"])
+        .status()
+        .await
+        .context("Failed to run cargo db migrate!")?;
+    if !status.success() {
+        return Err(anyhow!("Failed to run pending migrations!"));
+    }
+
+    Ok(())
+}
+
+/// Counts the migrations in the migrations directory that have not been applied to the database yet.
+async fn pending_migrations(root: &Path, config: &DatabaseConfig) -> Result<usize, anyhow::Error> {
+    let migrator = Migrator::new(root.join(&config.migrations_dir))
+        .await
+        .context("Failed to create migrator!")?;
+    let mut connection = PgConnection::connect(&config.url)
+        .await
+        .context("Failed to connect to database!")?;
+
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut connection)
+            .await
+            .context("Failed to check for migrations table!")?;
+    let applied: Vec<i64> = if has_migrations_table {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(&mut connection)
+            .await
+            .context("Failed to list applied migrations!")?
+    } else {
+        vec![]
+    };
+
+    Ok(migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .count())
+}
+{%- endunless %}
+
+/// Returns the name of the web crate's package and the path of its binary in the target directory.
+fn web_binary() -> Result<(String, PathBuf), anyhow::Error> {
+    let mut command = MetadataCommand::new();
+    let package_graph = PackageGraph::from_command(command.manifest_path("./Cargo.toml"))
+        .context("Failed to read the workspace's metadata!")?;
+    let workspace = package_graph.workspace();
+    let package = workspace
+        .iter_by_path()
+        .find(|(path, _)| *path == "web")
+        .map(|(_, metadata)| String::from(metadata.name()))
+        .ok_or_else(|| anyhow!("Could not find the web crate!"))?;
+    let binary = workspace.target_directory().join("debug").join(format!(
+        "{}{}",
+        package,
+        std::env::consts::EXE_SUFFIX
+    ));
+
+    Ok((package, binary.into_std_path_buf()))
+}
+
+fn get_cargo_path() -> Result<String, anyhow::Error> {
+    std::env::var("CARGO").map_err(|_| anyhow!("Please invoke me using Cargo, e.g.: `cargo dev`"))
+}
+
+/// Find the root of the gerust workspace.
+fn project_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
+    )
+    .join("..")
+    .canonicalize()?)
+}
@@ -0,0 +1,152 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::CredentialsFile;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+static BLUEPRINTS_DIR: include_dir::Dir =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprints/docker");
+
+/// The files `init` generates and the blueprints they are rendered from
+const FILES: [(&str, &str); 3] = [
+    ("Dockerfile", "Dockerfile"),
+    ("docker-compose.yml", "docker-compose.yml"),
+    (".dockerignore", "dockerignore"),
+];
+
+fn main() {
+    cli();
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to run the application in Docker.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(
+        about = "Generate a Dockerfile, a docker-compose.yml that runs the full stack, and a .dockerignore"
+    )]
+    Init {
+        #[arg(
+            long,
+            help = "Add a Redis server to docker-compose.yml and use it for the cache, channels, and rate limits (builds the web crate with its redis feature)."
+        )]
+        redis: bool,
+        #[arg(long, help = "Overwrite existing files.")]
+        force: bool,
+    },
+}
+
+#[allow(missing_docs)]
+fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    let root = match project_root() {
+        Ok(root) => root,
+        Err(e) => {
+            ui.error("Could not find the project root!", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command {
+        Commands::Init { redis, force } => {
+            if let Err(e) = init(&mut ui, &root, redis, force) {
+                ui.error("Could not generate the Docker files!", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Renders the Dockerfile, docker-compose.yml, and .dockerignore into the project root, skipping files that exist already unless `force` is set.
+///
+/// The docker-compose.yml the project was created with only boots the databases for development and tests – it is replaced as the generated file includes those services as well.
+fn init(ui: &mut UI, root: &Path, redis: bool, force: bool) -> Result<(), anyhow::Error> {
+    let variables = liquid::object!({
+        "project_name": "{{project-name}}",
+        "crate_name": "{{crate_name}}",
+        "has_db": {% if template_type != "minimal" %}true{% else %}false{% endif %},
+        "has_worker": {% if template_type == "full" %}true{% else %}false{% endif %},
+        "has_grpc": {% if grpc %}true{% else %}false{% endif %},
+        "redis": redis,
+    });
+
+    for (file, blueprint) in FILES {
+        let path = root.join(file);
+        if path.exists() && !force && !is_database_only_compose_file(&path) {
+            ui.info(&format!(
+                "Skipped {} as it exists already – pass --force to overwrite it.",
+                file
+            ));
+            continue;
+        }
+
+        let template = get_liquid_template(blueprint)?;
+        let output = template
+            .render(&variables)
+            .context("Failed to render Liquid template")?;
+        fs::write(&path, output).context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+        ui.success(&format!("Generated {}.", file));
+    }
+
+    {% unless template_type == "minimal" -%}
+    if !root.join("db/.sqlx").exists() {
+        ui.warning("Run `cargo db prepare` and check db/.sqlx into version control before building the image – queries cannot be checked against a database during the build.");
+    }
+    {% endunless -%}
+    ui.info(&format!(
+        "Sessions need a key in production – set SESSION_KEY before running `docker compose up`, e.g.: export SESSION_KEY={}",
+        CredentialsFile::generate_key()
+    ));
+
+    Ok(())
+}
+
+/// Whether the file is the docker-compose.yml the project was created with which doesn't define the application's `web` service.
+fn is_database_only_compose_file(path: &Path) -> bool {
+    path.ends_with("docker-compose.yml")
+        && fs::read_to_string(path)
+            .is_ok_and(|contents| !contents.lines().any(|line| line.trim_end() == "  web:"))
+}
+
+fn get_liquid_template(path: &str) -> Result<liquid::Template, anyhow::Error> {
+    let blueprint = BLUEPRINTS_DIR
+        .get_file(path)
+        .context(format!("Failed to get blueprint {}!", path))?;
+    let template_source = blueprint
+        .contents_utf8()
+        .context(format!("Failed to read blueprint {}!", path))?;
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template_source)
+        .context("Failed to parse blueprint as Liquid template")?;
+
+    Ok(template)
+}
+
+fn project_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
+    )
+    .join("..")
+    .canonicalize()?)
+}
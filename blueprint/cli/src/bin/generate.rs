@@ -1,16 +1,33 @@
+#![allow(missing_docs)]
 use anyhow::{anyhow, Context};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use cruet::{
-    case::{snake::to_snake_case, {%- if template_type != "minimal" -%}title::to_title_case{%- endif -%}},
+    case::{snake::to_snake_case, title::to_title_case},
+    case::pascal::to_pascal_case,
 {% if template_type != "minimal" -%}
+    case::camel::to_camel_case,
     string::{pluralize::to_plural, singularize::to_singular},
 {% endif -%}
 };
 use guppy::{graph::PackageGraph, MetadataCommand};
 use liquid::Template;
+use {{crate_name}}_cli::util::changes::{Action, Changes, Existing, Record};
+{% if template_type != "minimal" -%}
+use {{crate_name}}_cli::util::scaffold::{
+    add_dependency, append_to_chain, append_to_function, append_to_tuple_struct,
+    attach_route_layer, has_route, insert_import, insert_module, parse_field, register_api_doc,
+    register_routes, Field, FieldType,
+};
+{% else -%}
+use {{crate_name}}_cli::util::scaffold::{
+    append_to_chain, append_to_function, attach_route_layer, has_route, insert_import,
+    insert_module, register_routes,
+};
+{% endif -%}
 use {{crate_name}}_cli::util::ui::UI;
-use std::fs::{self, File, OpenOptions};
-use std::io::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
 {% if template_type != "minimal" -%}
 use std::time::SystemTime;
 {% endif -%}
@@ -18,6 +35,9 @@ use std::time::SystemTime;
 static BLUEPRINTS_DIR: include_dir::Dir =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprints");
 
+/// The changes the generators make to project files, written (or previewed with `--dry-run`) once the command is done
+static CHANGES: Mutex<Changes> = Mutex::new(Changes::new(Existing::Fail));
+
 #[tokio::main]
 async fn main() {
     cli().await;
@@ -30,6 +50,31 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Show the files that would be created or modified along with diffs of the changes without writing anything."
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "skip",
+        help = "Overwrite files that exist already."
+    )]
+    force: bool,
+
+    #[arg(long, global = true, help = "Keep files that exist already.")]
+    skip: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Revert the latest run of the generator with the name instead: delete the files it created and undo its changes to other files."
+    )]
+    revert: bool,
+
     #[arg(long, global = true, help = "Disable colored output.")]
     no_color: bool,
 
@@ -39,7 +84,7 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    #[command(about = "Generate a middleware")]
+    #[command(about = "Generate a middleware and register it with the application")]
     Middleware {
         #[arg(help = "The name of the middleware.")]
         name: String,
@@ -54,16 +99,66 @@ enum Commands {
         #[arg(help = "The name of the controller.")]
         name: String,
     },
+    #[command(
+        about = "Generate a channel that clients join via WebSockets and register it with the application"
+    )]
+    Channel {
+        #[arg(help = "The name of the channel.")]
+        name: String,
+    },
+    #[command(about = "Generate a one-off task, e.g. a backfill, and register it with the application")]
+    Task {
+        #[arg(help = "The name of the task.")]
+        name: String,
+    },
+    #[command(about = "Generate a handler that streams events to clients via Server-Sent Events")]
+    Sse {
+        #[arg(help = "The name of the stream.")]
+        name: String,
+    },
+    #[command(about = "Generate an HTML template and the handler that renders it")]
+    View {
+        #[arg(
+            help = "The controller and action of the view as <controller>/<action>, e.g. tasks/index."
+        )]
+        name: String,
+    },
+    #[command(about = "Configure a rate limit and attach it to the routes with a path")]
+    RateLimit {
+        #[arg(help = "The name of the rate limit.")]
+        name: String,
+        #[arg(help = "The path of the routes the rate limit applies to, e.g. /login.")]
+        path: String,
+    },
     {% if template_type != "minimal" -%}
     #[command(about = "Generate a migration")]
     Migration {
         #[arg(help = "The name of the migration.")]
         name: String,
     },
-    #[command(about = "Generate an entity")]
+    #[command(about = "Generate a data migration that changes existing data in batches, e.g. a backfill, and register it")]
+    DataMigration {
+        #[arg(help = "The name of the data migration.")]
+        name: String,
+    },
+    #[command(about = "Generate the migration that creates the table for the database session store")]
+    SessionsMigration,
+    #[command(about = "Generate the migration that creates the table for the database rate limit store")]
+    RateLimitsMigration,
+    #[command(about = "Generate an entity, and its migration if fields are given")]
     Entity {
         #[arg(help = "The name of the entity.")]
         name: String,
+        #[arg(
+            help = "The entity's fields as <name>:<type>[?][:unique], e.g. email:string:unique, age:int? or post:references (types: string, text, int, bigint, float, bool, uuid, references)."
+        )]
+        fields: Vec<String>,
+        #[arg(
+            long,
+            requires = "fields",
+            help = "Soft-delete records by setting their deleted_at column and generate a job that purges deleted records periodically."
+        )]
+        soft_delete: bool,
     },
     #[command(about = "Generate an entity test helper")]
     EntityTestHelper {
@@ -80,21 +175,106 @@ enum Commands {
         #[arg(help = "The name of the entity the controller is for.")]
         name: String,
     },
+    #[command(
+        about = "Generate an entity with its migration, a CRUD controller with routes, and tests"
+    )]
+    Crud {
+        #[arg(help = "The name of the entity.")]
+        name: String,
+        #[arg(
+            required = true,
+            help = "The entity's fields as <name>:<type>[?][:unique], e.g. email:string:unique, age:int? or post:references (types: string, text, int, bigint, float, bool, uuid, references)."
+        )]
+        fields: Vec<String>,
+    },
+    #[command(about = "Generate a background job and register it with the worker")]
+    Job {
+        #[arg(help = "The name of the job.")]
+        name: String,
+        #[arg(
+            long,
+            help = "Enqueue the job on this cron schedule, e.g. \"0 0 * * * *\" for every hour (fields: sec min hour day-of-month month day-of-week [year], in UTC)."
+        )]
+        schedule: Option<String>,
+    },
+    #[command(about = "Generate an email with its templates and a preview test")]
+    Mailer {
+        #[arg(help = "The name of the email.")]
+        name: String,
+    },
+    #[command(
+        about = "Generate email/password authentication with registration, login, logout, and API tokens"
+    )]
+    Auth,
+    #[command(
+        about = "Generate an attachment for an entity with its migration and the endpoints to upload, download, and delete it"
+    )]
+    Uploader {
+        #[arg(help = "The name of the attachment, e.g. avatar.")]
+        name: String,
+        #[arg(help = "The name of the entity the attachment belongs to, e.g. user.")]
+        entity: String,
+    },
+    #[command(about = "Generate a policy that decides which actions users may perform on an entity")]
+    Policy {
+        #[arg(help = "The name of the entity.")]
+        name: String,
+    },
+    #[command(about = "Generate a GraphQL object and queries for an entity")]
+    GraphqlType {
+        #[arg(help = "The name of the entity.")]
+        name: String,
+    },
+    #[command(
+        about = "Generate a gRPC service with its protobuf definition and register it with the gRPC server"
+    )]
+    GrpcService {
+        #[arg(help = "The name of the service.")]
+        name: String,
+    },
     {% endif -%}
 }
 
-#[allow(missing_docs)]
 pub async fn cli() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
     let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
 
+    // runs are recorded by generator and name so that they can be reverted
+    let (generator, name) = match matches.subcommand() {
+        Some((generator, args)) => (
+            String::from(generator),
+            args.try_get_one::<String>("name").ok().flatten().cloned(),
+        ),
+        None => unreachable!("A generator is always given"),
+    };
+    if cli.dry_run {
+        ui.info("Dry run – no files will be written.");
+    }
+    if cli.revert {
+        revert(&mut ui, &generator, name.as_deref(), cli.dry_run, cli.force);
+        return;
+    }
+
+    let existing = if cli.force {
+        Existing::Force
+    } else if cli.skip {
+        Existing::Skip
+    } else {
+        Existing::Fail
+    };
+    CHANGES.lock().unwrap().set_existing(existing);
+
     match cli.command {
         Commands::Middleware { name } => {
             ui.info("Generating middleware…");
             match generate_middleware(name).await {
-                Ok(file_name) => ui.success(&format!("Generated middleware {}.", &file_name)),
+                Ok(file_name) => {
+                    ui.success(&format!("Generated middleware {}.", &file_name));
+                    ui.info("Registered the middleware in ./web/src/middlewares/mod.rs – reorder the middlewares there if necessary!");
+                }
                 Err(e) => ui.error("Could not generate middleware!", e),
             }
         }
@@ -126,19 +306,118 @@ pub async fn cli() {
                 Err(e) => ui.error("Could not generate test for controller!", e),
             }
         }
+        Commands::Channel { name } => {
+            ui.info("Generating channel…");
+            match generate_channel(name).await {
+                Ok(struct_name) => ui.success(&format!("Generated channel {}.", &struct_name)),
+                Err(e) => ui.error("Could not generate channel!", e),
+            }
+        }
+        Commands::Task { name } => {
+            ui.info("Generating task…");
+            match generate_task(name).await {
+                Ok(struct_name) => ui.success(&format!("Generated task {}.", &struct_name)),
+                Err(e) => ui.error("Could not generate task!", e),
+            }
+        }
+        Commands::Sse { name } => {
+            ui.info("Generating event stream…");
+            match generate_sse(name).await {
+                Ok(files) => {
+                    for file in files {
+                        ui.success(&format!("Generated {}.", &file));
+                    }
+                }
+                Err(e) => ui.error("Could not generate event stream!", e),
+            }
+        }
+        Commands::View { name } => {
+            ui.info("Generating view…");
+            match generate_view(name).await {
+                Ok(files) => {
+                    for file in files {
+                        ui.success(&format!("Generated {}.", &file));
+                    }
+                    ui.success("Routed the handler in ./web/src/routes.rs.");
+                }
+                Err(e) => ui.error("Could not generate view!", e),
+            }
+        }
+        Commands::RateLimit { name, path } => {
+            ui.info("Generating rate limit…");
+            match generate_rate_limit(name, path.clone()).await {
+                Ok(name) => {
+                    ui.success(&format!("Added the rate limit {} to ./config/app.toml.", &name));
+                    ui.success(&format!(
+                        "Attached the rate limit to the routes at {} in ./web/src/routes.rs.",
+                        &path
+                    ));
+                    ui.info(&format!("Adjust the limit in the [rate_limit.limits.{}] section of the configuration!", &name));
+                }
+                Err(e) => ui.error("Could not generate rate limit!", e),
+            }
+        }
         {% if template_type != "minimal" -%}
         Commands::Migration { name } => {
             ui.info("Generating migration…");
-            match generate_migration(name).await {
+            match generate_migration(name, "").await {
                 Ok(file_name) => ui.success(&format!("Generated migration {}.", &file_name)),
                 Err(e) => ui.error("Could not generate migration!", e),
             }
         }
-        Commands::Entity { name } => {
+        Commands::DataMigration { name } => {
+            ui.info("Generating data migration…");
+            match generate_data_migration(name).await {
+                Ok(struct_name) => {
+                    ui.success(&format!("Generated data migration {}.", &struct_name));
+                    ui.info("Run cargo db migrate-data to run it!");
+                }
+                Err(e) => ui.error("Could not generate data migration!", e),
+            }
+        }
+        Commands::SessionsMigration => {
+            ui.info("Generating sessions migration…");
+            match generate_sessions_migration().await {
+                Ok(file_name) => {
+                    ui.success(&format!("Generated migration {}.", &file_name));
+                    ui.info(r#"Run cargo db migrate and set store = "database" in the [session] section of the configuration to store sessions in the database!"#);
+                }
+                Err(e) => ui.error("Could not generate sessions migration!", e),
+            }
+        }
+        Commands::RateLimitsMigration => {
+            ui.info("Generating rate limits migration…");
+            match generate_rate_limits_migration().await {
+                Ok(file_name) => {
+                    ui.success(&format!("Generated migration {}.", &file_name));
+                    ui.info(r#"Run cargo db migrate and set type = "postgres" in the [rate_limit.store] section of the configuration to store rate limits in the database!"#);
+                }
+                Err(e) => ui.error("Could not generate rate limits migration!", e),
+            }
+        }
+        Commands::Entity {
+            name,
+            fields,
+            soft_delete,
+        } => {
             ui.info("Generating entity…");
-            match generate_entity(name).await {
-                Ok(struct_name) => ui.success(&format!("Generated entity {}.", &struct_name)),
-                Err(e) => ui.error("Could not generate entity!", e),
+            if fields.is_empty() {
+                match generate_entity(name).await {
+                    Ok(struct_name) => {
+                        ui.success(&format!("Generated entity {}.", &struct_name))
+                    }
+                    Err(e) => ui.error("Could not generate entity!", e),
+                }
+            } else {
+                match generate_entity_with_fields(name, fields, soft_delete).await {
+                    Ok(file_paths) => {
+                        for file_path in file_paths {
+                            ui.success(&format!("Generated {}.", &file_path));
+                        }
+                        ui.info("Run cargo db migrate to create the table!");
+                    }
+                    Err(e) => ui.error("Could not generate entity!", e),
+                }
             }
         }
         Commands::EntityTestHelper { name } => {
@@ -181,26 +460,231 @@ pub async fn cli() {
                 Err(e) => ui.error("Could not generate test for CRUD controller!", e),
             }
         }
+        Commands::Crud { name, fields } => {
+            ui.info("Generating CRUD scaffold…");
+            match generate_crud(name, fields).await {
+                Ok(file_paths) => {
+                    for file_path in file_paths {
+                        ui.success(&format!("Generated {}.", &file_path));
+                    }
+                    ui.success("Routed the controller's actions in ./web/src/routes.rs.");
+                    ui.success("Added the controller's API docs to ./web/src/openapi.rs.");
+                    ui.info("Run cargo db migrate to create the table!");
+                }
+                Err(e) => ui.error("Could not generate CRUD scaffold!", e),
+            }
+        }
+        Commands::Job { name, schedule } => {
+            ui.info("Generating job…");
+            match generate_job(name, schedule).await {
+                Ok(struct_name) => ui.success(&format!("Generated job {}.", &struct_name)),
+                Err(e) => ui.error("Could not generate job!", e),
+            }
+        }
+        Commands::Mailer { name } => {
+            ui.info("Generating email…");
+            match generate_mailer(name).await {
+                Ok(struct_name) => ui.success(&format!("Generated email {}.", &struct_name)),
+                Err(e) => ui.error("Could not generate email!", e),
+            }
+        }
+        Commands::Auth => {
+            ui.info("Generating authentication…");
+            match generate_auth().await {
+                Ok(file_paths) => {
+                    for file_path in file_paths {
+                        ui.success(&format!("Generated {}.", &file_path));
+                    }
+                    ui.success("Added the argon2 and sha2 dependencies to ./db/Cargo.toml.");
+                    ui.success("Routed the controller's actions in ./web/src/routes.rs.");
+                    ui.info("Run cargo db migrate to create the users and user_tokens tables!");
+                }
+                Err(e) => ui.error("Could not generate authentication!", e),
+            }
+        }
+        Commands::Uploader { name, entity } => {
+            ui.info("Generating uploader…");
+            match generate_uploader(name, entity).await {
+                Ok(file_paths) => {
+                    for file_path in file_paths {
+                        ui.success(&format!("Generated {}.", &file_path));
+                    }
+                    ui.success("Routed the controller's actions in ./web/src/routes.rs.");
+                    ui.info("Run cargo db migrate to add the column!");
+                }
+                Err(e) => ui.error("Could not generate uploader!", e),
+            }
+        }
+        Commands::Policy { name } => {
+            ui.info("Generating policy…");
+            match generate_policy(name).await {
+                Ok(struct_name) => ui.success(&format!("Generated policy {}.", &struct_name)),
+                Err(e) => ui.error("Could not generate policy!", e),
+            }
+        }
+        Commands::GraphqlType { name } => {
+            ui.info("Generating GraphQL type…");
+            match generate_graphql_type(name).await {
+                Ok(files) => {
+                    for file in files {
+                        ui.success(&format!("Generated {}.", &file));
+                    }
+                }
+                Err(e) => ui.error("Could not generate GraphQL type!", e),
+            }
+        }
+        Commands::GrpcService { name } => {
+            ui.info("Generating gRPC service…");
+            match generate_grpc_service(name).await {
+                Ok(files) => {
+                    for file in files {
+                        ui.success(&format!("Generated {}.", &file));
+                    }
+                    ui.success("Registered the service in ./grpc/src/services/mod.rs.");
+                }
+                Err(e) => ui.error("Could not generate gRPC service!", e),
+            }
+        }
         {% endif -%}
     }
+
+    let changes = std::mem::take(&mut *CHANGES.lock().unwrap());
+    if cli.dry_run {
+        changes.preview(&mut ui);
+        return;
+    }
+    match changes.write() {
+        Ok(changes) => {
+            for change in &changes {
+                match change.action {
+                    Action::Overwrite => ui.warning(&format!("Overwrote {}.", change.path)),
+                    Action::Skip => {
+                        ui.warning(&format!("Skipped {} as it exists already.", change.path))
+                    }
+                    _ => ui.debug(&format!("{} {}", change.action, change.path)),
+                }
+            }
+            if changes.iter().any(|change| change.action != Action::Skip) {
+                let record = Record {
+                    generator,
+                    name,
+                    changes,
+                };
+                if let Err(e) = record.save() {
+                    ui.error("Could not record the generated files for reverting!", e);
+                }
+            }
+        }
+        Err(e) => ui.error("Could not write the generated files!", e),
+    }
+}
+
+fn revert(ui: &mut UI, generator: &str, name: Option<&str>, dry_run: bool, force: bool) {
+    let invocation = match name {
+        Some(name) => format!("{} {}", generator, name),
+        None => String::from(generator),
+    };
+    ui.info(&format!("Reverting generate {}…", invocation));
+
+    let (record_path, record) = match Record::find_latest(generator, name) {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            ui.error(
+                "Could not revert the generator!",
+                anyhow!(
+                    "There is no record of running generate {} in ./.gerust/generated!",
+                    invocation
+                ),
+            );
+            return;
+        }
+        Err(e) => {
+            ui.error("Could not revert the generator!", e);
+            return;
+        }
+    };
+
+    let mut changes = Changes::new(Existing::Fail);
+    match changes.revert(&record, force) {
+        Ok(kept) => {
+            for path in kept {
+                ui.warning(&format!(
+                    "Kept {} as it was changed since it was generated – revert it manually or pass --force to discard the changes!",
+                    path
+                ));
+            }
+        }
+        Err(e) => {
+            ui.error("Could not revert the generator!", e);
+            return;
+        }
+    }
+    if dry_run {
+        changes.preview(ui);
+        return;
+    }
+
+    match changes.write() {
+        Ok(changes) => {
+            for change in changes {
+                match change.action {
+                    Action::Delete => ui.success(&format!("Deleted {}.", change.path)),
+                    _ => ui.success(&format!("Restored {}.", change.path)),
+                }
+            }
+            let _ = fs::remove_file(&record_path);
+        }
+        Err(e) => ui.error("Could not revert the generator!", e),
+    }
 }
 
 async fn generate_middleware(name: String) -> Result<String, anyhow::Error> {
     let name = to_snake_case(&name).to_lowercase();
+    let web_crate_name = get_member_package_name("web")?;
+    let web_crate_name = to_snake_case(&web_crate_name);
+    let test_crate_name = get_member_package_name("test")?;
+    let test_crate_name = to_snake_case(&test_crate_name);
+    let has_db = has_db();
 
-    let template = get_liquid_template("middleware/file.rs")?;
     let variables = liquid::object!({
-        "name": name
+        "name": name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "has_db": has_db,
     });
-    let output = template
+    let output = get_liquid_template("middleware/file.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("middleware/test.rs")?
         .render(&variables)
         .context("Failed to render Liquid template")?;
 
     let file_path = format!("./web/src/middlewares/{}.rs", name);
     create_project_file(&file_path, output.as_bytes())?;
+
+    let middlewares_path = "./web/src/middlewares/mod.rs";
+    let middlewares = read_project_file(middlewares_path)?;
+    let middlewares = append_to_chain(
+        &middlewares,
+        "let middlewares = ServiceBuilder::new()",
+        &format!(
+            ".layer(axum::middleware::from_fn_with_state(app_state.clone(), {0}::{0}))",
+            name
+        ),
+    )?;
+    update_project_file(middlewares_path, middlewares.as_bytes())?;
     append_to_project_file(
-        "./web/src/middlewares/mod.rs",
-        &format!("pub mod {};", name),
+        middlewares_path,
+        &format!("/// The [`{0}::{0}`] middleware\npub mod {0};", name),
+    )?;
+
+    create_project_file(
+        &format!("./web/tests/api/{}_middleware_test.rs", name),
+        test.as_bytes(),
+    )?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_middleware_test;", name),
     )?;
 
     Ok(file_path)
@@ -229,14 +713,14 @@ async fn generate_controller(name: String) -> Result<String, anyhow::Error> {
 
 async fn generate_controller_test(name: String) -> Result<String, anyhow::Error> {
     let name = to_snake_case(&name).to_lowercase();
-    let macros_crate_name = get_member_package_name("macros")?;
-    let macros_crate_name = to_snake_case(&macros_crate_name);
+    let test_crate_name = get_member_package_name("test")?;
+    let test_crate_name = to_snake_case(&test_crate_name);
     let has_db = has_db();
 
     let template = get_liquid_template("controller/minimal/test.rs")?;
     let variables = liquid::object!({
         "name": name,
-        "macros_crate_name": macros_crate_name,
+        "test_crate_name": test_crate_name,
         "has_db": has_db,
     });
     let output = template
@@ -250,26 +734,365 @@ async fn generate_controller_test(name: String) -> Result<String, anyhow::Error>
     Ok(file_path)
 }
 
+async fn generate_rate_limit(name: String, path: String) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    let routes = attach_route_layer(
+        &routes,
+        &path,
+        &format!(
+            r#"axum::middleware::from_fn_with_state(shared_app_state.rate_limiter.limit("{}"), rate_limit::enforce)"#,
+            name
+        ),
+    )?;
+    let routes = insert_import(&routes, "use crate::rate_limit;");
+    update_project_file(routes_path, format!("{}\n", routes).as_bytes())?;
+
+    append_to_project_file(
+        "./config/app.toml",
+        &format!("\n[rate_limit.limits.{}]\nrequests = 10\nwindow = 60", name),
+    )?;
+
+    Ok(name)
+}
+
+async fn generate_channel(name: String) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_channel").unwrap_or(&name).to_string();
+    let struct_name = format!("{}Channel", to_pascal_case(&name));
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let channel_path = format!("./web/src/channels/{}.rs", name);
+    let test_path = format!("./web/tests/api/{}_channel_test.rs", name);
+    for path in [&channel_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "channel_name": name,
+        "channel_struct_name": struct_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "has_db": has_db(),
+    });
+    let channel = get_liquid_template("channel/channel.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("channel/test.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    create_project_file(&channel_path, channel.as_bytes())?;
+    create_project_file(&test_path, test.as_bytes())?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_channel_test;", name),
+    )?;
+
+    let channels_path = "./web/src/channels/mod.rs";
+    let channels = read_project_file(channels_path)?;
+    let channels = append_to_function(
+        &channels,
+        "register",
+        &format!(r#"registry.register("{0}", {0}::{1});"#, name, struct_name),
+    )?;
+    update_project_file(channels_path, channels.as_bytes())?;
+    append_to_project_file(
+        channels_path,
+        &format!("/// The [`{0}::{1}`] channel\npub mod {0};", name, struct_name),
+    )?;
+
+    Ok(struct_name)
+}
+
+async fn generate_task(name: String) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_task").unwrap_or(&name).to_string();
+    let struct_name = format!("{}Task", to_pascal_case(&name));
+
+    let task_path = format!("./web/src/tasks/{}.rs", name);
+    ensure_new_file(&task_path)?;
+
+    let variables = liquid::object!({
+        "task_name": name,
+        "task_struct_name": struct_name,
+    });
+    let task = get_liquid_template("task/task.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    create_project_file(&task_path, task.as_bytes())?;
+
+    let tasks_path = "./web/src/tasks/mod.rs";
+    let tasks = read_project_file(tasks_path)?;
+    let tasks = append_to_function(
+        &tasks,
+        "register",
+        &format!("registry.register::<{}::{}>();", name, struct_name),
+    )?;
+    let tasks = insert_module(
+        &tasks,
+        &format!("/// The [`{0}::{1}`] task\npub mod {0};", name, struct_name),
+    );
+    update_project_file(tasks_path, tasks.as_bytes())?;
+
+    Ok(struct_name)
+}
+
+async fn generate_sse(name: String) -> Result<Vec<String>, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let event_struct_name = format!("{}Event", to_pascal_case(&name));
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let stream_path = format!("./web/src/controllers/{}.rs", name);
+    let test_path = format!("./web/tests/api/{}_test.rs", name);
+    for path in [&stream_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "sse_name": name,
+        "event_struct_name": event_struct_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "has_db": has_db(),
+    });
+    let stream = get_liquid_template("sse/stream.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("sse/test.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    create_project_file(&stream_path, stream.as_bytes())?;
+    append_to_project_file(
+        "./web/src/controllers/mod.rs",
+        &format!(
+            "/// An endpoint that streams {0} events via Server-Sent Events\npub mod {0};",
+            name
+        ),
+    )?;
+
+    create_project_file(&test_path, test.as_bytes())?;
+    append_to_project_file("./web/tests/api/main.rs", &format!("mod {}_test;", name))?;
+
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    let routes = register_routes(
+        &routes,
+        &format!("use crate::controllers::{};", name),
+        &[format!(
+            r#".route("/{0}/stream", crate::registry::get({0}::stream))"#,
+            name
+        )],
+    )?;
+    update_project_file(routes_path, routes.as_bytes())?;
+
+    Ok(vec![stream_path, test_path])
+}
+
+async fn generate_view(name: String) -> Result<Vec<String>, anyhow::Error> {
+    let (controller, action) = name.split_once('/').ok_or_else(|| {
+        anyhow!("The view must be given as <controller>/<action>, e.g. tasks/index!")
+    })?;
+    let controller = to_snake_case(controller).to_lowercase();
+    let action = to_snake_case(action).to_lowercase();
+    let template_name = format!("{}/{}.html", controller, action);
+    let route_path = if action == "index" {
+        format!("/{}", controller)
+    } else {
+        format!("/{}/{}", controller, action)
+    };
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let template_path = format!("./web/templates/{}", template_name);
+    let controller_path = format!("./web/src/controllers/{}.rs", controller);
+    let test_path = format!("./web/tests/api/{}_{}_test.rs", controller, action);
+    for path in [&template_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    if has_route(&routes, "get", &route_path)? {
+        return Err(anyhow!(
+            r#"A GET route for "{}" exists already, e.g. a CRUD controller's – choose a different action, e.g. {}/list!"#,
+            route_path,
+            controller
+        ));
+    }
+    let existing_controller = if Path::new(&controller_path).exists() {
+        let source = read_project_file(&controller_path)?;
+        if source.contains(&format!("fn {}(", action)) {
+            return Err(anyhow!(
+                r#"Controller "{}" already has an action {}!"#,
+                controller_path,
+                action
+            ));
+        }
+        Some(source)
+    } else {
+        None
+    };
+
+    let variables = liquid::object!({
+        "action_name": action,
+        "template_name": template_name,
+        "route_path": route_path,
+        "title": to_title_case(&format!("{}_{}", controller, action)),
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "has_db": has_db(),
+    });
+    let template = get_liquid_template("view/template.html")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let handler = get_liquid_template("view/handler.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("view/test.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    create_project_file(&template_path, template.as_bytes())?;
+
+    let mut files = vec![template_path];
+    match existing_controller {
+        Some(source) => {
+            // only import the names the handler uses that the controller does not use already
+            let imports = [
+                ("context", "use crate::templates::context;"),
+                ("SharedAppState", "use crate::state::SharedAppState;"),
+                ("State", "use axum::extract::State;"),
+                ("Template", "use crate::templates::Template;"),
+            ];
+            let source = imports
+                .iter()
+                .rev()
+                .filter(|(name, _)| !contains_word(&source, name))
+                .fold(source.clone(), |result, (_, import)| {
+                    insert_import(&result, import)
+                });
+            update_project_file(
+                &controller_path,
+                format!("{}\n\n{}", source.trim_end(), handler).as_bytes(),
+            )?;
+        }
+        None => {
+            let imports = [
+                "use crate::state::SharedAppState;",
+                "use crate::templates::{context, Template};",
+                "use axum::extract::State;",
+            ];
+            create_project_file(
+                &controller_path,
+                format!("{}\n\n{}", imports.join("\n"), handler).as_bytes(),
+            )?;
+            append_to_project_file(
+                "./web/src/controllers/mod.rs",
+                &format!("/// The {0} controller's views\npub mod {0};", controller),
+            )?;
+            files.push(controller_path);
+        }
+    }
+
+    create_project_file(&test_path, test.as_bytes())?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_{}_test;", controller, action),
+    )?;
+    files.push(test_path);
+
+    let routes = register_routes(
+        &routes,
+        &format!("use crate::controllers::{};", controller),
+        &[format!(
+            r#".route("{}", crate::registry::get({}::{}))"#,
+            route_path, controller, action
+        )],
+    )?;
+    update_project_file(routes_path, routes.as_bytes())?;
+
+    Ok(files)
+}
+
 {% if template_type != "minimal" -%}
-async fn generate_migration(name: String) -> Result<String, anyhow::Error> {
+async fn generate_migration(name: String, contents: &str) -> Result<String, anyhow::Error> {
     let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
     let file_name = format!("{}__{}.sql", timestamp.as_secs(), name);
     let path = format!("./db/migrations/{}", file_name);
-    create_project_file(&path, "".as_bytes())?;
+    create_project_file(&path, contents.as_bytes())?;
 
     Ok(path)
 }
 
+async fn generate_data_migration(name: String) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_migration").unwrap_or(&name).to_string();
+    let struct_name = format!("{}Migration", to_pascal_case(&name));
+    let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+
+    let migration_path = format!("./db/src/data_migrations/{}.rs", name);
+    ensure_new_file(&migration_path)?;
+
+    let variables = liquid::object!({
+        "migration_name": name,
+        "migration_struct_name": struct_name,
+        "migration_version": timestamp.as_secs(),
+    });
+    let migration = get_liquid_template("data-migration/data_migration.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    create_project_file(&migration_path, migration.as_bytes())?;
+
+    let migrations_path = "./db/src/data_migrations/mod.rs";
+    let migrations = read_project_file(migrations_path)?;
+    let migrations = append_to_function(
+        &migrations,
+        "register",
+        &format!("registry.register::<{}::{}>();", name, struct_name),
+    )?;
+    let migrations = insert_module(
+        &migrations,
+        &format!("/// The [`{0}::{1}`] data migration\npub mod {0};", name, struct_name),
+    );
+    update_project_file(migrations_path, migrations.as_bytes())?;
+
+    Ok(struct_name)
+}
+
+async fn generate_sessions_migration() -> Result<String, anyhow::Error> {
+    let migration = get_liquid_template("sessions/migration.sql")?
+        .render(&liquid::object!({}))
+        .context("Failed to render Liquid template")?;
+
+    generate_migration(String::from("create_sessions_table"), &migration).await
+}
+
+async fn generate_rate_limits_migration() -> Result<String, anyhow::Error> {
+    let migration = get_liquid_template("rate-limits/migration.sql")?
+        .render(&liquid::object!({}))
+        .context("Failed to render Liquid template")?;
+
+    generate_migration(String::from("create_rate_limits_table"), &migration).await
+}
+
 async fn generate_entity(name: String) -> Result<String, anyhow::Error> {
     let name = to_singular(&name).to_lowercase();
     let name_plural = to_plural(&name);
     let struct_name = to_title_case(&name);
 
+    let macros_crate_name = to_snake_case(&get_member_package_name("macros")?);
+
     let template = get_liquid_template("entity/file.rs")?;
     let variables = liquid::object!({
         "entity_struct_name": struct_name,
         "entity_singular_name": name,
         "entity_plural_name": name_plural,
+        "macros_crate_name": macros_crate_name,
     });
     let output = template
         .render(&variables)
@@ -283,10 +1106,244 @@ async fn generate_entity(name: String) -> Result<String, anyhow::Error> {
         "./db/src/entities/mod.rs",
         &format!("pub mod {};", name_plural),
     )?;
+    register_factory(&name, &name_plural, &struct_name)?;
+    register_console_entity(&name_plural)?;
 
     Ok(struct_name)
 }
 
+async fn generate_entity_with_fields(
+    name: String,
+    fields: Vec<String>,
+    soft_delete: bool,
+) -> Result<Vec<String>, anyhow::Error> {
+    let fields = fields
+        .iter()
+        .map(|field| parse_field(field))
+        .collect::<Result<Vec<_>, _>>()?;
+    let name = to_singular(&to_snake_case(&name)).to_lowercase();
+    let name_plural = to_plural(&name);
+    let struct_name = to_pascal_case(&name);
+
+    let entity_path = format!("./db/src/entities/{}.rs", name_plural);
+    ensure_new_file(&entity_path)?;
+
+    let mut variables = entity_variables(&name, &fields, soft_delete);
+    variables.extend(liquid::object!({
+        "macros_crate_name": to_snake_case(&get_member_package_name("macros")?),
+    }));
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    let migration_path = generate_migration(
+        format!("create_{}_table", name_plural),
+        &render("crud/migration.sql")?,
+    )
+    .await?;
+
+    create_project_file(&entity_path, render("crud/entity.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./db/src/entities/mod.rs",
+        &format!(
+            "/// All functionality related to the [`{0}::{1}`] entity\npub mod {0};",
+            name_plural, struct_name
+        ),
+    )?;
+    register_factory(&name, &name_plural, &struct_name)?;
+    register_console_entity(&name_plural)?;
+
+    let mut files = vec![migration_path, entity_path];
+    // projects without the worker crate purge deleted records by calling the entity's purge function themselves
+    if soft_delete && get_member_package_name("worker").is_ok() {
+        files.extend(generate_purge_job(&name, &name_plural, &struct_name)?);
+    }
+    Ok(files)
+}
+
+/// Generates a scheduled job that purges the soft-deleted records of an entity (see the entity's `purge` function) and registers it with the worker.
+fn generate_purge_job(
+    name: &str,
+    name_plural: &str,
+    struct_name: &str,
+) -> Result<Vec<String>, anyhow::Error> {
+    let job_name = format!("purge_{}", name_plural);
+    let job_struct_name = format!("Purge{}Job", to_pascal_case(name_plural));
+
+    let job_path = format!("./worker/src/jobs/{}.rs", job_name);
+    let test_path = format!("./worker/tests/jobs/{}_test.rs", job_name);
+    for path in [&job_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "job_name": job_name,
+        "job_struct_name": job_struct_name,
+        "entity_struct_name": struct_name,
+        "entity_singular_name": name,
+        "entity_plural_name": name_plural,
+        "clock_crate_name": to_snake_case(&get_member_package_name("clock")?),
+        "db_crate_name": to_snake_case(&get_member_package_name("db")?),
+        "worker_crate_name": to_snake_case(&get_member_package_name("worker")?),
+    });
+    let job = get_liquid_template("job/purge.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("job/purge_test.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    create_project_file(&job_path, job.as_bytes())?;
+    create_project_file(&test_path, test.as_bytes())?;
+    append_to_project_file(
+        "./worker/tests/jobs/main.rs",
+        &format!("mod {}_test;", job_name),
+    )?;
+    register_job(&job_name, &job_struct_name, true)?;
+
+    Ok(vec![job_path, test_path])
+}
+
+/// Adds a function returning the factory for the entity to the factories module (`db/src/test_helpers/factories.rs`) so that tests can create records via e.g. `factory::post().create(&db_pool)`.
+fn register_factory(name: &str, name_plural: &str, struct_name: &str) -> Result<(), anyhow::Error> {
+    let template = get_liquid_template("factory/function.rs")?;
+    let variables = liquid::object!({
+        "entity_struct_name": struct_name,
+        "entity_singular_name": name,
+        "entity_plural_name": name_plural,
+    });
+    let output = template
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    append_to_project_file(
+        "./db/src/test_helpers/factories.rs",
+        output.trim_end(),
+    )
+}
+
+/// Registers the entity's functions as commands of the interactive console (`web/src/console.rs`) so that e.g. `posts.load <id>` can be run in `cargo console`.
+fn register_console_entity(name_plural: &str) -> Result<(), anyhow::Error> {
+    let console_path = "./web/src/console.rs";
+    let console = read_project_file(console_path)?;
+    let console = append_to_function(
+        &console,
+        "register",
+        &format!("entity!(registry, {});", name_plural),
+    )?;
+    update_project_file(console_path, console.as_bytes())
+}
+
+/// Builds the variables for the entity and migration templates from the entity's name and fields.
+fn entity_variables(name: &str, fields: &[Field], soft_delete: bool) -> liquid::Object {
+    let columns: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+    let insert_placeholders: Vec<String> = (1..=fields.len()).map(|i| format!("${}", i)).collect();
+    let update_assignments: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| format!("{} = ${}", column, i + 1))
+        .collect();
+    // prefer a required field for testing validation errors as optional fields can simply be left empty
+    let invalid_field = fields
+        .iter()
+        .filter(|field| field.field_type.validation().is_some())
+        .min_by_key(|field| field.optional);
+    let invalid_value = match invalid_field {
+        Some(field) if field.optional => r#"Some(String::from(""))"#,
+        Some(_) => r#"String::from("")"#,
+        None => "",
+    };
+    // floats are not compared reliably as their text representations may differ between Rust and PostgreSQL
+    let filter_field = fields
+        .iter()
+        .find(|field| !field.optional && field.field_type != FieldType::Float);
+    let has_string_fields = fields.iter().any(|field| !field.is_copy());
+    // optional fields are not sortable as cursor-based pagination skips rows with NULL values in the sort column
+    let sortable_columns: Vec<String> = std::iter::once("id")
+        .chain(
+            fields
+                .iter()
+                .filter(|field| !field.optional)
+                .map(|field| field.name.as_str()),
+        )
+        .map(|column| format!(r#""{}""#, column))
+        .collect();
+    let filterable_columns: Vec<String> = columns
+        .iter()
+        .map(|column| format!(r#""{}""#, column))
+        .collect();
+    // the JSON representation of a record with the fields' example values, with keys sorted as in snapshots
+    let mut snapshot_fields: Vec<(String, String)> = std::iter::once((
+        String::from("id"),
+        String::from(r#""[uuid]""#),
+    ))
+    .chain(
+        fields
+            .iter()
+            .map(|field| (field.name.clone(), field.example().1)),
+    )
+    .collect();
+    snapshot_fields.sort();
+    let snapshot_fields: Vec<liquid::Object> = snapshot_fields
+        .into_iter()
+        .map(|(name, json)| liquid::object!({ "name": name, "json": json }))
+        .collect();
+    let field_objects: Vec<liquid::Object> = fields
+        .iter()
+        .map(|field| {
+            let is_float = field.field_type == FieldType::Float;
+            // factories create associated records for required associations and use sequences for unique strings so that records don't collide
+            let factory = match field.association() {
+                Some(association) if !field.optional => format!(
+                    "association = crate::test_helpers::factories::{}",
+                    association
+                ),
+                _ if field.unique && !field.is_copy() => {
+                    format!(r#"sequence = "{} {}""#, field.name, "{}")
+                }
+                _ => String::new(),
+            };
+            liquid::object!({
+                "name": field.name,
+                "rust_type": field.rust_type(),
+                "sql_type": field.field_type.sql_type(),
+                "optional": field.optional,
+                "unique": field.unique,
+                "references": field.association().map(to_plural).unwrap_or_default(),
+                "dummy": field.dummy().unwrap_or_default(),
+                "factory": factory,
+                "example": field.example().0.unwrap_or_default(),
+                "validation": field.field_type.validation().unwrap_or_default(),
+                "borrow": if field.is_copy() { "" } else { "&" },
+                // floats may lose precision when being serialized to and deserialized from JSON
+                "matcher": if is_float { "approx_eq" } else { "eq" },
+                "unwrap": if is_float && field.optional { ".unwrap_or_default()" } else { "" },
+            })
+        })
+        .collect();
+
+    liquid::object!({
+        "entity_struct_name": to_pascal_case(name),
+        "entity_singular_name": name,
+        "entity_plural_name": to_plural(name),
+        "fields": field_objects,
+        "snapshot_fields": snapshot_fields,
+        "columns": columns.join(", "),
+        "insert_placeholders": insert_placeholders.join(", "),
+        "update_assignments": update_assignments.join(", "),
+        "sortable_columns": sortable_columns.join(", "),
+        "filterable_columns": filterable_columns.join(", "),
+        "id_placeholder": fields.len() + 1,
+        "has_string_fields": has_string_fields,
+        "invalid_field": invalid_field.map(|field| field.name.clone()).unwrap_or_default(),
+        "invalid_value": invalid_value,
+        "filter_field": filter_field.map(|field| field.name.clone()).unwrap_or_default(),
+        "soft_delete": soft_delete,
+    })
+}
+
 async fn generate_entity_test_helper(name: String) -> Result<String, anyhow::Error> {
     let name = to_singular(&name).to_lowercase();
     let name_plural = to_plural(&name);
@@ -321,16 +1378,13 @@ async fn generate_crud_controller(name: String) -> Result<String, anyhow::Error>
     let struct_name = to_title_case(&name_singular);
     let db_crate_name = get_member_package_name("db")?;
     let db_crate_name = to_snake_case(&db_crate_name);
-    let macros_crate_name = get_member_package_name("macros")?;
-    let macros_crate_name = to_snake_case(&macros_crate_name);
 
     let template = get_liquid_template("controller/crud/controller.rs")?;
     let variables = liquid::object!({
         "entity_struct_name": struct_name,
         "entity_singular_name": name_singular,
         "entity_plural_name": name_plural,
-        "db_crate_name": db_crate_name,
-        "macros_crate_name": macros_crate_name
+        "db_crate_name": db_crate_name
     });
     let output = template
         .render(&variables)
@@ -353,8 +1407,8 @@ async fn generate_crud_controller_test(name: String) -> Result<String, anyhow::E
     let struct_name = to_title_case(&name_singular);
     let db_crate_name = get_member_package_name("db")?;
     let db_crate_name = to_snake_case(&db_crate_name);
-    let macros_crate_name = get_member_package_name("macros")?;
-    let macros_crate_name = to_snake_case(&macros_crate_name);
+    let test_crate_name = get_member_package_name("test")?;
+    let test_crate_name = to_snake_case(&test_crate_name);
 
     let template = get_liquid_template("controller/crud/test.rs")?;
     let variables = liquid::object!({
@@ -362,7 +1416,7 @@ async fn generate_crud_controller_test(name: String) -> Result<String, anyhow::E
         "entity_singular_name": name_singular,
         "entity_plural_name": name_plural,
         "db_crate_name": db_crate_name,
-        "macros_crate_name": macros_crate_name
+        "test_crate_name": test_crate_name
     });
     let output = template
         .render(&variables)
@@ -374,6 +1428,571 @@ async fn generate_crud_controller_test(name: String) -> Result<String, anyhow::E
 
     Ok(file_path)
 }
+
+async fn generate_crud(name: String, fields: Vec<String>) -> Result<Vec<String>, anyhow::Error> {
+    let fields = fields
+        .iter()
+        .map(|field| parse_field(field))
+        .collect::<Result<Vec<_>, _>>()?;
+    let name = to_singular(&to_snake_case(&name)).to_lowercase();
+    let name_plural = to_plural(&name);
+    let struct_name = to_pascal_case(&name);
+    let db_crate_name = to_snake_case(&get_member_package_name("db")?);
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+    let macros_crate_name = to_snake_case(&get_member_package_name("macros")?);
+
+    let entity_path = format!("./db/src/entities/{}.rs", name_plural);
+    let controller_path = format!("./web/src/controllers/{}.rs", name_plural);
+    let test_path = format!("./web/tests/api/{}_test.rs", name_plural);
+    for path in [&entity_path, &controller_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let mut variables = entity_variables(&name, &fields, false);
+    variables.extend(liquid::object!({
+        "db_crate_name": db_crate_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "macros_crate_name": macros_crate_name,
+    }));
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    let migration_path = generate_migration(
+        format!("create_{}_table", name_plural),
+        &render("crud/migration.sql")?,
+    )
+    .await?;
+
+    create_project_file(&entity_path, render("crud/entity.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./db/src/entities/mod.rs",
+        &format!(
+            "/// All functionality related to the [`{0}::{1}`] entity\npub mod {0};",
+            name_plural, struct_name
+        ),
+    )?;
+    register_factory(&name, &name_plural, &struct_name)?;
+    register_console_entity(&name_plural)?;
+
+    create_project_file(&controller_path, render("crud/controller.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/src/controllers/mod.rs",
+        &format!(
+            "/// All endpoints for managing {0}\npub mod {0};",
+            name_plural
+        ),
+    )?;
+
+    create_project_file(&test_path, render("crud/test.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_test;", name_plural),
+    )?;
+
+    // the snapshots of the responses the generated tests assert (see the test crate's assert_json_snapshot_response macro)
+    let snapshot = render("crud/snapshot.snap")?;
+    let snapshot_paths: Vec<String> = ["create_success", "read_one_success"]
+        .iter()
+        .map(|test| {
+            format!(
+                "./web/tests/api/snapshots/api__{}_test__{}.snap",
+                name_plural, test
+            )
+        })
+        .collect();
+    for snapshot_path in &snapshot_paths {
+        create_project_file(snapshot_path, snapshot.as_bytes())?;
+    }
+
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    let routes = register_routes(
+        &routes,
+        &format!("use crate::controllers::{};", name_plural),
+        &[
+            format!(
+                r#".route("/{0}", crate::registry::get({0}::read_all).post({0}::create))"#,
+                name_plural
+            ),
+            format!(
+                r#".route("/{0}/:id", crate::registry::get({0}::read_one).put({0}::update).delete({0}::delete))"#,
+                name_plural
+            ),
+        ],
+    )?;
+    update_project_file(routes_path, routes.as_bytes())?;
+
+    let openapi_path = "./web/src/openapi.rs";
+    let openapi = read_project_file(openapi_path)?;
+    let openapi = register_api_doc(
+        &openapi,
+        &format!("use crate::controllers::{};", name_plural),
+        &format!("{}::ApiDoc::openapi()", name_plural),
+    )?;
+    update_project_file(openapi_path, openapi.as_bytes())?;
+
+    let mut files = vec![migration_path, entity_path, controller_path, test_path];
+    files.extend(snapshot_paths);
+    Ok(files)
+}
+
+async fn generate_job(name: String, schedule: Option<String>) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_job").unwrap_or(&name).to_string();
+    let struct_name = format!("{}Job", to_pascal_case(&name));
+    let worker_crate_name = get_member_package_name("worker")
+        .context("The project does not contain the worker crate that performs jobs!")?;
+    let worker_crate_name = to_snake_case(&worker_crate_name);
+
+    let job_path = format!("./worker/src/jobs/{}.rs", name);
+    ensure_new_file(&job_path)?;
+
+    let variables = liquid::object!({
+        "job_name": name,
+        "job_struct_name": struct_name,
+        "worker_crate_name": worker_crate_name,
+        "schedule": schedule,
+    });
+    let job = get_liquid_template("job/job.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let test = get_liquid_template("job/test.rs")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    create_project_file(&job_path, job.as_bytes())?;
+    create_project_file(
+        &format!("./worker/tests/jobs/{}_test.rs", name),
+        test.as_bytes(),
+    )?;
+    append_to_project_file(
+        "./worker/tests/jobs/main.rs",
+        &format!("mod {}_test;", name),
+    )?;
+    register_job(&name, &struct_name, schedule.is_some())?;
+
+    Ok(struct_name)
+}
+
+/// Registers a job with the worker in `worker/src/jobs/mod.rs` and declares its module there.
+fn register_job(name: &str, struct_name: &str, scheduled: bool) -> Result<(), anyhow::Error> {
+    let jobs_path = "./worker/src/jobs/mod.rs";
+    let jobs = read_project_file(jobs_path)?;
+    let jobs = append_to_function(
+        &jobs,
+        "register",
+        &format!(
+            "registry.{}::<{}::{}>();",
+            if scheduled {
+                "register_scheduled"
+            } else {
+                "register"
+            },
+            name,
+            struct_name
+        ),
+    )?;
+    update_project_file(jobs_path, jobs.as_bytes())?;
+    append_to_project_file(
+        jobs_path,
+        &format!("/// The [`{0}::{1}`] job\npub mod {0};", name, struct_name),
+    )
+}
+
+async fn generate_mailer(name: String) -> Result<String, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_email").unwrap_or(&name).to_string();
+    let struct_name = format!("{}Email", to_pascal_case(&name));
+    let mailer_crate_name = get_member_package_name("mailer")
+        .context("The project does not contain the mailer crate that sends emails!")?;
+    let mailer_crate_name = to_snake_case(&mailer_crate_name);
+
+    let email_path = format!("./mailer/src/emails/{}.rs", name);
+    ensure_new_file(&email_path)?;
+
+    let variables = liquid::object!({
+        "email_name": name,
+        "email_struct_name": struct_name,
+        "email_subject": to_title_case(&name),
+        "mailer_crate_name": mailer_crate_name,
+    });
+    for (template, path) in [
+        ("mailer/email.rs", email_path.clone()),
+        ("mailer/email.html", format!("./mailer/templates/{}.html", name)),
+        ("mailer/email.txt", format!("./mailer/templates/{}.txt", name)),
+        ("mailer/test.rs", format!("./mailer/tests/emails/{}_test.rs", name)),
+    ] {
+        let contents = get_liquid_template(template)?
+            .render(&variables)
+            .context("Failed to render Liquid template")?;
+        create_project_file(&path, contents.as_bytes())?;
+    }
+    append_to_project_file(
+        "./mailer/tests/emails/main.rs",
+        &format!("mod {}_test;", name),
+    )?;
+    append_to_project_file(
+        "./mailer/src/emails/mod.rs",
+        &format!("/// The [`{0}::{1}`] email\npub mod {0};", name, struct_name),
+    )?;
+
+    Ok(struct_name)
+}
+
+async fn generate_auth() -> Result<Vec<String>, anyhow::Error> {
+    let db_crate_name = to_snake_case(&get_member_package_name("db")?);
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let entity_path = "./db/src/entities/users.rs";
+    let controller_path = "./web/src/controllers/auth.rs";
+    let middleware_path = "./web/src/middlewares/auth.rs";
+    let test_path = "./web/tests/api/auth_test.rs";
+    if Path::new(entity_path).exists() {
+        return Err(anyhow!(
+            r#"Authentication can only be generated for projects without a users entity, e.g. projects created with the default template – "{}" exists already!"#,
+            entity_path
+        ));
+    }
+    for path in [entity_path, controller_path, middleware_path, test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "db_crate_name": db_crate_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+    });
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    let migration_path = generate_migration(
+        String::from("create_users_table"),
+        &render("auth/migration.sql")?,
+    )
+    .await?;
+
+    create_project_file(entity_path, render("auth/entity.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./db/src/entities/mod.rs",
+        "/// All functionality related to the [`users::User`] entity\npub mod users;",
+    )?;
+
+    let manifest_path = "./db/Cargo.toml";
+    let manifest = read_project_file(manifest_path)?;
+    let manifest = add_dependency(
+        &manifest,
+        "argon2",
+        r#"{ version = "0.5", features = ["std"] }"#,
+    )?;
+    let manifest = add_dependency(&manifest, "sha2", r#""0.10""#)?;
+    update_project_file(manifest_path, manifest.as_bytes())?;
+
+    create_project_file(middleware_path, render("auth/middleware.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/src/middlewares/mod.rs",
+        "/// Authentication via sessions and API tokens (see [`auth::CurrentUser`])\npub mod auth;",
+    )?;
+
+    create_project_file(controller_path, render("auth/controller.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/src/controllers/mod.rs",
+        "/// All endpoints for registering, signing in, and signing out users\npub mod auth;",
+    )?;
+
+    create_project_file(test_path, render("auth/test.rs")?.as_bytes())?;
+    append_to_project_file("./web/tests/api/main.rs", "mod auth_test;")?;
+
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    let routes = register_routes(
+        &routes,
+        "use crate::controllers::auth;\nuse crate::middlewares::auth::require_user;",
+        &[
+            String::from(r#".route("/auth/register", crate::registry::post(auth::register))"#),
+            String::from(r#".route("/auth/login", crate::registry::post(auth::login))"#),
+            String::from(r#".route("/auth/logout", crate::registry::post(auth::logout))"#),
+            String::from(r#".route("/auth/tokens", crate::registry::post(auth::create_token))"#),
+            String::from(
+                r#".route("/auth/me", crate::registry::get(auth::me).route_layer(axum::middleware::from_fn_with_state(shared_app_state.clone(), require_user)))"#,
+            ),
+        ],
+    )?;
+    update_project_file(routes_path, routes.as_bytes())?;
+
+    Ok(vec![
+        migration_path,
+        String::from(entity_path),
+        String::from(middleware_path),
+        String::from(controller_path),
+        String::from(test_path),
+    ])
+}
+
+async fn generate_uploader(name: String, entity: String) -> Result<Vec<String>, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let entity = to_singular(&to_snake_case(&entity)).to_lowercase();
+    let entity_plural = to_plural(&entity);
+    let db_crate_name = to_snake_case(&get_member_package_name("db")?);
+
+    let entity_path = format!("./db/src/entities/{}.rs", entity_plural);
+    if !Path::new(&entity_path).exists() {
+        return Err(anyhow!(
+            r#"Entity file "{}" does not exist – generate the entity first!"#,
+            entity_path
+        ));
+    }
+    // entities generated with --soft-delete only list records that have not been deleted
+    let soft_delete = read_project_file(&entity_path)?
+        .contains(r#"const SCOPE: Option<&'static str> = Some("deleted_at IS NULL");"#);
+    let controller_name = format!("{}_{}", entity, name);
+    let controller_path = format!("./web/src/controllers/{}.rs", controller_name);
+    ensure_new_file(&controller_path)?;
+
+    let variables = liquid::object!({
+        "uploader_name": name,
+        "entity_singular_name": entity,
+        "entity_plural_name": entity_plural,
+        "db_crate_name": db_crate_name,
+        "soft_delete": soft_delete,
+    });
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    let migration_path = generate_migration(
+        format!("add_{}_to_{}", name, entity_plural),
+        &render("uploader/migration.sql")?,
+    )
+    .await?;
+
+    append_to_project_file(&entity_path, &render("uploader/entity.rs")?)?;
+
+    create_project_file(&controller_path, render("uploader/controller.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/src/controllers/mod.rs",
+        &format!(
+            "/// The endpoints to upload, download, and delete the {} of {}\npub mod {};",
+            name, entity_plural, controller_name
+        ),
+    )?;
+
+    let routes_path = "./web/src/routes.rs";
+    let routes = read_project_file(routes_path)?;
+    let routes = register_routes(
+        &routes,
+        &format!("use crate::controllers::{};", controller_name),
+        &[format!(
+            r#".route("/{0}/:id/{1}", crate::registry::get({2}::show).put({2}::upload).delete({2}::delete))"#,
+            entity_plural, name, controller_name
+        )],
+    )?;
+    update_project_file(routes_path, routes.as_bytes())?;
+
+    Ok(vec![migration_path, entity_path, controller_path])
+}
+
+async fn generate_policy(name: String) -> Result<String, anyhow::Error> {
+    let name = to_singular(&to_snake_case(&name)).to_lowercase();
+    let name_plural = to_plural(&name);
+    let struct_name = to_pascal_case(&name);
+    let db_crate_name = to_snake_case(&get_member_package_name("db")?);
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let entity_path = format!("./db/src/entities/{}.rs", name_plural);
+    if !Path::new(&entity_path).exists() {
+        return Err(anyhow!(
+            r#"Entity file "{}" does not exist – generate the entity first!"#,
+            entity_path
+        ));
+    }
+    let users_path = "./db/src/entities/users.rs";
+    let users = fs::read_to_string(users_path).context(format!(
+        r#"Could not read file "{}" – policies decide for users, generate them with cargo generate auth first!"#,
+        users_path
+    ))?;
+    let policy_path = format!("./web/src/policies/{}.rs", name);
+    let test_path = format!("./web/tests/api/{}_policy_test.rs", name);
+    for path in [&policy_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "entity_struct_name": struct_name,
+        "entity_singular_name": name,
+        "entity_plural_name": name_plural,
+        "db_crate_name": db_crate_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+        "has_registration": users.contains("pub async fn register("),
+    });
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    create_project_file(&policy_path, render("policy/policy.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/src/policies/mod.rs",
+        &format!(
+            "/// The [`{0}::{1}Policy`] for {2}\npub mod {0};",
+            name, struct_name, name_plural
+        ),
+    )?;
+
+    create_project_file(&test_path, render("policy/test.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_policy_test;", name),
+    )?;
+
+    Ok(format!("{}Policy", struct_name))
+}
+
+async fn generate_graphql_type(name: String) -> Result<Vec<String>, anyhow::Error> {
+    let name = to_singular(&to_snake_case(&name)).to_lowercase();
+    let name_plural = to_plural(&name);
+    let struct_name = to_pascal_case(&name);
+    let db_crate_name = to_snake_case(&get_member_package_name("db")?);
+    let web_crate_name = to_snake_case(&get_member_package_name("web")?);
+    let test_crate_name = to_snake_case(&get_member_package_name("test")?);
+
+    let graphql_path = "./web/src/graphql/mod.rs";
+    let graphql = fs::read_to_string(graphql_path).context(format!(
+        r#"Could not read file "{}" – the project does not have a GraphQL API, generate it with gerust --graphql!"#,
+        graphql_path
+    ))?;
+    let entity_path = format!("./db/src/entities/{}.rs", name_plural);
+    if !Path::new(&entity_path).exists() {
+        return Err(anyhow!(
+            r#"Entity file "{}" does not exist – generate the entity first!"#,
+            entity_path
+        ));
+    }
+    let type_path = format!("./web/src/graphql/{}.rs", name_plural);
+    let test_path = format!("./web/tests/api/{}_graphql_test.rs", name_plural);
+    for path in [&type_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "entity_struct_name": struct_name,
+        "entity_singular_name": name,
+        "entity_plural_name": name_plural,
+        "graphql_singular_name": to_camel_case(&name),
+        "graphql_plural_name": to_camel_case(&name_plural),
+        "db_crate_name": db_crate_name,
+        "web_crate_name": web_crate_name,
+        "test_crate_name": test_crate_name,
+    });
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    create_project_file(&type_path, render("graphql-type/type.rs")?.as_bytes())?;
+    create_project_file(&test_path, render("graphql-type/test.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./web/tests/api/main.rs",
+        &format!("mod {}_graphql_test;", name_plural),
+    )?;
+
+    let graphql = append_to_tuple_struct(
+        &graphql,
+        "Query",
+        &format!("{}::{}Query", name_plural, struct_name),
+    )?;
+    let graphql = append_to_chain(
+        &graphql,
+        "let builder = Schema::build(",
+        &format!(".data({}::loader(db_pool))", name_plural),
+    )?;
+    update_project_file(graphql_path, graphql.as_bytes())?;
+    append_to_project_file(
+        graphql_path,
+        &format!(
+            "/// The GraphQL object and queries for {}\npub mod {};",
+            name_plural, name_plural
+        ),
+    )?;
+
+    Ok(vec![type_path, test_path])
+}
+
+async fn generate_grpc_service(name: String) -> Result<Vec<String>, anyhow::Error> {
+    let name = to_snake_case(&name).to_lowercase();
+    let name = name.strip_suffix("_service").unwrap_or(&name).to_string();
+    let trait_name = to_pascal_case(&name);
+    let struct_name = format!("{}Service", trait_name);
+    let grpc_crate_name = get_member_package_name("grpc").context(
+        "The project does not contain the gRPC crate – generate the project with gerust --grpc!",
+    )?;
+    let grpc_crate_name = to_snake_case(&grpc_crate_name);
+
+    let proto_path = format!("./grpc/proto/{}.proto", name);
+    let service_path = format!("./grpc/src/services/{}.rs", name);
+    let test_path = format!("./grpc/tests/services/{}_test.rs", name);
+    for path in [&proto_path, &service_path, &test_path] {
+        ensure_new_file(path)?;
+    }
+
+    let variables = liquid::object!({
+        "service_name": name,
+        "service_trait_name": trait_name,
+        "service_struct_name": struct_name,
+        "grpc_crate_name": grpc_crate_name,
+    });
+    let render = |path: &str| -> Result<String, anyhow::Error> {
+        get_liquid_template(path)?
+            .render(&variables)
+            .context("Failed to render Liquid template")
+    };
+
+    create_project_file(
+        &proto_path,
+        render("grpc-service/service.proto")?.as_bytes(),
+    )?;
+    create_project_file(&service_path, render("grpc-service/service.rs")?.as_bytes())?;
+    create_project_file(&test_path, render("grpc-service/test.rs")?.as_bytes())?;
+    append_to_project_file(
+        "./grpc/tests/services/main.rs",
+        &format!("mod {}_test;", name),
+    )?;
+
+    let services_path = "./grpc/src/services/mod.rs";
+    let services = read_project_file(services_path)?;
+    let services = append_to_function(
+        &services,
+        "register",
+        &format!(
+            "routes.add_service({}::{}::server(app_state));",
+            name, struct_name
+        ),
+    )?;
+    update_project_file(services_path, services.as_bytes())?;
+    append_to_project_file(
+        services_path,
+        &format!(
+            "/// The [`{0}::{1}`] service\npub mod {0};",
+            name, struct_name
+        ),
+    )?;
+
+    Ok(vec![proto_path, service_path, test_path])
+}
 {% endif -%}
 
 fn get_liquid_template(path: &str) -> Result<Template, anyhow::Error> {
@@ -393,34 +2012,37 @@ fn get_liquid_template(path: &str) -> Result<Template, anyhow::Error> {
 }
 
 fn create_project_file(path: &str, contents: &[u8]) -> Result<(), anyhow::Error> {
-    let mut file = File::create(path).context(format!(r#"Could not create file "{}""#, path))?;
-    file.write_all(contents)
-        .context(format!(r#"Could not write file "{}""#, path))?;
+    CHANGES
+        .lock()
+        .unwrap()
+        .create(path, String::from_utf8_lossy(contents).into_owned())
+}
 
-    Ok(())
+fn update_project_file(path: &str, contents: &[u8]) -> Result<(), anyhow::Error> {
+    CHANGES
+        .lock()
+        .unwrap()
+        .update(path, String::from_utf8_lossy(contents).into_owned())
 }
 
 fn append_to_project_file(path: &str, contents: &str) -> Result<(), anyhow::Error> {
-    let file_contents =
-        fs::read_to_string(path).context(format!(r#"Could not read file "{}"!"#, path))?;
-    let file_contents = file_contents.trim();
-
-    let mut options = OpenOptions::new();
-    options.write(true);
-
-    if file_contents.is_empty() {
-        options.truncate(true);
-    } else {
-        options.append(true);
-    }
+    CHANGES.lock().unwrap().append(path, contents)
+}
 
-    let mut file = options
-        .open(path)
-        .context(format!(r#"Could not open file "{}"!"#, path))?;
+fn read_project_file(path: &str) -> Result<String, anyhow::Error> {
+    CHANGES.lock().unwrap().read(path)
+}
 
-    writeln!(file, "{}", contents).context(format!(r#"Failed to append to file "{}"!"#, path))?;
+fn ensure_new_file(path: &str) -> Result<(), anyhow::Error> {
+    CHANGES.lock().unwrap().ensure_creatable(path)
+}
 
-    Ok(())
+fn contains_word(source: &str, word: &str) -> bool {
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    source.match_indices(word).any(|(i, _)| {
+        !source[..i].ends_with(is_ident_char)
+            && !source[i + word.len()..].starts_with(is_ident_char)
+    })
 }
 
 fn has_db() -> bool {
@@ -0,0 +1,314 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use {{crate_name}}_cli::util::{sql::format_table, ui::UI};
+use {{crate_name}}_config::{load_env_files, parse_env, read_config, Config, Environment};
+use sqlx::postgres::{PgConnectOptions, PgConnection};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::types::Uuid;
+use sqlx::{ConnectOptions, Row};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[tokio::main]
+async fn main() {
+    cli().await;
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to inspect and manage the queue of background jobs.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(short, long, global = true, help = "Choose the environment (development, test, production, or a custom one configured in config/environments).", value_parser = parse_env, default_value = "development")]
+    env: Environment,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Load env vars from this file instead of the environment's default .env file."
+    )]
+    env_file: Option<PathBuf>,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+/// The status of a job in the queue (see the worker crate's `queue::JobStatus`).
+#[derive(Clone, Copy, ValueEnum)]
+enum Status {
+    Queued,
+    Running,
+    Dead,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Queued => "queued",
+            Status::Running => "running",
+            Status::Dead => "dead",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "List the jobs in the queue")]
+    List {
+        #[arg(long, value_enum, help = "Only list jobs with this status.")]
+        status: Option<Status>,
+        #[arg(long, help = "Only list jobs with this name.")]
+        name: Option<String>,
+    },
+    #[command(about = "Show a job including its payload and last error")]
+    Show {
+        #[arg(help = "The ID of the job.")]
+        id: Uuid,
+    },
+    #[command(
+        about = "Queue jobs to be performed again right away with all of their attempts, e.g. dead jobs or jobs stuck as running after a worker crashed"
+    )]
+    Retry {
+        #[arg(required_unless_present = "dead", help = "The IDs of the jobs.")]
+        ids: Vec<Uuid>,
+        #[arg(long, conflicts_with = "ids", help = "Retry all dead jobs.")]
+        dead: bool,
+    },
+    #[command(about = "Mark jobs as dead so that they are not retried anymore")]
+    DeadLetter {
+        #[arg(required = true, help = "The IDs of the jobs.")]
+        ids: Vec<Uuid>,
+    },
+    #[command(about = "List the upcoming runs of recurring jobs")]
+    Schedule,
+}
+
+#[allow(missing_docs)]
+async fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    let config: Result<Config, anyhow::Error> = load_env_files(&cli.env, cli.env_file.as_deref())
+        .and_then(|env_files| {
+            if env_files.is_empty() {
+                ui.debug("Not loading any env file.");
+            }
+            for env_file in env_files {
+                ui.debug(&format!("Loaded env file {}.", env_file.display()));
+            }
+            read_config(&cli.env)
+        });
+    let connection = match config {
+        Ok(config) => connect(&config).await,
+        Err(e) => Err(e),
+    };
+    let mut connection = match connection {
+        Ok(connection) => connection,
+        Err(e) => {
+            ui.error("Could not connect to database!", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command {
+        Commands::List { status, name } => {
+            match list(&mut connection, status, name.as_deref()).await {
+                Ok(lines) => {
+                    for line in lines {
+                        ui.log(&line);
+                    }
+                }
+                Err(e) => ui.error("Could not list jobs!", e),
+            }
+        }
+        Commands::Show { id } => match show(&mut connection, id).await {
+            Ok(lines) => {
+                for line in lines {
+                    ui.log(&line);
+                }
+            }
+            Err(e) => ui.error("Could not show job!", e),
+        },
+        Commands::Retry { ids, dead } => {
+            ui.info("Retrying jobs…");
+            match retry(&mut connection, &ids, dead).await {
+                Ok(count) => ui.success(&format!("Queued {} job(s) to be retried.", count)),
+                Err(e) => ui.error("Could not retry jobs!", e),
+            }
+        }
+        Commands::DeadLetter { ids } => {
+            ui.info("Marking jobs as dead…");
+            match dead_letter(&mut connection, &ids).await {
+                Ok(count) => ui.success(&format!("Marked {} job(s) as dead.", count)),
+                Err(e) => ui.error("Could not mark jobs as dead!", e),
+            }
+        }
+        Commands::Schedule => match schedule(&mut connection).await {
+            Ok(lines) => {
+                for line in lines {
+                    ui.log(&line);
+                }
+            }
+            Err(e) => ui.error("Could not list schedules!", e),
+        },
+    }
+}
+
+async fn connect(config: &Config) -> Result<PgConnection, anyhow::Error> {
+    let connect_options =
+        PgConnectOptions::from_str(&config.database.url).context("Invalid database URL!")?;
+
+    connect_options
+        .connect()
+        .await
+        .context("Failed to connect to database!")
+}
+
+async fn list(
+    connection: &mut PgConnection,
+    status: Option<Status>,
+    name: Option<&str>,
+) -> Result<Vec<String>, anyhow::Error> {
+    let rows = sqlx::query(
+        "SELECT id, name, status, attempts, max_attempts, run_at, last_error FROM jobs
+        WHERE ($1::varchar IS NULL OR status = $1) AND ($2::varchar IS NULL OR name = $2)
+        ORDER BY run_at",
+    )
+    .bind(status.map(|status| status.as_str()))
+    .bind(name)
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load jobs!")?;
+
+    let rows: Vec<Vec<Option<String>>> = rows
+        .iter()
+        .map(|row| {
+            let id: Uuid = row.get("id");
+            let attempts: i32 = row.get("attempts");
+            let max_attempts: i32 = row.get("max_attempts");
+            let run_at: DateTime<Utc> = row.get("run_at");
+            let last_error: Option<String> = row.get("last_error");
+            vec![
+                Some(id.to_string()),
+                Some(row.get("name")),
+                Some(row.get("status")),
+                Some(format!("{}/{}", attempts, max_attempts)),
+                Some(run_at.to_rfc3339()),
+                last_error.map(|error| error.lines().next().unwrap_or_default().to_string()),
+            ]
+        })
+        .collect();
+
+    let mut lines = format_table(
+        &["id", "name", "status", "attempts", "run_at", "last_error"],
+        &rows,
+    );
+    lines.push(format!("({} jobs)", rows.len()));
+
+    Ok(lines)
+}
+
+async fn show(connection: &mut PgConnection, id: Uuid) -> Result<Vec<String>, anyhow::Error> {
+    let row = sqlx::query(
+        "SELECT name, payload::text AS payload, status, attempts, max_attempts, run_at, last_error, created_at FROM jobs WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to load job!")?
+    .ok_or_else(|| anyhow!(r#"No job found with ID "{}"!"#, id))?;
+
+    let name: String = row.get("name");
+    let payload: String = row.get("payload");
+    let status: String = row.get("status");
+    let attempts: i32 = row.get("attempts");
+    let max_attempts: i32 = row.get("max_attempts");
+    let run_at: DateTime<Utc> = row.get("run_at");
+    let created_at: DateTime<Utc> = row.get("created_at");
+    let last_error: Option<String> = row.get("last_error");
+
+    let mut lines = vec![
+        format!("id:         {}", id),
+        format!("name:       {}", name),
+        format!("status:     {}", status),
+        format!("attempts:   {}/{}", attempts, max_attempts),
+        format!("run_at:     {}", run_at.to_rfc3339()),
+        format!("created_at: {}", created_at.to_rfc3339()),
+        format!("payload:    {}", payload),
+    ];
+    if let Some(last_error) = last_error {
+        lines.push(format!("last_error: {}", last_error));
+    }
+
+    Ok(lines)
+}
+
+async fn retry(
+    connection: &mut PgConnection,
+    ids: &[Uuid],
+    dead: bool,
+) -> Result<u64, anyhow::Error> {
+    let query = if dead {
+        sqlx::query(
+            "UPDATE jobs SET status = 'queued', attempts = 0, run_at = now(), locked_at = NULL WHERE status = 'dead'",
+        )
+    } else {
+        sqlx::query(
+            "UPDATE jobs SET status = 'queued', attempts = 0, run_at = now(), locked_at = NULL WHERE id = ANY($1)",
+        )
+        .bind(ids)
+    };
+    let result = query
+        .execute(&mut *connection)
+        .await
+        .context("Failed to update jobs!")?;
+
+    Ok(result.rows_affected())
+}
+
+async fn dead_letter(connection: &mut PgConnection, ids: &[Uuid]) -> Result<u64, anyhow::Error> {
+    let result =
+        sqlx::query("UPDATE jobs SET status = 'dead', locked_at = NULL WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *connection)
+            .await
+            .context("Failed to update jobs!")?;
+
+    Ok(result.rows_affected())
+}
+
+async fn schedule(connection: &mut PgConnection) -> Result<Vec<String>, anyhow::Error> {
+    let rows = sqlx::query(
+        "SELECT name, schedule, next_run_at, last_run_at FROM job_schedules ORDER BY next_run_at",
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load schedules!")?;
+
+    let rows: Vec<Vec<Option<String>>> = rows
+        .iter()
+        .map(|row| {
+            let next_run_at: DateTime<Utc> = row.get("next_run_at");
+            let last_run_at: Option<DateTime<Utc>> = row.get("last_run_at");
+            vec![
+                Some(row.get("name")),
+                Some(row.get("schedule")),
+                Some(next_run_at.to_rfc3339()),
+                last_run_at.map(|last_run_at| last_run_at.to_rfc3339()),
+            ]
+        })
+        .collect();
+
+    let mut lines = format_table(&["name", "schedule", "next_run_at", "last_run_at"], &rows);
+    lines.push(format!("({} scheduled jobs)", rows.len()));
+
+    Ok(lines)
+}
@@ -0,0 +1,447 @@
+#![allow(missing_docs)]
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{referenced_secrets, Environment};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+static BLUEPRINTS_DIR: include_dir::Dir =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprints/release");
+
+/// The binaries a release contains and the descriptions of their systemd units – the `db` CLI is included to run migrations but doesn't get a unit of its own
+const BINARIES: &[(&str, Option<&str>)] = &[
+    ("{{project-name}}-web", Some("{{project-name}} web application")),
+    {%- if template_type == "full" %}
+    ("{{project-name}}-worker", Some("{{project-name}} worker")),
+    {%- endif %}
+    {%- if grpc %}
+    ("{{project-name}}-grpc", Some("{{project-name}} gRPC server")),
+    {%- endif %}
+    {%- unless template_type == "minimal" %}
+    ("db", None),
+    {%- endunless %}
+];
+
+/// The directories and files that are read at runtime, relative to the working directory
+const RUNTIME_FILES: &[&str] = &[
+    "config",
+    "web/public",
+    "web/templates",
+    {%- unless template_type == "minimal" %}
+    "db/migrations",
+    "db/seeds.sql",
+    "db/seeds",
+    {%- endunless %}
+];
+
+fn main() {
+    cli();
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to package and install releases for deploying to servers with systemd.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(
+        long,
+        global = true,
+        default_value = "/opt/{{project-name}}",
+        help = "The directory releases are installed in on the server – must be the same when building and installing a release."
+    )]
+    prefix: PathBuf,
+
+    #[arg(
+        long,
+        global = true,
+        default_value = "/etc/{{project-name}}/{{project-name}}.env",
+        help = "The file the systemd units read env vars from on the server – must be the same when building and installing a release."
+    )]
+    env_file: PathBuf,
+
+    #[arg(long, global = true, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, global = true, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(
+        about = "Build the stripped release binaries and package them with the files they need at runtime and systemd units into a tarball"
+    )]
+    Build {
+        #[arg(
+            long,
+            help = "The target triple to compile for, e.g. aarch64-unknown-linux-gnu (defaults to the host's)."
+        )]
+        target: Option<String>,
+    },
+    #[command(about = "Install a release tarball and its systemd units on this machine")]
+    Install {
+        #[arg(help = "The tarball built with `cargo release build`.")]
+        tarball: PathBuf,
+        #[arg(
+            long,
+            default_value = "/etc/systemd/system",
+            help = "The directory the systemd units are installed in."
+        )]
+        systemd_dir: PathBuf,
+    },
+}
+
+#[allow(missing_docs)]
+fn cli() {
+    let cli = Cli::parse();
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !cli.no_color, !cli.quiet);
+
+    match cli.command {
+        Commands::Build { target } => {
+            let result = project_root().and_then(|root| {
+                build(
+                    &mut ui,
+                    &root,
+                    target.as_deref(),
+                    &cli.prefix,
+                    &cli.env_file,
+                )
+            });
+            match result {
+                Ok(tarball) => ui.success(&format!("Built {}.", tarball.display())),
+                Err(e) => {
+                    ui.error("Could not build the release!", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Install {
+            tarball,
+            systemd_dir,
+        } => {
+            if let Err(e) = install(&mut ui, &tarball, &cli.prefix, &cli.env_file, &systemd_dir) {
+                ui.error("Could not install the release!", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Builds the release binaries for `target` and packages them into a tarball in `target/releases`, returning its path.
+///
+/// The tarball contains a directory named like the tarball with
+/// * the stripped binaries in `bin`
+/// * the files that are read at runtime (see [`RUNTIME_FILES`]) except for local configuration overrides and the master key
+/// * a systemd unit for each process in `systemd`, referencing the release's location below `prefix` and `env_file`
+/// * a template of the env file
+fn build(
+    ui: &mut UI,
+    root: &Path,
+    target: Option<&str>,
+    prefix: &Path,
+    env_file: &Path,
+) -> Result<PathBuf, anyhow::Error> {
+    // the configuration files are read relative to the working directory
+    std::env::set_current_dir(root).context("Failed to change to the project root!")?;
+    let cargo = std::env::var("CARGO")
+        .map_err(|_| anyhow!("Please invoke me using Cargo, e.g.: `cargo release build`"))?;
+    let target = match target {
+        Some(target) => String::from(target),
+        None => host_target()?,
+    };
+
+    ui.info(&format!("Building the release binaries for {}…", target));
+    let mut command = Command::new(&cargo);
+    command.args(["build", "--release", "--workspace", "--target", &target]);
+    command.args(["--config", "profile.release.strip=true"]);
+    for (binary, _) in BINARIES {
+        command.args(["--bin", binary]);
+    }
+    let status = command.status().context("Failed to run cargo build!")?;
+    if !status.success() {
+        return Err(anyhow!("The build failed"));
+    }
+
+    let target_dir = std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| root.join("target"));
+    let name = format!("{{project-name}}-{}-{}", env!("CARGO_PKG_VERSION"), target);
+    let releases_dir = target_dir.join("releases");
+    let release_dir = releases_dir.join(&name);
+    if release_dir.exists() {
+        fs::remove_dir_all(&release_dir)
+            .context(format!(r#"Failed to remove "{}"!"#, release_dir.display()))?;
+    }
+
+    ui.info("Packaging the release…");
+    let bin_dir = release_dir.join("bin");
+    fs::create_dir_all(&bin_dir)
+        .context(format!(r#"Failed to create "{}"!"#, bin_dir.display()))?;
+    let binaries_dir = target_dir.join(&target).join("release");
+    for (binary, _) in BINARIES {
+        fs::copy(binaries_dir.join(binary), bin_dir.join(binary))
+            .context(format!(r#"Failed to copy binary "{}"!"#, binary))?;
+    }
+    for path in RUNTIME_FILES {
+        let source = root.join(path);
+        if source.exists() {
+            copy_recursively(&source, &release_dir.join(path))?;
+        }
+    }
+
+    let secrets: Vec<String> = referenced_secrets(&Environment::Production)?
+        .into_iter()
+        .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .collect();
+    let variables = liquid::object!({
+        "project_name": "{{project-name}}",
+        "has_db": {% if template_type != "minimal" %}true{% else %}false{% endif %},
+        "prefix": prefix.display().to_string(),
+        "env_file": env_file.display().to_string(),
+        "referenced_secrets": secrets,
+    });
+    let systemd_dir = release_dir.join("systemd");
+    fs::create_dir_all(&systemd_dir)
+        .context(format!(r#"Failed to create "{}"!"#, systemd_dir.display()))?;
+    for (binary, description) in BINARIES {
+        if let Some(description) = description {
+            let mut variables = variables.clone();
+            variables.insert("binary".into(), liquid::model::Value::scalar(*binary));
+            variables.insert(
+                "description".into(),
+                liquid::model::Value::scalar(*description),
+            );
+            let unit = render("service", &variables)?;
+            write_file(&systemd_dir.join(format!("{}.service", binary)), &unit)?;
+        }
+    }
+    {% unless template_type == "minimal" -%}
+    let unit = render("migrate.service", &variables)?;
+    write_file(&systemd_dir.join("{{project-name}}-migrate.service"), &unit)?;
+    {% endunless -%}
+    let env_template = render("env", &variables)?;
+    write_file(&release_dir.join("{{project-name}}.env"), &env_template)?;
+
+    let tarball = releases_dir.join(format!("{}.tar.gz", name));
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&tarball)
+        .arg("-C")
+        .arg(&releases_dir)
+        .arg(&name)
+        .status()
+        .context("Failed to run tar!")?;
+    if !status.success() {
+        return Err(anyhow!("Failed to create {}!", tarball.display()));
+    }
+
+    Ok(tarball)
+}
+
+/// Installs the release from the tarball built with [`build`]:
+///
+/// 1. the release is extracted to `<prefix>/releases/<name>`
+/// 2. its `tmp` directory is linked to `<prefix>/shared/tmp` so that files stored by the local storage backend are kept across releases
+/// 3. `<prefix>/current` is switched to the release
+/// 4. the systemd units are copied to `systemd_dir`
+/// 5. the env file template is copied to `env_file` unless it exists already
+///
+/// Starting or restarting the processes is left to the user as it might need to be coordinated with other servers.
+fn install(
+    ui: &mut UI,
+    tarball: &Path,
+    prefix: &Path,
+    env_file: &Path,
+    systemd_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let name = tarball
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".tar.gz"))
+        .ok_or(anyhow!("{} is not a release tarball!", tarball.display()))?;
+    let releases_dir = prefix.join("releases");
+    let release_dir = releases_dir.join(name);
+
+    ui.info(&format!(
+        "Installing {} to {}…",
+        name,
+        release_dir.display()
+    ));
+    if release_dir.exists() {
+        fs::remove_dir_all(&release_dir)
+            .context(format!(r#"Failed to remove "{}"!"#, release_dir.display()))?;
+    }
+    fs::create_dir_all(&releases_dir)
+        .context(format!(r#"Failed to create "{}"!"#, releases_dir.display()))?;
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(&releases_dir)
+        .status()
+        .context("Failed to run tar!")?;
+    if !status.success() || !release_dir.exists() {
+        return Err(anyhow!("Failed to extract {}!", tarball.display()));
+    }
+
+    let shared_tmp_dir = prefix.join("shared").join("tmp");
+    fs::create_dir_all(&shared_tmp_dir).context(format!(
+        r#"Failed to create "{}"!"#,
+        shared_tmp_dir.display()
+    ))?;
+    symlink(&shared_tmp_dir, &release_dir.join("tmp"))?;
+
+    // the link is switched atomically by renaming a new link over the current one
+    let next = prefix.join("current.next");
+    let _ = fs::remove_file(&next);
+    symlink(&release_dir, &next)?;
+    fs::rename(&next, prefix.join("current")).context("Failed to switch to the release!")?;
+
+    let mut units = vec![];
+    let release_systemd_dir = release_dir.join("systemd");
+    fs::create_dir_all(systemd_dir)
+        .context(format!(r#"Failed to create "{}"!"#, systemd_dir.display()))?;
+    for entry in fs::read_dir(&release_systemd_dir).context(format!(
+        r#"Failed to read "{}"!"#,
+        release_systemd_dir.display()
+    ))? {
+        let entry = entry?;
+        fs::copy(entry.path(), systemd_dir.join(entry.file_name()))
+            .context(format!("Failed to install {}!", entry.path().display()))?;
+        units.push(entry.file_name().to_string_lossy().to_string());
+    }
+    units.sort();
+
+    if env_file.exists() {
+        ui.debug(&format!("Keeping the existing {}.", env_file.display()));
+    } else {
+        if let Some(dir) = env_file.parent() {
+            fs::create_dir_all(dir).context(format!(r#"Failed to create "{}"!"#, dir.display()))?;
+        }
+        let template = fs::read_to_string(release_dir.join("{{project-name}}.env"))
+            .context("Failed to read the env file template!")?;
+        create_private_file(env_file, &template)?;
+        ui.info(&format!(
+            "Created {} – fill in the settings before starting the application.",
+            env_file.display()
+        ));
+    }
+
+    let services = units
+        .iter()
+        .filter(|unit| !unit.ends_with("-migrate.service"))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    ui.success(&format!("Installed {}.", name));
+    ui.log("Make sure the {{project-name}} user exists and owns the shared directory, then (re)start the application:");
+    ui.log(&format!(
+        "  useradd --system --no-create-home {{project-name}} && chown -R {{project-name}}: {}",
+        prefix.join("shared").display()
+    ));
+    ui.log(&format!(
+        "  systemctl daemon-reload && systemctl enable {} && systemctl restart {}",
+        services, services
+    ));
+
+    Ok(())
+}
+
+/// Returns the target triple of the host, e.g. x86_64-unknown-linux-gnu.
+fn host_target() -> Result<String, anyhow::Error> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .context("Failed to run rustc!")?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(String::from)
+        .ok_or(anyhow!("Failed to determine the host's target triple!"))
+}
+
+/// Copies a file or a directory with all its contents, leaving out local configuration overrides and the master key (which must not be deployed with the release).
+fn copy_recursively(source: &Path, destination: &Path) -> Result<(), anyhow::Error> {
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    if name == "master.key" || name.ends_with(".local.toml") {
+        return Ok(());
+    }
+
+    if source.is_dir() {
+        fs::create_dir_all(destination)
+            .context(format!(r#"Failed to create "{}"!"#, destination.display()))?;
+        for entry in
+            fs::read_dir(source).context(format!(r#"Failed to read "{}"!"#, source.display()))?
+        {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(dir) = destination.parent() {
+            fs::create_dir_all(dir).context(format!(r#"Failed to create "{}"!"#, dir.display()))?;
+        }
+        fs::copy(source, destination)
+            .context(format!(r#"Failed to copy "{}"!"#, source.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> Result<(), anyhow::Error> {
+    std::os::unix::fs::symlink(original, link)
+        .context(format!(r#"Failed to link "{}"!"#, link.display()))
+}
+
+#[cfg(not(unix))]
+fn symlink(_original: &Path, _link: &Path) -> Result<(), anyhow::Error> {
+    Err(anyhow!("Releases can only be installed on Unix systems!"))
+}
+
+/// Creates a file that is only readable by the current user.
+fn create_private_file(path: &Path, contents: &str) -> Result<(), anyhow::Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options
+        .open(path)
+        .context(format!(r#"Failed to create "{}"!"#, path.display()))?;
+    file.write_all(contents.as_bytes())
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), anyhow::Error> {
+    fs::write(path, contents).context(format!(r#"Failed to write "{}"!"#, path.display()))
+}
+
+fn render(blueprint: &str, variables: &liquid::Object) -> Result<String, anyhow::Error> {
+    let template_source = BLUEPRINTS_DIR
+        .get_file(blueprint)
+        .and_then(|file| file.contents_utf8())
+        .context(format!("Failed to get blueprint {}!", blueprint))?;
+    liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template_source)
+        .context("Failed to parse blueprint as Liquid template")?
+        .render(variables)
+        .context("Failed to render Liquid template")
+}
+
+fn project_root() -> Result<PathBuf, anyhow::Error> {
+    Ok(PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|e| anyhow!(e).context("This command needs to be invoked using cargo"))?,
+    )
+    .join("..")
+    .canonicalize()?)
+}
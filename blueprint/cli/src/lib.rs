@@ -1,4 +1,4 @@
-//! The {{project-name}}-cli crate implements the project's CLI tools `db` and `generate` as well as contains functionality for displaying information in a console UI.
+//! The {{project-name}}-cli crate implements the project's CLI tools `db`, `generate`, `dev`, `docker`, `deploy`, `release`, `config`, and `credentials` as well as contains functionality for displaying information in a console UI.
 
 /// Utilities for CLIs
 pub mod util;
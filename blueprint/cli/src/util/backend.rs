@@ -0,0 +1,261 @@
+use anyhow::{anyhow, Context};
+use {{crate_name}}_config::{DatabaseBackend, DatabaseConfig};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::types::chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+#[cfg(feature = "mysql")]
+use super::mysql;
+#[cfg(feature = "sqlite")]
+use super::sqlite;
+
+/// Returns the name of the configured database (the path of the database file for SQLite).
+pub fn database_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => mysql::database_name(config),
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => sqlite::database_name(config),
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Creates the configured database.
+pub async fn create_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => mysql::create_database(config).await,
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => sqlite::create_database(config).await,
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Drops the configured database if it exists.
+pub async fn drop_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => mysql::drop_database(config).await,
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => sqlite::drop_database(config).await,
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Runs all pending migrations and returns the versions and descriptions of the migrations that were applied.
+///
+/// Before anything is applied, all applied migrations are checked to not have changed since they were applied.
+pub async fn migrate(
+    config: &DatabaseConfig,
+    migrator: &Migrator,
+) -> Result<Vec<(i64, String)>, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => {
+            let mut connection = mysql::connect(config, &mysql::connect_options(config)?).await?;
+            run_migrations(&mut connection, migrator).await
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => {
+            let mut connection = sqlite::connect(config, &sqlite::connect_options(config)?).await?;
+            run_migrations(&mut connection, migrator).await
+        }
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Rolls back the `steps` most recently applied migrations by running their down scripts and returns their versions.
+///
+/// Before anything is rolled back, all of those migrations are checked to have a down script.
+pub async fn rollback(
+    config: &DatabaseConfig,
+    migrator: &Migrator,
+    steps: usize,
+) -> Result<Vec<i64>, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => {
+            let mut connection = mysql::connect(config, &mysql::connect_options(config)?).await?;
+            revert_migrations(&mut connection, migrator, steps).await
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => {
+            let mut connection = sqlite::connect(config, &sqlite::connect_options(config)?).await?;
+            revert_migrations(&mut connection, migrator, steps).await
+        }
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Lists the versions, descriptions, and installation times of all successfully applied migrations, ordered by version.
+///
+/// Nothing is changed in the database, i.e. the migrations table is not created if it doesn't exist.
+pub async fn applied_migrations(
+    config: &DatabaseConfig,
+) -> Result<Vec<(i64, String, DateTime<Utc>)>, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => mysql::applied_migrations(config).await,
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => sqlite::applied_migrations(config).await,
+        backend => Err(unsupported(backend)),
+    }
+}
+
+/// Executes seed statements in a transaction and returns the number of rows affected by each of them.
+///
+/// In a dry run, the transaction is rolled back instead of committed.
+pub async fn run_seeds(
+    config: &DatabaseConfig,
+    statements: &[String],
+    dry_run: bool,
+) -> Result<Vec<u64>, anyhow::Error> {
+    match config.backend()? {
+        #[cfg(feature = "mysql")]
+        DatabaseBackend::MySql => mysql::run_seeds(config, statements, dry_run).await,
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => sqlite::run_seeds(config, statements, dry_run).await,
+        backend => Err(unsupported(backend)),
+    }
+}
+
+async fn run_migrations<C: Migrate>(
+    connection: &mut C,
+    migrator: &Migrator,
+) -> Result<Vec<(i64, String)>, anyhow::Error> {
+    lock(connection).await?;
+    let result = apply_pending_migrations(connection, migrator).await;
+    unlock(connection).await?;
+
+    result
+}
+
+async fn apply_pending_migrations<C: Migrate>(
+    connection: &mut C,
+    migrator: &Migrator,
+) -> Result<Vec<(i64, String)>, anyhow::Error> {
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+    let applied_migrations: HashMap<_, _> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?
+        .into_iter()
+        .map(|m| (m.version, m))
+        .collect();
+
+    let mut applied = vec![];
+    for migration in migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        match applied_migrations.get(&migration.version) {
+            Some(applied_migration) if applied_migration.checksum != migration.checksum => {
+                return Err(anyhow!(
+                    "Migration {} ({}) has changed since it was applied!",
+                    migration.version,
+                    migration.description
+                ));
+            }
+            Some(_) => {}
+            None => {
+                connection
+                    .apply(migration)
+                    .await
+                    .with_context(|| format!("Failed to apply migration {}!", migration.version))?;
+                applied.push((migration.version, migration.description.to_string()));
+            }
+        }
+    }
+
+    Ok(applied)
+}
+
+async fn revert_migrations<C: Migrate>(
+    connection: &mut C,
+    migrator: &Migrator,
+    steps: usize,
+) -> Result<Vec<i64>, anyhow::Error> {
+    lock(connection).await?;
+    let result = revert_latest_migrations(connection, migrator, steps).await;
+    unlock(connection).await?;
+
+    result
+}
+
+async fn revert_latest_migrations<C: Migrate>(
+    connection: &mut C,
+    migrator: &Migrator,
+    steps: usize,
+) -> Result<Vec<i64>, anyhow::Error> {
+    connection
+        .ensure_migrations_table()
+        .await
+        .context("Failed to ensure migrations table!")?;
+    let mut applied_versions: Vec<i64> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list applied migrations!")?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    applied_versions.sort_unstable_by(|a, b| b.cmp(a));
+    applied_versions.truncate(steps);
+
+    let down_migrations: Vec<_> = applied_versions
+        .iter()
+        .filter_map(|version| {
+            migrator
+                .iter()
+                .find(|m| m.version == *version && m.migration_type.is_down_migration())
+        })
+        .collect();
+    if down_migrations.len() != applied_versions.len() {
+        let irreversible: Vec<String> = applied_versions
+            .iter()
+            .filter(|version| !down_migrations.iter().any(|m| m.version == **version))
+            .map(i64::to_string)
+            .collect();
+        return Err(anyhow!(
+            "Cannot roll back; these migrations do not have a down script: {}",
+            irreversible.join(", ")
+        ));
+    }
+
+    for migration in down_migrations {
+        connection
+            .revert(migration)
+            .await
+            .with_context(|| format!("Failed to roll back migration {}!", migration.version))?;
+    }
+
+    Ok(applied_versions)
+}
+
+async fn lock<C: Migrate>(connection: &mut C) -> Result<(), anyhow::Error> {
+    connection
+        .lock()
+        .await
+        .context("Failed to acquire migration lock!")
+}
+
+async fn unlock<C: Migrate>(connection: &mut C) -> Result<(), anyhow::Error> {
+    connection
+        .unlock()
+        .await
+        .context("Failed to release migration lock!")
+}
+
+fn unsupported(backend: DatabaseBackend) -> anyhow::Error {
+    match backend {
+        DatabaseBackend::Postgres => anyhow!("PostgreSQL databases are handled by the db binary!"),
+        DatabaseBackend::MySql => anyhow!(
+            "MySQL databases are only supported if the `mysql` feature of the cli crate is enabled!"
+        ),
+        DatabaseBackend::Sqlite => anyhow!(
+            "SQLite databases are only supported if the `sqlite` feature of the cli crate is enabled!"
+        ),
+    }
+}
@@ -0,0 +1,507 @@
+use crate::util::diff::unified_diff;
+use crate::util::ui::UI;
+use anyhow::{anyhow, Context};
+use cruet::case::snake::to_snake_case;
+use serde::{Deserialize, Serialize};
+use similar::{DiffTag, TextDiff};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The directory the records of generator runs are stored in (see [`Record`])
+const RECORDS_DIR: &str = "./.gerust/generated";
+
+/// How generators handle files they create that exist already
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Existing {
+    /// Refuse to overwrite the file.
+    #[default]
+    Fail,
+    /// Overwrite the file (`--force`).
+    Force,
+    /// Keep the file as it is (`--skip`).
+    Skip,
+}
+
+/// What a generator does to a project file
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// The file is created.
+    Create,
+    /// The file exists already and is overwritten.
+    Overwrite,
+    /// The file exists already and is modified, e.g. to register routes or modules.
+    Modify,
+    /// The file exists already and is kept as it is.
+    Skip,
+    /// The file is deleted when reverting a generator run.
+    Delete,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Create => write!(f, "create"),
+            Action::Overwrite => write!(f, "overwrite"),
+            Action::Modify => write!(f, "modify"),
+            Action::Skip => write!(f, "skip"),
+            Action::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// A change to a project file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Change {
+    /// The path of the file relative to the project root, e.g. `./web/src/routes.rs`
+    pub path: String,
+    /// What happens to the file
+    pub action: Action,
+    /// The contents of the file before the change if it existed
+    pub original: Option<String>,
+    /// The contents of the file after the change
+    pub contents: String,
+}
+
+/// A record of the changes a generator run made, stored in `.gerust/generated` so that the run can be reverted with `cargo generate --revert`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    /// The generator that was run, e.g. `controller`
+    pub generator: String,
+    /// The name passed to the generator if it takes one
+    pub name: Option<String>,
+    /// The changes the generator made
+    pub changes: Vec<Change>,
+}
+
+impl Record {
+    /// Stores the record in `.gerust/generated`, returning its path.
+    pub fn save(&self) -> Result<PathBuf, anyhow::Error> {
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+        let path = Path::new(RECORDS_DIR).join(format!(
+            "{}_{}.json",
+            timestamp.as_millis(),
+            self.generator
+        ));
+        fs::create_dir_all(RECORDS_DIR)
+            .context(format!(r#"Could not create directory "{}"!"#, RECORDS_DIR))?;
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).context(format!(r#"Could not write file "{}"!"#, path.display()))?;
+
+        Ok(path)
+    }
+
+    /// Finds the record of the latest run of the generator with the name (compared in snake case), returning it along with its path.
+    pub fn find_latest(
+        generator: &str,
+        name: Option<&str>,
+    ) -> Result<Option<(PathBuf, Record)>, anyhow::Error> {
+        let Ok(entries) = fs::read_dir(RECORDS_DIR) else {
+            return Ok(None);
+        };
+        let mut paths = entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .context(format!(r#"Could not read directory "{}"!"#, RECORDS_DIR))?;
+        // the file names start with the timestamp of the run
+        paths.sort();
+
+        let name = name.map(to_snake_case);
+        for path in paths.into_iter().rev() {
+            let json = fs::read_to_string(&path)
+                .context(format!(r#"Could not read file "{}"!"#, path.display()))?;
+            let record: Record = serde_json::from_str(&json)
+                .context(format!(r#"Could not parse "{}"!"#, path.display()))?;
+            if record.generator == generator && record.name.as_deref().map(to_snake_case) == name {
+                return Ok(Some((path, record)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The changes a generator makes to project files
+///
+/// Changes are staged in memory rather than written right away – reading a file returns its staged contents so that generators can build on their own changes. Once generating is done, the changes are either written (see [`Changes::write`]) or previewed as diffs without writing anything (see [`Changes::preview`]).
+///
+/// Example:
+/// ```
+/// let mut changes = Changes::new(Existing::Fail);
+/// changes.create("./web/src/controllers/users.rs", String::from("// …"))?;
+/// changes.append("./web/src/controllers/mod.rs", "pub mod users;")?;
+/// changes.write()?;
+/// ```
+#[derive(Debug, Default)]
+pub struct Changes {
+    existing: Existing,
+    changes: Vec<Change>,
+}
+
+impl Changes {
+    /// Creates an empty set of changes that handles existing files according to `existing`.
+    pub const fn new(existing: Existing) -> Changes {
+        Changes {
+            existing,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Changes how existing files are handled for files created subsequently.
+    pub fn set_existing(&mut self, existing: Existing) {
+        self.existing = existing;
+    }
+
+    /// Returns whether the file exists, either on disk or as part of the changes.
+    pub fn exists(&self, path: &str) -> bool {
+        self.change(path).is_some() || Path::new(path).exists()
+    }
+
+    /// Checks that the file can be created, i.e. that it does not exist or existing files are overwritten or skipped (see [`Existing`]).
+    pub fn ensure_creatable(&self, path: &str) -> Result<(), anyhow::Error> {
+        if self.existing == Existing::Fail && self.exists(path) {
+            return Err(anyhow!(
+                r#"File "{}" already exists – pass --force to overwrite or --skip to keep it!"#,
+                path
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads the file, returning its changed contents if it was changed.
+    pub fn read(&self, path: &str) -> Result<String, anyhow::Error> {
+        match self.change(path) {
+            Some(change) => Ok(change.contents.clone()),
+            None => fs::read_to_string(path).context(format!(r#"Could not read file "{}"!"#, path)),
+        }
+    }
+
+    /// Creates the file, handling an existing file according to [`Existing`].
+    pub fn create(&mut self, path: &str, contents: String) -> Result<(), anyhow::Error> {
+        if let Some(change) = self.change_mut(path) {
+            change.contents = contents;
+            return Ok(());
+        }
+
+        self.ensure_creatable(path)?;
+        let change = if Path::new(path).exists() {
+            let original = self.read(path)?;
+            match self.existing {
+                Existing::Skip => Change {
+                    path: String::from(path),
+                    action: Action::Skip,
+                    contents: original.clone(),
+                    original: Some(original),
+                },
+                _ => Change {
+                    path: String::from(path),
+                    action: Action::Overwrite,
+                    original: Some(original),
+                    contents,
+                },
+            }
+        } else {
+            Change {
+                path: String::from(path),
+                action: Action::Create,
+                original: None,
+                contents,
+            }
+        };
+        self.changes.push(change);
+
+        Ok(())
+    }
+
+    /// Replaces the contents of the existing file.
+    pub fn update(&mut self, path: &str, contents: String) -> Result<(), anyhow::Error> {
+        if let Some(change) = self.change_mut(path) {
+            if change.action == Action::Skip {
+                change.action = Action::Modify;
+            }
+            change.contents = contents;
+            return Ok(());
+        }
+
+        let original = self.read(path)?;
+        self.changes.push(Change {
+            path: String::from(path),
+            action: Action::Modify,
+            original: Some(original),
+            contents,
+        });
+
+        Ok(())
+    }
+
+    /// Appends a line to the existing file unless it contains that line already.
+    pub fn append(&mut self, path: &str, line: &str) -> Result<(), anyhow::Error> {
+        let current = self.read(path)?;
+        if current.contains(line) {
+            return Ok(());
+        }
+
+        let contents = if current.trim().is_empty() {
+            format!("{}\n", line)
+        } else {
+            format!("{}{}\n", current, line)
+        };
+        self.update(path, contents)
+    }
+
+    /// Deletes the existing file.
+    pub fn delete(&mut self, path: &str) -> Result<(), anyhow::Error> {
+        let original = self.read(path)?;
+        self.changes.retain(|change| change.path != path);
+        self.changes.push(Change {
+            path: String::from(path),
+            action: Action::Delete,
+            original: Some(original),
+            contents: String::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Reverts the changes of a generator run: created files are deleted and modified or overwritten files are restored.
+    ///
+    /// For modified files that were changed since the run, the lines the generator inserted are removed if they can still be found. Other files that were changed since the run are only deleted or restored with `force` (discarding the changes). Returns the paths of the files that were kept as they are.
+    pub fn revert(&mut self, record: &Record, force: bool) -> Result<Vec<String>, anyhow::Error> {
+        let mut kept = vec![];
+        for change in record.changes.iter().rev() {
+            if !self.exists(&change.path) {
+                continue;
+            }
+            let current = self.read(&change.path)?;
+            let original = change.original.clone().unwrap_or_default();
+            match change.action {
+                Action::Create => {
+                    if current == change.contents || force {
+                        self.delete(&change.path)?;
+                    } else {
+                        kept.push(change.path.clone());
+                    }
+                }
+                Action::Overwrite => {
+                    if current == change.contents || force {
+                        self.update(&change.path, original)?;
+                    } else {
+                        kept.push(change.path.clone());
+                    }
+                }
+                Action::Modify => {
+                    if current == change.contents {
+                        self.update(&change.path, original)?;
+                    } else if let Some(reverted) =
+                        remove_insertions(&original, &change.contents, &current)
+                    {
+                        self.update(&change.path, reverted)?;
+                    } else if force {
+                        self.update(&change.path, original)?;
+                    } else {
+                        kept.push(change.path.clone());
+                    }
+                }
+                Action::Skip | Action::Delete => {}
+            }
+        }
+
+        Ok(kept)
+    }
+
+    /// Prints what would happen to each file along with a diff of the changes.
+    pub fn preview(&self, ui: &mut UI) {
+        for change in &self.changes {
+            ui.log(&format!("{} {}", change.action, change.path));
+            if change.action == Action::Skip {
+                continue;
+            }
+            let original = change.original.as_deref().unwrap_or_default();
+            let original_name = match change.original {
+                Some(_) => change.path.as_str(),
+                None => "/dev/null",
+            };
+            let name = match change.action {
+                Action::Delete => "/dev/null",
+                _ => change.path.as_str(),
+            };
+            let diff = unified_diff(original, &change.contents, original_name, name, ui.color());
+            ui.indent();
+            for line in diff.lines() {
+                ui.log(line);
+            }
+            ui.outdent();
+        }
+    }
+
+    /// Writes the changes to disk, creating directories as necessary (and removing directories that are left empty by deleting files), and returns them.
+    pub fn write(self) -> Result<Vec<Change>, anyhow::Error> {
+        for change in &self.changes {
+            if change.action == Action::Delete {
+                fs::remove_file(&change.path)
+                    .context(format!(r#"Could not delete file "{}"!"#, change.path))?;
+                if let Some(dir) = Path::new(&change.path).parent() {
+                    // only succeeds if the directory is empty
+                    let _ = fs::remove_dir(dir);
+                }
+            } else if change.action != Action::Skip {
+                if let Some(dir) = Path::new(&change.path).parent() {
+                    fs::create_dir_all(dir).context(format!(
+                        r#"Could not create directory "{}"!"#,
+                        dir.display()
+                    ))?;
+                }
+                fs::write(&change.path, &change.contents)
+                    .context(format!(r#"Could not write file "{}"!"#, change.path))?;
+            }
+        }
+
+        Ok(self.changes)
+    }
+
+    fn change(&self, path: &str) -> Option<&Change> {
+        self.changes.iter().find(|change| change.path == path)
+    }
+
+    fn change_mut(&mut self, path: &str) -> Option<&mut Change> {
+        self.changes.iter_mut().find(|change| change.path == path)
+    }
+}
+
+/// Removes the lines that were inserted into `original` to get `changed` from `current`, returning `None` if `changed` differs from `original` by anything but inserted lines or the inserted lines cannot be found in `current` exactly once.
+fn remove_insertions(original: &str, changed: &str, current: &str) -> Option<String> {
+    let diff = TextDiff::from_lines(original, changed);
+    let lines = diff.new_slices();
+    let mut reverted = String::from(current);
+    for op in diff.ops() {
+        match op.tag() {
+            DiffTag::Equal => {}
+            DiffTag::Insert => {
+                let inserted = lines[op.new_range()].concat();
+                if reverted.matches(&inserted).count() != 1 {
+                    return None;
+                }
+                reverted = reverted.replacen(&inserted, "", 1);
+            }
+            DiffTag::Delete | DiffTag::Replace => return None,
+        }
+    }
+
+    Some(reverted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_insertions, Action, Changes, Existing, Record};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("changes-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_changes_are_staged() {
+        let dir = temp_dir("staged");
+        let module = dir.join("mod.rs").display().to_string();
+        let file = dir.join("src/users.rs").display().to_string();
+        fs::write(&module, "pub mod tasks;\n").unwrap();
+
+        let mut changes = Changes::new(Existing::Fail);
+        changes.create(&file, String::from("// users\n")).unwrap();
+        changes.append(&module, "pub mod users;").unwrap();
+        changes.append(&module, "pub mod users;").unwrap();
+
+        assert_eq!(
+            changes.read(&module).unwrap(),
+            "pub mod tasks;\npub mod users;\n"
+        );
+        assert!(!PathBuf::from(&file).exists());
+
+        let written: Vec<(String, Action)> = changes
+            .write()
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.path, change.action))
+            .collect();
+        assert_eq!(
+            written,
+            vec![
+                (file.clone(), Action::Create),
+                (module.clone(), Action::Modify)
+            ]
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "// users\n");
+        assert_eq!(
+            fs::read_to_string(&module).unwrap(),
+            "pub mod tasks;\npub mod users;\n"
+        );
+    }
+
+    #[test]
+    fn test_existing_files() {
+        let dir = temp_dir("existing");
+        let file = dir.join("users.rs").display().to_string();
+        fs::write(&file, "// existing\n").unwrap();
+
+        let mut changes = Changes::new(Existing::Fail);
+        assert!(changes.create(&file, String::from("// new\n")).is_err());
+
+        changes.set_existing(Existing::Skip);
+        changes.create(&file, String::from("// new\n")).unwrap();
+        assert_eq!(changes.read(&file).unwrap(), "// existing\n");
+
+        let mut changes = Changes::new(Existing::Force);
+        changes.create(&file, String::from("// new\n")).unwrap();
+        let written = changes.write().unwrap();
+        assert_eq!(written[0].action, Action::Overwrite);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "// new\n");
+    }
+
+    #[test]
+    fn test_revert() {
+        let dir = temp_dir("revert");
+        let module = dir.join("mod.rs").display().to_string();
+        let file = dir.join("users.rs").display().to_string();
+        fs::write(&module, "pub mod tasks;\n").unwrap();
+
+        let mut changes = Changes::new(Existing::Fail);
+        changes.create(&file, String::from("// users\n")).unwrap();
+        changes.append(&module, "pub mod users;").unwrap();
+        let record = Record {
+            generator: String::from("controller"),
+            name: Some(String::from("users")),
+            changes: changes.write().unwrap(),
+        };
+        fs::write(&module, "pub mod tasks;\npub mod users;\npub mod posts;\n").unwrap();
+
+        let mut changes = Changes::new(Existing::Fail);
+        let kept = changes.revert(&record, false).unwrap();
+        assert!(kept.is_empty());
+        changes.write().unwrap();
+
+        assert!(!PathBuf::from(&file).exists());
+        assert_eq!(
+            fs::read_to_string(&module).unwrap(),
+            "pub mod tasks;\npub mod posts;\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_insertions() {
+        let original = "use a;\n\nfn main() {\n    a();\n}\n";
+        let changed = "use a;\nuse b;\n\nfn main() {\n    a();\n    b();\n}\n";
+        let current = "use a;\nuse b;\nuse c;\n\nfn main() {\n    a();\n    b();\n    c();\n}\n";
+
+        assert_eq!(
+            remove_insertions(original, changed, current).unwrap(),
+            "use a;\nuse c;\n\nfn main() {\n    a();\n    c();\n}\n"
+        );
+        assert_eq!(remove_insertions(original, changed, original), None);
+        assert_eq!(remove_insertions(changed, original, changed), None);
+    }
+}
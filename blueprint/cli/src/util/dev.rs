@@ -0,0 +1,418 @@
+use http_body_util::{combinators::BoxBody, BodyExt, Full};
+use hyper::body::{Body, Bytes, Frame, Incoming, SizeHint};
+use hyper::{header, server::conn::http1, service::service_fn, Request, Response, StatusCode};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use std::net::{Ipv4Addr, TcpListener as StdTcpListener};
+use std::path::{Component, Path};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time::{sleep, Instant};
+
+/// The body of the responses the [`Proxy`] sends.
+pub type ProxyBody = BoxBody<Bytes, hyper::Error>;
+
+/// The paths (relative to the project root) that changes in do not require rebuilding and restarting the application.
+///
+/// Templates are read from disk whenever they are rendered in development (see the `[templates]` section of `development.toml`) so that changes to them take effect without restarting the application.
+const IGNORED_PATHS: [&str; 6] = [
+    "target",
+    ".git",
+    "tmp",
+    "db/dumps",
+    "web/templates",
+    "node_modules",
+];
+
+/// The suffixes of files that changes to do not require rebuilding and restarting the application, e.g. editors' swap and backup files and the compressed copies of static files.
+const IGNORED_SUFFIXES: [&str; 6] = [".swp", ".swx", "~", ".br", ".gz", ".tmp"];
+
+/// Returns whether a change to the file at `path` (relative to the project root) requires rebuilding and restarting the application.
+pub fn requires_restart(path: &Path) -> bool {
+    if IGNORED_PATHS
+        .iter()
+        .any(|ignored| path.starts_with(ignored))
+    {
+        return false;
+    }
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    // e.g. emacs' lock files and the files vim creates to check whether a directory is writable
+    if file_name.starts_with(".#") || file_name == "4913" {
+        return false;
+    }
+
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+        && !IGNORED_SUFFIXES
+            .iter()
+            .any(|suffix| file_name.ends_with(suffix))
+}
+
+/// Prefixes a line of a process' output with the process' name so that the output of several processes can be told apart, e.g. `[web#2] Listening on 127.0.0.1:3000`.
+///
+/// If `color` is `true`, the prefix is colored with the ANSI color `code`, e.g. 36 for cyan.
+pub fn prefix_line(name: &str, line: &str, code: u8, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m[{}]\x1b[0m {}", code, name, line)
+    } else {
+        format!("[{}] {}", name, line)
+    }
+}
+
+/// Returns a port on the loopback interface that is currently free.
+pub fn free_port() -> Result<u16, std::io::Error> {
+    let listener = StdTcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// Waits until a server accepts connections on `port` on the loopback interface, giving up after `timeout`.
+pub async fn wait_for_port(port: u16, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if tokio::net::TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .is_ok()
+        {
+            return true;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+/// A reverse proxy that forwards HTTP requests to the application running on a port on the loopback interface.
+///
+/// The proxy listens on the configured port while the application runs on a random port that changes every time it is restarted. When a new instance of the application is ready, the proxy is switched over to it (see [`Proxy::switch`]) – requests that are in flight at that time (including WebSocket connections and streamed responses) finish against the previous instance while new requests are forwarded to the new one so that no requests fail while the application restarts.
+#[derive(Clone)]
+pub struct Proxy {
+    backend: Arc<RwLock<Option<Backend>>>,
+    client: Client<HttpConnector, Incoming>,
+}
+
+#[derive(Clone)]
+struct Backend {
+    port: u16,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Default for Proxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Proxy {
+    /// Creates a proxy that does not forward requests to any application yet – until it is switched to one (see [`Proxy::switch`]), it responds with 503 Service Unavailable.
+    pub fn new() -> Self {
+        Self {
+            backend: Arc::new(RwLock::new(None)),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+
+    /// Forwards all new requests to the application listening on `port`.
+    ///
+    /// Returns a [`Drain`] for the application the proxy forwarded requests to before (if any) that waits for the requests that are in flight to finish.
+    pub fn switch(&self, port: u16) -> Option<Drain> {
+        let backend = Backend {
+            port,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        };
+        let previous = self
+            .backend
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(backend);
+
+        previous.map(|previous| Drain {
+            in_flight: previous.in_flight,
+        })
+    }
+
+    /// Accepts connections on `listener` and forwards the requests received via them.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), std::io::Error> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let proxy = self.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let proxy = proxy.clone();
+                    async move { Ok::<_, hyper::Error>(proxy.forward(request).await) }
+                });
+                // errors are caused by clients that close connections, etc. and are not relevant here
+                let _ = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .with_upgrades()
+                    .await;
+            });
+        }
+    }
+
+    async fn forward(&self, mut request: Request<Incoming>) -> Response<ProxyBody> {
+        let backend = self
+            .backend
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let Some(backend) = backend else {
+            return text_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The application is starting…",
+            );
+        };
+        let in_flight = InFlight::new(&backend.in_flight);
+
+        let path_and_query = request
+            .uri()
+            .path_and_query()
+            .map_or("/", |path_and_query| path_and_query.as_str());
+        let uri = format!(
+            "http://{}:{}{}",
+            Ipv4Addr::LOCALHOST,
+            backend.port,
+            path_and_query
+        );
+        match uri.parse() {
+            Ok(uri) => *request.uri_mut() = uri,
+            Err(_) => return text_response(StatusCode::BAD_REQUEST, "Invalid request URI."),
+        }
+        let upgrade = request
+            .headers()
+            .contains_key(header::UPGRADE)
+            .then(|| hyper::upgrade::on(&mut request));
+
+        let mut response = match self.client.request(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                return text_response(
+                    StatusCode::BAD_GATEWAY,
+                    &format!("The application is not reachable: {}", e),
+                )
+            }
+        };
+
+        if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+            if let Some(upgrade) = upgrade {
+                let backend_upgrade = hyper::upgrade::on(&mut response);
+                tokio::spawn(async move {
+                    // the connection counts as in flight until it is closed
+                    let _in_flight = in_flight;
+                    if let (Ok(client), Ok(backend)) = tokio::join!(upgrade, backend_upgrade) {
+                        let _ = tokio::io::copy_bidirectional(
+                            &mut TokioIo::new(client),
+                            &mut TokioIo::new(backend),
+                        )
+                        .await;
+                    }
+                });
+                return response.map(|body| body.boxed());
+            }
+        }
+
+        response.map(|body| {
+            Tracked {
+                body,
+                _in_flight: in_flight,
+            }
+            .boxed()
+        })
+    }
+}
+
+/// Waits for the requests that are in flight to an application the [`Proxy`] was switched away from to finish (see [`Proxy::switch`]).
+pub struct Drain {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drain {
+    /// Waits until there are no more requests in flight, giving up after `timeout`, e.g. for WebSocket connections that stay open.
+    ///
+    /// Returns whether all requests finished.
+    pub async fn wait(self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.in_flight.load(Ordering::SeqCst) > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        true
+    }
+}
+
+/// Counts a request as in flight until it is dropped.
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    fn new(count: &Arc<AtomicUsize>) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A response body that keeps the request counted as in flight until the body was sent completely.
+struct Tracked {
+    body: Incoming,
+    _in_flight: InFlight,
+}
+
+impl Body for Tracked {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.body).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+fn text_response(status: StatusCode, text: &str) -> Response<ProxyBody> {
+    let mut response = Response::new(
+        Full::new(Bytes::from(text.to_owned()))
+            .map_err(|never| match never {})
+            .boxed(),
+    );
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{free_port, prefix_line, requires_restart, Proxy};
+    use std::net::Ipv4Addr;
+    use std::path::Path;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::oneshot;
+
+    #[test]
+    fn test_requires_restart() {
+        assert!(requires_restart(Path::new("web/src/lib.rs")));
+        assert!(requires_restart(Path::new("config/app.toml")));
+        assert!(requires_restart(Path::new("web/public/css/app.css")));
+        assert!(!requires_restart(Path::new("target/debug/my-app-web")));
+        assert!(!requires_restart(Path::new(".git/index")));
+        assert!(!requires_restart(Path::new(
+            "web/templates/home/index.html"
+        )));
+        assert!(!requires_restart(Path::new("web/src/.lib.rs.swp")));
+        assert!(!requires_restart(Path::new("web/src/lib.rs~")));
+        assert!(!requires_restart(Path::new("web/public/css/app.css.br")));
+        assert!(!requires_restart(Path::new("../other/src/lib.rs")));
+    }
+
+    #[test]
+    fn test_prefix_line() {
+        assert_eq!(
+            prefix_line("web#1", "Listening", 36, true),
+            "\x1b[36m[web#1]\x1b[0m Listening"
+        );
+        assert_eq!(
+            prefix_line("web#1", "Listening", 36, false),
+            "[web#1] Listening"
+        );
+    }
+
+    /// Starts a server on the loopback interface that responds to every request with `body`, optionally after waiting for `release`.
+    async fn backend(body: &'static str, release: Option<oneshot::Receiver<()>>) -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            if let Some(release) = release {
+                release.await.unwrap();
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        port
+    }
+
+    async fn get(port: u16) -> String {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    async fn start_proxy(proxy: &Proxy) -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(proxy.clone().serve(listener));
+        port
+    }
+
+    #[tokio::test]
+    async fn test_proxy_without_backend() {
+        let proxy = Proxy::new();
+        let port = start_proxy(&proxy).await;
+
+        let response = get(port).await;
+
+        assert!(response.starts_with("HTTP/1.1 503"));
+        assert!(response.ends_with("The application is starting…"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_switch() {
+        let proxy = Proxy::new();
+        let port = start_proxy(&proxy).await;
+        let (release, released) = oneshot::channel();
+        assert!(proxy.switch(backend("old", Some(released)).await).is_none());
+
+        // a request that is in flight while the proxy is switched to a new backend
+        let in_flight = tokio::spawn(get(port));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let drain = proxy.switch(backend("new", None).await).unwrap();
+
+        assert!(get(port).await.ends_with("new"));
+        assert_eq!(drain.in_flight.load(Ordering::SeqCst), 1);
+        release.send(()).unwrap();
+        assert!(in_flight.await.unwrap().ends_with("old"));
+        assert!(drain.wait(Duration::from_secs(1)).await);
+    }
+
+    #[test]
+    fn test_free_port() {
+        assert!(free_port().unwrap() > 0);
+    }
+}
@@ -0,0 +1,72 @@
+use similar::TextDiff;
+
+/// Renders a line-by-line unified diff between two texts.
+///
+/// If `color` is `true`, removed lines are rendered in red, added lines in green, and hunk headers in cyan using ANSI escape codes; otherwise a plain unified diff is returned.
+///
+/// Example:
+/// ```
+/// let diff = unified_diff("SELECT 1;\n", "SELECT 2;\n", "recorded", "current", false);
+/// ```
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str, color: bool) -> String {
+    let diff = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(old_name, new_name)
+        .to_string();
+
+    if !color {
+        return diff;
+    }
+
+    diff.lines()
+        .map(|line| {
+            let code = if line.starts_with("+++") || line.starts_with("---") {
+                "1"
+            } else if line.starts_with('+') {
+                "32"
+            } else if line.starts_with('-') {
+                "31"
+            } else if line.starts_with("@@") {
+                "36"
+            } else {
+                return format!("{}\n", line);
+            };
+            format!("\x1b[{}m{}\x1b[0m\n", code, line)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified_diff;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_unified_diff_plain() {
+        let diff = unified_diff(
+            "CREATE TABLE tasks (\n  id uuid\n);\n",
+            "CREATE TABLE tasks (\n  id uuid,\n  description text\n);\n",
+            "recorded",
+            "current",
+            false,
+        );
+
+        assert_snapshot!(diff, @r###"
+        --- recorded
+        +++ current
+        @@ -1,3 +1,4 @@
+         CREATE TABLE tasks (
+        -  id uuid
+        +  id uuid,
+        +  description text
+         );
+        "###);
+    }
+
+    #[test]
+    fn test_unified_diff_color() {
+        let diff = unified_diff("SELECT 1;\n", "SELECT 2;\n", "recorded", "current", true);
+
+        assert_snapshot!(diff.escape_debug(), @r###"\u{1b}[1m--- recorded\u{1b}[0m\n\u{1b}[1m+++ current\u{1b}[0m\n\u{1b}[36m@@ -1 +1 @@\u{1b}[0m\n\u{1b}[31m-SELECT 1;\u{1b}[0m\n\u{1b}[32m+SELECT 2;\u{1b}[0m\n"###);
+    }
+}
@@ -1,2 +0,0 @@
-/// Utilities for console UIs
-pub mod ui;
@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Context};
+use {{crate_name}}_config::DatabaseConfig;
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{ConnectOptions, Connection, Executor};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Parses the connect options from the configured database URL.
+pub fn connect_options(config: &DatabaseConfig) -> Result<MySqlConnectOptions, anyhow::Error> {
+    MySqlConnectOptions::from_str(&config.url).context("Invalid database URL!")
+}
+
+/// Returns the name of the configured database.
+pub fn database_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    connect_options(config)?
+        .get_database()
+        .map(String::from)
+        .ok_or(anyhow!(
+            "The database URL does not contain a database name!"
+        ))
+}
+
+/// Connects to the database with the given connect options.
+///
+/// The connect, statement, and lock timeouts (see [`DatabaseConfig`]) are applied; the statement timeout maps to MySQL's `max_execution_time` which only applies to `SELECT` statements.
+pub async fn connect(
+    config: &DatabaseConfig,
+    options: &MySqlConnectOptions,
+) -> Result<MySqlConnection, anyhow::Error> {
+    let mut connection = match config.timeouts.connect {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), options.connect())
+            .await
+            .map_err(|_| anyhow!("Connecting to the database timed out after {}s!", secs))?,
+        None => options.connect().await,
+    }
+    .context("Failed to connect to database!")?;
+
+    if let Some(secs) = config.timeouts.statement {
+        connection
+            .execute(format!("SET SESSION max_execution_time = {}", secs * 1000).as_str())
+            .await
+            .context("Failed to set statement timeout!")?;
+    }
+    if let Some(secs) = config.timeouts.lock {
+        connection
+            .execute(
+                format!(
+                    "SET SESSION lock_wait_timeout = {0}, innodb_lock_wait_timeout = {0}",
+                    secs
+                )
+                .as_str(),
+            )
+            .await
+            .context("Failed to set lock timeout!")?;
+    }
+
+    Ok(connection)
+}
+
+/// Creates the configured database, connecting to the `mysql` system database to do so.
+pub async fn create_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = database_name(config)?;
+    let mut connection = connect(config, &connect_options(config)?.database("mysql")).await?;
+    connection
+        .execute(format!("CREATE DATABASE {}", quote_identifier(&db_name)).as_str())
+        .await
+        .context("Failed to create database!")?;
+
+    Ok(db_name)
+}
+
+/// Drops the configured database if it exists, connecting to the `mysql` system database to do so.
+pub async fn drop_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = database_name(config)?;
+    let mut connection = connect(config, &connect_options(config)?.database("mysql")).await?;
+    connection
+        .execute(format!("DROP DATABASE IF EXISTS {}", quote_identifier(&db_name)).as_str())
+        .await
+        .context("Failed to drop database!")?;
+
+    Ok(db_name)
+}
+
+/// Lists the versions, descriptions, and installation times of all successfully applied migrations, ordered by version.
+///
+/// Nothing is changed in the database, i.e. the migrations table is not created if it doesn't exist.
+pub async fn applied_migrations(
+    config: &DatabaseConfig,
+) -> Result<Vec<(i64, String, DateTime<Utc>)>, anyhow::Error> {
+    let mut connection = connect(config, &connect_options(config)?).await?;
+
+    let migrations_tables: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM information_schema.tables
+        WHERE table_schema = DATABASE() AND table_name = '_sqlx_migrations'",
+    )
+    .fetch_one(&mut connection)
+    .await
+    .context("Failed to check for migrations table!")?;
+    if migrations_tables == 0 {
+        return Ok(vec![]);
+    }
+
+    sqlx::query_as(
+        "SELECT version, description, installed_on FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to list applied migrations!")
+}
+
+/// Executes seed statements in a transaction and returns the number of rows affected by each of them.
+///
+/// In a dry run, the transaction is rolled back instead of committed. Note that MySQL commits DDL statements (e.g. `CREATE TABLE`) implicitly so those cannot be rolled back.
+pub async fn run_seeds(
+    config: &DatabaseConfig,
+    statements: &[String],
+    dry_run: bool,
+) -> Result<Vec<u64>, anyhow::Error> {
+    let mut connection = connect(config, &connect_options(config)?).await?;
+
+    let mut transaction = connection
+        .begin()
+        .await
+        .context("Failed to start transaction!")?;
+    let mut rows_affected = vec![];
+    for statement in statements {
+        let result = transaction
+            .execute(statement.as_str())
+            .await
+            .context(format!("Failed to execute seed statement: {}", statement))?;
+        rows_affected.push(result.rows_affected());
+    }
+
+    if dry_run {
+        transaction
+            .rollback()
+            .await
+            .context("Failed to roll back transaction!")?;
+    } else {
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction!")?;
+    }
+
+    Ok(rows_affected)
+}
+
+/// Quotes an identifier (e.g. a database name) so it can safely be interpolated into MySQL statements.
+///
+/// Example:
+/// ```
+/// assert_eq!(quote_identifier("my`db"), "`my``db`");
+/// ```
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("`{}`", identifier.replace('`', "``"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quote_identifier;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_snapshot!(quote_identifier("my_app"), @"`my_app`");
+        assert_snapshot!(quote_identifier("my`db"), @"`my``db`");
+    }
+}
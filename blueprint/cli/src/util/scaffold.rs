@@ -0,0 +1,945 @@
+use anyhow::anyhow;
+
+/// The type of an entity field as declared in the field DSL, e.g. `string` in `title:string`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    /// A short string, stored as `varchar(255)`
+    String,
+    /// A string of arbitrary length, stored as `text`
+    Text,
+    /// A 32 bit integer, stored as `integer`
+    Integer,
+    /// A 64 bit integer, stored as `bigint`
+    BigInt,
+    /// A 64 bit floating point number, stored as `double precision`
+    Float,
+    /// A boolean, stored as `boolean`
+    Boolean,
+    /// A UUID, stored as `uuid`
+    Uuid,
+    /// The id of an associated record, stored as `uuid` with a foreign key constraint
+    Reference,
+}
+
+impl FieldType {
+    /// Parses a field type from its name in the field DSL.
+    pub fn parse(name: &str) -> Result<FieldType, anyhow::Error> {
+        match name {
+            "string" => Ok(FieldType::String),
+            "text" => Ok(FieldType::Text),
+            "int" | "integer" => Ok(FieldType::Integer),
+            "bigint" => Ok(FieldType::BigInt),
+            "float" => Ok(FieldType::Float),
+            "bool" | "boolean" => Ok(FieldType::Boolean),
+            "uuid" => Ok(FieldType::Uuid),
+            "references" => Ok(FieldType::Reference),
+            unknown => Err(anyhow!(
+                r#"Unknown field type "{}" – expected one of string, text, int, bigint, float, bool, uuid, references!"#,
+                unknown
+            )),
+        }
+    }
+
+    /// The Rust type the field is represented as in the entity struct.
+    pub fn rust_type(&self) -> &'static str {
+        match self {
+            FieldType::String | FieldType::Text => "String",
+            FieldType::Integer => "i32",
+            FieldType::BigInt => "i64",
+            FieldType::Float => "f64",
+            FieldType::Boolean => "bool",
+            FieldType::Uuid | FieldType::Reference => "Uuid",
+        }
+    }
+
+    /// The PostgreSQL type of the field's column.
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            FieldType::String => "varchar(255)",
+            FieldType::Text => "text",
+            FieldType::Integer => "integer",
+            FieldType::BigInt => "bigint",
+            FieldType::Float => "double precision",
+            FieldType::Boolean => "boolean",
+            FieldType::Uuid | FieldType::Reference => "uuid",
+        }
+    }
+
+    /// The arguments of the `#[dummy(…)]` attribute that generates fake values for the field in tests if the default fake values are not suitable.
+    pub fn dummy(&self) -> Option<&'static str> {
+        match self {
+            FieldType::String => Some(r#"faker = "Sentence(2..5)""#),
+            FieldType::Text => Some(r#"faker = "Paragraph(1..3)""#),
+            FieldType::Integer => Some(r#"faker = "1..1000""#),
+            FieldType::BigInt => Some(r#"faker = "1..1000000""#),
+            FieldType::Float => Some(r#"faker = "0.0..1000.0""#),
+            FieldType::Boolean => None,
+            FieldType::Uuid | FieldType::Reference => Some(r#"expr = "Uuid::new_v4()""#),
+        }
+    }
+
+    /// The arguments of the `#[validate(…)]` attribute that validates the field in changesets.
+    pub fn validation(&self) -> Option<&'static str> {
+        match self {
+            FieldType::String => Some("length(min = 1, max = 255)"),
+            FieldType::Text => Some("length(min = 1)"),
+            _ => None,
+        }
+    }
+
+    /// Whether values of the type are [`Copy`].
+    pub fn is_copy(&self) -> bool {
+        !matches!(self, FieldType::String | FieldType::Text)
+    }
+}
+
+/// A field of an entity, parsed from the field DSL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The name of the field, used for both the struct field and the database column
+    pub name: String,
+    /// The type of the field
+    pub field_type: FieldType,
+    /// Whether the field is optional, i.e. an [`Option`] in Rust and a nullable column in the database
+    pub optional: bool,
+    /// Whether the field's values must be unique, enforced by a unique index in the database
+    pub unique: bool,
+}
+
+impl Field {
+    /// The Rust type the field is represented as in the entity struct, wrapped in an [`Option`] for optional fields.
+    pub fn rust_type(&self) -> String {
+        if self.optional {
+            format!("Option<{}>", self.field_type.rust_type())
+        } else {
+            String::from(self.field_type.rust_type())
+        }
+    }
+
+    /// The arguments of the `#[dummy(…)]` attribute that generates fake values for the field in tests if the default fake values are not suitable.
+    pub fn dummy(&self) -> Option<String> {
+        match (self.field_type, self.optional) {
+            (FieldType::Uuid, true) => Some(String::from(r#"expr = "Some(Uuid::new_v4())""#)),
+            // optional associations are left empty as random ids would violate the foreign key constraint
+            (FieldType::Reference, true) => Some(String::from(r#"expr = "None""#)),
+            (field_type, _) => field_type.dummy().map(String::from),
+        }
+    }
+
+    /// Whether values of the field are [`Copy`].
+    pub fn is_copy(&self) -> bool {
+        self.field_type.is_copy()
+    }
+
+    /// A fixed example value for the field as a Rust expression that can be passed to the field's setter in the entity's factory along with its JSON representation in snapshots of API responses (in which UUIDs are redacted).
+    ///
+    /// Reference fields have no example value as the factory creates the associated record – their JSON representation is that of the associated record's id or `null` for optional references.
+    pub fn example(&self) -> (Option<String>, String) {
+        let (value, json) = match self.field_type {
+            FieldType::String | FieldType::Text => {
+                let example = format!("Example {}", self.name);
+                (
+                    format!(r#"String::from("{}")"#, example),
+                    format!(r#""{}""#, example),
+                )
+            }
+            FieldType::Integer | FieldType::BigInt => (String::from("42"), String::from("42")),
+            FieldType::Float => (String::from("1.5"), String::from("1.5")),
+            FieldType::Boolean => (String::from("true"), String::from("true")),
+            FieldType::Uuid => (String::from("Uuid::nil()"), String::from(r#""[uuid]""#)),
+            FieldType::Reference if self.optional => return (None, String::from("null")),
+            FieldType::Reference => return (None, String::from(r#""[uuid]""#)),
+        };
+        if self.optional {
+            (Some(format!("Some({})", value)), json)
+        } else {
+            (Some(value), json)
+        }
+    }
+
+    /// The name of the associated entity for [`FieldType::Reference`] fields, e.g. `post` for the `post_id` field.
+    pub fn association(&self) -> Option<&str> {
+        match self.field_type {
+            FieldType::Reference => self.name.strip_suffix("_id"),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a field from the `name:type` DSL, e.g. `title:string`.
+///
+/// A `?` after the type marks the field as optional and a trailing `:unique` requires the field's values to be unique. Fields of the `references` type hold the id of an associated record and are named after the associated entity with an `_id` suffix, e.g. `post:references` results in the `post_id` field.
+///
+/// Example:
+/// ```
+/// let field = parse_field("email:string:unique")?;
+/// let field = parse_field("age:int?")?;
+/// let field = parse_field("post:references")?;
+/// ```
+pub fn parse_field(field: &str) -> Result<Field, anyhow::Error> {
+    let mut parts = field.split(':');
+    let name = parts.next().unwrap_or_default();
+    let field_type = parts
+        .next()
+        .ok_or_else(|| anyhow!(r#"Invalid field "{}" – expected <name>:<type>!"#, field))?;
+
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !is_identifier || RESERVED_NAMES.contains(&name) {
+        return Err(anyhow!(
+            r#"Invalid field name "{}" – expected a snake_case name that is not a Rust keyword or "id"!"#,
+            name
+        ));
+    }
+
+    let (field_type, optional) = match field_type.strip_suffix('?') {
+        Some(field_type) => (field_type, true),
+        None => (field_type, false),
+    };
+
+    let mut unique = false;
+    for modifier in parts {
+        match modifier {
+            "unique" => unique = true,
+            unknown => {
+                return Err(anyhow!(
+                    r#"Unknown field modifier "{}" – expected unique!"#,
+                    unknown
+                ))
+            }
+        }
+    }
+
+    let field_type = FieldType::parse(field_type)?;
+    let name = match field_type {
+        FieldType::Reference => format!("{}_id", name),
+        _ => String::from(name),
+    };
+
+    Ok(Field {
+        name,
+        field_type,
+        optional,
+        unique,
+    })
+}
+
+/// Field names that cannot be used because they are Rust keywords or the name of the primary key
+const RESERVED_NAMES: &[&str] = &[
+    "id", "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+/// Registers routes in the source of the application's routes file (`web/src/routes.rs`).
+///
+/// The `routes` (e.g. `.route("/posts", get(posts::read_all))`) are inserted right before the router's `.with_state(…)` call so that they are not affected by any route layers (e.g. authentication middlewares) added before. The `import` (e.g. `use crate::controllers::posts;`) is added before the first `use` declaration unless the source already contains it.
+pub fn register_routes(
+    source: &str,
+    import: &str,
+    routes: &[String],
+) -> Result<String, anyhow::Error> {
+    let with_state = source
+        .rfind(".with_state(")
+        .ok_or_else(|| anyhow!("Failed to find the router's .with_state(…) call!"))?;
+    let (head, tail) = source.split_at(with_state);
+
+    let mut result = insert_import(head, import);
+    for route in routes {
+        result.push_str("\n        ");
+        result.push_str(route);
+    }
+    result.push_str("\n        ");
+    result.push_str(tail);
+
+    Ok(result)
+}
+
+/// Attaches a layer to the routes with a path in the source of the application's routes file (`web/src/routes.rs`), e.g. a rate limit to the routes at `/login`.
+///
+/// The `layer` (e.g. `axum::middleware::from_fn(…)`) is added to the method router of every `.route("<path>", …)` call via `.route_layer(…)` so that it only applies to those routes. Returns an error if no route with the path exists.
+pub fn attach_route_layer(source: &str, path: &str, layer: &str) -> Result<String, anyhow::Error> {
+    let mut result = String::new();
+    let mut rest = source;
+    let mut attached = false;
+    while let Some(start) = rest.find(".route(") {
+        let args = start + ".route(".len();
+        let close = args + find_closing_paren(&rest[args..])?;
+        let route = &rest[args..close];
+        let is_path = route
+            .trim_start()
+            .strip_prefix(&format!("\"{}\"", path))
+            .is_some_and(|route| route.trim_start().starts_with(','));
+        if is_path {
+            let method_router = route.trim_end().trim_end_matches(',').trim_end();
+            result.push_str(&rest[..args]);
+            result.push_str(method_router);
+            result.push_str(&format!(".route_layer({})", layer));
+            result.push_str(&route[method_router.len()..]);
+            attached = true;
+        } else {
+            result.push_str(&rest[..close]);
+        }
+        rest = &rest[close..];
+    }
+    if !attached {
+        return Err(anyhow!("Failed to find a route with the path {}!", path));
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+/// Returns whether the source of the application's routes file (`web/src/routes.rs`) already routes requests with the HTTP `method` (e.g. `get`) to the `path`, in which case adding another route for them makes axum panic at startup.
+pub fn has_route(source: &str, method: &str, path: &str) -> Result<bool, anyhow::Error> {
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    let method_call = format!("{}(", method);
+    let mut rest = source;
+    while let Some(start) = rest.find(".route(") {
+        let args = start + ".route(".len();
+        let close = args + find_closing_paren(&rest[args..])?;
+        let route = &rest[args..close];
+        let method_router = route
+            .trim_start()
+            .strip_prefix(&format!("\"{}\"", path))
+            .and_then(|route| route.trim_start().strip_prefix(','));
+        if let Some(method_router) = method_router {
+            let routes_method = method_router
+                .match_indices(&method_call)
+                .any(|(i, _)| !method_router[..i].ends_with(is_ident_char));
+            if routes_method {
+                return Ok(true);
+            }
+        }
+        rest = &rest[close..];
+    }
+
+    Ok(false)
+}
+
+/// Returns the index of the parenthesis that closes the one opened right before `source`, skipping string literals.
+fn find_closing_paren(source: &str) -> Result<usize, anyhow::Error> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in source.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string && depth == 0 => return Ok(i),
+            ')' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+
+    Err(anyhow!("Failed to find the closing parenthesis!"))
+}
+
+/// Registers a controller's OpenAPI document in the source of the application's OpenAPI file (`web/src/openapi.rs`).
+///
+/// The `api_doc` (e.g. `posts::ApiDoc::openapi()`) is merged into the document built by the `api_doc` function by appending a `.merge_from(…)` call to the function's body. The `import` (e.g. `use crate::controllers::posts;`) is added before the first `use` declaration.
+pub fn register_api_doc(
+    source: &str,
+    import: &str,
+    api_doc: &str,
+) -> Result<String, anyhow::Error> {
+    let function = source
+        .find("fn api_doc(")
+        .ok_or_else(|| anyhow!("Failed to find function api_doc!"))?;
+    let end = function
+        + source[function..]
+            .find("\n}")
+            .ok_or_else(|| anyhow!("Failed to find the end of function api_doc!"))?;
+    let (head, tail) = source.split_at(end);
+
+    let mut result = insert_import(head, import);
+    result.push_str("\n        .merge_from(");
+    result.push_str(api_doc);
+    result.push(')');
+    result.push_str(tail);
+
+    Ok(result)
+}
+
+/// Inserts a `use` declaration (e.g. `use crate::controllers::posts;`) before the first `use` declaration in the passed source unless the source already contains it, trimming trailing whitespace from the result.
+pub fn insert_import(source: &str, import: &str) -> String {
+    if source.lines().any(|line| line == import) {
+        return String::from(source.trim_end());
+    }
+
+    let mut result = String::new();
+    let first_use = source
+        .match_indices("use ")
+        .map(|(i, _)| i)
+        .find(|i| *i == 0 || source[..*i].ends_with('\n'));
+    match first_use {
+        Some(first_use) => {
+            result.push_str(&source[..first_use]);
+            result.push_str(import);
+            result.push('\n');
+            result.push_str(source[first_use..].trim_end());
+        }
+        None => result.push_str(source.trim_end()),
+    }
+    result
+}
+
+/// Inserts a module declaration (e.g. `pub mod backfill_slugs;` with its doc comment) before the source's test module or, if there is none, at the end of the source unless the source already contains it.
+///
+/// Declarations are not appended after the test module since clippy rejects items after it (see `clippy::items_after_test_module`).
+pub fn insert_module(source: &str, declaration: &str) -> String {
+    if source.contains(declaration) {
+        return String::from(source);
+    }
+
+    match source.find("#[cfg(test)]\nmod tests") {
+        Some(tests) => format!(
+            "{}\n\n{}\n\n{}",
+            source[..tests].trim_end(),
+            declaration,
+            &source[tests..]
+        ),
+        None => format!("{}\n{}\n", source.trim_end(), declaration),
+    }
+}
+
+/// Appends a statement to the body of a function in a source file, e.g. `registry.register::<ReportJob>();` to the `register` function that registers all jobs with the worker.
+pub fn append_to_function(
+    source: &str,
+    function: &str,
+    statement: &str,
+) -> Result<String, anyhow::Error> {
+    let signature = source
+        .find(&format!("fn {}(", function))
+        .ok_or_else(|| anyhow!("Failed to find function {}!", function))?;
+    let open = signature
+        + source[signature..]
+            .find('{')
+            .ok_or_else(|| anyhow!("Failed to find the body of function {}!", function))?;
+
+    let mut depth = 0;
+    let mut close = None;
+    for (i, c) in source[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close.ok_or_else(|| anyhow!("Failed to find the end of function {}!", function))?;
+
+    Ok(format!(
+        "{}\n    {}\n{}",
+        source[..close].trim_end(),
+        statement,
+        &source[close..]
+    ))
+}
+
+/// Appends a link to a method chain in a source file, e.g. `.layer(…)` to the [`tower::ServiceBuilder`](https://docs.rs/tower/latest/tower/struct.ServiceBuilder.html) that lists the application-wide middlewares.
+///
+/// The chain is identified by its `start` (e.g. `let middlewares = ServiceBuilder::new()`) and ends with the first `;` after that.
+pub fn append_to_chain(source: &str, start: &str, link: &str) -> Result<String, anyhow::Error> {
+    let start = source
+        .find(start)
+        .ok_or_else(|| anyhow!("Failed to find {}!", start))?;
+    let end = start
+        + source[start..]
+            .find(';')
+            .ok_or_else(|| anyhow!("Failed to find the end of {}!", &source[start..]))?;
+
+    Ok(format!(
+        "{}\n        {}{}",
+        source[..end].trim_end(),
+        link,
+        &source[end..]
+    ))
+}
+
+/// Appends a field to a tuple struct in a source file, e.g. `posts::PostQuery` to the `Query` struct that merges the queries of all GraphQL types.
+pub fn append_to_tuple_struct(
+    source: &str,
+    name: &str,
+    field: &str,
+) -> Result<String, anyhow::Error> {
+    let open = source
+        .find(&format!("struct {}(", name))
+        .map(|start| start + "struct (".len() + name.len())
+        .ok_or_else(|| anyhow!("Failed to find tuple struct {}!", name))?;
+    let close = open
+        + source[open..]
+            .find(");")
+            .ok_or_else(|| anyhow!("Failed to find the end of tuple struct {}!", name))?;
+
+    let fields = source[open..close].trim_end().trim_end_matches(',');
+    let separator = if fields.trim().is_empty() { "" } else { ", " };
+
+    Ok(format!(
+        "{}{}{}{}{}",
+        &source[..open],
+        fields,
+        separator,
+        field,
+        &source[close..]
+    ))
+}
+
+/// Adds a dependency to the `[dependencies]` section of a crate's `Cargo.toml`, e.g. `argon2 = "0.5"`.
+///
+/// The dependency is inserted in alphabetical order. If the crate depends on the dependency already, the manifest is returned unchanged.
+pub fn add_dependency(manifest: &str, name: &str, version: &str) -> Result<String, anyhow::Error> {
+    let section = manifest
+        .lines()
+        .position(|line| line.trim() == "[dependencies]")
+        .ok_or_else(|| anyhow!("Failed to find the [dependencies] section!"))?;
+
+    let mut lines: Vec<&str> = manifest.lines().collect();
+    let mut insert_at = section + 1;
+    for (i, line) in lines.iter().enumerate().skip(section + 1) {
+        let line = line.trim();
+        if line.starts_with('[') {
+            break;
+        }
+        let Some((dependency, _)) = line.split_once('=') else {
+            continue;
+        };
+        let dependency = dependency.trim();
+        if dependency == name {
+            return Ok(String::from(manifest));
+        }
+        if dependency < name {
+            insert_at = i + 1;
+        }
+    }
+
+    let dependency = format!("{} = {}", name, version);
+    lines.insert(insert_at, &dependency);
+
+    let mut result = lines.join("\n");
+    if manifest.ends_with('\n') {
+        result.push('\n');
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_dependency, append_to_chain, append_to_function, append_to_tuple_struct,
+        attach_route_layer, has_route, insert_import, insert_module, parse_field, register_api_doc,
+        register_routes,
+    };
+    use insta::{assert_debug_snapshot, assert_snapshot};
+
+    #[test]
+    fn test_parse_field() {
+        let fields: Vec<_> = [
+            "title:string",
+            "views:int?",
+            "external_id:uuid:unique",
+            "email:string?:unique",
+            "post:references",
+        ]
+            .iter()
+            .map(|field| parse_field(field).unwrap())
+            .collect();
+
+        assert_debug_snapshot!(fields, @r###"
+        [
+            Field {
+                name: "title",
+                field_type: String,
+                optional: false,
+                unique: false,
+            },
+            Field {
+                name: "views",
+                field_type: Integer,
+                optional: true,
+                unique: false,
+            },
+            Field {
+                name: "external_id",
+                field_type: Uuid,
+                optional: false,
+                unique: true,
+            },
+            Field {
+                name: "email",
+                field_type: String,
+                optional: true,
+                unique: true,
+            },
+            Field {
+                name: "post_id",
+                field_type: Reference,
+                optional: false,
+                unique: false,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_field_example() {
+        let examples: Vec<_> = [
+            "title:string",
+            "views:int?",
+            "post:references",
+            "post:references?",
+        ]
+        .iter()
+        .map(|field| parse_field(field).unwrap().example())
+        .collect();
+
+        assert_debug_snapshot!(examples, @r###"
+        [
+            (
+                Some(
+                    "String::from(\"Example title\")",
+                ),
+                "\"Example title\"",
+            ),
+            (
+                Some(
+                    "Some(42)",
+                ),
+                "42",
+            ),
+            (
+                None,
+                "\"[uuid]\"",
+            ),
+            (
+                None,
+                "null",
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_parse_field_errors() {
+        let errors: Vec<_> = [
+            "title",
+            "Title:string",
+            "type:string",
+            "id:uuid",
+            "title:date",
+            "title:string:index",
+        ]
+        .iter()
+        .map(|field| parse_field(field).unwrap_err().to_string())
+        .collect();
+
+        assert_debug_snapshot!(errors, @r###"
+        [
+            "Invalid field \"title\" – expected <name>:<type>!",
+            "Invalid field name \"Title\" – expected a snake_case name that is not a Rust keyword or \"id\"!",
+            "Invalid field name \"type\" – expected a snake_case name that is not a Rust keyword or \"id\"!",
+            "Invalid field name \"id\" – expected a snake_case name that is not a Rust keyword or \"id\"!",
+            "Unknown field type \"date\" – expected one of string, text, int, bigint, float, bool, uuid, references!",
+            "Unknown field modifier \"index\" – expected unique!",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_register_routes() {
+        let source = r#"use crate::state::AppState;
+use axum::Router;
+use std::sync::Arc;
+
+pub fn init_routes(app_state: AppState) -> Router {
+    let shared_app_state = Arc::new(app_state);
+    Router::new().with_state(shared_app_state)
+}
+"#;
+        let routes = [
+            String::from(r#".route("/posts", get(posts::read_all))"#),
+            String::from(r#".route("/posts/:id", get(posts::read_one))"#),
+        ];
+
+        assert_snapshot!(register_routes(source, "use crate::controllers::posts;", &routes).unwrap(), @r###"
+        use crate::controllers::posts;
+        use crate::state::AppState;
+        use axum::Router;
+        use std::sync::Arc;
+
+        pub fn init_routes(app_state: AppState) -> Router {
+            let shared_app_state = Arc::new(app_state);
+            Router::new()
+                .route("/posts", get(posts::read_all))
+                .route("/posts/:id", get(posts::read_one))
+                .with_state(shared_app_state)
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_has_route() {
+        let source = r#"pub fn init_routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/posts", crate::registry::get(posts::read_all).post(posts::create))
+        .route("/posts/:id", get(posts::read_one))
+        .route("/login", post(auth::login))
+        .route("/tags", forget(tags::read_all))
+        .with_state(shared_app_state)
+}
+"#;
+
+        assert!(has_route(source, "get", "/posts").unwrap());
+        assert!(has_route(source, "post", "/posts").unwrap());
+        assert!(has_route(source, "get", "/posts/:id").unwrap());
+        assert!(!has_route(source, "get", "/login").unwrap());
+        assert!(!has_route(source, "get", "/tags").unwrap());
+        assert!(!has_route(source, "get", "/posts/index").unwrap());
+    }
+
+    #[test]
+    fn test_attach_route_layer() {
+        let source = r#"pub fn init_routes(app_state: AppState) -> Router {
+    let shared_app_state = Arc::new(app_state);
+    Router::new()
+        .route("/login", post(auth::login))
+        .route("/login", get(auth::form))
+        .route("/logout", post(auth::logout))
+        .route(
+            "/login/reset",
+            post(auth::reset).route_layer(middleware::from_fn(|request, next| async { "(" })),
+        )
+        .with_state(shared_app_state)
+}
+"#;
+
+        assert_snapshot!(attach_route_layer(source, "/login", "limit(\"login\")").unwrap(), @r###"
+        pub fn init_routes(app_state: AppState) -> Router {
+            let shared_app_state = Arc::new(app_state);
+            Router::new()
+                .route("/login", post(auth::login).route_layer(limit("login")))
+                .route("/login", get(auth::form).route_layer(limit("login")))
+                .route("/logout", post(auth::logout))
+                .route(
+                    "/login/reset",
+                    post(auth::reset).route_layer(middleware::from_fn(|request, next| async { "(" })),
+                )
+                .with_state(shared_app_state)
+        }
+        "###);
+        assert_snapshot!(attach_route_layer(source, "/login/reset", "limit(\"login\")").unwrap(), @r###"
+        pub fn init_routes(app_state: AppState) -> Router {
+            let shared_app_state = Arc::new(app_state);
+            Router::new()
+                .route("/login", post(auth::login))
+                .route("/login", get(auth::form))
+                .route("/logout", post(auth::logout))
+                .route(
+                    "/login/reset",
+                    post(auth::reset).route_layer(middleware::from_fn(|request, next| async { "(" })).route_layer(limit("login")),
+                )
+                .with_state(shared_app_state)
+        }
+        "###);
+        assert!(attach_route_layer(source, "/signup", "limit(\"signup\")").is_err());
+    }
+
+    #[test]
+    fn test_insert_import() {
+        let source = r#"use crate::state::SharedAppState;
+use axum::extract::State;
+
+pub async fn index() {}
+"#;
+
+        let source = insert_import(source, "use crate::templates::Template;");
+        assert_snapshot!(insert_import(&source, "use crate::templates::Template;"), @r###"
+        use crate::templates::Template;
+        use crate::state::SharedAppState;
+        use axum::extract::State;
+
+        pub async fn index() {}
+        "###);
+    }
+
+    #[test]
+    fn test_register_api_doc() {
+        let source = r#"use utoipa::OpenApi;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+"#;
+        let source = register_api_doc(
+            source,
+            "use crate::controllers::posts;",
+            "posts::ApiDoc::openapi()",
+        )
+        .unwrap();
+        let source = register_api_doc(
+            &source,
+            "use crate::controllers::comments;",
+            "comments::ApiDoc::openapi()",
+        )
+        .unwrap();
+
+        assert_snapshot!(source, @r###"
+        use crate::controllers::comments;
+        use crate::controllers::posts;
+        use utoipa::OpenApi;
+
+        pub fn api_doc() -> utoipa::openapi::OpenApi {
+            ApiDoc::openapi()
+                .merge_from(posts::ApiDoc::openapi())
+                .merge_from(comments::ApiDoc::openapi())
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_append_to_function() {
+        let source = r#"use crate::Registry;
+
+/// Registers all jobs with the worker.
+pub fn register(registry: &mut Registry) {}
+"#;
+        let source =
+            append_to_function(source, "register", "registry.register::<a::AJob>();").unwrap();
+        let source =
+            append_to_function(&source, "register", "registry.register::<b::BJob>();").unwrap();
+
+        assert_snapshot!(source, @r###"
+        use crate::Registry;
+
+        /// Registers all jobs with the worker.
+        pub fn register(registry: &mut Registry) {
+            registry.register::<a::AJob>();
+            registry.register::<b::BJob>();
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_insert_module() {
+        let source = r#"/// Registers all tasks.
+pub fn register(registry: &mut Registry) {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_register() {}
+}
+"#;
+        let source = insert_module(source, "/// The [`a::ATask`] task\npub mod a;");
+        let source = insert_module(&source, "/// The [`b::BTask`] task\npub mod b;");
+        let source = insert_module(&source, "/// The [`b::BTask`] task\npub mod b;");
+
+        assert_snapshot!(source, @r###"
+        /// Registers all tasks.
+        pub fn register(registry: &mut Registry) {}
+
+        /// The [`a::ATask`] task
+        pub mod a;
+
+        /// The [`b::BTask`] task
+        pub mod b;
+
+        #[cfg(test)]
+        mod tests {
+            #[test]
+            fn test_register() {}
+        }
+        "###);
+
+        let source = insert_module("pub fn register() {}\n", "pub mod a;");
+        assert_eq!(source, "pub fn register() {}\npub mod a;\n");
+    }
+
+    #[test]
+    fn test_append_to_chain() {
+        let source = r#"pub fn register(router: Router, app_state: &SharedAppState) -> Router {
+    let middlewares = ServiceBuilder::new();
+
+    router.layer(middlewares)
+}
+"#;
+        let start = "let middlewares = ServiceBuilder::new()";
+        let source = append_to_chain(source, start, ".layer(from_fn(a::a))").unwrap();
+        let source = append_to_chain(&source, start, ".layer(from_fn(b::b))").unwrap();
+
+        assert_snapshot!(source, @r###"
+        pub fn register(router: Router, app_state: &SharedAppState) -> Router {
+            let middlewares = ServiceBuilder::new()
+                .layer(from_fn(a::a))
+                .layer(from_fn(b::b));
+
+            router.layer(middlewares)
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_append_to_tuple_struct() {
+        let source = r#"/// The root query.
+#[derive(MergedObject, Default)]
+pub struct Query(BaseQuery);
+"#;
+        let source = append_to_tuple_struct(source, "Query", "posts::PostQuery").unwrap();
+        let source = append_to_tuple_struct(&source, "Query", "comments::CommentQuery").unwrap();
+
+        assert_snapshot!(source, @r###"
+        /// The root query.
+        #[derive(MergedObject, Default)]
+        pub struct Query(BaseQuery, posts::PostQuery, comments::CommentQuery);
+        "###);
+    }
+
+    #[test]
+    fn test_add_dependency() {
+        let manifest = r#"[package]
+name = "my-app-db"
+
+[dependencies]
+anyhow = "1.0"
+serde = { version = "1.0", features = ["derive"] }
+uuid = "1.5"
+
+[dev-dependencies]
+fake = "3.0"
+"#;
+        let manifest = add_dependency(
+            manifest,
+            "argon2",
+            r#"{ version = "0.5", features = ["std"] }"#,
+        )
+        .unwrap();
+        let manifest = add_dependency(&manifest, "sha2", r#""0.10""#).unwrap();
+        let manifest = add_dependency(&manifest, "serde", r#""1.0""#).unwrap();
+
+        assert_snapshot!(manifest, @r###"
+        [package]
+        name = "my-app-db"
+
+        [dependencies]
+        anyhow = "1.0"
+        argon2 = { version = "0.5", features = ["std"] }
+        serde = { version = "1.0", features = ["derive"] }
+        sha2 = "0.10"
+        uuid = "1.5"
+
+        [dev-dependencies]
+        fake = "3.0"
+        "###);
+    }
+}
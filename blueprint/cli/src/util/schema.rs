@@ -0,0 +1,301 @@
+use sqlx::{Acquire, Postgres};
+
+/// A table in the database's schema as determined by [`introspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// The table's name
+    pub name: String,
+    /// The table's columns in the order they are defined in
+    pub columns: Vec<Column>,
+    /// The table's constraints as `(name, definition)` pairs, e.g. `("tasks_pkey", "PRIMARY KEY (id)")`
+    pub constraints: Vec<(String, String)>,
+    /// The definitions of the table's indexes that are not backing a constraint, e.g. `CREATE INDEX …`
+    pub indexes: Vec<String>,
+}
+
+/// A column of a [`Table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// The column's name
+    pub name: String,
+    /// The column's data type, e.g. `character varying(255)`
+    pub data_type: String,
+    /// Whether the column is `NOT NULL`
+    pub not_null: bool,
+    /// The column's default expression if any
+    pub default: Option<String>,
+}
+
+impl Table {
+    /// Renders a normalized `CREATE TABLE` statement for the table followed by `CREATE INDEX` statements for its indexes.
+    ///
+    /// Example:
+    /// ```
+    /// let ddl = table.to_ddl();
+    /// // CREATE TABLE tasks (
+    /// //     id uuid NOT NULL DEFAULT gen_random_uuid(),
+    /// //     CONSTRAINT tasks_pkey PRIMARY KEY (id)
+    /// // );
+    /// ```
+    pub fn to_ddl(&self) -> String {
+        let definitions: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut definition = format!("{} {}", column.name, column.data_type);
+                if column.not_null {
+                    definition.push_str(" NOT NULL");
+                }
+                if let Some(default) = &column.default {
+                    definition.push_str(&format!(" DEFAULT {}", default));
+                }
+                definition
+            })
+            .chain(
+                self.constraints
+                    .iter()
+                    .map(|(name, definition)| format!("CONSTRAINT {} {}", name, definition)),
+            )
+            .collect();
+
+        let mut ddl = format!(
+            "CREATE TABLE {} (\n    {}\n);",
+            self.name,
+            definitions.join(",\n    ")
+        );
+        for index in &self.indexes {
+            ddl.push_str(&format!("\n{};", index));
+        }
+
+        ddl
+    }
+}
+
+/// Renders a migration that reproduces the given tables, e.g. as the baseline migration for an existing database.
+///
+/// Sequences that are referenced in column defaults (e.g. of `serial` columns) are created before the tables, and foreign key constraints are added after all tables so that the tables can be created in any order. The migration starts with a comment marking it as auto-generated since other objects like extensions, types, views, or functions are not included.
+///
+/// Example:
+/// ```
+/// let tables = introspect(&mut connection).await?;
+/// let migration = baseline_migration(&tables);
+/// ```
+pub fn baseline_migration(tables: &[Table]) -> String {
+    let mut migration = String::from(
+        "-- This migration was generated from an existing database's schema and needs to be reviewed!\n\
+        -- Extensions, types, views, functions, triggers, identity columns, and sequence settings are not included.\n\n",
+    );
+
+    let mut sequences: Vec<&str> = vec![];
+    for column in tables.iter().flat_map(|table| &table.columns) {
+        let sequence = column
+            .default
+            .as_deref()
+            .and_then(|default| default.strip_prefix("nextval('"))
+            .and_then(|default| default.split('\'').next());
+        if let Some(sequence) = sequence {
+            if !sequences.contains(&sequence) {
+                sequences.push(sequence);
+            }
+        }
+    }
+    for sequence in &sequences {
+        migration.push_str(&format!("CREATE SEQUENCE {};\n\n", sequence));
+    }
+
+    let mut foreign_keys = vec![];
+    for table in tables {
+        let (table_foreign_keys, constraints): (Vec<_>, Vec<_>) = table
+            .constraints
+            .iter()
+            .cloned()
+            .partition(|(_, definition)| definition.starts_with("FOREIGN KEY"));
+        let table_without_foreign_keys = Table {
+            constraints,
+            ..table.clone()
+        };
+        migration.push_str(&table_without_foreign_keys.to_ddl());
+        migration.push_str("\n\n");
+        foreign_keys.extend(
+            table_foreign_keys
+                .into_iter()
+                .map(|(name, definition)| (&table.name, name, definition)),
+        );
+    }
+    for (table, name, definition) in foreign_keys {
+        migration.push_str(&format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} {};\n",
+            table, name, definition
+        ));
+    }
+
+    migration
+}
+
+/// Introspects the tables in the `public` schema of the database.
+///
+/// The tables are returned sorted by name; the tables that keep track of applied migrations (`_sqlx_migrations`, `_gerust_migration_sources`, and `_gerust_data_migrations`) are not included.
+pub async fn introspect<'a, A>(connection: A) -> Result<Vec<Table>, sqlx::Error>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut connection = connection.acquire().await?;
+
+    let table_names: Vec<String> = sqlx::query_scalar(
+        "SELECT c.relname::text FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'public' AND c.relkind IN ('r', 'p') AND c.relname NOT IN ('_sqlx_migrations', '_gerust_migration_sources', '_gerust_data_migrations')
+        ORDER BY c.relname",
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let columns: Vec<(String, String, String, bool, Option<String>)> = sqlx::query_as(
+        "SELECT c.relname::text, a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull, pg_get_expr(d.adbin, d.adrelid)
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        WHERE n.nspname = 'public' AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY c.relname, a.attnum",
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let constraints: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT c.relname::text, con.conname::text, pg_get_constraintdef(con.oid)
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'public'
+        ORDER BY c.relname, con.conname",
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let indexes: Vec<(String, String)> = sqlx::query_as(
+        "SELECT i.tablename::text, i.indexdef FROM pg_indexes i
+        WHERE i.schemaname = 'public' AND NOT EXISTS (
+            SELECT 1 FROM pg_constraint con WHERE con.conname = i.indexname
+        )
+        ORDER BY i.tablename, i.indexname",
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let tables = table_names
+        .into_iter()
+        .map(|name| Table {
+            columns: columns
+                .iter()
+                .filter(|(table, ..)| *table == name)
+                .map(|(_, column, data_type, not_null, default)| Column {
+                    name: column.clone(),
+                    data_type: data_type.clone(),
+                    not_null: *not_null,
+                    default: default.clone(),
+                })
+                .collect(),
+            constraints: constraints
+                .iter()
+                .filter(|(table, ..)| *table == name)
+                .map(|(_, constraint, definition)| (constraint.clone(), definition.clone()))
+                .collect(),
+            indexes: indexes
+                .iter()
+                .filter(|(table, _)| *table == name)
+                .map(|(_, definition)| definition.clone())
+                .collect(),
+            name,
+        })
+        .collect();
+
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{baseline_migration, Column, Table};
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_to_ddl() {
+        let table = Table {
+            name: String::from("tasks"),
+            columns: vec![
+                Column {
+                    name: String::from("id"),
+                    data_type: String::from("uuid"),
+                    not_null: true,
+                    default: Some(String::from("gen_random_uuid()")),
+                },
+                Column {
+                    name: String::from("description"),
+                    data_type: String::from("character varying(255)"),
+                    not_null: false,
+                    default: None,
+                },
+            ],
+            constraints: vec![(String::from("tasks_pkey"), String::from("PRIMARY KEY (id)"))],
+            indexes: vec![String::from(
+                "CREATE INDEX tasks_description_idx ON public.tasks USING btree (description)",
+            )],
+        };
+
+        assert_snapshot!(table.to_ddl(), @r###"
+        CREATE TABLE tasks (
+            id uuid NOT NULL DEFAULT gen_random_uuid(),
+            description character varying(255),
+            CONSTRAINT tasks_pkey PRIMARY KEY (id)
+        );
+        CREATE INDEX tasks_description_idx ON public.tasks USING btree (description);
+        "###);
+    }
+
+    #[test]
+    fn test_baseline_migration() {
+        let users = Table {
+            name: String::from("users"),
+            columns: vec![Column {
+                name: String::from("id"),
+                data_type: String::from("integer"),
+                not_null: true,
+                default: Some(String::from("nextval('users_id_seq'::regclass)")),
+            }],
+            constraints: vec![(String::from("users_pkey"), String::from("PRIMARY KEY (id)"))],
+            indexes: vec![],
+        };
+        let tasks = Table {
+            name: String::from("tasks"),
+            columns: vec![Column {
+                name: String::from("user_id"),
+                data_type: String::from("integer"),
+                not_null: true,
+                default: None,
+            }],
+            constraints: vec![(
+                String::from("tasks_user_id_fkey"),
+                String::from("FOREIGN KEY (user_id) REFERENCES users(id)"),
+            )],
+            indexes: vec![],
+        };
+
+        assert_snapshot!(baseline_migration(&[tasks, users]), @r###"
+        -- This migration was generated from an existing database's schema and needs to be reviewed!
+        -- Extensions, types, views, functions, triggers, identity columns, and sequence settings are not included.
+
+        CREATE SEQUENCE users_id_seq;
+
+        CREATE TABLE tasks (
+            user_id integer NOT NULL
+        );
+
+        CREATE TABLE users (
+            id integer NOT NULL DEFAULT nextval('users_id_seq'::regclass),
+            CONSTRAINT users_pkey PRIMARY KEY (id)
+        );
+
+        ALTER TABLE tasks ADD CONSTRAINT tasks_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id);
+        "###);
+    }
+}
@@ -0,0 +1,605 @@
+use anyhow::{anyhow, Context};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// The directive that includes another SQL file, e.g. `-- gerust:include shared/currencies.sql`.
+const INCLUDE_DIRECTIVE: &str = "-- gerust:include ";
+
+/// Splits a string of SQL into its individual statements.
+///
+/// Statements are separated by `;`. Semicolons inside of string literals, quoted identifiers, dollar-quoted strings (e.g. function bodies), and comments are not treated as separators. Statements that are empty or only consist of comments are dropped; all other statements are returned trimmed and without the trailing `;`.
+///
+/// Example:
+/// ```
+/// let statements = split_statements("INSERT INTO users (name) VALUES ('a;b'); DELETE FROM tasks;");
+/// assert_eq!(statements, vec!["INSERT INTO users (name) VALUES ('a;b')", "DELETE FROM tasks"]);
+/// ```
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut splitter = StatementSplitter::default();
+    let mut statements = vec![];
+    for line in sql.split_inclusive('\n') {
+        statements.extend(splitter.push(line));
+    }
+    statements.extend(splitter.finish());
+
+    statements
+}
+
+/// Splits SQL into statements like [`split_statements`] but incrementally, line by line, so that statements can span the lines pushed into it.
+#[derive(Default)]
+struct StatementSplitter {
+    current: String,
+    has_content: bool,
+    state: SplitState,
+}
+
+/// What the [`StatementSplitter`] is in the middle of at the end of the last line pushed into it.
+#[derive(Default)]
+enum SplitState {
+    #[default]
+    Code,
+    Quoted(char),
+    LineComment,
+    BlockComment(usize),
+    DollarQuoted(String),
+}
+
+impl StatementSplitter {
+    /// Splits a line (including its trailing newline), returning the statements it completes.
+    fn push(&mut self, sql: &str) -> Vec<String> {
+        let mut statements = vec![];
+        let mut chars = sql.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            match &mut self.state {
+                SplitState::Quoted(quote) => {
+                    self.current.push(c);
+                    if c == *quote {
+                        // doubled quotes are escaped quotes, e.g. 'it''s'
+                        if matches!(chars.peek(), Some((_, n)) if n == quote) {
+                            self.current.push(chars.next().unwrap().1);
+                        } else {
+                            self.state = SplitState::Code;
+                        }
+                    }
+                }
+                SplitState::LineComment => {
+                    self.current.push(c);
+                    if c == '\n' {
+                        self.state = SplitState::Code;
+                    }
+                }
+                SplitState::BlockComment(depth) => {
+                    self.current.push(c);
+                    if c == '*' && matches!(chars.peek(), Some((_, '/'))) {
+                        self.current.push(chars.next().unwrap().1);
+                        *depth -= 1;
+                        if *depth == 0 {
+                            self.state = SplitState::Code;
+                        }
+                    } else if c == '/' && matches!(chars.peek(), Some((_, '*'))) {
+                        // block comments can be nested in PostgreSQL
+                        self.current.push(chars.next().unwrap().1);
+                        *depth += 1;
+                    }
+                }
+                SplitState::DollarQuoted(tag) => {
+                    let body_end = sql[i..].find(tag.as_str()).map(|end| i + end + tag.len());
+                    self.current
+                        .push_str(&sql[i..body_end.unwrap_or(sql.len())]);
+                    while matches!(chars.peek(), Some((j, _)) if body_end.is_none_or(|end| *j < end))
+                    {
+                        chars.next();
+                    }
+                    if body_end.is_some() {
+                        self.state = SplitState::Code;
+                    }
+                }
+                SplitState::Code => match c {
+                    '\'' | '"' => {
+                        self.current.push(c);
+                        self.has_content = true;
+                        self.state = SplitState::Quoted(c);
+                    }
+                    '-' if matches!(chars.peek(), Some((_, '-'))) => {
+                        self.current.push(c);
+                        self.state = SplitState::LineComment;
+                    }
+                    '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                        self.current.push(c);
+                        self.current.push(chars.next().unwrap().1);
+                        self.state = SplitState::BlockComment(1);
+                    }
+                    '$' => {
+                        self.has_content = true;
+                        match dollar_quote_tag(&sql[i..]) {
+                            Some(tag) => {
+                                self.current.push_str(tag);
+                                while matches!(chars.peek(), Some((j, _)) if *j < i + tag.len()) {
+                                    chars.next();
+                                }
+                                self.state = SplitState::DollarQuoted(String::from(tag));
+                            }
+                            None => self.current.push(c),
+                        }
+                    }
+                    ';' => {
+                        if self.has_content {
+                            statements.push(String::from(self.current.trim()));
+                        }
+                        self.current.clear();
+                        self.has_content = false;
+                    }
+                    c => {
+                        if !c.is_whitespace() {
+                            self.has_content = true;
+                        }
+                        self.current.push(c);
+                    }
+                },
+            }
+        }
+
+        statements
+    }
+
+    /// Returns the last statement if it isn't terminated by a `;`.
+    fn finish(self) -> Option<String> {
+        self.has_content.then(|| String::from(self.current.trim()))
+    }
+}
+
+/// Reads the statements of a SQL file one by one, resolving `-- gerust:include` directives like [`read_with_includes`].
+///
+/// Unlike [`read_with_includes`] and [`split_statements`], the file is read line by line so that only the statement currently being read is held in memory, e.g. for restoring a multi-GB dump.
+///
+/// Example:
+/// ```
+/// for statement in StatementReader::open(Path::new("db/dumps/production.sql"))? {
+///     connection.execute(statement?.as_str()).await?;
+/// }
+/// ```
+pub struct StatementReader {
+    files: Vec<(PathBuf, BufReader<File>)>,
+    including: Vec<PathBuf>,
+    splitter: StatementSplitter,
+    statements: VecDeque<String>,
+    done: bool,
+}
+
+impl StatementReader {
+    /// Opens the SQL file at `path` for reading its statements.
+    pub fn open(path: &Path) -> Result<Self, anyhow::Error> {
+        let mut reader = Self {
+            files: vec![],
+            including: vec![],
+            splitter: StatementSplitter::default(),
+            statements: VecDeque::new(),
+            done: false,
+        };
+        reader.open_file(path)?;
+
+        Ok(reader)
+    }
+
+    fn open_file(&mut self, path: &Path) -> Result<(), anyhow::Error> {
+        let file = File::open(path)
+            .context(format!(r#"Could not read SQL file "{}"!"#, path.display()))?;
+        let canonical_path = path
+            .canonicalize()
+            .context(format!(r#"Could not resolve "{}"!"#, path.display()))?;
+        if self.including.contains(&canonical_path) {
+            return Err(include_cycle_error(&self.including, &canonical_path));
+        }
+
+        self.including.push(canonical_path);
+        self.files.push((path.to_path_buf(), BufReader::new(file)));
+
+        Ok(())
+    }
+
+    /// Reads the next line from the innermost file being included, returning `None` once all files have been read.
+    fn read_line(&mut self) -> Result<Option<String>, anyhow::Error> {
+        loop {
+            let Some((path, file)) = self.files.last_mut() else {
+                return Ok(None);
+            };
+            let mut line = String::new();
+            let read = file
+                .read_line(&mut line)
+                .context(format!(r#"Could not read SQL file "{}"!"#, path.display()))?;
+            if read > 0 {
+                return Ok(Some(line));
+            }
+            self.files.pop();
+            self.including.pop();
+        }
+    }
+}
+
+impl Iterator for StatementReader {
+    type Item = Result<String, anyhow::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.statements.is_empty() && !self.done {
+            let line = match self.read_line() {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    self.done = true;
+                    self.statements
+                        .extend(std::mem::take(&mut self.splitter).finish());
+                    break;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            match line.trim().strip_prefix(INCLUDE_DIRECTIVE) {
+                Some(include) => {
+                    let path = &self.files.last().unwrap().0;
+                    let include_path = path.parent().unwrap_or(Path::new(".")).join(include.trim());
+                    let context = format!(
+                        r#"Could not include "{}" from "{}"!"#,
+                        include.trim(),
+                        path.display()
+                    );
+                    if let Err(e) = self.open_file(&include_path).context(context) {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                None => {
+                    // lines are normalized like in `read_with_includes` so that every line ends with a newline
+                    let line = line.trim_end_matches(['\r', '\n']);
+                    self.statements
+                        .extend(self.splitter.push(&format!("{}\n", line)));
+                }
+            }
+        }
+
+        self.statements.pop_front().map(Ok)
+    }
+}
+
+/// Reads a SQL file, resolving any `-- gerust:include path/to/file.sql` directives in it.
+///
+/// Each directive must be on a line of its own and is replaced with the contents of the included file; paths are resolved relative to the including file. Included files can include other files in turn. Including a file that doesn't exist or including a file that is already being included (i.e. an include cycle) results in an error.
+///
+/// Example:
+/// ```
+/// // seeds.sql:
+/// // -- gerust:include shared/currencies.sql
+/// // INSERT INTO users (name) VALUES ('admin');
+/// let sql = read_with_includes(Path::new("db/seeds.sql"))?;
+/// ```
+pub fn read_with_includes(path: &Path) -> Result<String, anyhow::Error> {
+    resolve_includes(path, &mut vec![])
+}
+
+fn resolve_includes(path: &Path, including: &mut Vec<PathBuf>) -> Result<String, anyhow::Error> {
+    let sql = fs::read_to_string(path)
+        .context(format!(r#"Could not read SQL file "{}"!"#, path.display()))?;
+    let canonical_path = path
+        .canonicalize()
+        .context(format!(r#"Could not resolve "{}"!"#, path.display()))?;
+    if including.contains(&canonical_path) {
+        return Err(include_cycle_error(including, &canonical_path));
+    }
+
+    including.push(canonical_path);
+    let mut resolved = String::with_capacity(sql.len());
+    for line in sql.lines() {
+        match line.trim().strip_prefix(INCLUDE_DIRECTIVE) {
+            Some(include) => {
+                let include_path = path.parent().unwrap_or(Path::new(".")).join(include.trim());
+                let included = resolve_includes(&include_path, including).context(format!(
+                    r#"Could not include "{}" from "{}"!"#,
+                    include.trim(),
+                    path.display()
+                ))?;
+                // included files are resolved line by line as well so they always end with a newline
+                resolved.push_str(&included);
+            }
+            None => {
+                resolved.push_str(line);
+                resolved.push('\n');
+            }
+        }
+    }
+    including.pop();
+
+    Ok(resolved)
+}
+
+fn include_cycle_error(including: &[PathBuf], path: &Path) -> anyhow::Error {
+    let cycle: Vec<String> = including
+        .iter()
+        .map(PathBuf::as_path)
+        .chain(std::iter::once(path))
+        .map(|path| path.display().to_string())
+        .collect();
+    anyhow!("Include cycle detected: {}!", cycle.join(" -> "))
+}
+
+/// Quotes an identifier (e.g. a role or database name) so it can safely be interpolated into SQL.
+///
+/// Example:
+/// ```
+/// assert_eq!(quote_identifier(r#"my"role"#), r#""my""role""#);
+/// ```
+pub fn quote_identifier(identifier: &str) -> String {
+    format!(r#""{}""#, identifier.replace('"', r#""""#))
+}
+
+/// Quotes a string literal so it can safely be interpolated into SQL.
+///
+/// Example:
+/// ```
+/// assert_eq!(quote_literal("it's"), "'it''s'");
+/// ```
+pub fn quote_literal(literal: &str) -> String {
+    format!("'{}'", literal.replace('\'', "''"))
+}
+
+/// Formats the rows of a query result as a table like psql does, with the column names as the header and `NULL` values as empty cells.
+///
+/// Example:
+/// ```
+/// let lines = format_table(&["id", "name"], &[vec![Some(String::from("1")), None]]);
+/// assert_eq!(lines, vec![" id | name", "----+------", " 1  |"]);
+/// ```
+pub fn format_table<S: AsRef<str>>(columns: &[S], rows: &[Vec<Option<String>>]) -> Vec<String> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.as_ref().chars().count())
+        .collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            let value_width = value.as_deref().unwrap_or_default().chars().count();
+            *width = (*width).max(value_width);
+        }
+    }
+
+    let format_line = |values: Vec<&str>| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!(" {:<width$} ", value, width = width))
+            .collect::<Vec<_>>()
+            .join("|")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![format_line(columns.iter().map(AsRef::as_ref).collect())];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<_>>()
+            .join("+"),
+    );
+    for row in rows {
+        lines.push(format_line(
+            row.iter()
+                .map(|value| value.as_deref().unwrap_or_default())
+                .collect(),
+        ));
+    }
+
+    lines
+}
+
+/// Returns the opening tag of a dollar-quoted string (e.g. `$$` or `$body$`) if `sql` starts with one.
+fn dollar_quote_tag(sql: &str) -> Option<&str> {
+    let end = sql[1..].find('$')? + 1;
+    let tag = &sql[1..end];
+    let is_tag = tag
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_alphabetic() || (i > 0 && c.is_ascii_digit()));
+
+    is_tag.then(|| &sql[..=end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_table, quote_identifier, quote_literal, read_with_includes, split_statements,
+        StatementReader,
+    };
+    use insta::{assert_debug_snapshot, assert_snapshot};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_split_simple() {
+        let statements = split_statements(
+            "INSERT INTO users (name) VALUES ('a');\nINSERT INTO users (name) VALUES ('b');",
+        );
+
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "INSERT INTO users (name) VALUES ('a')",
+            "INSERT INTO users (name) VALUES ('b')",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_split_without_trailing_semicolon() {
+        let statements = split_statements("DELETE FROM tasks;\nDELETE FROM users\n");
+
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "DELETE FROM tasks",
+            "DELETE FROM users",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_split_quoted() {
+        let statements = split_statements(
+            r#"INSERT INTO "weird;table" (name) VALUES ('it''s; fine');SELECT 1;"#,
+        );
+
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "INSERT INTO \"weird;table\" (name) VALUES ('it''s; fine')",
+            "SELECT 1",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_split_comments() {
+        let statements = split_statements(
+            "-- a comment; with a semicolon\n/* a block; /* nested; */ comment */\nSELECT 1; -- trailing\n;;",
+        );
+
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "-- a comment; with a semicolon\n/* a block; /* nested; */ comment */\nSELECT 1",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_split_dollar_quoted() {
+        let statements = split_statements(
+            "CREATE FUNCTION f() RETURNS void AS $body$ BEGIN PERFORM 1; END; $body$ LANGUAGE plpgsql; SELECT $$a;b$$, $1;",
+        );
+
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "CREATE FUNCTION f() RETURNS void AS $body$ BEGIN PERFORM 1; END; $body$ LANGUAGE plpgsql",
+            "SELECT $$a;b$$, $1",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_read_with_includes() {
+        let dir = create_temp_dir("includes");
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(
+            dir.join("seeds.sql"),
+            "-- gerust:include shared/currencies.sql\nINSERT INTO users (name) VALUES ('admin');\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("shared/currencies.sql"),
+            "INSERT INTO currencies (code) VALUES ('EUR');\n  -- gerust:include usd.sql\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("shared/usd.sql"),
+            "INSERT INTO currencies (code) VALUES ('USD');",
+        )
+        .unwrap();
+
+        let sql = read_with_includes(&dir.join("seeds.sql")).unwrap();
+
+        assert_snapshot!(sql, @r###"
+        INSERT INTO currencies (code) VALUES ('EUR');
+        INSERT INTO currencies (code) VALUES ('USD');
+        INSERT INTO users (name) VALUES ('admin');
+        "###);
+    }
+
+    #[test]
+    fn test_read_with_includes_errors() {
+        let dir = create_temp_dir("include-errors");
+        fs::write(dir.join("a.sql"), "-- gerust:include b.sql\n").unwrap();
+        fs::write(dir.join("b.sql"), "-- gerust:include a.sql\n").unwrap();
+        fs::write(dir.join("missing.sql"), "-- gerust:include nope.sql\n").unwrap();
+
+        let error = read_with_includes(&dir.join("a.sql")).unwrap_err();
+        assert!(format!("{:#}", error).contains("Include cycle detected"));
+
+        let error = read_with_includes(&dir.join("missing.sql")).unwrap_err();
+        assert!(format!("{:#}", error).contains(r#"Could not include "nope.sql""#));
+    }
+
+    #[test]
+    fn test_statement_reader() {
+        let dir = create_temp_dir("reader");
+        fs::write(
+            dir.join("dump.sql"),
+            "INSERT INTO users (name) VALUES ('a;\nb');\n-- gerust:include functions.sql\nDELETE FROM tasks",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("functions.sql"),
+            "CREATE FUNCTION f() RETURNS void AS $$\nBEGIN\n  PERFORM 1;\nEND;\n$$ LANGUAGE plpgsql;\n/* a\n; comment */",
+        )
+        .unwrap();
+
+        let statements: Vec<String> = StatementReader::open(&dir.join("dump.sql"))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            statements,
+            split_statements(&read_with_includes(&dir.join("dump.sql")).unwrap())
+        );
+        assert_debug_snapshot!(statements, @r###"
+        [
+            "INSERT INTO users (name) VALUES ('a;\nb')",
+            "CREATE FUNCTION f() RETURNS void AS $$\nBEGIN\n  PERFORM 1;\nEND;\n$$ LANGUAGE plpgsql",
+            "/* a\n; comment */\nDELETE FROM tasks",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_statement_reader_errors() {
+        let dir = create_temp_dir("reader-errors");
+        fs::write(dir.join("a.sql"), "SELECT 1;\n-- gerust:include b.sql\n").unwrap();
+        fs::write(dir.join("b.sql"), "-- gerust:include a.sql\n").unwrap();
+
+        let mut reader = StatementReader::open(&dir.join("a.sql")).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), "SELECT 1");
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(format!("{:#}", error).contains("Include cycle detected"));
+        assert!(reader.next().is_none());
+
+        assert!(StatementReader::open(&dir.join("nope.sql")).is_err());
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_snapshot!(quote_identifier(r#"my"role"#), @r###""my""role""###);
+        assert_snapshot!(quote_literal("it's"), @"'it''s'");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let lines = format_table(
+            &["id", "description"],
+            &[
+                vec![Some(String::from("1")), Some(String::from("Buy milk"))],
+                vec![Some(String::from("1234")), None],
+            ],
+        );
+
+        assert_snapshot!(lines.join("\n"), @r###"
+         id   | description
+        ------+-------------
+         1    | Buy milk
+         1234 |
+        "###);
+    }
+
+    fn create_temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gerust-sql-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Context};
+use {{crate_name}}_config::DatabaseConfig;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{ConnectOptions, Connection, Executor};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Parses the connect options from the configured database URL.
+pub fn connect_options(config: &DatabaseConfig) -> Result<SqliteConnectOptions, anyhow::Error> {
+    SqliteConnectOptions::from_str(&config.url).context("Invalid database URL!")
+}
+
+/// Returns the path of the configured database file.
+pub fn database_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    Ok(connect_options(config)?
+        .get_filename()
+        .to_string_lossy()
+        .into_owned())
+}
+
+/// Connects to the database with the given connect options.
+///
+/// The connect and lock timeouts (see [`DatabaseConfig`]) are applied; the lock timeout maps to SQLite's busy timeout. SQLite has no statement timeout so that setting is ignored.
+pub async fn connect(
+    config: &DatabaseConfig,
+    options: &SqliteConnectOptions,
+) -> Result<SqliteConnection, anyhow::Error> {
+    let mut options = options.clone();
+    if let Some(secs) = config.timeouts.lock {
+        options = options.busy_timeout(Duration::from_secs(secs));
+    }
+
+    match config.timeouts.connect {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), options.connect())
+            .await
+            .map_err(|_| anyhow!("Connecting to the database timed out after {}s!", secs))?,
+        None => options.connect().await,
+    }
+    .context("Failed to connect to database!")
+}
+
+/// Creates the configured database file; fails if the file exists already.
+pub async fn create_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = database_name(config)?;
+    if PathBuf::from(&db_name).exists() {
+        return Err(anyhow!("Database file {} exists already!", db_name));
+    }
+
+    let connection = connect(config, &connect_options(config)?.create_if_missing(true)).await?;
+    connection
+        .close()
+        .await
+        .context("Failed to create database!")?;
+
+    Ok(db_name)
+}
+
+/// Deletes the configured database file (along with its `-wal` and `-shm` files) if it exists.
+pub async fn drop_database(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = database_name(config)?;
+    for suffix in ["", "-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{}", db_name, suffix));
+        if path.exists() {
+            std::fs::remove_file(&path).context(format!("Failed to delete {}!", path.display()))?;
+        }
+    }
+
+    Ok(db_name)
+}
+
+/// Lists the versions, descriptions, and installation times of all successfully applied migrations, ordered by version.
+///
+/// Nothing is changed in the database, i.e. the migrations table is not created if it doesn't exist.
+pub async fn applied_migrations(
+    config: &DatabaseConfig,
+) -> Result<Vec<(i64, String, DateTime<Utc>)>, anyhow::Error> {
+    let mut connection = connect(config, &connect_options(config)?).await?;
+
+    let migrations_tables: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_one(&mut connection)
+    .await
+    .context("Failed to check for migrations table!")?;
+    if migrations_tables == 0 {
+        return Ok(vec![]);
+    }
+
+    sqlx::query_as(
+        "SELECT version, description, installed_on FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to list applied migrations!")
+}
+
+/// Executes seed statements in a transaction and returns the number of rows affected by each of them.
+///
+/// In a dry run, the transaction is rolled back instead of committed.
+pub async fn run_seeds(
+    config: &DatabaseConfig,
+    statements: &[String],
+    dry_run: bool,
+) -> Result<Vec<u64>, anyhow::Error> {
+    let mut connection = connect(config, &connect_options(config)?).await?;
+
+    let mut transaction = connection
+        .begin()
+        .await
+        .context("Failed to start transaction!")?;
+    let mut rows_affected = vec![];
+    for statement in statements {
+        let result = transaction
+            .execute(statement.as_str())
+            .await
+            .context(format!("Failed to execute seed statement: {}", statement))?;
+        rows_affected.push(result.rows_affected());
+    }
+
+    if dry_run {
+        transaction
+            .rollback()
+            .await
+            .context("Failed to roll back transaction!")?;
+    } else {
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction!")?;
+    }
+
+    Ok(rows_affected)
+}
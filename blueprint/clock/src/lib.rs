@@ -0,0 +1,105 @@
+//! The {{crate_name}}_clock crate contains the [`Clock`] the application reads the current time from so that tests can freeze and advance time.
+
+use chrono::{DateTime, SubsecRound, TimeDelta, Utc};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The source of the current time.
+///
+/// Code that depends on the current time, e.g. to check whether a token has expired or a job is due, reads it from the clock in the web application's state or the worker's `JobContext` instead of calling [`Utc::now`] directly:
+///
+/// ```
+/// let expired = token.expires_at <= app_state.clock.now();
+/// ```
+///
+/// The application uses the [`SystemClock`] while tests use a [`FrozenClock`] that only moves when the test moves it.
+pub trait Clock: Send + Sync + Debug {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The clock that returns the system's current time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stands still until it is set or advanced explicitly – meant for tests.
+///
+/// Clones share the same time so that a test can keep a clone of the clock it passed to the application and move the time the application sees:
+///
+/// ```
+/// let clock = FrozenClock::default();
+/// let app_state = init_app_state(config).await.with_clock(Arc::new(clock.clone()));
+///
+/// clock.advance(Duration::from_secs(60 * 60));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrozenClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl FrozenClock {
+    /// Creates a clock frozen at `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(Arc::new(Mutex::new(now)))
+    }
+
+    /// Sets the clock to `now`.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.0.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let duration = TimeDelta::from_std(duration).expect("Duration out of range!");
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl Default for FrozenClock {
+    /// Creates a clock frozen at the system's current time, truncated to microseconds which is the precision the database stores times with so that times read back from the database equal the clock's.
+    fn default() -> Self {
+        Self::new(Utc::now().trunc_subsecs(6))
+    }
+}
+
+impl Clock for FrozenClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+
+    #[test]
+    fn test_frozen_clock() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:30:00Z")
+            .unwrap()
+            .to_utc();
+        let clock = FrozenClock::new(now);
+        let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+
+        assert_that!(shared.now(), eq(now));
+
+        clock.advance(Duration::from_secs(90));
+        assert_that!(shared.now(), eq(now + TimeDelta::seconds(90)));
+
+        clock.set(now);
+        assert_that!(shared.now(), eq(now));
+    }
+
+    #[test]
+    fn test_frozen_clock_default() {
+        let clock = FrozenClock::default();
+
+        assert_that!(clock.now(), eq(clock.now()));
+        assert_that!(clock.now().timestamp_subsec_nanos() % 1000, eq(0));
+    }
+}
@@ -0,0 +1,374 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// The env var that holds the key for decrypting the credentials file (see [`CredentialsFile`]).
+pub const MASTER_KEY_ENV_VAR: &str = "APP_MASTER_KEY";
+
+/// The length of the nonce that is prepended to the encrypted credentials
+const NONCE_LENGTH: usize = 12;
+
+/// A source of secrets such as database passwords or API keys.
+///
+/// Secrets are referenced from the configuration files via `${NAME}` placeholders (see [`crate::read_config`]) and resolved by the providers in a [`Secrets`] chain. Implement this trait to plug in a custom secrets store:
+///
+/// ```rust
+/// struct VaultSecrets;
+///
+/// impl SecretsProvider for VaultSecrets {
+///     fn secret(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+///         // look up the secret in Vault…
+///     }
+/// }
+///
+/// let secrets = Secrets::new().with(EnvSecrets).with(VaultSecrets);
+/// let config: Config = read_config_with_secrets(&Environment::Production, &secrets)?;
+/// ```
+pub trait SecretsProvider: Send + Sync {
+    /// Returns the secret with the given name or `None` if the provider doesn't have it.
+    fn secret(&self, name: &str) -> Result<Option<String>, anyhow::Error>;
+}
+
+/// A chain of [`SecretsProvider`]s that are asked for a secret one after the other.
+#[derive(Default)]
+pub struct Secrets {
+    providers: Vec<Box<dyn SecretsProvider>>,
+}
+
+impl Secrets {
+    /// Creates an empty chain without any providers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a provider to the chain.
+    pub fn with(mut self, provider: impl SecretsProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Creates the default chain as configured in the `[secrets]` section of the configuration files (see [`SecretsConfig`]).
+    ///
+    /// Secrets are looked up in env vars first ([`EnvSecrets`]), then in the credentials file if it exists ([`CredentialsFile`]), and finally in AWS Secrets Manager if a secret ID is configured ([`AwsSecretsManager`]).
+    pub fn from_config(config: &SecretsConfig) -> Self {
+        let mut secrets = Self::new().with(EnvSecrets);
+        if config.credentials_path.exists() {
+            secrets = secrets.with(CredentialsFile::new(&config.credentials_path));
+        }
+        if let Some(secret_id) = &config.aws_secret_id {
+            secrets = secrets.with(AwsSecretsManager::new(
+                secret_id,
+                config.aws_region.as_deref(),
+            ));
+        }
+        secrets
+    }
+
+    /// Returns the secret with the given name from the first provider that has it.
+    pub fn get(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+        for provider in &self.providers {
+            if let Some(secret) = provider.secret(name)? {
+                return Ok(Some(secret));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The configuration of the default secrets providers (see [`Secrets::from_config`]).
+///
+/// The settings are configured in the `[secrets]` section of the configuration files:
+///
+/// * `credentials_path`: `config/credentials.enc` – the encrypted credentials file
+/// * `aws_secret_id`: none – the ID or ARN of a secret in AWS Secrets Manager whose value is a JSON object of secrets
+/// * `aws_region`: none – the region of that secret (the AWS CLI's default region is used if not set)
+#[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// The path of the encrypted credentials file
+    pub credentials_path: PathBuf,
+    /// The ID or ARN of the secret in AWS Secrets Manager
+    pub aws_secret_id: Option<String>,
+    /// The region of the secret in AWS Secrets Manager
+    pub aws_region: Option<String>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            credentials_path: PathBuf::from("config/credentials.enc"),
+            aws_secret_id: None,
+            aws_region: None,
+        }
+    }
+}
+
+/// Provides secrets from env vars with the same name.
+pub struct EnvSecrets;
+
+impl SecretsProvider for EnvSecrets {
+    fn secret(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+        Ok(env::var(name).ok())
+    }
+}
+
+/// Provides secrets from an encrypted TOML file, by default `config/credentials.enc`.
+///
+/// The file is encrypted with AES-256-GCM using the key from the `APP_MASTER_KEY` env var or the `master.key` file next to the credentials file (which must not be checked into version control). Nested tables are addressed with dotted names, e.g. `${stripe.api_key}`. The file is edited via `cargo credentials edit`.
+pub struct CredentialsFile {
+    path: PathBuf,
+    credentials: OnceLock<Result<HashMap<String, String>, String>>,
+}
+
+impl CredentialsFile {
+    /// Creates a provider for the credentials file at `path`; the file is only read and decrypted once a secret is looked up.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            credentials: OnceLock::new(),
+        }
+    }
+
+    /// Reads the key for the credentials file at `path` from the `APP_MASTER_KEY` env var or the `master.key` file next to it.
+    pub fn read_key(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+        let key = match env::var(MASTER_KEY_ENV_VAR) {
+            Ok(key) => key,
+            Err(_) => {
+                let key_path = Self::key_path(path);
+                fs::read_to_string(&key_path).context(format!(
+                    r#"Failed to read the master key from {} or "{}"!"#,
+                    MASTER_KEY_ENV_VAR,
+                    key_path.display()
+                ))?
+            }
+        };
+
+        decode_key(key.trim())
+    }
+
+    /// Returns the path of the file holding the key for the credentials file at `path`.
+    pub fn key_path(path: &Path) -> PathBuf {
+        path.with_file_name("master.key")
+    }
+
+    /// Generates a new random key, encoded as Base64.
+    pub fn generate_key() -> String {
+        BASE64.encode(Aes256Gcm::generate_key(OsRng))
+    }
+
+    /// Decrypts the contents of a credentials file.
+    pub fn decrypt(key: &[u8], encrypted: &str) -> Result<String, anyhow::Error> {
+        let encrypted = BASE64
+            .decode(encrypted.trim())
+            .context("Failed to decode credentials!")?;
+        if encrypted.len() < NONCE_LENGTH {
+            return Err(anyhow!("Invalid credentials file!"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt credentials – is the master key correct?"))?;
+
+        String::from_utf8(plaintext).context("Decrypted credentials are not valid UTF-8!")
+    }
+
+    /// Encrypts the contents of a credentials file with a random nonce.
+    pub fn encrypt(key: &[u8], plaintext: &str) -> Result<String, anyhow::Error> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("Failed to encrypt credentials!"))?;
+
+        Ok(BASE64.encode([nonce.as_slice(), &ciphertext].concat()))
+    }
+
+    fn load(&self) -> Result<HashMap<String, String>, anyhow::Error> {
+        let key = Self::read_key(&self.path)?;
+        let encrypted = fs::read_to_string(&self.path)
+            .context(format!(r#"Failed to read "{}"!"#, self.path.display()))?;
+        Self::parse(&Self::decrypt(&key, &encrypted)?)
+    }
+
+    /// Parses the decrypted contents of a credentials file into a map of dotted names to secrets.
+    pub fn parse(plaintext: &str) -> Result<HashMap<String, String>, anyhow::Error> {
+        let credentials: toml::Table = plaintext.parse().context("Failed to parse credentials!")?;
+
+        let mut secrets = HashMap::new();
+        flatten_table("", &credentials, &mut secrets);
+        Ok(secrets)
+    }
+}
+
+impl SecretsProvider for CredentialsFile {
+    fn secret(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+        let credentials = self
+            .credentials
+            .get_or_init(|| self.load().map_err(|e| format!("{:#}", e)));
+        match credentials {
+            Ok(credentials) => Ok(credentials.get(name).cloned()),
+            Err(e) => Err(anyhow!("{}", e)),
+        }
+    }
+}
+
+/// Provides secrets from a secret in AWS Secrets Manager whose value is a JSON object, e.g. `{"DB_PASSWORD": "…"}`.
+///
+/// The secret is fetched via the AWS CLI (which must be installed and configured with credentials) once a secret is looked up for the first time.
+pub struct AwsSecretsManager {
+    secret_id: String,
+    region: Option<String>,
+    secrets: OnceLock<Result<HashMap<String, String>, String>>,
+}
+
+impl AwsSecretsManager {
+    /// Creates a provider for the secret with the given ID or ARN.
+    pub fn new(secret_id: &str, region: Option<&str>) -> Self {
+        Self {
+            secret_id: secret_id.to_string(),
+            region: region.map(String::from),
+            secrets: OnceLock::new(),
+        }
+    }
+
+    fn load(&self) -> Result<HashMap<String, String>, anyhow::Error> {
+        let mut command = Command::new("aws");
+        command.args([
+            "secretsmanager",
+            "get-secret-value",
+            "--secret-id",
+            &self.secret_id,
+            "--query",
+            "SecretString",
+            "--output",
+            "text",
+        ]);
+        if let Some(region) = &self.region {
+            command.args(["--region", region]);
+        }
+        let output = command
+            .output()
+            .context("Failed to run the AWS CLI – make sure it is installed!")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to fetch secret {} from AWS Secrets Manager: {}",
+                self.secret_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        serde_json::from_slice(&output.stdout).context(format!(
+            "Secret {} is not a JSON object of strings!",
+            self.secret_id
+        ))
+    }
+}
+
+impl SecretsProvider for AwsSecretsManager {
+    fn secret(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+        let secrets = self
+            .secrets
+            .get_or_init(|| self.load().map_err(|e| format!("{:#}", e)));
+        match secrets {
+            Ok(secrets) => Ok(secrets.get(name).cloned()),
+            Err(e) => Err(anyhow!("{}", e)),
+        }
+    }
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let key = BASE64
+        .decode(key)
+        .context("The master key is not valid Base64!")?;
+    if key.len() != 32 {
+        return Err(anyhow!("The master key must be 32 bytes long!"));
+    }
+
+    Ok(key)
+}
+
+fn flatten_table(prefix: &str, table: &toml::Table, secrets: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(table) => flatten_table(&name, table, secrets),
+            toml::Value::String(value) => {
+                secrets.insert(name, value.clone());
+            }
+            value => {
+                secrets.insert(name, value.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+// the closures run in figment::Jail return figment's large error type
+#[allow(clippy::result_large_err)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = decode_key(&CredentialsFile::generate_key()).unwrap();
+        let encrypted = CredentialsFile::encrypt(&key, "DB_PASSWORD = \"s3cr3t\"").unwrap();
+
+        assert_that!(encrypted, not(contains_substring("s3cr3t")));
+        assert_that!(
+            CredentialsFile::decrypt(&key, &encrypted).unwrap(),
+            eq("DB_PASSWORD = \"s3cr3t\"")
+        );
+
+        let other_key = decode_key(&CredentialsFile::generate_key()).unwrap();
+        assert_that!(
+            CredentialsFile::decrypt(&other_key, &encrypted),
+            err(anything())
+        );
+    }
+
+    #[test]
+    fn test_credentials_file() {
+        figment::Jail::expect_with(|jail| {
+            let key = CredentialsFile::generate_key();
+            let encrypted = CredentialsFile::encrypt(
+                &decode_key(&key).unwrap(),
+                r#"
+                DB_PASSWORD = "s3cr3t"
+
+                [stripe]
+                api_key = "sk_test"
+                "#,
+            )
+            .unwrap();
+            jail.create_dir("config")?;
+            jail.create_file("config/credentials.enc", &encrypted)?;
+            jail.create_file("config/master.key", &key)?;
+
+            let secrets = Secrets::from_config(&SecretsConfig::default());
+            assert_that!(secrets.get("DB_PASSWORD").unwrap(), some(eq("s3cr3t")));
+            assert_that!(secrets.get("stripe.api_key").unwrap(), some(eq("sk_test")));
+            assert_that!(secrets.get("UNKNOWN").unwrap(), none());
+
+            jail.set_env("DB_PASSWORD", "from env");
+            assert_that!(secrets.get("DB_PASSWORD").unwrap(), some(eq("from env")));
+
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,128 @@
+use figment::providers::Serialized;
+use figment::value::{Dict, Value};
+use figment::Provider;
+use serde::{de::Error, Deserialize, Deserializer};
+use std::env;
+
+/// Strongly-typed, application-specific settings that are read from the `[app]` section of the configuration files.
+///
+/// This trait is implemented via `#[derive(Settings)]` which is configured with `#[settings(…)]` attributes on the struct's fields:
+///
+/// * `env = "NAME"` overrides the setting with the value of the `NAME` env var if that is set – this takes precedence over all other configuration sources
+/// * `validate = path::to::function` validates the setting once the configuration is read; the function receives a reference to the setting's value and returns `Result<(), String>` (see [`validators`] for built-in validators)
+///
+/// ```rust
+/// #[derive(Deserialize, Settings, Clone, Debug)]
+/// pub struct AppSettings {
+///     #[settings(env = "STRIPE_KEY", validate = validators::not_empty)]
+///     pub stripe_key: String,
+///     #[serde(default)]
+///     pub feature_flags: HashMap<String, bool>,
+/// }
+/// ```
+pub trait Settings {
+    /// Returns the env vars that override settings as pairs of setting and env var names, e.g. `("stripe_key", "STRIPE_KEY")`.
+    fn env_overrides() -> Vec<(&'static str, &'static str)>;
+
+    /// Validates the settings, returning an error message for every invalid setting.
+    fn validate(&self) -> Result<(), Vec<String>>;
+}
+
+/// Built-in validators for use with `#[settings(validate = …)]`.
+pub mod validators {
+    /// Fails if the value is an empty string.
+    pub fn not_empty<T: AsRef<str> + ?Sized>(value: &T) -> Result<(), String> {
+        if value.as_ref().trim().is_empty() {
+            Err(String::from("must not be empty"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns a provider with the values of the env vars that override settings (see [`Settings::env_overrides`]) under the `key` the settings are read from.
+pub(crate) fn env_overrides<S: Settings>(key: &str) -> impl Provider {
+    let mut overrides = Dict::new();
+    for (setting, var) in S::env_overrides() {
+        if let Ok(value) = env::var(var) {
+            // env var values are parsed just like figment's `Env` provider does, e.g. "true" as a bool
+            let value: Value = value.parse().unwrap_or_else(|e| match e {});
+            overrides.insert(String::from(setting), value);
+        }
+    }
+
+    Serialized::default(key, overrides)
+}
+
+/// Deserializes [`Settings`] and validates them.
+pub(crate) fn deserialize_settings<'de, D, S>(deserializer: D) -> Result<S, D::Error>
+where
+    D: Deserializer<'de>,
+    S: Settings + Deserialize<'de>,
+{
+    let settings = S::deserialize(deserializer)?;
+    settings
+        .validate()
+        .map_err(|errors| D::Error::custom(format!("invalid settings: {}", errors.join(", "))))?;
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+// the closures run in figment::Jail return figment's large error type
+#[allow(clippy::result_large_err)]
+mod tests {
+    use super::*;
+    use crate::{validators, Settings};
+    use figment::{
+        providers::{Format, Toml},
+        Figment,
+    };
+    use googletest::prelude::*;
+
+    #[derive(Deserialize, Settings, Debug)]
+    struct TestSettings {
+        #[settings(env = "GERUST_TEST_STRIPE_KEY", validate = validators::not_empty)]
+        stripe_key: String,
+        #[settings(env = "GERUST_TEST_RETRIES")]
+        #[serde(default)]
+        retries: u32,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct TestConfig {
+        #[serde(deserialize_with = "deserialize_settings")]
+        app: TestSettings,
+    }
+
+    fn read_test_config(toml: &str) -> std::result::Result<TestConfig, figment::Error> {
+        Figment::new()
+            .merge(Toml::string(toml))
+            .merge(env_overrides::<TestSettings>("app"))
+            .extract()
+    }
+
+    #[test]
+    fn test_settings() {
+        figment::Jail::expect_with(|jail| {
+            let config = read_test_config("[app]\nstripe_key = \"sk_test\"").unwrap();
+            assert_that!(config.app.stripe_key, eq("sk_test"));
+            assert_that!(config.app.retries, eq(0));
+
+            jail.set_env("GERUST_TEST_STRIPE_KEY", "sk_env");
+            jail.set_env("GERUST_TEST_RETRIES", "3");
+            let config = read_test_config("[app]\nstripe_key = \"sk_test\"").unwrap();
+            assert_that!(config.app.stripe_key, eq("sk_env"));
+            assert_that!(config.app.retries, eq(3));
+
+            jail.set_env("GERUST_TEST_STRIPE_KEY", " ");
+            let error = read_test_config("[app]\nstripe_key = \"sk_test\"").unwrap_err();
+            assert_that!(
+                error.to_string(),
+                contains_substring("invalid settings: stripe_key: must not be empty")
+            );
+
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,6 @@
+//! Makes the crate track changes to the migrations it embeds.
+
+/// Rebuilds the crate whenever migrations are added or changed so that the migrations embedded via [`sqlx::migrate!`] are up-to-date (see `MIGRATOR` in `src/lib.rs`).
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
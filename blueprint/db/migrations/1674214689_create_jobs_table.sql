@@ -0,0 +1,15 @@
+CREATE TABLE jobs (
+    id uuid PRIMARY KEY default gen_random_uuid(),
+    name varchar(255) NOT NULL,
+    payload jsonb NOT NULL,
+    status varchar(16) NOT NULL default 'queued',
+    attempts integer NOT NULL default 0,
+    max_attempts integer NOT NULL,
+    run_at timestamptz NOT NULL default now(),
+    locked_at timestamptz,
+    last_error text,
+    trace_context jsonb,
+    created_at timestamptz NOT NULL default now()
+);
+
+CREATE INDEX jobs_status_run_at_idx ON jobs (status, run_at);
@@ -0,0 +1,6 @@
+CREATE TABLE job_schedules (
+    name varchar(255) PRIMARY KEY,
+    schedule varchar(255) NOT NULL,
+    next_run_at timestamptz NOT NULL,
+    last_run_at timestamptz
+);
@@ -0,0 +1,40 @@
+#![allow(missing_docs)]
+use anyhow::Context;
+use {{crate_name}}_db::data_migrations::{self, Registry};
+use {{crate_name}}_db::DbPool;
+
+/// Runs the data migrations (see [`{{crate_name}}_db::data_migrations`]) against the database `DATABASE_URL` points to.
+///
+/// This is invoked by `cargo db migrate-data` which passes `--batch-size <N>` and optionally `--version <VERSION>` to only run that migration. The progress is printed to stdout as one JSON object per line for `cargo db migrate-data` to report it.
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set!")?;
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |name: &str| -> Result<Option<i64>, anyhow::Error> {
+        match args.iter().position(|arg| arg == name) {
+            Some(i) => {
+                let value = args.get(i + 1).context(format!("Missing value for {}!", name))?;
+                Ok(Some(value.parse().context(format!("Invalid value for {}!", name))?))
+            }
+            None => Ok(None),
+        }
+    };
+    let batch_size = arg("--batch-size")?.unwrap_or(1000);
+    let version = arg("--version")?;
+
+    let mut registry = Registry::default();
+    data_migrations::register(&mut registry);
+
+    let db_pool = DbPool::connect(&url)
+        .await
+        .context("Failed to connect to database!")?;
+    registry
+        .run(&db_pool, version, batch_size, &mut |progress| {
+            if let Ok(line) = serde_json::to_string(&progress) {
+                println!("{}", line);
+            }
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+#![allow(missing_docs)]
+use anyhow::Context;
+use {{crate_name}}_db::{seeds, transaction, DbPool};
+
+/// Runs the Rust seeds (see [`{{crate_name}}_db::seeds`]) against the database `DATABASE_URL` points to.
+///
+/// This is invoked by `cargo db seed --rust` – if `--dry-run` is passed, the transaction the seeds run in is rolled back instead of committed.
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set!")?;
+    let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+
+    let db_pool = DbPool::connect(&url)
+        .await
+        .context("Failed to connect to database!")?;
+    let mut tx = transaction(&db_pool).await?;
+    seeds::seed(&mut tx)
+        .await
+        .context("Failed to seed database!")?;
+
+    if dry_run {
+        tx.rollback()
+            .await
+            .context("Failed to roll back transaction!")?;
+    } else {
+        tx.commit().await.context("Failed to commit transaction!")?;
+    }
+
+    Ok(())
+}
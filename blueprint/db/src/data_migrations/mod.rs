@@ -0,0 +1,402 @@
+use crate::DbPool;
+use anyhow::{anyhow, Context};
+use serde::Serialize;
+use sqlx::{Postgres, Transaction};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A data migration changes existing data, e.g. backfilling a new column, as opposed to a schema migration in `db/migrations` that changes the database's structure.
+///
+/// Data migrations are run with `cargo db migrate-data` in the order of their versions. A migration processes the records in batches: every batch runs in a transaction of its own that also records the migration's progress, so that a run that was interrupted resumes with the batch after the last committed one. Data migrations can only be run if they are registered in [`register`].
+///
+/// Example:
+/// ```
+/// #[derive(Default)]
+/// pub struct BackfillSlugsMigration;
+///
+/// impl DataMigration for BackfillSlugsMigration {
+///     const VERSION: i64 = 1718000000;
+///     const NAME: &'static str = "backfill_slugs";
+///
+///     async fn remaining(&self, db_pool: &DbPool, cursor: Option<&str>) -> Result<Option<i64>, anyhow::Error> {
+///         let count = sqlx::query_scalar("SELECT count(*) FROM posts WHERE slug IS NULL")
+///             .fetch_one(db_pool)
+///             .await?;
+///         Ok(Some(count))
+///     }
+///
+///     async fn migrate_batch(&self, tx: &mut Transaction<'_, Postgres>, cursor: Option<&str>, batch_size: i64) -> Result<Batch, anyhow::Error> {
+///         let ids: Vec<Uuid> = sqlx::query_scalar(
+///             "UPDATE posts SET slug = lower(replace(title, ' ', '-'))
+///             WHERE id IN (SELECT id FROM posts WHERE slug IS NULL ORDER BY id LIMIT $1)
+///             RETURNING id",
+///         )
+///         .bind(batch_size)
+///         .fetch_all(&mut **tx)
+///         .await?;
+///         Ok(Batch::more_or_done(ids.len() as i64, batch_size, ids.iter().max()))
+///     }
+/// }
+/// ```
+pub trait DataMigration: Default + Send + Sync + 'static {
+    /// The version of the migration, usually the timestamp of its creation; must be unique across all data migrations
+    const VERSION: i64;
+
+    /// The name of the migration
+    const NAME: &'static str;
+
+    /// Returns the number of records that remain to be migrated after `cursor` (or from the start if there is no cursor yet) for reporting the progress, or `None` if it cannot be determined upfront.
+    fn remaining(
+        &self,
+        db_pool: &DbPool,
+        cursor: Option<&str>,
+    ) -> impl Future<Output = Result<Option<i64>, anyhow::Error>> + Send;
+
+    /// Migrates the next batch of at most `batch_size` records after `cursor` (or the first batch if there is no cursor yet).
+    ///
+    /// The batch runs in a transaction that is only committed together with the returned cursor so that either both the batch's changes and the progress are recorded or neither is.
+    fn migrate_batch(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        cursor: Option<&str>,
+        batch_size: i64,
+    ) -> impl Future<Output = Result<Batch, anyhow::Error>> + Send;
+}
+
+/// The result of migrating a batch of records (see [`DataMigration::migrate_batch`]).
+#[derive(Debug, PartialEq)]
+pub struct Batch {
+    /// The number of records that were migrated
+    pub processed: i64,
+    /// The cursor the next batch continues after, e.g. the ID of the last migrated record, or `None` if the migration is complete
+    pub cursor: Option<String>,
+}
+
+impl Batch {
+    /// A batch after which more records remain to be migrated, continuing after `cursor`.
+    pub fn more(processed: i64, cursor: impl ToString) -> Self {
+        Self {
+            processed,
+            cursor: Some(cursor.to_string()),
+        }
+    }
+
+    /// The last batch of the migration.
+    pub fn done(processed: i64) -> Self {
+        Self {
+            processed,
+            cursor: None,
+        }
+    }
+
+    /// The next batch if the batch was full, i.e. `processed` equals `batch_size`, and the last one otherwise.
+    pub fn more_or_done(processed: i64, batch_size: i64, cursor: Option<impl ToString>) -> Self {
+        match cursor {
+            Some(cursor) if processed >= batch_size => Self::more(processed, cursor),
+            _ => Self::done(processed),
+        }
+    }
+}
+
+/// The progress of a data migration run, reported after each step (see [`Registry::run`]).
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Progress {
+    /// A migration started or resumed after `processed` records
+    Started {
+        /// The migration's version
+        version: i64,
+        /// The migration's name
+        name: &'static str,
+        /// The number of records processed so far
+        processed: i64,
+        /// The total number of records to process if the migration counts them
+        total: Option<i64>,
+    },
+    /// A batch of a migration was committed
+    Batch {
+        /// The migration's version
+        version: i64,
+        /// The migration's name
+        name: &'static str,
+        /// The number of records processed so far
+        processed: i64,
+        /// The total number of records to process if the migration counts them
+        total: Option<i64>,
+    },
+    /// A migration is complete
+    Completed {
+        /// The migration's version
+        version: i64,
+        /// The migration's name
+        name: &'static str,
+        /// The number of records processed so far
+        processed: i64,
+    },
+}
+
+/// The table that keeps track of the data migrations' progress.
+pub const TABLE: &str = "_gerust_data_migrations";
+
+type MigrationFuture<'a> = Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + 'a>>;
+type Handler = Box<
+    dyn for<'a> Fn(&'a DbPool, i64, &'a mut (dyn FnMut(Progress) + Send)) -> MigrationFuture<'a>
+        + Send
+        + Sync,
+>;
+
+struct RegisteredMigration {
+    name: &'static str,
+    handler: Handler,
+}
+
+/// The data migrations that can be run, ordered by their versions (see [`DataMigration::VERSION`]).
+#[derive(Default)]
+pub struct Registry {
+    migrations: BTreeMap<i64, RegisteredMigration>,
+}
+
+impl Registry {
+    /// Registers a data migration.
+    ///
+    /// Example:
+    /// ```
+    /// registry.register::<BackfillSlugsMigration>();
+    /// ```
+    pub fn register<M: DataMigration>(&mut self) {
+        self.migrations.insert(
+            M::VERSION,
+            RegisteredMigration {
+                name: M::NAME,
+                handler: Box::new(|db_pool, batch_size, on_progress| {
+                    Box::pin(run_migration(M::default(), db_pool, batch_size, on_progress))
+                }),
+            },
+        );
+    }
+
+    /// Returns the versions and names of the registered data migrations, ordered by version.
+    pub fn migrations(&self) -> impl Iterator<Item = (i64, &'static str)> + '_ {
+        self.migrations
+            .iter()
+            .map(|(version, migration)| (*version, migration.name))
+    }
+
+    /// Runs all data migrations that are not complete yet in the order of their versions, or only the one with `version` if passed, and returns the versions of the migrations that were run.
+    ///
+    /// Migrations that were interrupted resume after the last committed batch. The progress is passed to `on_progress` after each step.
+    pub async fn run(
+        &self,
+        db_pool: &DbPool,
+        version: Option<i64>,
+        batch_size: i64,
+        on_progress: &mut (dyn FnMut(Progress) + Send),
+    ) -> Result<Vec<i64>, anyhow::Error> {
+        if let Some(version) = version {
+            if !self.migrations.contains_key(&version) {
+                return Err(anyhow!("Unknown data migration {}!", version));
+            }
+        }
+
+        ensure_table(db_pool).await?;
+        let completed: Vec<i64> = sqlx::query_scalar(&format!(
+            "SELECT version FROM {} WHERE completed_at IS NOT NULL",
+            TABLE
+        ))
+        .fetch_all(db_pool)
+        .await
+        .context("Failed to list completed data migrations!")?;
+
+        let mut run = vec![];
+        for (migration_version, migration) in &self.migrations {
+            if version.is_some_and(|version| version != *migration_version)
+                || completed.contains(migration_version)
+            {
+                continue;
+            }
+            (migration.handler)(db_pool, batch_size, on_progress)
+                .await
+                .context(format!(
+                    "Data migration {} ({}) failed!",
+                    migration_version, migration.name
+                ))?;
+            run.push(*migration_version);
+        }
+
+        Ok(run)
+    }
+}
+
+/// Registers all data migrations.
+///
+/// Only registered data migrations are run – data migrations generated with `cargo generate data-migration` are registered automatically.
+#[allow(unused_variables)]
+pub fn register(registry: &mut Registry) {}
+
+/// Creates the table that keeps track of the data migrations' progress (see [`TABLE`]) unless it exists already.
+async fn ensure_table(db_pool: &DbPool) -> Result<(), anyhow::Error> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            cursor TEXT,
+            processed BIGINT NOT NULL DEFAULT 0,
+            started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            completed_at TIMESTAMPTZ
+        )",
+        TABLE
+    ))
+    .execute(db_pool)
+    .await
+    .context("Failed to create the data migrations table!")?;
+
+    Ok(())
+}
+
+/// Runs a data migration batch by batch, starting after the recorded cursor if the migration was interrupted before.
+async fn run_migration<M: DataMigration>(
+    migration: M,
+    db_pool: &DbPool,
+    batch_size: i64,
+    on_progress: &mut (dyn FnMut(Progress) + Send),
+) -> Result<(), anyhow::Error> {
+    let state: Option<(Option<String>, i64)> = sqlx::query_as(&format!(
+        "SELECT cursor, processed FROM {} WHERE version = $1",
+        TABLE
+    ))
+    .bind(M::VERSION)
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to load the data migration's progress!")?;
+    let (mut cursor, mut processed) = state.unwrap_or_default();
+
+    let total = migration
+        .remaining(db_pool, cursor.as_deref())
+        .await
+        .context("Failed to count the records to migrate!")?
+        .map(|remaining| processed + remaining);
+    on_progress(Progress::Started {
+        version: M::VERSION,
+        name: M::NAME,
+        processed,
+        total,
+    });
+
+    loop {
+        let mut tx = db_pool
+            .begin()
+            .await
+            .context("Failed to begin transaction!")?;
+        let batch = migration
+            .migrate_batch(&mut tx, cursor.as_deref(), batch_size)
+            .await?;
+        processed += batch.processed;
+        cursor = batch.cursor;
+        sqlx::query(&format!(
+            "INSERT INTO {} (version, name, cursor, processed, completed_at)
+            VALUES ($1, $2, $3, $4, CASE WHEN $3 IS NULL THEN now() END)
+            ON CONFLICT (version) DO UPDATE SET cursor = EXCLUDED.cursor, processed = EXCLUDED.processed, completed_at = EXCLUDED.completed_at",
+            TABLE
+        ))
+        .bind(M::VERSION)
+        .bind(M::NAME)
+        .bind(cursor.as_deref())
+        .bind(processed)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record the data migration's progress!")?;
+        tx.commit().await.context("Failed to commit transaction!")?;
+
+        if cursor.is_none() {
+            on_progress(Progress::Completed {
+                version: M::VERSION,
+                name: M::NAME,
+                processed,
+            });
+            return Ok(());
+        }
+        on_progress(Progress::Batch {
+            version: M::VERSION,
+            name: M::NAME,
+            processed,
+            total,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Batch, DataMigration, Registry};
+    use crate::DbPool;
+    use sqlx::{Postgres, Transaction};
+
+    #[derive(Default)]
+    struct FirstMigration;
+
+    impl DataMigration for FirstMigration {
+        const VERSION: i64 = 1;
+        const NAME: &'static str = "first";
+
+        async fn remaining(
+            &self,
+            _db_pool: &DbPool,
+            _cursor: Option<&str>,
+        ) -> Result<Option<i64>, anyhow::Error> {
+            Ok(None)
+        }
+
+        async fn migrate_batch(
+            &self,
+            _tx: &mut Transaction<'_, Postgres>,
+            _cursor: Option<&str>,
+            _batch_size: i64,
+        ) -> Result<Batch, anyhow::Error> {
+            Ok(Batch::done(0))
+        }
+    }
+
+    #[derive(Default)]
+    struct SecondMigration;
+
+    impl DataMigration for SecondMigration {
+        const VERSION: i64 = 2;
+        const NAME: &'static str = "second";
+
+        async fn remaining(
+            &self,
+            _db_pool: &DbPool,
+            _cursor: Option<&str>,
+        ) -> Result<Option<i64>, anyhow::Error> {
+            Ok(None)
+        }
+
+        async fn migrate_batch(
+            &self,
+            _tx: &mut Transaction<'_, Postgres>,
+            _cursor: Option<&str>,
+            _batch_size: i64,
+        ) -> Result<Batch, anyhow::Error> {
+            Ok(Batch::done(0))
+        }
+    }
+
+    #[test]
+    fn test_migrations_are_ordered_by_version() {
+        let mut registry = Registry::default();
+        registry.register::<SecondMigration>();
+        registry.register::<FirstMigration>();
+
+        assert_eq!(
+            registry.migrations().collect::<Vec<_>>(),
+            vec![(1, "first"), (2, "second")]
+        );
+    }
+
+    #[test]
+    fn test_more_or_done() {
+        assert_eq!(Batch::more_or_done(10, 10, Some(42)), Batch::more(10, 42));
+        assert_eq!(Batch::more_or_done(3, 10, Some(42)), Batch::done(3));
+        assert_eq!(Batch::more_or_done(0, 10, None::<i64>), Batch::done(0));
+    }
+}
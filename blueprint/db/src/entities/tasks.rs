@@ -1,13 +1,16 @@
 #[cfg(feature = "test-helpers")]
 use fake::{faker::lorem::en::*, Dummy};
+#[cfg(feature = "test-helpers")]
+use {{crate_name}}_macros::Factory;
 use serde::Deserialize;
 use serde::Serialize;
 use sqlx::Postgres;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 /// A task, i.e. TODO item.
-#[derive(Serialize, Debug, Deserialize)]
+#[derive(Serialize, Debug, Deserialize, ToSchema)]
 pub struct Task {
     /// The id of the record.
     pub id: Uuid,
@@ -24,8 +27,11 @@ pub struct Task {
 /// ```
 /// let task_changeset: TaskChangeset = Faker.fake();
 /// ```
-#[derive(Deserialize, Validate, Clone)]
-#[cfg_attr(feature = "test-helpers", derive(Serialize, Dummy))]
+///
+/// Tasks can also be created via the [`TaskFactory`] (see [`crate::test_helpers::factories`]).
+#[derive(Deserialize, Validate, Clone, ToSchema)]
+#[cfg_attr(feature = "test-helpers", derive(Serialize, Dummy, Factory))]
+#[cfg_attr(feature = "test-helpers", factory(entity = Task, create = create))]
 pub struct TaskChangeset {
     /// The description must be at least 1 character long.
     #[cfg_attr(feature = "test-helpers", dummy(faker = "Sentence(3..8)"))]
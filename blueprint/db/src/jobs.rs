@@ -0,0 +1,260 @@
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::Postgres;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The status of a job in the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum JobStatus {
+    /// The job waits to be performed once its `run_at` time has come.
+    Queued,
+    /// The job is currently being performed by a worker.
+    Running,
+    /// The job failed as often as it may and will not be retried (unless retried manually via `cargo jobs retry`).
+    Dead,
+}
+
+/// A job in the queue.
+///
+/// Jobs are removed from the queue once they have been performed successfully.
+#[derive(Debug, Clone)]
+pub struct QueuedJob {
+    /// The id of the job.
+    pub id: Uuid,
+    /// The name of the job that identifies the code performing it, e.g. `"send_report"`.
+    pub name: String,
+    /// The job's data, serialized as JSON.
+    pub payload: serde_json::Value,
+    /// The status of the job.
+    pub status: JobStatus,
+    /// How often the job has been attempted to be performed so far.
+    pub attempts: i32,
+    /// How often the job is attempted to be performed before it is considered dead.
+    pub max_attempts: i32,
+    /// The earliest time the job is performed at.
+    pub run_at: DateTime<Utc>,
+    /// The error of the last failed attempt to perform the job, if any.
+    pub last_error: Option<String>,
+    /// The context of the trace the job was enqueued in, e.g. `{"traceparent": "00-…"}`, if any.
+    pub trace_context: Option<serde_json::Value>,
+    /// The time the job was enqueued at.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Pushes a job onto the queue to be performed as soon as possible.
+///
+/// The `trace_context` is the context of the trace the job is enqueued in (e.g. the trace of a request) that the worker continues when performing the job. When the `executor` is a transaction, the job is only visible to workers once the transaction is committed.
+pub async fn push(
+    name: &str,
+    payload: serde_json::Value,
+    max_attempts: i32,
+    trace_context: Option<serde_json::Value>,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<Uuid, crate::Error> {
+    let record = sqlx::query!(
+        "INSERT INTO jobs (name, payload, max_attempts, trace_context) VALUES ($1, $2, $3, $4) RETURNING id",
+        name,
+        payload,
+        max_attempts,
+        trace_context
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(record.id)
+}
+
+/// Load one [`QueuedJob`] from the queue identified by its ID.
+///
+/// If no job can be found for the ID, a [`crate::Error::NoRecordFound`] will be returned.
+pub async fn load(
+    id: Uuid,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<QueuedJob, crate::Error> {
+    sqlx::query_as!(
+        QueuedJob,
+        r#"SELECT id, name, payload, status AS "status: JobStatus", attempts, max_attempts, run_at, last_error, trace_context, created_at FROM jobs WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(crate::Error::DbError)?
+    .ok_or(crate::Error::NoRecordFound)
+}
+
+/// Claims the next job that is due to be performed, marking it as running.
+///
+/// Jobs are claimed with `FOR UPDATE SKIP LOCKED` so that any number of workers can claim jobs concurrently without claiming the same job twice or waiting for each other. Returns `None` if no job is due.
+pub async fn claim_next(
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<Option<QueuedJob>, crate::Error> {
+    sqlx::query_as!(
+        QueuedJob,
+        r#"UPDATE jobs SET status = 'running', attempts = attempts + 1, locked_at = now()
+        WHERE id = (
+            SELECT id FROM jobs WHERE status = 'queued' AND run_at <= now()
+            ORDER BY run_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+        )
+        RETURNING id, name, payload, status AS "status: JobStatus", attempts, max_attempts, run_at, last_error, trace_context, created_at"#
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(crate::Error::DbError)
+}
+
+/// Removes a job that was performed successfully from the queue.
+pub async fn complete(
+    id: Uuid,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<(), crate::Error> {
+    sqlx::query!("DELETE FROM jobs WHERE id = $1", id)
+        .execute(executor)
+        .await
+        .map_err(crate::Error::DbError)?;
+
+    Ok(())
+}
+
+/// Records a failed attempt to perform a job.
+///
+/// The job is queued again to be retried after `retry_in` unless it has been attempted `max_attempts` times already in which case it is considered dead. Returns the job's new status.
+pub async fn fail(
+    id: Uuid,
+    error: &str,
+    retry_in: Duration,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<JobStatus, crate::Error> {
+    let record = sqlx::query!(
+        r#"UPDATE jobs SET
+            status = CASE WHEN attempts >= max_attempts THEN 'dead' ELSE 'queued' END,
+            run_at = now() + make_interval(secs => $2),
+            locked_at = NULL,
+            last_error = $3
+        WHERE id = $1
+        RETURNING status AS "status: JobStatus""#,
+        id,
+        retry_in.as_secs_f64(),
+        error
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(crate::Error::DbError)?
+    .ok_or(crate::Error::NoRecordFound)?;
+
+    Ok(record.status)
+}
+
+/// Counts the jobs in the queue by their status. Statuses without jobs are left out.
+pub async fn count_by_status(
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<Vec<(JobStatus, i64)>, crate::Error> {
+    let records = sqlx::query!(
+        r#"SELECT status AS "status: JobStatus", count(*) AS "count!" FROM jobs GROUP BY status"#
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| (record.status, record.count))
+        .collect())
+}
+
+/// The schedule of a recurring job.
+///
+/// Schedules are shared by all workers so that a recurring job is only enqueued once per scheduled run no matter how many workers are running.
+#[derive(Debug, Clone)]
+pub struct JobSchedule {
+    /// The name of the job that is enqueued on the schedule.
+    pub name: String,
+    /// The cron expression the job is scheduled with, e.g. `"0 0 * * * *"`.
+    pub schedule: String,
+    /// The time the job is enqueued at next.
+    pub next_run_at: DateTime<Utc>,
+    /// The time the job was last enqueued at, if ever.
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+/// Stores the schedule of a recurring job.
+///
+/// If the job was already scheduled with the same cron expression before, its next run time is kept so that runs are neither skipped nor repeated when workers restart. If the cron expression changed, the job's next run time is set to `next_run_at`.
+pub async fn upsert_schedule(
+    name: &str,
+    schedule: &str,
+    next_run_at: DateTime<Utc>,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<(), crate::Error> {
+    sqlx::query!(
+        "INSERT INTO job_schedules (name, schedule, next_run_at) VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE SET
+            next_run_at = CASE WHEN job_schedules.schedule = EXCLUDED.schedule THEN job_schedules.next_run_at ELSE EXCLUDED.next_run_at END,
+            schedule = EXCLUDED.schedule",
+        name,
+        schedule,
+        next_run_at
+    )
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(())
+}
+
+/// Removes the schedules of all jobs except the ones named in `names`, e.g. after a recurring job was removed from the application.
+pub async fn prune_schedules(
+    names: &[String],
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<(), crate::Error> {
+    sqlx::query!(
+        "DELETE FROM job_schedules WHERE NOT (name = ANY($1))",
+        names
+    )
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(())
+}
+
+/// Loads all schedules ordered by the time the jobs are enqueued at next.
+pub async fn load_schedules(
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<Vec<JobSchedule>, crate::Error> {
+    sqlx::query_as!(
+        JobSchedule,
+        "SELECT name, schedule, next_run_at, last_run_at FROM job_schedules ORDER BY next_run_at"
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(crate::Error::DbError)
+}
+
+/// Claims the scheduled run of a recurring job if it is due at `now`, moving the job's next run time forward to `next_run_at`.
+///
+/// The schedule is only updated if its next run time has come and still is the one the worker saw, so that if several workers try to claim the same run concurrently, only one of them succeeds. Returns whether the run was claimed, in which case the caller is responsible for enqueuing the job – ideally in the same transaction so that the run is not lost if enqueuing fails.
+pub async fn claim_scheduled_run(
+    name: &str,
+    scheduled_at: DateTime<Utc>,
+    next_run_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<bool, crate::Error> {
+    let result = sqlx::query!(
+        "UPDATE job_schedules SET next_run_at = $3, last_run_at = $4
+        WHERE name = $1 AND next_run_at = $2 AND next_run_at <= $4",
+        name,
+        scheduled_at,
+        next_run_at,
+        now
+    )
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(result.rows_affected() == 1)
+}
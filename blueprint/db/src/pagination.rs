@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Postgres, QueryBuilder, Row};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// An entity whose records can be listed page by page with [`load_page`].
+///
+/// The column lists are interpolated into the SQL queries as they are and must therefore only ever contain static column names – never values sent by clients. Sortable columns should be `NOT NULL` since rows with `NULL` values in the sort column are skipped by cursor-based pagination.
+///
+/// Example:
+/// ```
+/// impl Listable for Post {
+///     const TABLE: &'static str = "posts";
+///     const COLUMNS: &'static str = "id, title, views";
+///     const SORTABLE_COLUMNS: &'static [&'static str] = &["id", "title"];
+///     const FILTERABLE_COLUMNS: &'static [&'static str] = &["title", "views"];
+///
+///     fn id(&self) -> Uuid {
+///         self.id
+///     }
+/// }
+/// ```
+pub trait Listable: for<'r> FromRow<'r, PgRow> + Send + Unpin {
+    /// The table the records are stored in.
+    const TABLE: &'static str;
+    /// The columns to select, separated by commas.
+    const COLUMNS: &'static str;
+    /// The columns that records may be sorted by.
+    const SORTABLE_COLUMNS: &'static [&'static str];
+    /// The columns that records may be filtered by.
+    const FILTERABLE_COLUMNS: &'static [&'static str];
+    /// A condition all listed records must meet, e.g. `deleted_at IS NULL` for entities whose records are soft-deleted.
+    const SCOPE: Option<&'static str> = None;
+
+    /// The ID of the record which is used as the cursor for the next page.
+    fn id(&self) -> Uuid;
+}
+
+/// Which page of records to load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageRequest {
+    /// Page-based pagination: skips `(page - 1) * per_page` records (pages start at 1).
+    Offset {
+        /// The number of the page
+        page: u32,
+        /// The maximum number of records on a page
+        per_page: u32,
+    },
+    /// Cursor-based pagination: loads the records following the record with the ID `after` (see [`Page::next_cursor`]) or the first records if there is no cursor.
+    Cursor {
+        /// The ID of the last record of the previous page
+        after: Option<Uuid>,
+        /// The maximum number of records on a page
+        per_page: u32,
+    },
+}
+
+impl PageRequest {
+    fn per_page(&self) -> u32 {
+        match self {
+            PageRequest::Offset { per_page, .. } | PageRequest::Cursor { per_page, .. } => {
+                *per_page
+            }
+        }
+    }
+}
+
+/// The direction records are sorted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Ascending order, i.e. smallest value first.
+    #[default]
+    Asc,
+    /// Descending order, i.e. largest value first.
+    Desc,
+}
+
+/// The column records are sorted by. Records with equal values in the column are sorted by their IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    /// The column, one of [`Listable::SORTABLE_COLUMNS`]
+    pub column: &'static str,
+    /// The direction
+    pub direction: Direction,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self {
+            column: "id",
+            direction: Direction::Asc,
+        }
+    }
+}
+
+/// A filter that only matches records whose value in `column` equals `value`.
+///
+/// Values are compared as text, e.g. `true` matches `true` in a boolean column and `42` matches `42` in an integer column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// The column, one of [`Listable::FILTERABLE_COLUMNS`]
+    pub column: &'static str,
+    /// The value to compare the column's value to
+    pub value: String,
+}
+
+/// A page of records along with the total number of records matching the filters.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct Page<T> {
+    /// The records on the page.
+    pub items: Vec<T>,
+    /// The total number of records matching the filters across all pages.
+    pub total: i64,
+    /// The cursor to load the next page with, i.e. the ID of the last record on the page, if there are more records.
+    pub next_cursor: Option<Uuid>,
+}
+
+/// Loads a page of records of a [`Listable`] entity, sorted by `sort` and filtered by `filters`.
+///
+/// This runs two queries, one to count the records matching the filters and one to load the records on the page. If the cursor of a [`PageRequest::Cursor`] refers to a record that does not exist (anymore), the page is empty. Only records meeting [`Listable::SCOPE`] are listed.
+pub async fn load_page<'a, T: Listable>(
+    page: PageRequest,
+    sort: Sort,
+    filters: &[Filter],
+    executor: impl sqlx::Acquire<'a, Database = Postgres>,
+) -> Result<Page<T>, crate::Error> {
+    load_page_in_scope(page, sort, filters, T::SCOPE, executor).await
+}
+
+/// Loads a page of records like [`load_page`] but with `scope` in place of [`Listable::SCOPE`], e.g. `deleted_at IS NOT NULL` to only list soft-deleted records or `None` to list all records.
+///
+/// Like the entity's columns, the scope is interpolated into the SQL queries as it is and must never contain values sent by clients.
+pub async fn load_page_in_scope<'a, T: Listable>(
+    page: PageRequest,
+    sort: Sort,
+    filters: &[Filter],
+    scope: Option<&str>,
+    executor: impl sqlx::Acquire<'a, Database = Postgres>,
+) -> Result<Page<T>, crate::Error> {
+    let mut connection = executor.acquire().await.map_err(crate::Error::DbError)?;
+
+    let total: i64 = count_query::<T>(filters, scope)
+        .build()
+        .fetch_one(&mut *connection)
+        .await
+        .map_err(crate::Error::DbError)?
+        .try_get(0)
+        .map_err(crate::Error::DbError)?;
+
+    let per_page = page.per_page() as usize;
+    let mut items: Vec<T> = page_query::<T>(page, sort, filters, scope)
+        .build_query_as()
+        .fetch_all(&mut *connection)
+        .await
+        .map_err(crate::Error::DbError)?;
+
+    // one more record than requested is loaded to determine whether there is a next page
+    let next_cursor = if items.len() > per_page {
+        items.truncate(per_page);
+        items.last().map(T::id)
+    } else {
+        None
+    };
+
+    Ok(Page {
+        items,
+        total,
+        next_cursor,
+    })
+}
+
+fn count_query<'a, T: Listable>(
+    filters: &'a [Filter],
+    scope: Option<&str>,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query = QueryBuilder::new(format!("SELECT COUNT(*) FROM {}", T::TABLE));
+    push_conditions::<T>(&mut query, filters, scope, None);
+    query
+}
+
+fn page_query<'a, T: Listable>(
+    page: PageRequest,
+    sort: Sort,
+    filters: &'a [Filter],
+    scope: Option<&str>,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query = QueryBuilder::new(format!("SELECT {} FROM {}", T::COLUMNS, T::TABLE));
+    let cursor = match page {
+        PageRequest::Cursor {
+            after: Some(after), ..
+        } => Some((after, sort)),
+        _ => None,
+    };
+    push_conditions::<T>(&mut query, filters, scope, cursor);
+
+    let direction = match sort.direction {
+        Direction::Asc => "ASC",
+        Direction::Desc => "DESC",
+    };
+    if sort.column == "id" {
+        query.push(format!(" ORDER BY id {}", direction));
+    } else {
+        query.push(format!(
+            " ORDER BY {} {}, id {}",
+            sort.column, direction, direction
+        ));
+    }
+
+    query
+        .push(" LIMIT ")
+        .push_bind(i64::from(page.per_page()) + 1);
+    if let PageRequest::Offset { page, per_page } = page {
+        query
+            .push(" OFFSET ")
+            .push_bind(i64::from(page.saturating_sub(1)) * i64::from(per_page));
+    }
+
+    query
+}
+
+fn push_conditions<'a, T: Listable>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    filters: &'a [Filter],
+    scope: Option<&str>,
+    cursor: Option<(Uuid, Sort)>,
+) {
+    let mut separator = " WHERE ";
+    if let Some(scope) = scope {
+        query.push(separator).push(format!("({})", scope));
+        separator = " AND ";
+    }
+    for filter in filters {
+        query
+            .push(separator)
+            .push(format!("{}::text = ", filter.column))
+            .push_bind(&filter.value);
+        separator = " AND ";
+    }
+
+    // the records following the cursor are the ones whose sort column value and ID are greater (or less when sorted in descending order) than the cursor record's
+    if let Some((after, sort)) = cursor {
+        let operator = match sort.direction {
+            Direction::Asc => ">",
+            Direction::Desc => "<",
+        };
+        query
+            .push(separator)
+            .push(format!(
+                "({0}, id) {1} (SELECT {0}, id FROM {2} WHERE id = ",
+                sort.column,
+                operator,
+                T::TABLE
+            ))
+            .push_bind(after)
+            .push(")");
+    }
+}
@@ -0,0 +1,68 @@
+use sqlx::{Postgres, Row};
+
+// The `rate_limits` table is only created once the migration for it is generated via `cargo generate rate-limits-migration`, so the queries in this module are not checked at compile time.
+
+/// Takes a token from the bucket with the given key that holds up to `capacity` tokens and refills at `refill_rate` tokens per second, creating a full bucket if it does not exist yet.
+///
+/// Returns whether a token was taken, i.e. false if the bucket holds less than one token.
+pub async fn take(
+    key: &str,
+    capacity: f64,
+    refill_rate: f64,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<bool, crate::Error> {
+    let row = sqlx::query(
+        "INSERT INTO rate_limits AS bucket (key, tokens, updated_at) VALUES ($1, $2 - 1, now())
+        ON CONFLICT (key) DO UPDATE
+        SET tokens = LEAST($2, bucket.tokens + EXTRACT(EPOCH FROM now() - bucket.updated_at)::double precision * $3) - 1,
+            updated_at = now()
+        WHERE LEAST($2, bucket.tokens + EXTRACT(EPOCH FROM now() - bucket.updated_at)::double precision * $3) >= 1
+        RETURNING tokens",
+    )
+    .bind(key)
+    .bind(capacity)
+    .bind(refill_rate)
+    .fetch_optional(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(row.is_some())
+}
+
+/// Returns the number of tokens in the bucket with the given key that holds up to `capacity` tokens and refills at `refill_rate` tokens per second, or `capacity` if the bucket does not exist.
+pub async fn tokens(
+    key: &str,
+    capacity: f64,
+    refill_rate: f64,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<f64, crate::Error> {
+    let row = sqlx::query(
+        "SELECT LEAST($2, tokens + EXTRACT(EPOCH FROM now() - updated_at)::double precision * $3) AS tokens
+        FROM rate_limits WHERE key = $1",
+    )
+    .bind(key)
+    .bind(capacity)
+    .bind(refill_rate)
+    .fetch_optional(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(row.map_or(capacity, |row| row.get("tokens")))
+}
+
+/// Deletes the buckets that have not been used for longer than `max_age_secs` seconds, returning the number of deleted buckets.
+///
+/// Buckets that have not been used for longer than it takes them to refill are full again and can be deleted without changing any limits.
+pub async fn delete_stale(
+    max_age_secs: f64,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<u64, crate::Error> {
+    let result =
+        sqlx::query("DELETE FROM rate_limits WHERE updated_at < now() - make_interval(secs => $1)")
+            .bind(max_age_secs)
+            .execute(executor)
+            .await
+            .map_err(crate::Error::DbError)?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,68 @@
+use serde_json::Value;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Postgres, Row};
+
+// The `sessions` table is only created once the migration for it is generated via `cargo generate sessions-migration`, so the queries in this module are not checked at compile time.
+
+/// Loads the data of the session with the given ID unless the session has expired by `now`.
+pub async fn load(
+    id: &str,
+    now: DateTime<Utc>,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<Option<Value>, crate::Error> {
+    let row = sqlx::query("SELECT data FROM sessions WHERE id = $1 AND expires_at > $2")
+        .bind(id)
+        .bind(now)
+        .fetch_optional(executor)
+        .await
+        .map_err(crate::Error::DbError)?;
+
+    Ok(row.map(|row| row.get("data")))
+}
+
+/// Stores the data of the session with the given ID, creating the session if it does not exist yet.
+pub async fn save(
+    id: &str,
+    data: &Value,
+    expires_at: DateTime<Utc>,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<(), crate::Error> {
+    sqlx::query(
+        "INSERT INTO sessions (id, data, expires_at) VALUES ($1, $2, $3)
+        ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data, expires_at = EXCLUDED.expires_at",
+    )
+    .bind(id)
+    .bind(data)
+    .bind(expires_at)
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(())
+}
+
+/// Deletes the session with the given ID.
+pub async fn delete(
+    id: &str,
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<(), crate::Error> {
+    sqlx::query("DELETE FROM sessions WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await
+        .map_err(crate::Error::DbError)?;
+
+    Ok(())
+}
+
+/// Deletes all expired sessions, returning the number of deleted sessions.
+pub async fn delete_expired(
+    executor: impl sqlx::Executor<'_, Database = Postgres>,
+) -> Result<u64, crate::Error> {
+    let result = sqlx::query("DELETE FROM sessions WHERE expires_at <= now()")
+        .execute(executor)
+        .await
+        .map_err(crate::Error::DbError)?;
+
+    Ok(result.rows_affected())
+}
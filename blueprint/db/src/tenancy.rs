@@ -0,0 +1,58 @@
+use crate::DbPool;
+use anyhow::Context;
+use sqlx::{Executor, Postgres, Transaction};
+
+/// Returns the `search_path` for a tenant's schema: the schema itself, followed by `public` for the tables and extensions shared by all tenants.
+pub fn search_path(schema: &str) -> String {
+    format!(r#""{}", public"#, schema.replace('"', r#""""#))
+}
+
+/// Starts a new database transaction in a tenant's schema.
+///
+/// The tenant's schema is put first on the `search_path` for the duration of the transaction only, so that unqualified table names, e.g. in the entity functions, refer to the tenant's tables while the connection is returned to the pool unchanged, e.g.:
+///
+/// ```
+/// let mut tx = tenancy::transaction(&app_state.db_pool, "tenant_acme").await?;
+/// let tasks = tasks::load_all(&mut *tx).await?;
+/// tx.commit().await?;
+/// ```
+pub async fn transaction(
+    db_pool: &DbPool,
+    schema: &str,
+) -> Result<Transaction<'static, Postgres>, anyhow::Error> {
+    let mut tx = db_pool
+        .begin()
+        .await
+        .context("Failed to begin transaction")?;
+    sqlx::query("SELECT set_config('search_path', $1, true)")
+        .bind(search_path(schema))
+        .execute(&mut *tx)
+        .await
+        .context("Failed to set the search path")?;
+
+    Ok(tx)
+}
+
+/// Returns whether a schema exists.
+pub async fn schema_exists<'e, E>(executor: E, schema: &str) -> Result<bool, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)")
+        .bind(schema)
+        .fetch_one(executor)
+        .await
+}
+
+/// Returns the names of the tenants' schemas, i.e. of all schemas whose names start with `prefix`, ordered by name.
+pub async fn schemas<'e, E>(executor: E, prefix: &str) -> Result<Vec<String>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_scalar(
+        "SELECT nspname::text FROM pg_namespace WHERE starts_with(nspname, $1) ORDER BY nspname",
+    )
+    .bind(prefix)
+    .fetch_all(executor)
+    .await
+}
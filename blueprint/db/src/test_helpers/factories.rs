@@ -0,0 +1,54 @@
+//! Factories for creating records in tests.
+//!
+//! Every entity has a factory that is generated by deriving [`{{crate_name}}_macros::Factory`] for its changeset. A factory starts with fake data for all fields, which can be overridden via its setters before the record is created:
+//!
+//! ```
+//! let task = factory::task()
+//!     .description("Buy milk")
+//!     .create(&context.db_pool)
+//!     .await;
+//! ```
+//!
+//! Associated records are created automatically unless the association is set explicitly, e.g. `factory::comment().create(&db_pool)` creates a post for the comment first while `factory::comment().post_id(post.id).create(&db_pool)` uses the existing post. Fields that must be unique can use a [`sequence`].
+//!
+//! Factories for new entities are added to this module by `cargo generate entity`.
+
+use crate::DbPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A factory for creating records of type `T` in tests.
+///
+/// This trait is not implemented manually but via the [`{{crate_name}}_macros::Factory`] derive.
+#[allow(async_fn_in_trait)]
+pub trait Factory<T>: Sized {
+    /// The changeset the factory builds.
+    type Changeset;
+
+    /// Builds the changeset without creating a record for it – associated records that have not been set explicitly are created though so that the changeset can be used to create a record, e.g. via the API.
+    async fn build(self, db_pool: &DbPool) -> Self::Changeset;
+
+    /// Builds the changeset and creates a record for it.
+    ///
+    /// This panics if the record cannot be created as factories are only used in tests.
+    async fn create(self, db_pool: &DbPool) -> T;
+}
+
+/// Returns the next number of the sequence that is shared by all factories.
+///
+/// The numbers are unique across all tests in a test run so that they can be used for fields that must be unique, e.g. via `#[factory(sequence = "user{}@example.com")]`.
+pub fn sequence() -> u64 {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(1);
+    SEQUENCE.fetch_add(1, Ordering::Relaxed)
+}
+{%- if template_type == 'full' %}
+
+/// Returns a factory for [`crate::entities::tasks::Task`]s.
+pub fn task() -> crate::entities::tasks::TaskFactory {
+    Default::default()
+}
+
+/// Returns a factory for [`crate::entities::users::User`]s.
+pub fn user() -> crate::test_helpers::users::UserFactory {
+    Default::default()
+}
+{%- endif %}
@@ -1,5 +1,6 @@
 use crate::entities::users::User;
 use fake::{faker::name::en::*, Dummy};
+use {{crate_name}}_macros::Factory;
 use sqlx::postgres::PgPool;
 
 /// A changeset representing the data that is intended to be used to either create a new user or update an existing user.
@@ -11,7 +12,10 @@ use sqlx::postgres::PgPool;
 /// ```
 /// let user_changeset: UserChangeset = Faker.fake();
 /// ```
-#[derive(Debug, Clone, Dummy)]
+///
+/// Users can also be created via the [`UserFactory`] (see [`crate::test_helpers::factories`]).
+#[derive(Debug, Clone, Dummy, Factory)]
+#[factory(entity = User, create = create)]
 pub struct UserChangeset {
     /// The user's name
     #[dummy(faker = "Name()")]
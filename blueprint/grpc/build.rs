@@ -0,0 +1,28 @@
+//! Generates the gRPC services' code from the protobuf files in `proto/` at build time.
+
+use std::fs;
+
+/// Compiles all protobuf files in `proto/` to the Rust types and traits the services implement (see [`tonic::include_proto!`]).
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // use the protoc binary that ships with protoc-bin-vendored unless one is configured explicitly
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    let mut protos = vec![];
+    for entry in fs::read_dir("proto")? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "proto")
+        {
+            protos.push(path);
+        }
+    }
+    protos.sort();
+
+    println!("cargo:rerun-if-changed=proto");
+    tonic_build::configure().compile_protos(&protos, &["proto"])?;
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::state::SharedAppState;
+use proto::greeter_server::{Greeter, GreeterServer};
+use proto::{HelloReply, HelloRequest};
+use tonic::{Request, Response, Status};
+
+/// The types and traits generated from `proto/greeter.proto`.
+// the generated types are only documented if the messages in the proto file have comments
+#[allow(missing_docs)]
+pub mod proto {
+    tonic::include_proto!("greeter");
+}
+
+/// An example service that greets clients.
+pub struct GreeterService {
+    /// The state the service shares with all other services (see [`crate::state::AppState`]).
+    pub app_state: SharedAppState,
+}
+
+impl GreeterService {
+    /// Creates the server that serves the service, to be registered in [`super::register`].
+    pub fn server(app_state: &SharedAppState) -> GreeterServer<Self> {
+        GreeterServer::new(Self {
+            app_state: app_state.clone(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl Greeter for GreeterService {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let HelloRequest { name } = request.into_inner();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name must not be empty."));
+        }
+
+        Ok(Response::new(HelloReply {
+            message: format!("Hello, {}!", name),
+        }))
+    }
+}
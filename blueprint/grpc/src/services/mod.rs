@@ -0,0 +1,26 @@
+use crate::state::{AppState, SharedAppState};
+use std::sync::Arc;
+use tonic::service::{Routes, RoutesBuilder};
+
+/// Initializes the services the gRPC server serves.
+///
+/// This function wraps the [`AppState`] in a [`SharedAppState`] and passes it to all services registered in [`register`].
+pub fn init_services(app_state: AppState) -> Routes {
+    let app_state: SharedAppState = Arc::new(app_state);
+
+    let mut routes = RoutesBuilder::default();
+    register(&mut routes, &app_state);
+
+    routes.routes()
+}
+
+/// Registers all services with the gRPC server.
+///
+/// The server only serves services that are registered here – services generated with `cargo generate grpc-service` are registered automatically.
+#[allow(unused_variables)]
+pub fn register(routes: &mut RoutesBuilder, app_state: &SharedAppState) {
+    routes.add_service(greeter::GreeterService::server(app_state));
+}
+
+/// The [`greeter::GreeterService`] service
+pub mod greeter;
@@ -0,0 +1 @@
+// emails generated with `cargo generate mailer` are added here
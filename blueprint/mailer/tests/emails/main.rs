@@ -0,0 +1,2 @@
+#![allow(missing_docs)]
+// tests for emails generated with `cargo generate mailer` are added here
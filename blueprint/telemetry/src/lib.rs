@@ -0,0 +1,103 @@
+//! The {{crate_name}}_telemetry crate sets up the log output and the export of traces via OpenTelemetry for the application's processes, e.g. the web application{% if template_type == "full" %} and the worker{% endif %}.
+
+use anyhow::Context;
+use {{crate_name}}_config::{LogFormat, LoggingConfig, TracingConfig};
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use tracing::warn;
+use tracing_panic::panic_hook;
+use tracing_subscriber::{
+    filter::EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer,
+};
+
+/// Initializes tracing for the process that reports its spans under `service_name`.
+///
+/// This function
+///
+/// * registers a [`tracing_subscriber::fmt::Subscriber`] that logs in the format configured in the `[logging]` section of the configuration (see [`{{crate_name}}_config::LoggingConfig`])
+/// * registers a [`tracing_opentelemetry::OpenTelemetryLayer`] that exports spans to the OpenTelemetry collector configured in the `[tracing]` section of the configuration if any (see [`init_tracer_provider`])
+/// * registers a [`tracing_panic::panic_hook`]
+///
+/// The function respects the `RUST_LOG` if set or defaults to filtering spans and events with level [`tracing_subscriber::filter::LevelFilter::INFO`] and higher. The returned [`TracingGuard`] exports the remaining spans when it is dropped so it must be kept until the process exits.
+pub fn init_tracing(
+    logging_config: &LoggingConfig,
+    tracing_config: &TracingConfig,
+    service_name: &str,
+) -> TracingGuard {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new("info"))
+        .unwrap();
+    let fmt_layer = match logging_config.format {
+        LogFormat::Pretty => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer().json().boxed(),
+    };
+    let tracer_provider = init_tracer_provider(tracing_config, service_name);
+    let tracer = match &tracer_provider {
+        Ok(Some(tracer_provider)) => Some(tracer_provider.tracer(String::from(service_name))),
+        _ => None,
+    };
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
+        .with(filter)
+        .init();
+
+    std::panic::set_hook(Box::new(panic_hook));
+
+    let tracer_provider = tracer_provider.unwrap_or_else(|e| {
+        warn!(err.msg = %e, "Failed to set up the export of traces");
+        None
+    });
+    TracingGuard { tracer_provider }
+}
+
+/// Creates the provider of the tracer that exports spans via OTLP/HTTP to the collector configured in the `[tracing]` section of the configuration, reporting them under `service_name`. Returns `None` if no collector is configured.
+///
+/// The provider exports `sampling_ratio` of all traces unless a trace is continued from a caller in which case it follows the caller's sampling decision. This function also registers the propagator that reads and writes the `traceparent` and `tracestate` headers of the [W3C Trace Context](https://www.w3.org/TR/trace-context/) format.
+pub fn init_tracer_provider(
+    config: &TracingConfig,
+    service_name: &str,
+) -> Result<Option<SdkTracerProvider>, anyhow::Error> {
+    let Some(endpoint) = &config.otlp_endpoint else {
+        return Ok(None);
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .context("Failed to build the OTLP exporter!")?;
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sampling_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(String::from(service_name))
+                .build(),
+        )
+        .build();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(Some(tracer_provider))
+}
+
+/// Exports the spans that have not been exported yet when it is dropped (see [`init_tracing`]).
+pub struct TracingGuard {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Some(tracer_provider) = self.tracer_provider.take() {
+            if let Err(e) = tracer_provider.shutdown() {
+                warn!(err.msg = %e, "Failed to export the remaining spans");
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
+    Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tower::ServiceExt;
+
+/// A client for sending requests to the application under test.
+///
+/// The client is available as `client` in the test context. Headers that are added to the client, e.g. via [`TestClient::with_token`], are sent with every request the returned client sends – the client in the test context itself is not changed:
+///
+/// ```
+/// let client = context.client.with_token(&user.token);
+///
+/// let response = client.post_json("/tasks", &task_changeset).await;
+///
+/// assert_that!(response.status(), eq(StatusCode::CREATED));
+/// let task: Task = response.json();
+/// ```
+#[derive(Clone)]
+pub struct TestClient {
+    app: Router,
+    headers: HeaderMap,
+}
+
+impl TestClient {
+    /// Creates a client for the application under test.
+    pub fn new(app: Router) -> Self {
+        Self {
+            app,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Returns a client that sends the header with every request.
+    pub fn with_header(&self, name: HeaderName, value: &str) -> Self {
+        let mut client = self.clone();
+        client.headers.insert(
+            name,
+            HeaderValue::from_str(value).expect("Invalid header value!"),
+        );
+        client
+    }
+
+    /// Returns a client that sends the token in the `Authorization` header with every request as expected by the `auth` middleware.
+    pub fn with_token(&self, token: &str) -> Self {
+        self.with_header(header::AUTHORIZATION, token)
+    }
+
+    /// Returns a client that sends the token as a bearer token in the `Authorization` header with every request.
+    pub fn with_bearer_token(&self, token: &str) -> Self {
+        self.with_header(header::AUTHORIZATION, &format!("Bearer {}", token))
+    }
+
+    /// Returns a client that sends the cookie, e.g. a session cookie set by a previous response (see [`TestResponse::cookie`]), with every request.
+    pub fn with_cookie(&self, cookie: &str) -> Self {
+        self.with_header(header::COOKIE, cookie)
+    }
+
+    /// Sends a GET request.
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, Body::empty(), None).await
+    }
+
+    /// Sends a POST request without a body.
+    pub async fn post(&self, uri: &str) -> TestResponse {
+        self.send(Method::POST, uri, Body::empty(), None).await
+    }
+
+    /// Sends a DELETE request.
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.send(Method::DELETE, uri, Body::empty(), None).await
+    }
+
+    /// Sends a POST request with the payload serialized as JSON.
+    pub async fn post_json<T>(&self, uri: &str, payload: &T) -> TestResponse
+    where
+        T: Serialize + ?Sized,
+    {
+        self.send_json(Method::POST, uri, payload).await
+    }
+
+    /// Sends a PUT request with the payload serialized as JSON.
+    pub async fn put_json<T>(&self, uri: &str, payload: &T) -> TestResponse
+    where
+        T: Serialize + ?Sized,
+    {
+        self.send_json(Method::PUT, uri, payload).await
+    }
+
+    /// Sends a PATCH request with the payload serialized as JSON.
+    pub async fn patch_json<T>(&self, uri: &str, payload: &T) -> TestResponse
+    where
+        T: Serialize + ?Sized,
+    {
+        self.send_json(Method::PATCH, uri, payload).await
+    }
+
+    async fn send_json<T>(&self, method: Method, uri: &str, payload: &T) -> TestResponse
+    where
+        T: Serialize + ?Sized,
+    {
+        let body = serde_json::to_vec(payload).expect("Failed to serialize JSON payload!");
+        self.send(method, uri, Body::from(body), Some("application/json"))
+            .await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Body,
+        content_type: Option<&str>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+        let request = request.body(body).expect("Invalid request!");
+
+        let response = self.app.clone().oneshot(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        // We don't care about the size limit in tests.
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("Failed to read response body");
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+}
+
+/// A response of the application under test to a request sent via a [`TestClient`].
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    /// Returns the status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the value of the header if it is present and valid UTF-8.
+    pub fn header(&self, name: HeaderName) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the `name=value` pair of the cookie set via the `Set-Cookie` header, e.g. to send it with subsequent requests via [`TestClient::with_cookie`].
+    pub fn cookie(&self) -> Option<&str> {
+        self.header(header::SET_COOKIE)
+            .and_then(|set_cookie| set_cookie.split(';').next())
+    }
+
+    /// Returns the body as raw bytes.
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Returns the body as text.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.body).expect("Response body is not valid UTF-8")
+    }
+
+    /// Returns the body as parsed JSON.
+    pub fn json<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice::<T>(&self.body).expect("Failed to deserialize JSON body")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::HeaderMap,
+        routing::{get, post},
+        Json,
+    };
+    use googletest::prelude::*;
+    use serde_json::{json, Value};
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/echo",
+                post(
+                    |headers: HeaderMap, Json(payload): Json<Value>| async move {
+                        let authorization = headers
+                            .get(header::AUTHORIZATION)
+                            .map(|value| value.to_str().unwrap().to_string());
+                        Json(json!({ "payload": payload, "authorization": authorization }))
+                    },
+                ),
+            )
+            .route(
+                "/login",
+                get(|| async { ([(header::SET_COOKIE, "session=abc; HttpOnly")], "welcome") }),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_post_json() {
+        let client = TestClient::new(app());
+
+        let response = client.post_json("/echo", &json!({ "a": 1 })).await;
+
+        assert_that!(response.status(), eq(StatusCode::OK));
+        let body: Value = response.json();
+        assert_that!(body["payload"], eq(&json!({ "a": 1 })));
+        assert_that!(body["authorization"], eq(&Value::Null));
+    }
+
+    #[tokio::test]
+    async fn test_with_bearer_token() {
+        let client = TestClient::new(app()).with_bearer_token("secret");
+
+        let response = client.post_json("/echo", &json!({})).await;
+
+        let body: Value = response.json();
+        assert_that!(body["authorization"], eq(&json!("Bearer secret")));
+    }
+
+    #[tokio::test]
+    async fn test_with_token_leaves_original_client_unchanged() {
+        let client = TestClient::new(app());
+        let authorized_client = client.with_token("secret");
+
+        let response = authorized_client.post_json("/echo", &json!({})).await;
+        let body: Value = response.json();
+        assert_that!(body["authorization"], eq(&json!("secret")));
+
+        let response = client.post_json("/echo", &json!({})).await;
+        let body: Value = response.json();
+        assert_that!(body["authorization"], eq(&Value::Null));
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let client = TestClient::new(app());
+
+        let response = client.get("/login").await;
+
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(response.text(), eq("welcome"));
+        assert_that!(response.cookie(), some(eq("session=abc")));
+    }
+
+    #[tokio::test]
+    async fn test_get_unknown_route() {
+        let client = TestClient::new(app());
+
+        let response = client.get("/unknown").await;
+
+        assert_that!(response.status(), eq(StatusCode::NOT_FOUND));
+    }
+}
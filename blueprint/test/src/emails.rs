@@ -0,0 +1,183 @@
+use {{crate_name}}_mailer::{CapturedEmail, CapturedEmails};
+
+/// Asserts that the application sent an email that matches all of the criteria and returns it.
+///
+/// The emails the application sent are available as `emails` in the test context (see [`crate::DbTestContext`]). The criteria are the functions of [`EmailQuery`]:
+///
+/// ```
+/// let response = context.client.post_json("/auth/register", &payload).await;
+///
+/// assert_that!(response.status(), eq(StatusCode::CREATED));
+/// let email = assert_email_sent!(context.emails, to: "jane@example.com", subject_contains: "Welcome");
+/// assert_that!(email.text, contains_substring("/auth/confirm"));
+/// ```
+#[macro_export]
+macro_rules! assert_email_sent {
+    ($emails:expr, $($criterion:ident: $value:expr),+ $(,)?) => {
+        $crate::EmailQuery::default()
+            $(.$criterion($value))+
+            .assert_sent(&$emails)
+    };
+}
+
+/// Criteria that the emails sent by the application are matched against (see [`assert_email_sent!`]).
+#[derive(Clone, Debug, Default)]
+pub struct EmailQuery {
+    to: Option<String>,
+    subject: Option<String>,
+    subject_contains: Option<String>,
+    body_contains: Option<String>,
+}
+
+impl EmailQuery {
+    /// Matches emails sent to the recipient.
+    pub fn to(mut self, to: &str) -> Self {
+        self.to = Some(String::from(to));
+        self
+    }
+
+    /// Matches emails with the subject.
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(String::from(subject));
+        self
+    }
+
+    /// Matches emails whose subject contains the text.
+    pub fn subject_contains(mut self, text: &str) -> Self {
+        self.subject_contains = Some(String::from(text));
+        self
+    }
+
+    /// Matches emails whose plain text or HTML part contains the text.
+    pub fn body_contains(mut self, text: &str) -> Self {
+        self.body_contains = Some(String::from(text));
+        self
+    }
+
+    /// Whether the email matches all criteria.
+    pub fn matches(&self, email: &CapturedEmail) -> bool {
+        self.to.as_ref().is_none_or(|to| email.to == *to)
+            && self
+                .subject
+                .as_ref()
+                .is_none_or(|subject| email.subject == *subject)
+            && self
+                .subject_contains
+                .as_ref()
+                .is_none_or(|text| email.subject.contains(text.as_str()))
+            && self.body_contains.as_ref().is_none_or(|text| {
+                email.text.contains(text.as_str()) || email.html.contains(text.as_str())
+            })
+    }
+
+    /// Returns the last email that matches all criteria, panicking with a list of all sent emails if there is none.
+    pub fn assert_sent(&self, emails: &CapturedEmails) -> CapturedEmail {
+        let emails = emails.all();
+        match emails.iter().rev().find(|email| self.matches(email)) {
+            Some(email) => email.clone(),
+            None => {
+                let sent: Vec<String> = emails
+                    .iter()
+                    .map(|email| format!(r#"  to: "{}", subject: "{}""#, email.to, email.subject))
+                    .collect();
+                panic!(
+                    "No email matching {:?} was sent! Sent emails:\n{}",
+                    self,
+                    if sent.is_empty() {
+                        String::from("  (none)")
+                    } else {
+                        sent.join("\n")
+                    }
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+    use {{crate_name}}_mailer::{Email, Mailer, MemoryMailer};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct WelcomeEmail;
+
+    impl Email for WelcomeEmail {
+        const HTML_TEMPLATE: &'static str = "<a href=\"/auth/confirm\">Confirm</a>";
+        const TEXT_TEMPLATE: &'static str = "Confirm at /auth/confirm";
+
+        fn to(&self) -> String {
+            String::from("jane@example.com")
+        }
+
+        fn subject(&self) -> String {
+            String::from("Welcome, Jane!")
+        }
+    }
+
+    async fn send_welcome_email() -> CapturedEmails {
+        let mailer = MemoryMailer::new("noreply@example.com".parse().unwrap());
+        let emails = mailer.captured_emails().unwrap();
+        let mailer: &dyn Mailer = &mailer;
+        mailer.send(&WelcomeEmail).await.unwrap();
+        emails
+    }
+
+    #[tokio::test]
+    async fn test_assert_email_sent() {
+        let emails = send_welcome_email().await;
+
+        let email = crate::assert_email_sent!(
+            emails,
+            to: "jane@example.com",
+            subject_contains: "Welcome",
+            body_contains: "/auth/confirm",
+        );
+
+        assert_that!(email.subject, eq("Welcome, Jane!"));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = r#"Sent emails:
+  to: "jane@example.com", subject: "Welcome, Jane!""#)]
+    async fn test_assert_email_sent_no_match() {
+        let emails = send_welcome_email().await;
+
+        crate::assert_email_sent!(emails, to: "john@example.com");
+    }
+
+    #[test]
+    fn test_matches() {
+        let email = CapturedEmail {
+            to: String::from("jane@example.com"),
+            subject: String::from("Welcome, Jane!"),
+            text: String::from("Hi Jane!"),
+            html: String::from("<p>Hi Jane!</p>"),
+        };
+
+        assert_that!(EmailQuery::default().matches(&email), eq(true));
+        assert_that!(
+            EmailQuery::default()
+                .subject("Welcome, Jane!")
+                .matches(&email),
+            eq(true)
+        );
+        assert_that!(
+            EmailQuery::default().subject("Welcome").matches(&email),
+            eq(false)
+        );
+        assert_that!(
+            EmailQuery::default().body_contains("<p>Hi").matches(&email),
+            eq(true)
+        );
+        assert_that!(
+            EmailQuery::default()
+                .to("jane@example.com")
+                .body_contains("Bye")
+                .matches(&email),
+            eq(false)
+        );
+    }
+}
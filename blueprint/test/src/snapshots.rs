@@ -0,0 +1,183 @@
+use serde_json::{Map, Value};
+
+/// Asserts that the JSON body of a [`crate::TestResponse`] matches a snapshot.
+///
+/// Volatile values such as ids and timestamps differ between test runs and are replaced with placeholders before the body is compared to the snapshot (see [`Redactions`]). Snapshots are stored in the `snapshots` directory next to the test file and are reviewed and accepted with [`cargo insta review`](https://insta.rs/docs/cli/):
+///
+/// ```
+/// let response = context.client.get(&format!("/tasks/{}", task.id)).await;
+///
+/// assert_that!(response.status(), eq(StatusCode::OK));
+/// assert_json_snapshot_response!(response);
+/// ```
+///
+/// Custom [`Redactions`] can be passed as the second argument:
+///
+/// ```
+/// assert_json_snapshot_response!(response, Redactions::default().key("token", "[token]"));
+/// ```
+{% raw %}#[macro_export]
+macro_rules! assert_json_snapshot_response {
+    ($response:expr $(,)?) => {
+        $crate::assert_json_snapshot_response!($response, $crate::Redactions::default())
+    };
+    ($response:expr, $redactions:expr $(,)?) => {{
+        let body = $redactions.apply($response.json());
+        $crate::insta::assert_json_snapshot!(body)
+    }};
+}{% endraw %}
+
+/// Redactions replace volatile values in JSON response bodies with placeholders so that they can be compared to snapshots.
+///
+/// By default, all strings that are UUIDs are replaced with `"[uuid]"` and all strings that are RFC 3339 timestamps with `"[timestamp]"`. Additional values can be redacted by key, e.g. tokens that are generated randomly:
+///
+/// ```
+/// let redactions = Redactions::default().key("token", "[token]");
+/// ```
+///
+/// Object keys are sorted so that snapshots don't depend on the order in which fields are serialized.
+#[derive(Clone, Debug)]
+pub struct Redactions {
+    uuids: bool,
+    timestamps: bool,
+    keys: Vec<(String, String)>,
+}
+
+impl Default for Redactions {
+    fn default() -> Self {
+        Self {
+            uuids: true,
+            timestamps: true,
+            keys: vec![],
+        }
+    }
+}
+
+impl Redactions {
+    /// Returns redactions that leave all values unchanged.
+    pub fn none() -> Self {
+        Self {
+            uuids: false,
+            timestamps: false,
+            keys: vec![],
+        }
+    }
+
+    /// Sets whether UUIDs are replaced with `"[uuid]"`.
+    pub fn uuids(mut self, enabled: bool) -> Self {
+        self.uuids = enabled;
+        self
+    }
+
+    /// Sets whether RFC 3339 timestamps are replaced with `"[timestamp]"`.
+    pub fn timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    /// Replaces the values of all object fields with the key with the placeholder, regardless of their type.
+    pub fn key(mut self, key: &str, placeholder: &str) -> Self {
+        self.keys
+            .push((String::from(key), String::from(placeholder)));
+        self
+    }
+
+    /// Applies the redactions to the value.
+    pub fn apply(&self, value: Value) -> Value {
+        match value {
+            Value::String(string) if self.uuids && uuid::Uuid::try_parse(&string).is_ok() => {
+                Value::String(String::from("[uuid]"))
+            }
+            Value::String(string)
+                if self.timestamps && chrono::DateTime::parse_from_rfc3339(&string).is_ok() =>
+            {
+                Value::String(String::from("[timestamp]"))
+            }
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(|value| self.apply(value)).collect())
+            }
+            Value::Object(object) => {
+                let mut entries: Vec<(String, Value)> = object.into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                let object: Map<String, Value> = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = match self.keys.iter().find(|(k, _)| *k == key) {
+                            Some((_, placeholder)) => Value::String(placeholder.clone()),
+                            None => self.apply(value),
+                        };
+                        (key, value)
+                    })
+                    .collect();
+                Value::Object(object)
+            }
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_redacts_uuids_and_timestamps() {
+        let value = json!({
+            "id": "0b3f5f7a-9a4e-4a5c-8f0e-5d8c2b1e7a61",
+            "created_at": "2024-05-01T12:30:00.123456Z",
+            "description": "Buy milk",
+            "done": false,
+        });
+
+        let redacted = Redactions::default().apply(value);
+
+        assert_that!(
+            redacted,
+            eq(&json!({
+                "created_at": "[timestamp]",
+                "description": "Buy milk",
+                "done": false,
+                "id": "[uuid]",
+            }))
+        );
+    }
+
+    #[test]
+    fn test_apply_redacts_nested_values_and_keys() {
+        let value = json!({
+            "items": [{ "id": "0b3f5f7a-9a4e-4a5c-8f0e-5d8c2b1e7a61", "token": 12345 }],
+            "total": 1,
+        });
+
+        let redacted = Redactions::default().key("token", "[token]").apply(value);
+
+        assert_that!(
+            redacted,
+            eq(&json!({
+                "items": [{ "id": "[uuid]", "token": "[token]" }],
+                "total": 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_apply_sorts_keys() {
+        let value = json!({ "b": 1, "a": 2 });
+
+        let redacted = Redactions::none().apply(value);
+
+        let keys: Vec<&String> = redacted.as_object().unwrap().keys().collect();
+        assert_that!(keys, elements_are![eq(&"a"), eq(&"b")]);
+    }
+
+    #[test]
+    fn test_none_leaves_values_unchanged() {
+        let value = json!({ "id": "0b3f5f7a-9a4e-4a5c-8f0e-5d8c2b1e7a61" });
+
+        let redacted = Redactions::none().apply(value.clone());
+
+        assert_that!(redacted, eq(&value));
+    }
+}
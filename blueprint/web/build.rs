@@ -0,0 +1,115 @@
+//! Fingerprints (and optionally precompresses) the static files in `public/` at build time.
+
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory the static files are served from in development (see `StaticFilesConfig` in the config crate).
+const PUBLIC_DIR: &str = "public";
+
+/// Fingerprints the files in `public/` with a hash of their contents and writes the pairs of their paths and fingerprinted paths to `$OUT_DIR/assets.rs` which is included in the `static_files` module. With the `precompress` feature, the files are also compressed with Brotli and gzip, written next to the originals as `.br` and `.gz` files.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed={}", PUBLIC_DIR);
+
+    let mut files = vec![];
+    collect_files(Path::new(PUBLIC_DIR), &mut files)?;
+    files.sort();
+
+    let mut assets = String::from("&[\n");
+    for file in files {
+        let contents = fs::read(&file)?;
+        let path = file
+            .strip_prefix(PUBLIC_DIR)?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let fingerprinted = fingerprint(&path, &contents);
+        writeln!(assets, "    ({:?}, {:?}),", path, fingerprinted)?;
+
+        #[cfg(feature = "precompress")]
+        precompress(&file, &contents)?;
+    }
+    assets.push_str("]\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out_dir.join("assets.rs"), assets)?;
+
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), std::io::Error> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        // hidden files like .gitkeep are not served and compressed copies are served automatically
+        if name.starts_with('.') || name.ends_with(".br") || name.ends_with(".gz") {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Inserts the first 8 hex digits of the SHA-256 hash of `contents` before the extension, e.g. `css/app.css` becomes `css/app.1a2b3c4d.css`.
+fn fingerprint(path: &str, contents: &[u8]) -> String {
+    let hash: String = Sha256::digest(contents)[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let (dir, name) = path
+        .rsplit_once('/')
+        .map_or(("", path), |(dir, name)| (dir, name));
+    let name = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}.{}.{}", stem, hash, extension),
+        _ => format!("{}.{}", name, hash),
+    };
+
+    if dir.is_empty() {
+        name
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+#[cfg(feature = "precompress")]
+fn precompress(file: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let modified = fs::metadata(file)?.modified()?;
+    let is_fresh = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|compressed| compressed >= modified)
+    };
+
+    let br = PathBuf::from(format!("{}.br", file.display()));
+    if !is_fresh(&br) {
+        let mut compressed = vec![];
+        brotli::BrotliCompress(
+            &mut &contents[..],
+            &mut compressed,
+            &brotli::enc::BrotliEncoderParams::default(),
+        )?;
+        fs::write(&br, compressed)?;
+    }
+
+    let gz = PathBuf::from(format!("{}.gz", file.display()));
+    if !is_fresh(&gz) {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::best());
+        encoder.write_all(contents)?;
+        fs::write(&gz, encoder.finish()?)?;
+    }
+
+    Ok(())
+}
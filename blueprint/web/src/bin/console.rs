@@ -0,0 +1,147 @@
+#![allow(missing_docs)]
+use anyhow::Context as _;
+use clap::Parser;
+use futures_util::TryStreamExt;
+use serde_json::{Map, Value};
+use sqlx::{Column, Either, Executor, Row};
+use std::io::Write;
+use std::sync::Arc;
+use tokio::io::{stdin, AsyncBufReadExt, BufReader};
+use {{crate_name}}_config::{get_env, load_config, Config};
+use {{crate_name}}_web::console::{self, parse_line, Context, Registry};
+use {{crate_name}}_web::state::init_app_state;
+
+#[derive(Parser)]
+#[command(author, version, about = "An interactive console to run the application's code, e.g. entity functions, and SQL against its database.", long_about = None)]
+struct Cli {}
+
+#[tokio::main]
+async fn main() {
+    Cli::parse();
+
+    if let Err(e) = run().await {
+        eprintln!("Could not run the console: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Loads the configuration and initializes the application state for the current environment like the application does when it starts, then runs the commands entered on stdin until `exit` (or the end of the input).
+async fn run() -> Result<(), anyhow::Error> {
+    let env = get_env().context("Cannot get environment!")?;
+    let config: Config = load_config(&env).context("Cannot load config!")?;
+    let app_state = init_app_state(config.clone()).await;
+    let context = Context {
+        config: Arc::new(config),
+        app_state: Arc::new(app_state),
+    };
+    let mut registry = Registry::default();
+    console::register(&mut registry);
+
+    println!(
+        "Connected to the {} environment. Enter help to list the commands or exit to quit.",
+        env
+    );
+    let mut lines = BufReader::new(stdin()).lines();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines
+            .next_line()
+            .await
+            .context("Failed to read from stdin!")?
+        else {
+            break;
+        };
+
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let result = match command {
+            "" => continue,
+            "exit" | "quit" => break,
+            "help" => {
+                print_help(&registry);
+                continue;
+            }
+            "config" => {
+                println!("{:#?}", context.config);
+                continue;
+            }
+            "sql" => sql(&context, rest.trim()).await,
+            _ => match parse_line(line) {
+                Ok(Some((name, args))) => registry.run(&name, context.clone(), args).await,
+                Ok(None) => continue,
+                Err(e) => Err(e),
+            },
+        };
+
+        match result {
+            Ok(Value::Null) => println!("ok"),
+            Ok(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the built-in commands and the ones registered with the console (see [`{{crate_name}}_web::console::register`]).
+fn print_help(registry: &Registry) {
+    let mut commands: Vec<(String, &str)> = vec![
+        (
+            String::from("sql <statement>"),
+            "Runs the SQL statement and prints the rows it returned",
+        ),
+        (String::from("config"), "Prints the configuration"),
+        (String::from("help"), "Lists the commands"),
+        (String::from("exit"), "Quits the console"),
+    ];
+    commands.extend(registry.commands().map(|(name, command)| {
+        (
+            format!("{} {}", name, command.usage).trim_end().to_string(),
+            command.description,
+        )
+    }));
+    let width = commands
+        .iter()
+        .map(|(signature, _)| signature.len())
+        .max()
+        .unwrap_or_default();
+
+    println!("Commands:");
+    for (signature, description) in commands {
+        println!("  {:<width$}  {}", signature, description, width = width);
+    }
+    println!(
+        {% raw %}r#"Arguments are JSON values, e.g. {{"description": "Buy milk"}}, or strings otherwise, e.g. IDs."#{% endraw %}
+    );
+}
+
+/// Runs an SQL statement against the application's database and returns the rows it returned as JSON objects with the values in PostgreSQL's text representation, or the number of rows it affected.
+async fn sql(context: &Context, statement: &str) -> Result<Value, anyhow::Error> {
+    let mut rows: Vec<Value> = vec![];
+    let mut rows_affected = 0;
+    // without arguments, the statement is run via the simple query protocol so all values are returned in their text representation
+    let mut results = context.app_state.db_pool.fetch_many(statement);
+    while let Some(result) = results.try_next().await? {
+        match result {
+            Either::Left(result) => rows_affected += result.rows_affected(),
+            Either::Right(row) => {
+                let mut object = Map::new();
+                for (i, column) in row.columns().iter().enumerate() {
+                    let value: Option<String> = row.try_get_unchecked(i)?;
+                    object.insert(
+                        column.name().to_string(),
+                        value.map_or(Value::Null, Value::String),
+                    );
+                }
+                rows.push(Value::Object(object));
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        Ok(Value::from(format!("{} rows affected", rows_affected)))
+    } else {
+        Ok(Value::Array(rows))
+    }
+}
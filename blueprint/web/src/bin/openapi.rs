@@ -0,0 +1,58 @@
+#![allow(missing_docs)]
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use {{crate_name}}_web::openapi::api_doc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to work with the application's OpenAPI document.", long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Write the OpenAPI document to a JSON file")]
+    Export {
+        #[arg(
+            short,
+            long,
+            default_value = "openapi.json",
+            help = "The file to write the document to, relative to the project root."
+        )]
+        output: PathBuf,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Export { output } => match export(&output) {
+            Ok(path) => println!("Exported the OpenAPI document to {}.", path.display()),
+            Err(e) => {
+                eprintln!("Could not export the OpenAPI document: {:#}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Writes the application's OpenAPI document (see [`{{crate_name}}_web::openapi::api_doc`]) to the passed path, relative to the project root, and returns the path the document was written to.
+fn export(output: &Path) -> Result<PathBuf, anyhow::Error> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .context("Failed to determine the project root!")?;
+    let path = root.join(output);
+
+    let json = api_doc()
+        .to_pretty_json()
+        .context("Failed to serialize the OpenAPI document!")?;
+    fs::write(&path, format!("{}\n", json))
+        .context(format!(r#"Failed to write "{}"!"#, path.display()))?;
+
+    Ok(path)
+}
@@ -0,0 +1,111 @@
+#![allow(missing_docs)]
+use anyhow::Context;
+use clap::Parser;
+use {{crate_name}}_config::{get_env, load_config, Config};
+use {{crate_name}}_web::registry::{RouteInfo, Routes};
+use {{crate_name}}_web::{routes::init_registry, state::init_app_state};
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to list the application's routes.", long_about = None)]
+struct Cli {
+    #[arg(long, help = "Only list routes whose path starts with the prefix")]
+    prefix: Option<String>,
+    #[arg(long, help = "Print the routes as JSON")]
+    json: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    match load_routes().await {
+        Ok(routes) => {
+            let listed: Vec<&RouteInfo> = routes
+                .routes()
+                .iter()
+                .filter(|route| match &cli.prefix {
+                    Some(prefix) => route.path.starts_with(prefix.as_str()),
+                    None => true,
+                })
+                .collect();
+            if cli.json {
+                match serde_json::to_string_pretty(&listed) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Could not serialize the routes: {:#}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                print_routes(routes.middlewares(), &listed);
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not load the routes: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Initializes the application state for the current environment like the application does when it starts and returns the routes registered with it (see [`{{crate_name}}_web::routes::init_registry`]).
+async fn load_routes() -> Result<Routes, anyhow::Error> {
+    let env = get_env().context("Cannot get environment!")?;
+    let config: Config = load_config(&env).context("Cannot load config!")?;
+    let app_state = init_app_state(config).await;
+
+    Ok(init_registry(app_state))
+}
+
+/// Prints the middlewares that apply to all routes, followed by a table of the routes with their handlers and the middlewares that only apply to them.
+fn print_routes(middlewares: &[String], routes: &[&RouteInfo]) {
+    println!("Middlewares applied to all routes (outermost first):");
+    for middleware in middlewares {
+        println!("  {}", middleware);
+    }
+    println!();
+
+    let header = ["METHOD", "PATH", "HANDLER", "MIDDLEWARES"];
+    let rows: Vec<[String; 4]> = routes
+        .iter()
+        .map(|route| {
+            [
+                String::from(route.method),
+                route.path.clone(),
+                route.handler.clone(),
+                route.middlewares.join(", "),
+            ]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..3)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain(std::iter::once(header[i].len()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    println!(
+        "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+        header[0],
+        header[1],
+        header[2],
+        header[3],
+        w0 = widths[0],
+        w1 = widths[1],
+        w2 = widths[2]
+    );
+    for row in rows {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+#![allow(missing_docs)]
+use anyhow::Context;
+use clap::{Arg, ArgMatches, Command};
+use std::path::PathBuf;
+use std::sync::Arc;
+use {{crate_name}}_config::{get_env, load_env_files, parse_env, read_config, Config, Environment};
+use {{crate_name}}_web::init_tracing;
+use {{crate_name}}_web::state::init_app_state;
+use {{crate_name}}_web::tasks::{self, Registry, TaskContext};
+
+/// Builds the CLI with a subcommand of `run` for every registered task so that the tasks' arguments are parsed and documented like the CLI's own.
+fn cli(registry: &Registry) -> Command {
+    Command::new("task")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("A CLI tool to list and run the application's tasks.")
+        .propagate_version(true)
+        .subcommand_required(true)
+        .arg(
+            Arg::new("env")
+                .short('e')
+                .long("env")
+                .global(true)
+                .value_name("ENV")
+                .value_parser(parse_env)
+                .help("Choose the environment (development, test, production, or a custom one configured in config/environments); defaults to the one set via APP_ENVIRONMENT or development."),
+        )
+        .arg(
+            Arg::new("env_file")
+                .long("env-file")
+                .global(true)
+                .value_name("ENV_FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Load env vars from this file instead of the environment's default .env file."),
+        )
+        .subcommand(Command::new("list").about("List the tasks with their descriptions and arguments"))
+        .subcommand(
+            Command::new("run")
+                .about("Run a task")
+                .subcommand_required(true)
+                .subcommands(registry.commands().cloned()),
+        )
+}
+
+#[tokio::main]
+async fn main() {
+    let mut registry = Registry::default();
+    tasks::register(&mut registry);
+    let matches = cli(&registry).get_matches();
+
+    match matches.subcommand() {
+        Some(("list", _)) => list(&registry),
+        Some(("run", run_matches)) => {
+            let Some((name, task_matches)) = run_matches.subcommand() else {
+                unreachable!("a task is required");
+            };
+            let _tracing = init_tracing();
+            if let Err(e) = run(&registry, name, task_matches, &matches).await {
+                eprintln!("Task {} failed: {:#}", name, e);
+                std::process::exit(1);
+            }
+            println!("Task {} finished.", name);
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
+}
+
+/// Prints the registered tasks with their descriptions and arguments.
+fn list(registry: &Registry) {
+    let mut empty = true;
+    for command in registry.commands() {
+        empty = false;
+        println!(
+            "{}  {}",
+            command.get_name(),
+            command
+                .get_about()
+                .map(|about| about.to_string())
+                .unwrap_or_default()
+        );
+        for arg in command.get_arguments() {
+            let mut line = format!("    {}", usage(arg));
+            if let Some(help) = arg.get_help() {
+                line.push_str(&format!("  {}", help));
+            }
+            let defaults: Vec<String> = arg
+                .get_default_values()
+                .iter()
+                .map(|value| value.to_string_lossy().to_string())
+                .collect();
+            if !defaults.is_empty() && arg.get_action().takes_values() {
+                line.push_str(&format!(" [default: {}]", defaults.join(", ")));
+            }
+            println!("{}", line);
+        }
+    }
+    if empty {
+        println!("No tasks are registered – generate one with `cargo generate task <name>`.");
+    }
+}
+
+/// Returns how an argument is passed on the command line, e.g. `--batch-size <BATCH_SIZE>`.
+fn usage(arg: &Arg) -> String {
+    let value_name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+    match arg.get_long() {
+        Some(long) if arg.get_action().takes_values() => format!("--{} <{}>", long, value_name),
+        Some(long) => format!("--{}", long),
+        None => format!("<{}>", value_name),
+    }
+}
+
+/// Loads the configuration for the selected environment, initializes the application state like the application does when it starts, and runs the task.
+async fn run(
+    registry: &Registry,
+    name: &str,
+    task_matches: &ArgMatches,
+    matches: &ArgMatches,
+) -> Result<(), anyhow::Error> {
+    let env = match matches.get_one::<Environment>("env") {
+        Some(env) => env.clone(),
+        None => get_env().context("Cannot get environment!")?,
+    };
+    load_env_files(
+        &env,
+        matches.get_one::<PathBuf>("env_file").map(PathBuf::as_path),
+    )
+    .context("Cannot load env files!")?;
+    let config: Config = read_config(&env).context("Cannot load config!")?;
+
+    println!("Running task {} in the {} environment…", name, env);
+    let app_state = init_app_state(config.clone()).await;
+    let context = TaskContext {
+        env,
+        config,
+        app_state: Arc::new(app_state),
+    };
+    registry.run(name, task_matches, &context).await
+}
@@ -0,0 +1,133 @@
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+mod hub;
+mod socket;
+
+pub use hub::Channels;
+pub use socket::{socket, Socket};
+
+/// A message exchanged with clients via the socket, serialized as JSON.
+///
+/// Clients send messages to join (`"event": "join"`) or leave (`"event": "leave"`) a topic or to send any other event to the channel of a topic they joined. Every message a client sends is answered with a reply (`"event": "reply"`) that carries the same `ref` and the result as its payload, e.g.:
+///
+/// ```json
+/// { "topic": "rooms:lobby", "event": "join", "payload": {}, "ref": "1" }
+/// { "topic": "rooms:lobby", "event": "reply", "payload": { "status": "ok", "response": {} }, "ref": "1" }
+/// ```
+///
+/// Messages that are pushed to a client (see [`Socket::push`]) or broadcast to all clients that joined a topic (see [`Channels::broadcast`]) do not have a `ref`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Message {
+    /// The topic, e.g. `rooms:lobby`; the part before the first `:` is the name of the channel that handles the topic
+    pub topic: String,
+    /// The event, e.g. `join` or `new_message`
+    pub event: String,
+    /// The event's data
+    #[serde(default)]
+    pub payload: Value,
+    /// The reference the client set to match replies to its messages
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// An error returned by a [`Channel`].
+#[derive(thiserror::Error, Debug)]
+pub enum ChannelError {
+    /// The channel rejected the message, e.g. because the user is not allowed to join the topic. The reason is sent to the client in the reply.
+    #[error("Rejected: {0}")]
+    Rejected(String),
+    /// Any other error. It is logged while the client only receives an opaque error reply.
+    #[error("Error: {0}")]
+    Other(#[from] anyhow::Error),
+}
+
+/// A channel that handles the messages clients send for its topics.
+///
+/// A channel is registered with a name (see [`register`]) and handles all topics that start with that name followed by a `:` (or are the name itself), e.g. a channel registered as `rooms` handles `rooms:lobby` and `rooms:42`. Clients have to join a topic before they can send messages for it and receive the messages that are broadcast to it.
+///
+/// Example:
+/// ```
+/// pub struct RoomsChannel;
+///
+/// #[async_trait]
+/// impl Channel for RoomsChannel {
+///     async fn join(
+///         &self,
+///         topic: &str,
+///         _payload: Value,
+///         _socket: &Socket,
+///     ) -> Result<Value, ChannelError> {
+///         Ok(json!({ "joined": topic }))
+///     }
+///
+///     async fn handle_message(
+///         &self,
+///         topic: &str,
+///         event: &str,
+///         payload: Value,
+///         socket: &Socket,
+///     ) -> Result<Value, ChannelError> {
+///         match event {
+///             "new_message" => {
+///                 socket.app_state.channels.broadcast(topic, "new_message", payload).await?;
+///                 Ok(Value::Null)
+///             }
+///             _ => Err(ChannelError::Rejected(format!("Unknown event {}.", event))),
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Channel: Send + Sync + 'static {
+    /// Called when a client joins a topic of the channel. The client joins the topic if this returns `Ok` and the value is sent to the client in the reply.
+    async fn join(
+        &self,
+        topic: &str,
+        payload: Value,
+        socket: &Socket,
+    ) -> Result<Value, ChannelError>;
+
+    /// Called when a client leaves a topic, either explicitly or because it disconnected.
+    #[allow(unused_variables)]
+    async fn leave(&self, topic: &str, socket: &Socket) -> Result<(), ChannelError> {
+        Ok(())
+    }
+
+    /// Called for every other event a client sends for a topic it joined. The value is sent to the client in the reply.
+    async fn handle_message(
+        &self,
+        topic: &str,
+        event: &str,
+        payload: Value,
+        socket: &Socket,
+    ) -> Result<Value, ChannelError>;
+}
+
+/// The channels clients can join, keyed by their names.
+#[derive(Default)]
+pub struct Registry {
+    channels: HashMap<&'static str, Arc<dyn Channel>>,
+}
+
+impl Registry {
+    /// Registers a channel that handles all topics starting with `name`.
+    pub fn register<C: Channel>(&mut self, name: &'static str, channel: C) {
+        self.channels.insert(name, Arc::new(channel));
+    }
+
+    /// Returns the channel that handles a topic, if any.
+    pub fn get(&self, topic: &str) -> Option<Arc<dyn Channel>> {
+        let name = topic.split_once(':').map_or(topic, |(name, _)| name);
+        self.channels.get(name).cloned()
+    }
+}
+
+/// Registers all channels with the application.
+///
+/// Clients can only join the topics of channels that are registered here – channels generated with `cargo generate channel` are registered automatically.
+#[allow(unused_variables)]
+pub fn register(registry: &mut Registry) {}
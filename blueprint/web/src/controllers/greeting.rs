@@ -1,14 +1,26 @@
 use axum::response::Json;
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+
+/// The OpenAPI document describing the greeting endpoint (see [`crate::openapi`]).
+#[derive(OpenApi)]
+#[openapi(paths(hello))]
+pub struct ApiDoc;
 
 /// A greeting to respond with to the requesting client
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct Greeting {
     /// Who do we say hello to?
     pub hello: String,
 }
 
 /// Responds with a [`Greeting`], encoded as JSON.
+#[utoipa::path(
+    get,
+    path = "/greet",
+    tag = "greeting",
+    responses((status = 200, description = "The greeting.", body = Greeting))
+)]
 #[axum::debug_handler]
 pub async fn hello() -> Json<Greeting> {
     Json(Greeting {
@@ -1,17 +1,34 @@
-use crate::{error::Error, state::SharedAppState};
+use crate::{error::AppError, state::SharedAppState, validation::Validated};
 use axum::{extract::Path, extract::State, http::StatusCode, Json};
-use {{crate_name}}_db::{entities::tasks, transaction};
+use {{crate_name}}_db::{entities::tasks, transaction, Error as DbError};
 use tracing::info;
+use utoipa::OpenApi;
 use uuid::Uuid;
 
+/// The OpenAPI document describing the task endpoints (see [`crate::openapi`]).
+#[derive(OpenApi)]
+#[openapi(paths(create, create_batch, read_all, read_one, update, delete))]
+pub struct ApiDoc;
+
 /// Creates a task in the database.
 ///
-/// This function creates a task in the database (see [`{{crate_name}}_db::entities::tasks::create`]) based on a [`{{crate_name}}_db::entities::tasks::TaskChangeset`] (sent as JSON). If the task is created successfully, a 201 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]'s JSON representation in the response body. If the changeset is invalid, a 422 response is returned.
+/// This function creates a task in the database (see [`{{crate_name}}_db::entities::tasks::create`]) based on a [`{{crate_name}}_db::entities::tasks::TaskChangeset`] (sent as JSON). If the task is created successfully, a 201 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]'s JSON representation in the response body. If the changeset is invalid, a 422 response listing the invalid fields is returned (see [`crate::validation::Validated`]).
+#[utoipa::path(
+    post,
+    path = "/tasks",
+    tag = "tasks",
+    request_body = tasks::TaskChangeset,
+    responses(
+        (status = 201, description = "The task was created.", body = tasks::Task),
+        (status = 401, description = "The request is not authenticated."),
+        (status = 422, description = "The changeset is invalid."),
+    )
+)]
 #[axum::debug_handler]
 pub async fn create(
     State(app_state): State<SharedAppState>,
-    Json(task): Json<tasks::TaskChangeset>,
-) -> Result<(StatusCode, Json<tasks::Task>), Error> {
+    Validated(task): Validated<tasks::TaskChangeset>,
+) -> Result<(StatusCode, Json<tasks::Task>), AppError> {
     Ok(tasks::create(task, &app_state.db_pool)
         .await
         .map(|task| (StatusCode::CREATED, Json(task)))?)
@@ -19,14 +36,25 @@ pub async fn create(
 
 /// Creates multiple tasks in the database.
 ///
-/// This function creates multiple tasks in the database (see [`{{crate_name}}_db::entities::tasks::create`]) based on [`{{crate_name}}_db::entities::tasks::TaskChangeset`]s (sent as JSON). If all tasks are created successfully, a 201 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]s' JSON representation in the response body. If any of the passed changesets is invalid, a 422 response is returned.
+/// This function creates multiple tasks in the database (see [`{{crate_name}}_db::entities::tasks::create`]) based on [`{{crate_name}}_db::entities::tasks::TaskChangeset`]s (sent as JSON). If all tasks are created successfully, a 201 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]s' JSON representation in the response body. If any of the passed changesets is invalid, a 422 response listing the invalid fields is returned (see [`crate::validation::Validated`]).
 ///
 /// This function creates all tasks in a transaction so that either all are created successfully or none is.
+#[utoipa::path(
+    put,
+    path = "/tasks",
+    tag = "tasks",
+    request_body = Vec<tasks::TaskChangeset>,
+    responses(
+        (status = 201, description = "The tasks were created.", body = Vec<tasks::Task>),
+        (status = 401, description = "The request is not authenticated."),
+        (status = 422, description = "Any of the changesets is invalid."),
+    )
+)]
 #[axum::debug_handler]
 pub async fn create_batch(
     State(app_state): State<SharedAppState>,
-    Json(tasks): Json<Vec<tasks::TaskChangeset>>,
-) -> Result<(StatusCode, Json<Vec<tasks::Task>>), Error> {
+    Validated(tasks): Validated<Vec<tasks::TaskChangeset>>,
+) -> Result<(StatusCode, Json<Vec<tasks::Task>>), AppError> {
     let mut tx = transaction(&app_state.db_pool).await?;
 
     let mut results: Vec<tasks::Task> = vec![];
@@ -35,7 +63,7 @@ pub async fn create_batch(
         results.push(task);
     }
 
-    tx.commit().await.map_err(anyhow::Error::from)?;
+    tx.commit().await.map_err(DbError::from)?;
 
     Ok((StatusCode::CREATED, Json(results)))
 }
@@ -43,8 +71,14 @@ pub async fn create_batch(
 /// Reads and responds with all the tasks currently present in the database.
 ///
 /// This function reads all [`{{crate_name}}_db::entities::tasks::Task`]s from the database (see [`{{crate_name}}_db::entities::tasks::load_all`]) and responds with their JSON representations.
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    tag = "tasks",
+    responses((status = 200, description = "All tasks.", body = Vec<tasks::Task>))
+)]
 #[axum::debug_handler]
-pub async fn read_all(State(app_state): State<SharedAppState>) -> Result<Json<Vec<tasks::Task>>, Error> {
+pub async fn read_all(State(app_state): State<SharedAppState>) -> Result<Json<Vec<tasks::Task>>, AppError> {
     let tasks = tasks::load_all(&app_state.db_pool).await?;
 
     info!("responding with {:?}", tasks);
@@ -55,24 +89,47 @@ pub async fn read_all(State(app_state): State<SharedAppState>) -> Result<Json<Ve
 /// Reads and responds with a task identified by its ID.
 ///
 /// This function reads one [`{{crate_name}}_db::entities::tasks::Task`] identified by its ID from the database (see [`{{crate_name}}_db::entities::tasks::load`]) and responds with its JSON representations. If no task is found for the ID, a 404 response is returned.
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(("id" = Uuid, Path, description = "The id of the task.")),
+    responses(
+        (status = 200, description = "The task.", body = tasks::Task),
+        (status = 404, description = "No task exists for the id."),
+    )
+)]
 #[axum::debug_handler]
 pub async fn read_one(
     State(app_state): State<SharedAppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<tasks::Task>, Error> {
+) -> Result<Json<tasks::Task>, AppError> {
     let task = tasks::load(id, &app_state.db_pool).await?;
     Ok(Json(task))
 }
 
 /// Updates a task in the database.
 ///
-/// This function updates a task identified by its ID in the database (see [`{{crate_name}}_db::entities::tasks::update`]) with the data from the passed [`{{crate_name}}_db::entities::tasks::TaskChangeset`] (sent as JSON). If the task is updated successfully, a 200 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]'s JSON representation in the response body. If the changeset is invalid, a 422 response is returned.
+/// This function updates a task identified by its ID in the database (see [`{{crate_name}}_db::entities::tasks::update`]) with the data from the passed [`{{crate_name}}_db::entities::tasks::TaskChangeset`] (sent as JSON). If the task is updated successfully, a 200 response is returned with the created [`{{crate_name}}_db::entities::tasks::Task`]'s JSON representation in the response body. If the changeset is invalid, a 422 response listing the invalid fields is returned (see [`crate::validation::Validated`]).
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(("id" = Uuid, Path, description = "The id of the task.")),
+    request_body = tasks::TaskChangeset,
+    responses(
+        (status = 200, description = "The task was updated.", body = tasks::Task),
+        (status = 401, description = "The request is not authenticated."),
+        (status = 404, description = "No task exists for the id."),
+        (status = 422, description = "The changeset is invalid."),
+    )
+)]
 #[axum::debug_handler]
 pub async fn update(
     State(app_state): State<SharedAppState>,
     Path(id): Path<Uuid>,
-    Json(task): Json<tasks::TaskChangeset>,
-) -> Result<Json<tasks::Task>, Error> {
+    Validated(task): Validated<tasks::TaskChangeset>,
+) -> Result<Json<tasks::Task>, AppError> {
     let task = tasks::update(id, task, &app_state.db_pool).await?;
     Ok(Json(task))
 }
@@ -80,11 +137,22 @@ pub async fn update(
 /// Deletes a task identified by its ID from the database.
 ///
 /// This function deletes one [`{{crate_name}}_db::entities::tasks::Task`] identified by the entity's id from the database (see [`{{crate_name}}_db::entities::tasks::delete`]) and responds with a 204 status code and empty response body. If no task is found for the ID, a 404 response is returned.
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(("id" = Uuid, Path, description = "The id of the task.")),
+    responses(
+        (status = 204, description = "The task was deleted."),
+        (status = 401, description = "The request is not authenticated."),
+        (status = 404, description = "No task exists for the id."),
+    )
+)]
 #[axum::debug_handler]
 pub async fn delete(
     State(app_state): State<SharedAppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, Error> {
+) -> Result<StatusCode, AppError> {
     tasks::delete(id, &app_state.db_pool).await?;
     Ok(StatusCode::NO_CONTENT)
 }
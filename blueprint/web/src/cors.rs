@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Context};
+use axum::http::{HeaderName, HeaderValue, Method};
+use {{crate_name}}_config::CorsConfig;
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+/// The value that allows any origin, method, or header.
+const WILDCARD: &str = "*";
+
+/// Builds the layer that answers preflight requests and adds the CORS headers to responses as configured in the `[cors]` section of the configuration (see [`CorsConfig`]).
+///
+/// Returns an error if any of the origins, methods, or headers is invalid or if credentials are allowed along with a wildcard since browsers reject that combination.
+pub fn layer(config: &CorsConfig) -> Result<CorsLayer, anyhow::Error> {
+    let wildcards = [
+        &config.allowed_origins,
+        &config.allowed_methods,
+        &config.allowed_headers,
+    ]
+    .into_iter()
+    .any(|values| is_wildcard(values));
+    if config.allow_credentials && wildcards {
+        return Err(anyhow!(
+            r#"CORS credentials cannot be allowed along with "*" – list the allowed origins, methods, and headers instead!"#
+        ));
+    }
+
+    let origins = if is_wildcard(&config.allowed_origins) {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| parse_origin(origin))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = if is_wildcard(&config.allowed_methods) {
+        AllowMethods::from(Any)
+    } else {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_uppercase().as_bytes())
+                    .with_context(|| format!("Invalid CORS method {}!", method))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowMethods::list(methods)
+    };
+    let headers = if is_wildcard(&config.allowed_headers) {
+        AllowHeaders::from(Any)
+    } else {
+        let headers = config
+            .allowed_headers
+            .iter()
+            .map(|header| {
+                HeaderName::try_from(header.as_str())
+                    .with_context(|| format!("Invalid CORS header {}!", header))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowHeaders::list(headers)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age)))
+}
+
+/// Returns whether `values` include the wildcard and thus allow any value.
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|value| value == WILDCARD)
+}
+
+/// Parses an origin, i.e. a scheme and host with an optional port but without a path, e.g. `https://app.example.com`.
+fn parse_origin(origin: &str) -> Result<HeaderValue, anyhow::Error> {
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"));
+    match host {
+        Some(host) if !host.is_empty() && !host.contains('/') => HeaderValue::from_str(origin)
+            .with_context(|| format!("Invalid CORS origin {}!", origin)),
+        _ => Err(anyhow!(
+            "Invalid CORS origin {} – expected a scheme and host without a path, e.g. https://app.example.com!",
+            origin
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use googletest::prelude::*;
+    use tower::ServiceExt;
+
+    async fn preflight(config: &CorsConfig, origin: &str) -> axum::response::Response {
+        let app = Router::new()
+            .route("/tasks", get(|| async { "tasks" }))
+            .layer(layer(config).unwrap());
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/tasks")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_layer() {
+        let config = CorsConfig {
+            allowed_origins: vec![String::from("https://app.example.com")],
+            allow_credentials: true,
+            ..CorsConfig::default()
+        };
+
+        let response = preflight(&config, "https://app.example.com").await;
+        assert_that!(response.status(), eq(StatusCode::OK));
+        let headers = response.headers();
+        assert_that!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            eq("https://app.example.com")
+        );
+        assert_that!(
+            headers[header::ACCESS_CONTROL_ALLOW_METHODS],
+            eq("GET,POST,PUT,PATCH,DELETE")
+        );
+        assert_that!(
+            headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS],
+            eq("true")
+        );
+        assert_that!(headers[header::ACCESS_CONTROL_MAX_AGE], eq("3600"));
+
+        let response = preflight(&config, "https://evil.example.com").await;
+        assert_that!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer_default() {
+        let response = preflight(&CorsConfig::default(), "https://app.example.com").await;
+
+        assert_that!(
+            response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer_wildcard() {
+        let config = CorsConfig {
+            allowed_origins: vec![String::from("*")],
+            ..CorsConfig::default()
+        };
+
+        let response = preflight(&config, "http://localhost:5173").await;
+
+        assert_that!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            eq("*")
+        );
+    }
+
+    #[test]
+    fn test_layer_invalid() {
+        let invalid = [
+            CorsConfig {
+                allowed_origins: vec![String::from("*")],
+                allow_credentials: true,
+                ..CorsConfig::default()
+            },
+            CorsConfig {
+                allowed_origins: vec![String::from("https://app.example.com/")],
+                ..CorsConfig::default()
+            },
+            CorsConfig {
+                allowed_origins: vec![String::from("app.example.com")],
+                ..CorsConfig::default()
+            },
+            CorsConfig {
+                allowed_methods: vec![String::from("GET POST")],
+                ..CorsConfig::default()
+            },
+            CorsConfig {
+                allowed_headers: vec![String::from("content type")],
+                ..CorsConfig::default()
+            },
+        ];
+
+        for config in invalid {
+            assert_that!(layer(&config), err(anything()));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use async_graphql::dataloader::Loader;
+use {{crate_name}}_db::{pagination::Listable, DbPool};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// A data loader that loads the records of a [`Listable`] entity by their IDs.
+///
+/// Wrapped in an [`async_graphql::dataloader::DataLoader`], all IDs that resolvers request while a query is executed are batched into a single database query which avoids loading related records one by one (the N+1 problem). The data loaders are registered with the schema in [`super::build_schema`] and are accessed via the context:
+///
+/// ```
+/// let loader = ctx.data::<DataLoader<EntityLoader<Post>>>()?;
+/// let post = loader.load_one(id).await?;
+/// ```
+pub struct EntityLoader<T> {
+    db_pool: DbPool,
+    entity: PhantomData<fn() -> T>,
+}
+
+impl<T> EntityLoader<T> {
+    /// Creates a loader that loads records from the database `db_pool` is connected to.
+    pub fn new(db_pool: DbPool) -> Self {
+        Self {
+            db_pool,
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<T> Loader<Uuid> for EntityLoader<T>
+where
+    T: Listable + Sync + 'static,
+{
+    type Value = Arc<T>;
+    type Error = Arc<{{crate_name}}_db::Error>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let scope = T::SCOPE
+            .map(|scope| format!(" AND ({})", scope))
+            .unwrap_or_default();
+        let query = format!(
+            "SELECT {} FROM {} WHERE id = ANY($1){}",
+            T::COLUMNS,
+            T::TABLE,
+            scope
+        );
+        let records: Vec<T> = sqlx::query_as(&query)
+            .bind(keys)
+            .fetch_all(&self.db_pool)
+            .await
+            .map_err(|e| Arc::new({{crate_name}}_db::Error::DbError(e)))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| (record.id(), Arc::new(record)))
+            .collect())
+    }
+}
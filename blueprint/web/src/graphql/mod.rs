@@ -0,0 +1,66 @@
+use crate::state::SharedAppState;
+use async_graphql::{
+    http::GraphiQLSource, EmptyMutation, EmptySubscription, MergedObject, Object, Schema,
+};
+use axum::{
+    extract::State,
+    response::{Html, Json},
+    routing::get,
+    Router,
+};
+use {{crate_name}}_db::DbPool;
+
+mod loader;
+
+pub use loader::EntityLoader;
+
+/// The path the GraphQL endpoint (and the GraphiQL IDE if enabled) is served at.
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// The application's GraphQL schema.
+pub type AppSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// The root query that merges the queries of all GraphQL types – the queries of types generated with `cargo generate graphql-type` are added automatically.
+#[derive(MergedObject, Default)]
+pub struct Query(BaseQuery);
+
+/// The fields of the root query that are not related to any type.
+#[derive(Default)]
+pub struct BaseQuery;
+
+#[Object]
+impl BaseQuery {
+    /// The version of the application.
+    async fn version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+}
+
+/// Builds the application's GraphQL schema.
+///
+/// The database pool and the data loaders of all GraphQL types (see [`EntityLoader`]) are available to resolvers via the context, e.g. `ctx.data::<DbPool>()?`.
+pub fn build_schema(db_pool: &DbPool) -> AppSchema {
+    let builder = Schema::build(Query::default(), EmptyMutation, EmptySubscription)
+        .data(db_pool.clone());
+
+    builder.finish()
+}
+
+/// Executes a GraphQL request against the application's schema.
+#[axum::debug_handler]
+pub async fn execute(
+    State(app_state): State<SharedAppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(app_state.graphql.execute(request).await)
+}
+
+/// Returns a router that serves the GraphiQL IDE to explore the GraphQL API at [`GRAPHQL_PATH`].
+///
+/// The route is only added to the application if the `server.api_docs` setting is enabled which it is in the development environment by default (see [`{{crate_name}}_config::ServerConfig`]).
+pub fn graphiql() -> Router {
+    Router::new().route(
+        GRAPHQL_PATH,
+        get(|| async { Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish()) }),
+    )
+}
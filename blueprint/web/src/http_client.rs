@@ -0,0 +1,425 @@
+use crate::telemetry;
+use anyhow::{anyhow, Context};
+use axum::http::{self, header};
+use {{crate_name}}_config::{HttpClientConfig, HttpClientMode};
+use rand::{thread_rng, Rng};
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// The client that requests to other services are sent with, configured in the `[http_client]` section of the configuration (see [`HttpClientConfig`]).
+///
+/// Connections are pooled and reused across requests, so the client is created once and shared via the application state. Requests are started with [`HttpClient::request`] for any URL or with [`HttpClient::service`] for the paths of a configured service, and sent with [`HttpClient::send`], e.g.:
+///
+/// ```
+/// let request = app_state
+///     .http_client
+///     .service("payments", Method::POST, "/charges")?
+///     .bearer_auth(&app_state.settings.stripe_key)
+///     .form(&charge);
+/// let charge: Charge = app_state.http_client.send(request).await?.json().await?;
+/// ```
+///
+/// Every request is sent in a span that follows OpenTelemetry's semantic conventions for HTTP clients and continues the current trace (see [`telemetry::make_client_span`]). Idempotent requests are retried with exponential backoff and jitter if they fail to connect, time out, or receive a 429, 502, 503, or 504 response. Depending on the configured [`HttpClientMode`], responses are recorded in or replayed from the `recordings_dir` so that tests don't call real services.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    services: Arc<HashMap<String, Service>>,
+    max_retries: u32,
+    retry_delay: Duration,
+    mode: HttpClientMode,
+    recordings_dir: Arc<PathBuf>,
+}
+
+struct Service {
+    base_url: String,
+    timeout: Option<Duration>,
+}
+
+impl HttpClient {
+    /// Creates the client configured in the `[http_client]` section of the configuration.
+    ///
+    /// Returns an error if the base URL of a service is not a valid URL.
+    pub fn from_config(config: &HttpClientConfig) -> Result<Self, anyhow::Error> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .connect_timeout(Duration::from_secs(config.connect_timeout))
+            .build()
+            .context("Failed to build the HTTP client!")?;
+        let services = config
+            .services
+            .iter()
+            .map(|(name, service)| {
+                Url::parse(&service.base_url).context(format!(
+                    "Invalid base URL {} of service {}!",
+                    service.base_url, name
+                ))?;
+                let service = Service {
+                    base_url: String::from(service.base_url.trim_end_matches('/')),
+                    timeout: service.timeout.map(Duration::from_secs),
+                };
+                Ok((name.clone(), service))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
+        Ok(Self {
+            client,
+            services: Arc::new(services),
+            max_retries: config.max_retries,
+            retry_delay: Duration::from_millis(config.retry_delay),
+            mode: config.mode,
+            recordings_dir: Arc::new(config.recordings_dir.clone()),
+        })
+    }
+
+    /// Starts a `method` request to `url`.
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    /// Starts a `method` request to `path` of the service configured as `name` in the `[http_client.services.<name>]` section of the configuration, e.g. `/charges`, with the service's timeout.
+    ///
+    /// Returns an error if no service is configured as `name`.
+    pub fn service(
+        &self,
+        name: &str,
+        method: Method,
+        path: &str,
+    ) -> Result<RequestBuilder, anyhow::Error> {
+        let service = self
+            .services
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown HTTP service {}!", name))?;
+        let url = format!("{}/{}", service.base_url, path.trim_start_matches('/'));
+        let request = self.client.request(method, url);
+
+        Ok(match service.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        })
+    }
+
+    /// Sends the request, retrying it if it is idempotent and failed temporarily, and returns the response.
+    ///
+    /// Responses with error statuses are returned like all other responses (see [`Response::error_for_status`]). Returns an error if the request is invalid, if no response was received, or, when replaying responses, if no response was recorded for the request.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, anyhow::Error> {
+        let request = request.build().context("Invalid HTTP request!")?;
+        let url = request.url().clone();
+
+        let response = match self.mode {
+            HttpClientMode::Live => self.execute(request).await,
+            HttpClientMode::Record => {
+                let path = recording_path(&self.recordings_dir, &request);
+                let method = request.method().clone();
+                let response = self.execute(request).await?;
+                record(&path, &method, response).await
+            }
+            HttpClientMode::Replay => replay(&recording_path(&self.recordings_dir, &request)).await,
+        };
+        response.context(format!("Failed to send request to {}!", url))
+    }
+
+    async fn execute(&self, mut request: Request) -> Result<Response, anyhow::Error> {
+        let retries = if is_idempotent(request.method()) {
+            self.max_retries
+        } else {
+            0
+        };
+
+        let mut attempt = 0;
+        loop {
+            // requests with streamed bodies cannot be cloned and are not retried
+            let retry = if attempt < retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = send_traced(&self.client, request).await;
+            let Some(retry) = retry else {
+                return Ok(result?);
+            };
+            match &result {
+                Ok(response) if !is_temporary_status(response.status()) => return Ok(result?),
+                Err(e) if !(e.is_connect() || e.is_timeout()) => return Ok(result?),
+                _ => {}
+            }
+
+            tokio::time::sleep(backoff(self.retry_delay, attempt)).await;
+            attempt += 1;
+            request = retry;
+        }
+    }
+}
+
+/// Sends the request in a span that continues the current trace (see [`telemetry::make_client_span`]).
+async fn send_traced(client: &Client, request: Request) -> Result<Response, reqwest::Error> {
+    let mut request = http::Request::try_from(request)?;
+    let span = telemetry::make_client_span(&mut request);
+    let request = Request::try_from(request)?;
+
+    let started_at = Instant::now();
+    let response = client.execute(request).instrument(span.clone()).await?;
+    let status = http::Response::builder()
+        .status(response.status())
+        .body(())
+        .expect("a response with only a status is valid");
+    telemetry::record_response(&status, started_at.elapsed(), &span);
+
+    Ok(response)
+}
+
+/// Returns whether requests with the `method` can be sent again without changing their effect.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+    )
+}
+
+/// Returns whether a response with the `status` indicates that the request might succeed when it is sent again.
+fn is_temporary_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Returns the time to wait before retrying a request that failed `attempt + 1` times: between half of and the full `delay`, doubled for every attempt, so that clients retrying at the same time spread out.
+fn backoff(delay: Duration, attempt: u32) -> Duration {
+    let max = delay.saturating_mul(2u32.saturating_pow(attempt));
+    max / 2 + thread_rng().gen_range(Duration::ZERO..=max / 2)
+}
+
+/// A response recorded in the `recordings_dir` along with the request it answered.
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// Returns the path of the recording of the response to `request`, e.g. `get-api.stripe.com-5d41402abc4b2a76.json`, which is derived from the request's method, URL, and body.
+fn recording_path(dir: &Path, request: &Request) -> PathBuf {
+    let mut hash = Sha256::new();
+    hash.update(request.method().as_str());
+    hash.update(request.url().as_str());
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        hash.update(body);
+    }
+    let hash: String = hash.finalize()[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let host = request.url().host_str().unwrap_or("localhost");
+
+    dir.join(format!(
+        "{}-{}-{}.json",
+        request.method().as_str().to_lowercase(),
+        host,
+        hash
+    ))
+}
+
+/// Records the response to a `method` request in the file at `path` and returns it.
+///
+/// Bodies are recorded as text so that recordings can be read and edited; bodies that are not valid UTF-8 are not recorded faithfully.
+async fn record(
+    path: &Path,
+    method: &Method,
+    response: Response,
+) -> Result<Response, anyhow::Error> {
+    let recording = Recording {
+        method: method.to_string(),
+        url: response.url().to_string(),
+        status: response.status().as_u16(),
+        headers: response
+            .headers()
+            .iter()
+            // the body is replayed as a whole
+            .filter(|(name, _)| {
+                *name != header::CONTENT_LENGTH && *name != header::TRANSFER_ENCODING
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: String::from_utf8_lossy(&response.bytes().await?).into_owned(),
+    };
+
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .context("Failed to create the recordings directory!")?;
+    }
+    let json = serde_json::to_vec_pretty(&recording).context("Failed to serialize recording!")?;
+    tokio::fs::write(path, json)
+        .await
+        .context(format!("Failed to write recording {}!", path.display()))?;
+
+    to_response(recording)
+}
+
+/// Returns the response recorded in the file at `path`.
+async fn replay(path: &Path) -> Result<Response, anyhow::Error> {
+    let json = match tokio::fs::read(path).await {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(anyhow!(
+                "No response was recorded in {} – record it by running the test with APP_HTTP_CLIENT__MODE=record!",
+                path.display()
+            ));
+        }
+        Err(e) => {
+            return Err(anyhow::Error::new(e)
+                .context(format!("Failed to read recording {}!", path.display())))
+        }
+    };
+    let recording: Recording =
+        serde_json::from_slice(&json).context(format!("Invalid recording {}!", path.display()))?;
+
+    to_response(recording)
+}
+
+fn to_response(recording: Recording) -> Result<Response, anyhow::Error> {
+    let mut response = http::Response::builder().status(recording.status);
+    for (name, value) in &recording.headers {
+        response = response.header(name, value);
+    }
+    let response = response
+        .body(recording.body)
+        .context("Invalid recorded response!")?;
+
+    Ok(Response::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use googletest::prelude::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::net::TcpListener;
+
+    /// Serves `/flaky`, which fails with a 503 response until it is called for the third time, and `/greeting` on a random port, returning the server's URL and the number of calls to `/flaky`.
+    async fn serve() -> (String, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let flaky_calls = calls.clone();
+        let flaky = move || {
+            let calls = flaky_calls.clone();
+            async move {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+                    _ => (StatusCode::OK, "ok"),
+                }
+            }
+        };
+        let app = Router::new()
+            .route("/flaky", get(flaky.clone()).post(flaky))
+            .route("/greeting", get(|| async { "Hello!" }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, calls)
+    }
+
+    fn build_client(mode: HttpClientMode, recordings_dir: &Path, base_url: &str) -> HttpClient {
+        HttpClient::from_config(&HttpClientConfig {
+            retry_delay: 1,
+            mode,
+            recordings_dir: recordings_dir.to_path_buf(),
+            services: HashMap::from([(
+                String::from("example"),
+                {{crate_name}}_config::HttpServiceConfig {
+                    base_url: format!("{}/", base_url),
+                    timeout: None,
+                },
+            )]),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_idempotent_requests() {
+        let (url, calls) = serve().await;
+        let client = build_client(HttpClientMode::Live, Path::new("."), &url);
+
+        let request = client.request(Method::GET, &format!("{}/flaky", url));
+        let response = client.send(request).await.unwrap();
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(calls.load(Ordering::SeqCst), eq(3));
+
+        calls.store(0, Ordering::SeqCst);
+        let request = client.request(Method::POST, &format!("{}/flaky", url));
+        let response = client.send(request).await.unwrap();
+        assert_that!(response.status(), eq(StatusCode::SERVICE_UNAVAILABLE));
+        assert_that!(calls.load(Ordering::SeqCst), eq(1));
+    }
+
+    #[tokio::test]
+    async fn test_service() {
+        let (url, _) = serve().await;
+        let client = build_client(HttpClientMode::Live, Path::new("."), &url);
+
+        let request = client.service("example", Method::GET, "/greeting").unwrap();
+        let response = client.send(request).await.unwrap();
+        assert_that!(response.text().await.unwrap(), eq("Hello!"));
+
+        assert_that!(
+            client.service("unknown", Method::GET, "/").is_err(),
+            eq(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        let (url, _) = serve().await;
+        let dir = std::env::temp_dir().join(format!("recordings-{}", std::process::id()));
+
+        let client = build_client(HttpClientMode::Record, &dir, &url);
+        let request = client.service("example", Method::GET, "/greeting").unwrap();
+        let response = client.send(request).await.unwrap();
+        assert_that!(response.text().await.unwrap(), eq("Hello!"));
+
+        // the server is not called when replaying
+        let client = build_client(HttpClientMode::Replay, &dir, &url);
+        let request = client.service("example", Method::GET, "/greeting").unwrap();
+        let response = client.send(request).await.unwrap();
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(response.text().await.unwrap(), eq("Hello!"));
+
+        let request = client.service("example", Method::GET, "/other").unwrap();
+        assert_that!(client.send(request).await.is_err(), eq(true));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_backoff() {
+        let delay = Duration::from_millis(100);
+
+        for _ in 0..10 {
+            assert_that!(
+                backoff(delay, 0),
+                all!(ge(Duration::from_millis(50)), le(delay))
+            );
+            assert_that!(
+                backoff(delay, 2),
+                all!(
+                    ge(Duration::from_millis(200)),
+                    le(Duration::from_millis(400))
+                )
+            );
+        }
+    }
+}
@@ -1,23 +1,91 @@
 //! The {{crate_name}}_web crate contains the application's web interface which mainly are controllers implementing HTTP endpoints. It also includes the application tests that are black-box tests, interfacing with the application like any other HTTP client.
 
 use anyhow::Context;
-use axum::serve;
-use {{crate_name}}_config::{Config, get_env, load_config};
+use axum::{serve, Router};
+use {{crate_name}}_config::{Config, StorageBackend, get_env, load_config};
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tracing::info;
-use tracing_panic::panic_hook;
-use tracing_subscriber::{filter::EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
 
+pub use {{crate_name}}_telemetry::TracingGuard;
+
+/// Channels that clients join via WebSockets to exchange messages with the application (see [`channels::Channel`]).
+pub mod channels;
+{%- unless template_type == "minimal" %}
+/// The commands of the interactive console (`cargo console`) that runs application code, e.g. entity functions, against the database (see [`console::register`]).
+pub mod console;
+{%- endunless %}
 /// The application's controllers that implement request handlers.
 pub mod controllers;
+/// Builds the CORS layer from the `[cors]` section of the configuration (see [`cors::layer`]).
+pub mod cors;
+/// Protects against cross-site request forgery with per-session tokens (see [`csrf::CsrfToken`]).
+pub mod csrf;
+{%- if graphql %}
+/// The application's GraphQL schema, the endpoint that executes GraphQL requests, and the GraphiQL IDE to explore it.
+pub mod graphql;
+{%- endif %}
+/// The liveness and readiness endpoints and the [`health::HealthCheck`] trait for the checks the readiness endpoint runs.
+pub mod health;
+/// The [`http_client::HttpClient`] that requests to other services are sent with.
+pub mod http_client;
+{%- if template_type == "full" %}
+/// Lists the emails captured by the mailer in development (see [`mailbox::router`]).
+pub mod mailbox;
+{%- endif %}
 /// Middlewares that incoming requests are passed through before being passed to [`controllers`].
 pub mod middlewares;
+/// The application's Prometheus metrics and the middleware that records request metrics (see [`metrics::Metrics`]).
+pub mod metrics;
+/// The application's OpenAPI document and the Swagger UI to explore it.
+pub mod openapi;
+{%- unless template_type == "minimal" %}
+/// The [`pagination::Pagination`], [`pagination::Sorted`], and [`pagination::Filtered`] extractors for list endpoints.
+pub mod pagination;
+/// Policies that decide which actions users may perform on resources and the [`policies::Authorize`] extractor to check them.
+pub mod policies;
+{%- endunless %}
+/// Limits the requests of clients with token buckets (see [`rate_limit::RateLimiter`]).
+pub mod rate_limit;
+/// The [`registry::Routes`] that record every route's method, path, handler, and middlewares along with the router so that `cargo routes` can list them.
+pub mod registry;
+/// Caches the responses of routes in the application's cache (see [`response_cache::cache`]).
+pub mod response_cache;
 /// Contains the application's route definitions.
 pub mod routes;
+/// Cookie and database sessions and the [`sessions::Session`] extractor to access them in controllers.
+pub mod sessions;
+/// The [`sse::EventStream`] response and [`sse::LastEventId`] extractor for streaming events to clients via Server-Sent Events.
+pub mod sse;
 /// Contains the application state definition and functionality to initialize it.
 pub mod state;
+/// Serves the static files in `web/public` and resolves their fingerprinted URLs (see [`static_files::asset_path`]).
+pub mod static_files;
+/// The [`storage::Storage`] trait for storing files and its local-disk and S3 backends.
+pub mod storage;
+/// One-off tasks like backfills that are run with `cargo task run <name>` (see [`tasks::Task`]).
+pub mod tasks;
+/// Exports traces to an OpenTelemetry collector and propagates them across HTTP requests.
+pub mod telemetry;
+{%- unless template_type == "minimal" %}
+/// Resolves requests to tenants that each have a database schema of their own (see [`tenancy::Tenant`]).
+pub mod tenancy;
+/// The [`transaction::Tx`] extractor that runs a request's queries in a transaction that is committed or rolled back depending on the response.
+pub mod transaction;
+{%- endunless %}
+/// The application's HTML templates and the [`templates::Template`] response that renders them.
+pub mod templates;
+/// The [`uploads::Upload`] extractor for files uploaded via multipart forms.
+pub mod uploads;
 /// Contains the application's error type and related conversion implementation.
 pub mod error;
+{%- unless template_type == "minimal" %}
+/// The [`validation::Validated`] extractor that validates request payloads.
+pub mod validation;
+{%- endunless %}
 
 /// Runs the application.
 ///
@@ -26,41 +94,170 @@ pub mod error;
 /// 1. Determine the environment the application is running in (see [`{{crate_name}}_config::get_env`])
 /// 2. Load the configuration (see [`{{crate_name}}_config::load_config`])
 /// 3. Initialize the application state (see [`state::init_app_state`])
-/// 4. Initialize the application's router (see [`routes::init_routes`])
+/// 4. Initialize the application's router (see [`routes::init_routes`]), including the static files (see [`static_files::router`]), the files in the local storage if configured (see [`storage::local::router`]),{% if template_type == "full" %} the mailbox if the mailer captures emails (see [`mailbox::router`]),{% endif %} and the API docs if enabled (see [`openapi::swagger_ui`]{% if graphql %} and [`graphql::graphiql`]{% endif %})
 /// 5. Boot the application and start listening for requests on the configured interface and port
+/// 6. Serve the Prometheus metrics on the separate interface and port configured in the `[metrics]` section if enabled (see [`metrics::Metrics`])
+/// 7. Shut down gracefully on Ctrl+C or SIGTERM (see [`serve_with_shutdown`]){% unless template_type == "minimal" %} and close the database pools{% endunless %}
 pub async fn run() -> anyhow::Result<()> {
     let env = get_env().context("Cannot get environment!")?;
     let config: Config = load_config(&env).context("Cannot load config!")?;
 
     let app_state = state::init_app_state(config.clone()).await;
-    let app = routes::init_routes(app_state);
+    {%- unless template_type == "minimal" %}
+    let db_pools = app_state.db_pools.clone();
+    {%- endunless %}
+    {%- if template_type == "full" %}
+    let captured_emails = app_state.mailer.captured_emails();
+    {%- endif %}
+    let mut app = routes::init_routes(app_state).merge(static_files::router(&config.static_files));
+    if config.server.api_docs {
+        app = app.merge(openapi::swagger_ui());
+        info!("Serving API docs at {}", openapi::API_DOCS_PATH);
+        {%- if graphql %}
+        app = app.merge(graphql::graphiql());
+        info!("Serving GraphiQL at {}", graphql::GRAPHQL_PATH);
+        {%- endif %}
+    }
+    if let StorageBackend::Local { dir } = &config.storage.backend {
+        app = app.merge(storage::local::router(dir));
+    }
+    {%- if template_type == "full" %}
+    if let Some(emails) = captured_emails {
+        app = app.merge(mailbox::router(emails));
+        info!("Serving the mailbox at {}", mailbox::MAILBOX_PATH);
+    }
+    {%- endif %}
+
+    if config.metrics.enabled {
+        {%- unless template_type == "minimal" %}
+        let metrics = metrics::Metrics::install(&db_pools)?;
+        {%- else %}
+        let metrics = metrics::Metrics::install()?;
+        {%- endunless %}
+        let metrics_addr = config.metrics.addr();
+        let metrics_listener = TcpListener::bind(&metrics_addr).await?;
+        info!(
+            "Serving metrics at {}{}",
+            &metrics_addr,
+            metrics::METRICS_PATH
+        );
+        tokio::spawn(async move {
+            if let Err(e) = serve(
+                metrics_listener,
+                metrics::router(metrics).into_make_service(),
+            )
+            .await
+            {
+                warn!(err.msg = %e, "Metrics server failed");
+            }
+        });
+    }
 
     let addr = config.server.addr();
     let listener = TcpListener::bind(&addr).await?;
     info!("Listening on {}", &addr);
-    serve(listener, app.into_make_service()).await?;
+    serve_with_shutdown(
+        listener,
+        app,
+        shutdown_signal(),
+        Duration::from_secs(config.server.shutdown_timeout),
+    )
+    .await?;
+    {%- unless template_type == "minimal" %}
+    db_pools.close().await;
+    {%- endunless %}
+    info!("Server shut down");
 
     Ok(())
 }
 
-/// Initializes tracing.
+/// Serves the application on the `listener` until the `shutdown` future completes, then shuts down gracefully.
+///
+/// Once `shutdown` completes, the server stops accepting connections and waits for the requests in flight to finish for up to `timeout` before it returns anyway, e.g. when clients keep WebSockets or Server-Sent Events streams open. The address each connection comes from is available to handlers and middlewares via [`axum::extract::ConnectInfo`]. [`run`] shuts down on [`shutdown_signal`] with the `shutdown_timeout` from the `[server]` section of the configuration while tests can pass any future, e.g. a [`tokio::sync::oneshot::Receiver`]:
 ///
-/// This function
+/// ```
+/// let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+/// let server = tokio::spawn(serve_with_shutdown(listener, app, async {
+///     let _ = shutdown_signal.await;
+/// }, Duration::from_secs(5)));
 ///
-/// * registers a [`tracing_subscriber::fmt::Subscriber`]
-/// * registers a [`tracing_panic::panic_hook`]
+/// shutdown.send(()).unwrap();
+/// server.await.unwrap().unwrap();
+/// ```
+pub async fn serve_with_shutdown(
+    listener: TcpListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let (draining_sender, draining) = oneshot::channel();
+    let server = serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            info!("Shutting down, waiting for running requests to finish…");
+            let _ = draining_sender.send(());
+        })
+        .into_future();
+    let deadline = async move {
+        match draining.await {
+            Ok(()) => tokio::time::sleep(timeout).await,
+            // the server stopped without shutting down, e.g. due to an error
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        result = server => result.context("Failed to run the server!")?,
+        _ = deadline => warn!(
+            "Requests still running after {}s, shutting down anyway",
+            timeout.as_secs()
+        ),
+    }
+
+    Ok(())
+}
+
+/// Completes when the process receives a Ctrl+C or SIGTERM signal.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C!");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM!")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Initializes tracing, reporting spans under the service name configured in the `[tracing]` section of the configuration (see [`{{crate_name}}_telemetry::init_tracing`]).
 ///
-/// The function respects the `RUST_LOG` if set or defaults to filtering spans and events with level [`tracing_subscriber::filter::LevelFilter::INFO`] and higher.
-pub fn init_tracing() {
-    let filter = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new("info"))
-        .unwrap();
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(filter)
-        .init();
-
-    std::panic::set_hook(Box::new(panic_hook));
+/// The returned [`TracingGuard`] exports the remaining spans when it is dropped so it must be kept until the application exits.
+pub fn init_tracing() -> TracingGuard {
+    // errors loading the configuration are reported by `run`
+    let (logging_config, tracing_config) = get_env()
+        .and_then(|env| load_config::<Config>(&env))
+        .map(|config| (config.logging, config.tracing))
+        .unwrap_or_default();
+
+    {{crate_name}}_telemetry::init_tracing(
+        &logging_config,
+        &tracing_config,
+        &tracing_config.service_name,
+    )
 }
 
 /// Helpers that simplify writing application tests.
@@ -0,0 +1,142 @@
+use crate::error::AppError;
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::{Html, Redirect},
+    routing::get,
+    Router,
+};
+use minijinja::{context, Environment};
+use {{crate_name}}_mailer::CapturedEmails;
+
+/// The path the mailbox is served at (see [`router`]).
+pub const MAILBOX_PATH: &str = "/dev/mailbox";
+
+/// The page listing the captured emails – the HTML parts are shown in sandboxed iframes so that their styles don't leak into the page.
+const MAILBOX_HTML: &str = r#"{% raw %}<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Mailbox</title>
+  <style>
+    body { font-family: sans-serif; margin: 2rem; }
+    article { border: 1px solid #ccc; margin-bottom: 1rem; padding: 1rem; }
+    iframe { border: 1px solid #eee; height: 20rem; width: 100%; }
+    pre { background: #f6f6f6; padding: 1rem; white-space: pre-wrap; }
+  </style>
+</head>
+<body>
+  <h1>Mailbox</h1>
+  <form method="post"><button>Clear</button></form>
+  {% for email in emails %}
+  <article>
+    <h2>{{ email.subject }}</h2>
+    <p>To: {{ email.to }}</p>
+    {% if email.html %}<iframe sandbox srcdoc="{{ email.html }}"></iframe>{% endif %}
+    <pre>{{ email.text }}</pre>
+  </article>
+  {% else %}
+  <p>No emails have been sent yet.</p>
+  {% endfor %}
+</body>
+</html>
+{% endraw %}"#;
+
+/// Serves the mailbox at [`MAILBOX_PATH`] that lists the emails captured by the application's mailer, newest first, for inspecting them manually. Posting to the path clears the mailbox.
+///
+/// The route is only added to the application if the mailer captures emails, i.e. if the `memory` transport is configured in the `[mailer.transport]` section which it is in the development environment by default (see [`{{crate_name}}_config::MailTransportConfig`]).
+pub fn router(emails: CapturedEmails) -> Router {
+    Router::new()
+        .route(MAILBOX_PATH, get(index).post(clear))
+        .with_state(emails)
+}
+
+async fn index(State(emails): State<CapturedEmails>) -> Result<Html<String>, AppError> {
+    let mut env = Environment::new();
+    env.add_template("mailbox.html", MAILBOX_HTML)
+        .context("Invalid mailbox template!")?;
+    let mut emails = emails.all();
+    emails.reverse();
+    let html = env
+        .get_template("mailbox.html")
+        .and_then(|template| template.render(context! { emails }))
+        .context("Failed to render the mailbox!")?;
+
+    Ok(Html(html))
+}
+
+async fn clear(State(emails): State<CapturedEmails>) -> Redirect {
+    emails.clear();
+    Redirect::to(MAILBOX_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use googletest::prelude::*;
+    use {{crate_name}}_mailer::{Email, Mailer, MemoryMailer};
+    use serde::Serialize;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[derive(Serialize)]
+    struct TestEmail;
+
+    impl Email for TestEmail {
+        const HTML_TEMPLATE: &'static str = "<p>Hi <Jane>!</p>";
+        const TEXT_TEMPLATE: &'static str = "Hi Jane!";
+
+        fn to(&self) -> String {
+            String::from("jane@example.com")
+        }
+
+        fn subject(&self) -> String {
+            String::from("Welcome!")
+        }
+    }
+
+    async fn request(app: &Router, method: Method) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(MAILBOX_PATH)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_router() {
+        let mailer: Arc<dyn Mailer> = Arc::new(MemoryMailer::new(
+            "My App <noreply@example.com>".parse().unwrap(),
+        ));
+        let app = router(mailer.captured_emails().unwrap());
+
+        let (status, body) = request(&app, Method::GET).await;
+        assert_that!(status, eq(StatusCode::OK));
+        assert_that!(body, contains_substring("No emails have been sent yet."));
+
+        mailer.send(&TestEmail).await.unwrap();
+
+        let (_, body) = request(&app, Method::GET).await;
+        assert_that!(body, contains_substring("<h2>Welcome!</h2>"));
+        assert_that!(body, contains_substring("To: jane@example.com"));
+        assert_that!(
+            body,
+            contains_substring(r#"srcdoc="&lt;p&gt;Hi &lt;Jane&gt;!"#)
+        );
+        assert_that!(body, contains_substring("<pre>Hi Jane!</pre>"));
+
+        let (status, _) = request(&app, Method::POST).await;
+        assert_that!(status, eq(StatusCode::SEE_OTHER));
+        assert_that!(mailer.captured_emails().unwrap().all(), len(eq(0)));
+    }
+}
@@ -3,7 +3,7 @@ use {{crate_name}}_web::{init_tracing, run};
 
 #[tokio::main]
 async fn main() {
-    init_tracing();
+    let _tracing = init_tracing();
 
     if let Err(e) = run().await {
         tracing::error!(
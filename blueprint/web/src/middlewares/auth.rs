@@ -1,8 +1,9 @@
-use crate::state::SharedAppState;
+use super::logging::record_user;
+use crate::{error::AppError, state::SharedAppState};
 use axum::body::Body;
 use axum::{
     extract::State,
-    http::{self, Request, StatusCode},
+    http::{self, Request},
     middleware::Next,
     response::Response,
 };
@@ -17,7 +18,7 @@ pub async fn auth(
     State(app_state): State<SharedAppState>,
     mut req: Request<Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, AppError> {
     let auth_header = req
         .headers()
         .get(http::header::AUTHORIZATION)
@@ -27,21 +28,24 @@ pub async fn auth(
         auth_header
     } else {
         log_rejection_reason("Missing authorization header");
-        return Err(StatusCode::UNAUTHORIZED);
+        return Err(AppError::Unauthorized);
     };
 
     match users::load_with_token(auth_header, &app_state.db_pool).await {
         Ok(Some(current_user)) => {
+            let user_id = current_user.id;
             req.extensions_mut().insert(current_user);
-            Ok(next.run(req).await)
+            let mut response = next.run(req).await;
+            record_user(&mut response, user_id);
+            Ok(response)
         }
         Ok(None) => {
             log_rejection_reason("Unknown user token");
-            return Err(StatusCode::UNAUTHORIZED);
+            return Err(AppError::Unauthorized);
         }
-        Err(_) => {
+        Err(e) => {
             log_rejection_reason("Database error");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
@@ -0,0 +1,142 @@
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use std::fmt::Display;
+use std::time::Instant;
+use tracing::info;
+
+/// The header that holds the ID of a request, in both the request and the response.
+///
+/// Requests that arrive without the header (e.g. from clients rather than a load balancer or another service that already assigned an ID) are assigned a random UUID. The ID is recorded in the request's span (see [`crate::telemetry::make_span`]) so that all log lines of the request include it.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The ID of the user who made a request (see [`record_user`]).
+#[derive(Clone, Debug)]
+struct UserId(String);
+
+/// Returns the ID of the request with the `headers` (see [`REQUEST_ID_HEADER`]).
+pub fn request_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Records the user who made a request so that the request's log line includes their ID (see [`log`]).
+///
+/// Middlewares that authenticate requests call this with the response to an authenticated request{% if template_type == "full" %}, e.g. [`super::auth::auth`]{% endif %}.
+pub fn record_user(response: &mut Response, user_id: impl Display) {
+    response
+        .extensions_mut()
+        .insert(UserId(user_id.to_string()));
+}
+
+/// Logs a line for every completed request with the request's method, path, the response's status, the time it took to handle the request in milliseconds, and the ID of the user who made it if they were authenticated (see [`record_user`]).
+///
+/// Like all other log lines of the request, the line is recorded in the request's span and thus includes the request's ID.
+pub async fn log(request: Request, next: Next) -> Response {
+    let started_at = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+
+    let response = next.run(request).await;
+
+    let user_id = response
+        .extensions()
+        .get::<UserId>()
+        .map(|user_id| user_id.0.as_str());
+    info!(
+        http.request.method = %method,
+        url.path = path,
+        http.response.status_code = response.status().as_u16(),
+        latency_ms = u64::try_from(started_at.elapsed().as_millis()).unwrap_or(u64::MAX),
+        user.id = user_id,
+        "Request completed"
+    );
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use googletest::prelude::*;
+    use serde_json::Value;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn authenticate(request: Request, next: Next) -> Response {
+        let mut response = next.run(request).await;
+        record_user(&mut response, "42");
+        response
+    }
+
+    async fn logged_request(app: Router, uri: &str) -> Value {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(buffer.clone())
+            .finish();
+        let _subscriber = tracing::subscriber::set_default(subscriber);
+
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.layer(middleware::from_fn(log))
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        let output = buffer.0.lock().unwrap().clone();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_log() {
+        let app = Router::new().route("/tasks/:id", get(|| async { "task" }));
+
+        let line = logged_request(app, "/tasks/1").await;
+
+        assert_that!(line["fields"]["message"], eq(&"Request completed"));
+        assert_that!(line["fields"]["http.request.method"], eq(&"GET"));
+        assert_that!(line["fields"]["url.path"], eq(&"/tasks/1"));
+        assert_that!(line["fields"]["http.response.status_code"], eq(&200));
+        assert_that!(line["fields"]["latency_ms"].is_u64(), eq(true));
+        assert_that!(line["fields"].get("user.id"), none());
+    }
+
+    #[tokio::test]
+    async fn test_log_authenticated() {
+        let app = Router::new()
+            .route("/tasks/:id", get(|| async { "task" }))
+            .layer(middleware::from_fn(authenticate));
+
+        let line = logged_request(app, "/tasks/1").await;
+
+        assert_that!(line["fields"]["user.id"], eq(&"42"));
+    }
+}
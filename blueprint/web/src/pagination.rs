@@ -0,0 +1,260 @@
+use crate::error::AppError;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use {{crate_name}}_db::pagination::{Direction, Filter, Listable, PageRequest, Sort};
+use serde::Deserialize;
+use std::marker::PhantomData;
+use uuid::Uuid;
+
+/// The number of records on a page if the request does not specify it.
+pub const DEFAULT_PER_PAGE: u32 = 20;
+
+/// The maximum number of records on a page a request may ask for.
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// An extractor that reads which page of records to load from the query string.
+///
+/// Pages are requested either by number (`?page=2&per_page=20`, pages start at 1) or by cursor (`?after=<id>&per_page=20`) where the cursor is the `next_cursor` of the previous page (see [`{{crate_name}}_db::pagination::Page`]). Without parameters, the first page with [`DEFAULT_PER_PAGE`] records is loaded. Invalid parameters, e.g. `per_page` exceeding [`MAX_PER_PAGE`], are rejected with a 400 response.
+///
+/// Example:
+/// ```
+/// pub async fn read_all(
+///     State(app_state): State<SharedAppState>,
+///     Pagination(page): Pagination,
+///     Sorted(sort, _): Sorted<posts::Post>,
+///     Filtered(filters, _): Filtered<posts::Post>,
+/// ) -> Result<Json<Page<posts::Post>>, AppError> {
+///     let posts = load_page(page, sort, &filters, &app_state.db_pool).await?;
+///     Ok(Json(posts))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination(pub PageRequest);
+
+#[derive(Deserialize)]
+struct PaginationParams {
+    page: Option<u32>,
+    per_page: Option<u32>,
+    after: Option<Uuid>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Pagination
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<PaginationParams>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        if !(1..=MAX_PER_PAGE).contains(&per_page) {
+            return Err(AppError::BadRequest(format!(
+                "per_page must be between 1 and {}.",
+                MAX_PER_PAGE
+            )));
+        }
+
+        match (params.page, params.after) {
+            (Some(_), Some(_)) => Err(AppError::BadRequest(String::from(
+                "page and after cannot be combined.",
+            ))),
+            (Some(0), None) => Err(AppError::BadRequest(String::from(
+                "page must be at least 1.",
+            ))),
+            (page, None) => Ok(Pagination(PageRequest::Offset {
+                page: page.unwrap_or(1),
+                per_page,
+            })),
+            (None, after) => Ok(Pagination(PageRequest::Cursor { after, per_page })),
+        }
+    }
+}
+
+/// An extractor that reads the column to sort records by from the `sort` query parameter.
+///
+/// The column must be one of the entity's [`Listable::SORTABLE_COLUMNS`], prefixed with `-` to sort in descending order, e.g. `?sort=-title`; other columns are rejected with a 400 response. Without the parameter, records are sorted by their IDs (see [`Sort::default`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Sorted<T>(pub Sort, pub PhantomData<fn() -> T>);
+
+#[derive(Deserialize)]
+struct SortParams {
+    sort: Option<String>,
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Sorted<T>
+where
+    T: Listable,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<SortParams>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let Some(sort) = params.sort else {
+            return Ok(Sorted(Sort::default(), PhantomData));
+        };
+        let (name, direction) = match sort.strip_prefix('-') {
+            Some(name) => (name, Direction::Desc),
+            None => (sort.as_str(), Direction::Asc),
+        };
+        let column = T::SORTABLE_COLUMNS
+            .iter()
+            .find(|column| **column == name)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Cannot sort by {}, sortable columns are: {}.",
+                    name,
+                    T::SORTABLE_COLUMNS.join(", ")
+                ))
+            })?;
+
+        Ok(Sorted(Sort { column, direction }, PhantomData))
+    }
+}
+
+/// An extractor that reads filters from the query string.
+///
+/// Every query parameter named like one of the entity's [`Listable::FILTERABLE_COLUMNS`] becomes a [`Filter`] that only matches records with that value, e.g. `?title=Hello`. All other parameters are ignored.
+#[derive(Debug, Clone)]
+pub struct Filtered<T>(pub Vec<Filter>, pub PhantomData<fn() -> T>);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Filtered<T>
+where
+    T: Listable,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let filters = params
+            .into_iter()
+            .filter_map(|(name, value)| {
+                T::FILTERABLE_COLUMNS
+                    .iter()
+                    .find(|column| **column == name)
+                    .map(|column| Filter { column, value })
+            })
+            .collect();
+
+        Ok(Filtered(filters, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use googletest::prelude::*;
+    use sqlx::FromRow;
+
+    #[derive(FromRow, Debug)]
+    struct Post {
+        id: Uuid,
+    }
+
+    impl Listable for Post {
+        const TABLE: &'static str = "posts";
+        const COLUMNS: &'static str = "id, title";
+        const SORTABLE_COLUMNS: &'static [&'static str] = &["id", "title"];
+        const FILTERABLE_COLUMNS: &'static [&'static str] = &["title"];
+
+        fn id(&self) -> Uuid {
+            self.id
+        }
+    }
+
+    async fn extract<E: FromRequestParts<()>>(uri: &str) -> std::result::Result<E, E::Rejection> {
+        let (mut parts, _) = Request::get(uri).body(()).unwrap().into_parts();
+        E::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let Pagination(page) = extract("/posts").await.unwrap();
+        assert_that!(
+            page,
+            eq(PageRequest::Offset {
+                page: 1,
+                per_page: DEFAULT_PER_PAGE
+            })
+        );
+
+        let Pagination(page) = extract("/posts?page=3&per_page=5").await.unwrap();
+        assert_that!(
+            page,
+            eq(PageRequest::Offset {
+                page: 3,
+                per_page: 5
+            })
+        );
+
+        let after = Uuid::new_v4();
+        let Pagination(page) = extract(&format!("/posts?after={}", after)).await.unwrap();
+        assert_that!(
+            page,
+            eq(PageRequest::Cursor {
+                after: Some(after),
+                per_page: DEFAULT_PER_PAGE
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_pagination() {
+        for uri in [
+            "/posts?page=0",
+            "/posts?per_page=101",
+            "/posts?page=x",
+            "/posts?page=1&after=00000000-0000-0000-0000-000000000000",
+        ] {
+            let result = extract::<Pagination>(uri).await;
+            assert_that!(result, err(matches_pattern!(AppError::BadRequest(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sorted() {
+        let Sorted(sort, _) = extract::<Sorted<Post>>("/posts").await.unwrap();
+        assert_that!(sort, eq(Sort::default()));
+
+        let Sorted(sort, _) = extract::<Sorted<Post>>("/posts?sort=-title").await.unwrap();
+        assert_that!(
+            sort,
+            eq(Sort {
+                column: "title",
+                direction: Direction::Desc
+            })
+        );
+
+        let result = extract::<Sorted<Post>>("/posts?sort=password").await;
+        assert_that!(result, err(matches_pattern!(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_filtered() {
+        let Filtered(filters, _) = extract::<Filtered<Post>>("/posts?title=Hello&page=2&id=1")
+            .await
+            .unwrap();
+
+        assert_that!(
+            filters,
+            eq(&vec![Filter {
+                column: "title",
+                value: String::from("Hello")
+            }])
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::AppError;
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+
+/// Decides which actions a user may perform on a resource, e.g. whether a user may update a post.
+///
+/// All actions are denied unless a policy allows them by overriding the respective function. Policies are implemented by unit structs, one per resource, that are generated with `cargo generate policy <entity>`:
+///
+/// ```
+/// pub struct PostPolicy;
+///
+/// impl Policy<Post> for PostPolicy {
+///     type User = User;
+///
+///     fn can_read(_user: &User, _post: &Post) -> bool {
+///         true
+///     }
+///
+///     fn can_update(user: &User, post: &Post) -> bool {
+///         post.user_id == user.id
+///     }
+/// }
+/// ```
+///
+/// Request handlers check policies via the [`Authorize`] extractor.
+pub trait Policy<Resource> {
+    /// The type of the users the policy decides for, typically the user entity.
+    type User;
+
+    /// Whether the user may create resources.
+    fn can_create(_user: &Self::User) -> bool {
+        false
+    }
+
+    /// Whether the user may read the resource.
+    fn can_read(_user: &Self::User, _resource: &Resource) -> bool {
+        false
+    }
+
+    /// Whether the user may update the resource.
+    fn can_update(_user: &Self::User, _resource: &Resource) -> bool {
+        false
+    }
+
+    /// Whether the user may delete the resource.
+    fn can_delete(_user: &Self::User, _resource: &Resource) -> bool {
+        false
+    }
+}
+
+/// Authorizes actions of the current user with [`Policy`] functions.
+///
+/// The extractor takes the user from the request's extensions where authentication middlewares put it. If there is no user, i.e. the route is not protected by an authentication middleware or the request is not authenticated, [`AppError::Unauthorized`] is returned which results in a 401 response. If a policy denies an action, [`AppError::Forbidden`] is returned which results in a 403 response.
+///
+/// Example:
+/// ```
+/// pub async fn update(
+///     State(app_state): State<SharedAppState>,
+///     authorize: Authorize<User>,
+///     Path(id): Path<Uuid>,
+///     Json(post): Json<posts::PostChangeset>,
+/// ) -> Result<Json<posts::Post>, AppError> {
+///     let existing = posts::load(id, &app_state.db_pool).await?;
+///     authorize.check(PostPolicy::can_update, &existing)?;
+///
+///     let post = posts::update(id, post, &app_state.db_pool).await?;
+///     Ok(Json(post))
+/// }
+/// ```
+pub struct Authorize<U>(pub U);
+
+impl<U> Authorize<U> {
+    /// Returns the user actions are authorized for.
+    pub fn user(&self) -> &U {
+        &self.0
+    }
+
+    /// Checks a policy function for an action on a resource, e.g. `authorize.check(PostPolicy::can_update, &post)`.
+    pub fn check<R>(
+        &self,
+        rule: impl FnOnce(&U, &R) -> bool,
+        resource: &R,
+    ) -> Result<(), AppError> {
+        if rule(&self.0, resource) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden)
+        }
+    }
+
+    /// Checks a policy function for an action that does not involve an existing resource, e.g. `authorize.check_create(PostPolicy::can_create)`.
+    pub fn check_create(&self, rule: impl FnOnce(&U) -> bool) -> Result<(), AppError> {
+        if rule(&self.0) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden)
+        }
+    }
+}
+
+#[async_trait]
+impl<S, U> FromRequestParts<S> for Authorize<U>
+where
+    S: Send + Sync,
+    U: Clone + Send + Sync + 'static,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<U>()
+            .cloned()
+            .map(Authorize)
+            .ok_or(AppError::Unauthorized)
+    }
+}
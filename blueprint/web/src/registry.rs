@@ -0,0 +1,394 @@
+use axum::extract::Request;
+use axum::handler::Handler;
+use axum::response::IntoResponse;
+use axum::routing::{MethodRouter, Route};
+use axum::Router;
+use serde::Serialize;
+use std::any::type_name;
+use std::convert::Infallible;
+use tower::{Layer, Service};
+
+/// A route of the application as listed by `cargo routes`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RouteInfo {
+    /// The HTTP method, e.g. `GET`
+    pub method: &'static str,
+    /// The path, e.g. `/tasks/:id`
+    pub path: String,
+    /// The path of the handler function relative to the crate, e.g. `controllers::tasks::read_one`
+    pub handler: String,
+    /// The middlewares that only apply to this route in the order requests pass through them (see [`Routes::middlewares`] for the ones that apply to all routes)
+    pub middlewares: Vec<String>,
+}
+
+/// The application's router along with a record of its routes
+///
+/// [`Routes`] wraps [`axum::Router`] with the same API for adding routes and layers, except that routes are added with [`Endpoint`]s (built with [`get`], [`post`], etc.) instead of [`axum::routing::MethodRouter`]s so that the handler functions are recorded as well. The recorded routes are listed by `cargo routes`, e.g.:
+///
+/// ```
+/// let routes = Routes::new()
+///     .route("/tasks", get(tasks::read_all).post(tasks::create))
+///     .nest("/admin", admin_routes)
+///     .with_state(shared_app_state);
+/// let router: axum::Router = routes.into_router();
+/// ```
+pub struct Routes<S = ()> {
+    router: Router<S>,
+    routes: Vec<RouteInfo>,
+    middlewares: Vec<String>,
+}
+
+impl<S> Default for Routes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Routes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Creates an empty router.
+    pub fn new() -> Self {
+        Routes {
+            router: Router::new(),
+            routes: vec![],
+            middlewares: vec![],
+        }
+    }
+
+    /// Routes requests to the path to the endpoint's handlers (see [`axum::Router::route`]).
+    pub fn route(mut self, path: &str, endpoint: Endpoint<S>) -> Self {
+        for (method, handler) in endpoint.handlers {
+            self.routes.push(RouteInfo {
+                method,
+                path: String::from(path),
+                handler,
+                middlewares: endpoint.middlewares.clone(),
+            });
+        }
+        self.router = self.router.route(path, endpoint.method_router);
+        self
+    }
+
+    /// Nests the routes of another router at the path (see [`axum::Router::nest`]).
+    pub fn nest(mut self, path: &str, routes: Routes<S>) -> Self {
+        let prefix = path.trim_end_matches('/');
+        for route in routes.flatten_middlewares() {
+            self.routes.push(RouteInfo {
+                path: format!("{}{}", prefix, route.path),
+                ..route
+            });
+        }
+        self.router = self.router.nest(path, routes.router);
+        self
+    }
+
+    /// Merges the routes of another router (see [`axum::Router::merge`]).
+    pub fn merge(mut self, routes: Routes<S>) -> Self {
+        self.routes.extend(routes.flatten_middlewares());
+        self.router = self.router.merge(routes.router);
+        self
+    }
+
+    /// Applies the layer to the routes that were added before (see [`axum::Router::route_layer`]).
+    pub fn route_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        let names = layer_names(type_name::<L>());
+        for route in &mut self.routes {
+            route.middlewares.splice(0..0, names.iter().cloned());
+        }
+        self.router = self.router.route_layer(layer);
+        self
+    }
+
+    /// Applies the layer to all routes (see [`axum::Router::layer`]).
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.middlewares.splice(0..0, layer_names(type_name::<L>()));
+        self.router = self.router.layer(layer);
+        self
+    }
+
+    /// Provides the state to the handlers and middlewares (see [`axum::Router::with_state`]).
+    pub fn with_state<S2>(self, state: S) -> Routes<S2> {
+        Routes {
+            router: self.router.with_state(state),
+            routes: self.routes,
+            middlewares: self.middlewares,
+        }
+    }
+
+    /// Returns the recorded routes in the order they were added.
+    pub fn routes(&self) -> &[RouteInfo] {
+        &self.routes
+    }
+
+    /// Returns the middlewares that apply to all routes in the order requests pass through them.
+    pub fn middlewares(&self) -> &[String] {
+        &self.middlewares
+    }
+
+    /// Returns the [`axum::Router`] that serves the routes.
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+
+    /// Returns the routes with the middlewares that apply to all of them added to each route's middlewares – for nesting and merging into other routers.
+    fn flatten_middlewares(&self) -> Vec<RouteInfo> {
+        self.routes
+            .iter()
+            .map(|route| RouteInfo {
+                middlewares: self
+                    .middlewares
+                    .iter()
+                    .chain(route.middlewares.iter())
+                    .cloned()
+                    .collect(),
+                ..route.clone()
+            })
+            .collect()
+    }
+}
+
+/// The handlers for a path by HTTP method, built with [`get`], [`post`], [`put`], [`patch`], and [`delete`] and chained like [`axum::routing::MethodRouter`], e.g. `get(tasks::read_all).post(tasks::create)`
+pub struct Endpoint<S> {
+    method_router: MethodRouter<S>,
+    handlers: Vec<(&'static str, String)>,
+    middlewares: Vec<String>,
+}
+
+impl<S> Endpoint<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Applies the layer to the endpoint's handlers (see [`axum::routing::MethodRouter::route_layer`]).
+    pub fn route_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.middlewares.splice(0..0, layer_names(type_name::<L>()));
+        self.method_router = self.method_router.route_layer(layer);
+        self
+    }
+}
+
+macro_rules! methods {
+    ($($name:ident => $method:literal),*) => {
+        $(
+            #[doc = concat!("Routes `", $method, "` requests to the handler (see [`axum::routing::", stringify!($name), "`]).")]
+            pub fn $name<H, T, S>(handler: H) -> Endpoint<S>
+            where
+                H: Handler<T, S>,
+                T: 'static,
+                S: Clone + Send + Sync + 'static,
+            {
+                Endpoint {
+                    method_router: axum::routing::$name(handler),
+                    handlers: vec![($method, handler_name::<H>())],
+                    middlewares: vec![],
+                }
+            }
+        )*
+
+        impl<S> Endpoint<S>
+        where
+            S: Clone + Send + Sync + 'static,
+        {
+            $(
+                #[doc = concat!("Routes `", $method, "` requests to the handler as well.")]
+                pub fn $name<H, T>(mut self, handler: H) -> Self
+                where
+                    H: Handler<T, S>,
+                    T: 'static,
+                {
+                    self.method_router = self.method_router.$name(handler);
+                    self.handlers.push(($method, handler_name::<H>()));
+                    self
+                }
+            )*
+        }
+    };
+}
+
+methods!(get => "GET", post => "POST", put => "PUT", patch => "PATCH", delete => "DELETE");
+
+/// Returns the path of the handler function relative to the crate, e.g. `controllers::tasks::read_one`.
+fn handler_name<H>() -> String {
+    strip_crate(type_name::<H>())
+}
+
+/// Returns the names of the middlewares a layer consists of in the order requests pass through them, based on the layer's type name:
+/// * the layers of a [`tower::ServiceBuilder`] are listed individually
+/// * middlewares added via [`axum::middleware::from_fn`] or [`axum::middleware::from_fn_with_state`] are named after their function, e.g. `middlewares::auth::auth`
+/// * other layers are named after their type, e.g. `TraceLayer`
+fn layer_names(type_name: &str) -> Vec<String> {
+    let (path, args) = parse_type_name(type_name);
+    match path {
+        "tower::builder::ServiceBuilder" => {
+            args.first().map(|l| layer_names(l)).unwrap_or_default()
+        }
+        // ServiceBuilder::layer wraps the previous layers in the new one: Stack<New, Previous>
+        "tower_layer::stack::Stack" if args.len() == 2 => {
+            let mut names = layer_names(args[1]);
+            names.extend(layer_names(args[0]));
+            names
+        }
+        "tower_layer::identity::Identity" => vec![],
+        "axum::middleware::from_fn::FromFnLayer" if !args.is_empty() => {
+            vec![strip_crate(args[0])]
+        }
+        _ => vec![String::from(path.rsplit("::").next().unwrap_or(path))],
+    }
+}
+
+/// Splits a type name like `a::B<c::D, E<F>>` into its path and its top-level generic arguments.
+fn parse_type_name(type_name: &str) -> (&str, Vec<&str>) {
+    let type_name = type_name.trim();
+    let Some(start) = type_name.find('<') else {
+        return (type_name, vec![]);
+    };
+    let Some(inner) = type_name[start + 1..].strip_suffix('>') else {
+        return (type_name, vec![]);
+    };
+
+    let mut args = vec![];
+    let mut depth = 0;
+    let mut arg_start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            // `->` in function pointer types is not a closing bracket
+            '>' if i > 0 && inner[..i].ends_with('-') => {}
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(inner[arg_start..i].trim());
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[arg_start..].trim());
+
+    (&type_name[..start], args)
+}
+
+/// Removes this crate's name from the start of a path.
+fn strip_crate(path: &str) -> String {
+    let prefix = concat!(env!("CARGO_CRATE_NAME"), "::");
+    String::from(path.strip_prefix(prefix).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, layer_names, Routes};
+    use axum::middleware::{self, Next};
+    use axum::{extract::Request, response::Response};
+    use tower::ServiceBuilder;
+    use tower_http::trace::TraceLayer;
+
+    async fn list() -> &'static str {
+        "list"
+    }
+
+    async fn create() -> &'static str {
+        "create"
+    }
+
+    async fn guard(request: Request, next: Next) -> Response {
+        next.run(request).await
+    }
+
+    #[test]
+    fn test_routes_are_recorded() {
+        let admin: Routes = Routes::new()
+            .route("/tasks", get(list))
+            .layer(middleware::from_fn(guard));
+        let routes: Routes = Routes::new()
+            .route(
+                "/tasks",
+                get(list)
+                    .post(create)
+                    .route_layer(middleware::from_fn(guard)),
+            )
+            .nest("/admin", admin)
+            .layer(
+                ServiceBuilder::new()
+                    .layer(TraceLayer::new_for_http())
+                    .layer(middleware::from_fn(guard)),
+            );
+
+        let listed: Vec<(&str, &str, &str, String)> = routes
+            .routes()
+            .iter()
+            .map(|route| {
+                (
+                    route.method,
+                    route.path.as_str(),
+                    route.handler.as_str(),
+                    route.middlewares.join(", "),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (
+                    "GET",
+                    "/tasks",
+                    "registry::tests::list",
+                    String::from("registry::tests::guard")
+                ),
+                (
+                    "POST",
+                    "/tasks",
+                    "registry::tests::create",
+                    String::from("registry::tests::guard")
+                ),
+                (
+                    "GET",
+                    "/admin/tasks",
+                    "registry::tests::list",
+                    String::from("registry::tests::guard")
+                ),
+            ]
+        );
+        assert_eq!(
+            routes.middlewares(),
+            &[
+                String::from("TraceLayer"),
+                String::from("registry::tests::guard")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layer_names() {
+        assert_eq!(
+            layer_names("tower_http::cors::CorsLayer"),
+            vec![String::from("CorsLayer")]
+        );
+        assert_eq!(
+            layer_names("axum::middleware::from_fn::FromFnLayer<fn(u8) -> u8, (), (u8,)>"),
+            vec![String::from("fn(u8) -> u8")]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use {{crate_name}}_cache::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// The header that tells clients whether a response was served from the cache (`hit`) or not (`miss`).
+pub const CACHE_STATUS_HEADER: HeaderName = HeaderName::from_static("x-cache");
+
+/// The maximum size of response bodies that are cached
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+/// The cache that responses are cached in and how long they are cached for, the state of the [`cache`] middleware.
+#[derive(Clone)]
+pub struct CachedFor {
+    cache: Arc<dyn Cache>,
+    ttl: Duration,
+}
+
+impl CachedFor {
+    /// Caches responses in `cache` for `ttl`.
+    pub fn new(cache: Arc<dyn Cache>, ttl: Duration) -> Self {
+        Self { cache, ttl }
+    }
+}
+
+/// Serves responses to `GET` requests from the cache, keyed by the request's path and query string, and caches them if they are not cached yet.
+///
+/// The middleware is attached to the routes whose responses can be cached in [`crate::routes`], e.g.:
+///
+/// ```
+/// .route(
+///     "/tasks",
+///     get(tasks::read_all).route_layer(axum::middleware::from_fn_with_state(
+///         CachedFor::new(shared_app_state.cache.clone(), Duration::from_secs(60)),
+///         response_cache::cache,
+///     )),
+/// )
+/// ```
+///
+/// Only successful responses whose bodies are not streamed and at most 1 MB large are cached, along with their `Content-Type` header. Requests with an `Authorization` header and responses that set cookies are never cached; since all clients are served the same cached response, routes whose responses depend on the session must not be cached. Cached responses carry the [`CACHE_STATUS_HEADER`]. Requests pass to the handler if the cache cannot be reached.
+pub async fn cache(State(cached): State<CachedFor>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET || request.headers().contains_key(header::AUTHORIZATION) {
+        return next.run(request).await;
+    }
+
+    let key = match request.uri().query() {
+        Some(query) => format!("response:{}?{}", request.uri().path(), query),
+        None => format!("response:{}", request.uri().path()),
+    };
+    match cached.cache.get_bytes(&key).await {
+        Ok(Some(cached_response)) => {
+            if let Some(response) = decode(cached_response) {
+                return response;
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!(err.msg = %e, "Failed to read the response cache"),
+    }
+
+    let response = next.run(request).await;
+    let cacheable = response.status() == StatusCode::OK
+        && !response.headers().contains_key(header::SET_COOKIE)
+        && response
+            .body()
+            .size_hint()
+            .exact()
+            .is_some_and(|size| size <= MAX_BODY_SIZE);
+    if !cacheable {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, MAX_BODY_SIZE as usize).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .map(HeaderValue::as_bytes)
+        .unwrap_or_default();
+    let mut cached_response = Vec::with_capacity(content_type.len() + 1 + body.len());
+    cached_response.extend_from_slice(content_type);
+    cached_response.push(b'\n');
+    cached_response.extend_from_slice(&body);
+    if let Err(e) = cached
+        .cache
+        .set_bytes(&key, cached_response, cached.ttl)
+        .await
+    {
+        warn!(err.msg = %e, "Failed to write the response cache");
+    }
+
+    parts
+        .headers
+        .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("miss"));
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Builds the response from a cached response, i.e. its `Content-Type` header and body separated by a newline, `None` if it is malformed.
+fn decode(cached_response: Vec<u8>) -> Option<Response> {
+    let mut cached_response = Bytes::from(cached_response);
+    let separator = cached_response.iter().position(|byte| *byte == b'\n')?;
+    let content_type = cached_response.split_to(separator);
+    let body = cached_response.split_off(1);
+
+    let mut response = Response::new(Body::from(body));
+    if let Ok(content_type) = HeaderValue::from_maybe_shared(content_type) {
+        if !content_type.is_empty() {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type);
+        }
+    }
+    response
+        .headers_mut()
+        .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("hit"));
+    Some(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Json, Router};
+    use googletest::prelude::*;
+    use {{crate_name}}_cache::memory::MemoryCache;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tower::ServiceExt;
+
+    fn app(calls: Arc<AtomicU32>) -> Router {
+        let cached = CachedFor::new(Arc::new(MemoryCache::new(10)), Duration::from_secs(60));
+        let count = move || async move { Json(calls.fetch_add(1, Ordering::SeqCst)) };
+        Router::new()
+            .route("/count", get(count))
+            .route(
+                "/login",
+                get(|| async { ([(header::SET_COOKIE, "id=1")], "welcome") }),
+            )
+            .route(
+                "/missing",
+                get(|| async { (StatusCode::NOT_FOUND, "not found") }),
+            )
+            .layer(middleware::from_fn_with_state(cached, cache))
+    }
+
+    async fn send(app: &Router, request: Request) -> (Option<String>, Option<String>, String) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .map(|value: &HeaderValue| value.to_str().unwrap().to_string())
+        };
+        let (cache_status, content_type) =
+            (header(CACHE_STATUS_HEADER), header(header::CONTENT_TYPE));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            cache_status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    fn get_request(uri: &str) -> Request {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let app = app(Arc::default());
+
+        let (cache_status, _, body) = send(&app, get_request("/count")).await;
+        assert_that!(cache_status, some(eq("miss")));
+        assert_that!(body, eq("0"));
+
+        let (cache_status, content_type, body) = send(&app, get_request("/count")).await;
+        assert_that!(cache_status, some(eq("hit")));
+        assert_that!(content_type, some(eq("application/json")));
+        assert_that!(body, eq("0"));
+
+        let (cache_status, _, body) = send(&app, get_request("/count?page=2")).await;
+        assert_that!(cache_status, some(eq("miss")));
+        assert_that!(body, eq("1"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_not_cacheable() {
+        let app = app(Arc::default());
+
+        for _ in 0..2 {
+            let request = Request::get("/count")
+                .header(header::AUTHORIZATION, "Bearer token")
+                .body(Body::empty())
+                .unwrap();
+            let (cache_status, _, _) = send(&app, request).await;
+            assert_that!(cache_status, none());
+
+            let (cache_status, _, _) = send(&app, get_request("/login")).await;
+            assert_that!(cache_status, none());
+
+            let (cache_status, _, _) = send(&app, get_request("/missing")).await;
+            assert_that!(cache_status, none());
+        }
+    }
+}
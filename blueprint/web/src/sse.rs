@@ -0,0 +1,223 @@
+use crate::error::AppError;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderName},
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::time::Duration;
+
+/// The header clients send when they reconnect to a stream, carrying the ID of the last event they received.
+pub static LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// The interval in which an [`EventStream`] sends keep-alive comments if there are no events so that proxies do not close idle connections.
+pub const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// An event sent to clients via an [`EventStream`], its data serialized as JSON.
+///
+/// Events with an ID allow clients to resume the stream after reconnecting: browsers send the ID of the last event they received in the `Last-Event-ID` header (see [`LastEventId`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event<T> {
+    data: T,
+    id: Option<String>,
+    name: Option<String>,
+}
+
+impl<T: Serialize> Event<T> {
+    /// Creates an unnamed event without an ID that carries `data`.
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            id: None,
+            name: None,
+        }
+    }
+
+    /// Sets the event's ID. The ID must not contain newlines.
+    pub fn id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the event's name which clients listen for, e.g. with `addEventListener("tick", …)` in browsers. The name must not contain newlines.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    fn into_sse(self) -> Result<sse::Event, axum::Error> {
+        let mut event = sse::Event::default()
+            .json_data(&self.data)
+            .inspect_err(|e| {
+                tracing::error!(err.msg = %e, "Failed to serialize event");
+            })?;
+        if let Some(id) = self.id {
+            event = event.id(id);
+        }
+        if let Some(name) = self.name {
+            event = event.event(name);
+        }
+
+        Ok(event)
+    }
+}
+
+/// A response that streams [`Event`]s to the client via [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+///
+/// The stream sends a keep-alive comment every [`DEFAULT_KEEP_ALIVE`] (see [`EventStream::keep_alive`]) while there are no events and ends the response when the stream of events ends. Clients reconnect automatically when the connection is lost, after the interval set with [`EventStream::retry`] if any.
+///
+/// Example:
+/// ```
+/// pub async fn stream(
+///     LastEventId(last_event_id): LastEventId,
+/// ) -> EventStream<impl Stream<Item = Event<Tick>> + Send + 'static> {
+///     let start = last_event_id
+///         .and_then(|id| id.parse::<u64>().ok())
+///         .map_or(0, |id| id + 1);
+///     let events = futures_util::stream::iter(start..)
+///         .map(|count| Event::new(Tick { count }).id(count).name("tick"));
+///
+///     EventStream::new(events).retry(Duration::from_secs(5))
+/// }
+/// ```
+pub struct EventStream<S> {
+    events: S,
+    keep_alive: Duration,
+    retry: Option<Duration>,
+}
+
+impl<S, T> EventStream<S>
+where
+    S: Stream<Item = Event<T>> + Send + 'static,
+    T: Serialize + 'static,
+{
+    /// Creates a response that streams `events`.
+    pub fn new(events: S) -> Self {
+        Self {
+            events,
+            keep_alive: DEFAULT_KEEP_ALIVE,
+            retry: None,
+        }
+    }
+
+    /// Sets the interval in which keep-alive comments are sent while there are no events.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = interval;
+        self
+    }
+
+    /// Sets the time clients wait before reconnecting after the connection was lost.
+    pub fn retry(mut self, interval: Duration) -> Self {
+        self.retry = Some(interval);
+        self
+    }
+}
+
+impl<S, T> IntoResponse for EventStream<S>
+where
+    S: Stream<Item = Event<T>> + Send + 'static,
+    T: Serialize + 'static,
+{
+    fn into_response(self) -> Response {
+        // the retry interval is sent to clients once, before any events
+        let retry = self
+            .retry
+            .map(|retry| Ok(sse::Event::default().retry(retry)));
+        let events = stream::iter(retry).chain(self.events.map(Event::into_sse));
+
+        Sse::new(events)
+            .keep_alive(KeepAlive::new().interval(self.keep_alive))
+            .into_response()
+    }
+}
+
+/// An extractor that reads the ID of the last event a reconnecting client received from the `Last-Event-ID` header.
+///
+/// The ID is `None` if the client connects for the first time; handlers use it to resume the stream after the last event the client received, e.g. by replaying the events it missed. Header values that are not valid UTF-8 are rejected with a 400 response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LastEventId(pub Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for LastEventId
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(&LAST_EVENT_ID) else {
+            return Ok(LastEventId(None));
+        };
+        let id = value
+            .to_str()
+            .map_err(|_| AppError::BadRequest(String::from("Invalid Last-Event-ID header.")))?;
+
+        Ok(LastEventId(Some(String::from(id))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, HeaderValue, Request};
+    use googletest::prelude::*;
+    use serde_json::json;
+
+    async fn extract(request: Request<()>) -> std::result::Result<LastEventId, AppError> {
+        let (mut parts, _) = request.into_parts();
+        LastEventId::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let events = stream::iter([
+            Event::new(json!({ "count": 1 })).id(1).name("tick"),
+            Event::new(json!({ "count": 2 })),
+        ]);
+
+        let response = EventStream::new(events)
+            .retry(Duration::from_secs(3))
+            .into_response();
+
+        assert_that!(
+            response.headers().get(header::CONTENT_TYPE),
+            some(eq(&HeaderValue::from_static("text/event-stream")))
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_that!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            eq("retry:3000\n\ndata: {\"count\":1}\nid: 1\nevent: tick\n\ndata: {\"count\":2}\n\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_last_event_id() {
+        let request = Request::get("/ticks").body(()).unwrap();
+        assert_that!(extract(request).await, ok(eq(&LastEventId(None))));
+
+        let request = Request::get("/ticks")
+            .header(&LAST_EVENT_ID, "41")
+            .body(())
+            .unwrap();
+        assert_that!(
+            extract(request).await,
+            ok(eq(&LastEventId(Some(String::from("41")))))
+        );
+
+        let request = Request::get("/ticks")
+            .header(&LAST_EVENT_ID, HeaderValue::from_bytes(b"\xff").unwrap())
+            .body(())
+            .unwrap();
+        assert_that!(
+            extract(request).await,
+            err(matches_pattern!(AppError::BadRequest(_)))
+        );
+    }
+}
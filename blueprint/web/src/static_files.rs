@@ -0,0 +1,198 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode, Uri},
+    response::Response,
+    Router,
+};
+use {{crate_name}}_config::StaticFilesConfig;
+use std::convert::Infallible;
+use std::path::Path;
+use tower::{service_fn, ServiceExt};
+use tower_http::services::ServeDir;
+
+/// The path static files are served at.
+pub const STATIC_PATH: &str = "/static";
+
+/// The `Cache-Control` header of fingerprinted files – their URLs change whenever their contents change so clients may cache them forever.
+static CACHE_IMMUTABLE: HeaderValue =
+    HeaderValue::from_static("public, max-age=31536000, immutable");
+
+/// The `Cache-Control` header of files requested by their original paths – clients have to revalidate them before using cached copies.
+static CACHE_REVALIDATE: HeaderValue = HeaderValue::from_static("no-cache");
+
+/// The paths of the files in `web/public` and their fingerprinted paths, generated by the web crate's build script.
+static ASSETS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+
+/// Returns the URL of a static file, fingerprinted with a hash of its contents if the file existed when the application was built, e.g. `/static/css/app.1a2b3c4d.css` for `css/app.css`.
+///
+/// Fingerprinted URLs change whenever the file changes so that they are served with headers that allow clients to cache them forever. Files that did not exist when the application was built are referenced by their original paths, e.g. `/static/uploads/logo.png`.
+///
+/// Example:
+/// ```
+/// let stylesheet = format!(r#"<link rel="stylesheet" href="{}">"#, asset_path("css/app.css"));
+/// ```
+pub fn asset_path(path: &str) -> String {
+    resolve(path, ASSETS)
+}
+
+fn resolve(path: &str, assets: &[(&str, &str)]) -> String {
+    let path = path.trim_start_matches('/');
+    let path = assets
+        .iter()
+        .find(|(original, _)| *original == path)
+        .map_or(path, |(_, fingerprinted)| fingerprinted);
+
+    format!("{}/{}", STATIC_PATH, path)
+}
+
+/// Returns a router that serves the static files in the directory configured in the `[static_files]` section of the configuration at [`STATIC_PATH`].
+///
+/// Files are served by their original paths as well as by their fingerprinted paths (see [`asset_path`]), with a `Cache-Control` header that allows clients to cache fingerprinted files forever while they have to revalidate other files. Clients that accept Brotli or gzip compressed responses receive the `.br` or `.gz` copies of files if those exist (see the web crate's `precompress` feature).
+pub fn router(config: &StaticFilesConfig) -> Router {
+    serve(&config.dir, ASSETS)
+}
+
+fn serve(dir: &Path, assets: &'static [(&'static str, &'static str)]) -> Router {
+    let files = ServeDir::new(dir).precompressed_br().precompressed_gzip();
+    let service = service_fn(move |request: Request| {
+        let files = files.clone();
+        async move {
+            let path = request.uri().path().trim_start_matches('/');
+            let original = assets
+                .iter()
+                .find(|(_, fingerprinted)| *fingerprinted == path)
+                .map(|(original, _)| *original);
+
+            let mut request = request;
+            if let Some(original) = original {
+                *request.uri_mut() = rewrite_path(request.uri(), original);
+            }
+            let Ok(response) = files.oneshot(request).await;
+            let mut response = response.map(Body::new);
+
+            if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+                let cache_control = if original.is_some() {
+                    CACHE_IMMUTABLE.clone()
+                } else {
+                    CACHE_REVALIDATE.clone()
+                };
+                response
+                    .headers_mut()
+                    .insert(header::CACHE_CONTROL, cache_control);
+            }
+
+            Ok::<Response, Infallible>(response)
+        }
+    });
+
+    Router::new().nest_service(STATIC_PATH, service)
+}
+
+fn rewrite_path(uri: &Uri, path: &str) -> Uri {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("/{}?{}", path, query),
+        None => format!("/{}", path),
+    };
+
+    Uri::try_from(path_and_query).unwrap_or_else(|_| uri.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googletest::prelude::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    static TEST_ASSETS: &[(&str, &str)] = &[("css/app.css", "css/app.1a2b3c4d.css")];
+
+    fn create_public_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("static-files-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("css")).unwrap();
+        fs::write(dir.join("css/app.css"), "body { margin: 0; }").unwrap();
+        dir
+    }
+
+    async fn get(app: Router, uri: &str) -> Response {
+        app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_that!(
+            resolve("css/app.css", TEST_ASSETS),
+            eq("/static/css/app.1a2b3c4d.css")
+        );
+        assert_that!(
+            resolve("/css/app.css", TEST_ASSETS),
+            eq("/static/css/app.1a2b3c4d.css")
+        );
+        assert_that!(
+            resolve("img/logo.png", TEST_ASSETS),
+            eq("/static/img/logo.png")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_fingerprinted() {
+        let dir = create_public_dir("fingerprinted");
+        let app = serve(&dir, TEST_ASSETS);
+
+        let response = get(app, "/static/css/app.1a2b3c4d.css").await;
+
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(
+            response.headers().get(header::CACHE_CONTROL),
+            some(eq(&CACHE_IMMUTABLE))
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_that!(&body[..], eq(b"body { margin: 0; }"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve_original() {
+        let dir = create_public_dir("original");
+        let app = serve(&dir, TEST_ASSETS);
+
+        let response = get(app.clone(), "/static/css/app.css").await;
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(
+            response.headers().get(header::CACHE_CONTROL),
+            some(eq(&CACHE_REVALIDATE))
+        );
+
+        let response = get(app, "/static/css/missing.css").await;
+        assert_that!(response.status(), eq(StatusCode::NOT_FOUND));
+        assert_that!(response.headers().get(header::CACHE_CONTROL), none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve_precompressed() {
+        let dir = create_public_dir("precompressed");
+        fs::write(dir.join("css/app.css.gz"), "compressed").unwrap();
+        let app = serve(&dir, TEST_ASSETS);
+
+        let request = Request::get("/static/css/app.1a2b3c4d.css")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_that!(
+            response.headers().get(header::CONTENT_ENCODING),
+            some(eq(&HeaderValue::from_static("gzip")))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use super::{encode_key, hex, Storage};
+use crate::csrf::constant_time_eq;
+use crate::error::AppError;
+use anyhow::{anyhow, Context};
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{Path as UrlPath, Query, Request, State},
+    response::Response,
+    routing::get,
+    Router,
+};
+use hmac::{Hmac, Mac};
+use rand::{thread_rng, RngCore};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+/// The path the files stored on the local disk are served at.
+pub const STORAGE_PATH: &str = "/storage";
+
+/// The key URLs are signed with, generated when it is first used so that signed URLs become invalid when the application restarts
+static SIGNING_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+/// Stores files in a directory on the local disk.
+///
+/// Files are served at [`STORAGE_PATH`] by the [`router`] to clients with a signed URL. URLs are signed with a key that is generated when the application starts so that they are only valid for the process that signed them – the local storage is meant for development, tests, and applications running on a single node.
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    /// Creates a storage that stores files in `dir`, which is created when the first file is stored.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), anyhow::Error> {
+        let path = resolve(&self.dir, key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("Failed to create the storage directory!")?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .context(format!("Failed to store file {}!", key))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, anyhow::Error> {
+        match tokio::fs::read(resolve(&self.dir, key)?).await {
+            Ok(data) => Ok(Some(Bytes::from(data))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to read file {}!", key))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), anyhow::Error> {
+        match tokio::fs::remove_file(resolve(&self.dir, key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to delete file {}!", key))),
+        }
+    }
+
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, anyhow::Error> {
+        resolve(&self.dir, key)?;
+        let expires = unix_now() + expires_in.as_secs();
+
+        Ok(format!(
+            "{}/{}?expires={}&signature={}",
+            STORAGE_PATH,
+            encode_key(key),
+            expires,
+            sign(key, expires)
+        ))
+    }
+}
+
+/// Returns the path of the file stored under `key`, rejecting keys that are absolute or point outside of `dir`, e.g. `../config/master.key`.
+fn resolve(dir: &Path, key: &str) -> Result<PathBuf, anyhow::Error> {
+    let path = Path::new(key);
+    let is_relative = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if key.is_empty() || !is_relative {
+        return Err(anyhow!("Invalid storage key {}!", key));
+    }
+
+    Ok(dir.join(path))
+}
+
+fn sign(key: &str, expires: u64) -> String {
+    let signing_key = SIGNING_KEY.get_or_init(|| {
+        let mut signing_key = [0; 32];
+        thread_rng().fill_bytes(&mut signing_key);
+        signing_key
+    });
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(signing_key).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}", key, expires).as_bytes());
+
+    hex(&mac.finalize().into_bytes())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns a router that serves the files stored in `dir` at [`STORAGE_PATH`] to clients with a signed URL that has not expired yet (see [`LocalStorage`]).
+pub fn router(dir: &Path) -> Router {
+    Router::new()
+        .route(&format!("{}/*key", STORAGE_PATH), get(serve))
+        .with_state(Arc::new(dir.to_path_buf()))
+}
+
+#[derive(Deserialize)]
+struct Signature {
+    expires: u64,
+    signature: String,
+}
+
+async fn serve(
+    State(dir): State<Arc<PathBuf>>,
+    UrlPath(key): UrlPath<String>,
+    signature: Option<Query<Signature>>,
+    request: Request,
+) -> Result<Response, AppError> {
+    let Some(Query(signature)) = signature else {
+        return Err(AppError::Forbidden);
+    };
+    if signature.expires < unix_now()
+        || !constant_time_eq(&sign(&key, signature.expires), &signature.signature)
+    {
+        return Err(AppError::Forbidden);
+    }
+    let path = resolve(&dir, &key).map_err(|_| AppError::NotFound)?;
+
+    let Ok(response) = ServeFile::new(path).oneshot(request).await;
+    Ok(response.map(Body::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use googletest::prelude::*;
+
+    fn create_storage_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("storage-{}-{}", name, std::process::id()))
+    }
+
+    async fn get(app: Router, uri: &str) -> Response {
+        app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let dir = create_storage_dir("round-trip");
+        let storage = LocalStorage::new(dir.clone());
+
+        storage
+            .put("users/1/avatar.png", Bytes::from("png"), "image/png")
+            .await
+            .unwrap();
+        assert_that!(
+            storage.get("users/1/avatar.png").await.unwrap(),
+            some(eq(&Bytes::from("png")))
+        );
+
+        storage.delete("users/1/avatar.png").await.unwrap();
+        assert_that!(storage.get("users/1/avatar.png").await.unwrap(), none());
+        assert_that!(storage.delete("users/1/avatar.png").await, ok(anything()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_keys() {
+        let storage = LocalStorage::new(create_storage_dir("invalid"));
+
+        for key in [
+            "",
+            "../config/master.key",
+            "/etc/passwd",
+            "users/../../secret",
+        ] {
+            assert_that!(
+                storage.put(key, Bytes::from("data"), "text/plain").await,
+                err(anything())
+            );
+            assert_that!(storage.get(key).await, err(anything()));
+            assert_that!(
+                storage.signed_url(key, Duration::from_secs(60)),
+                err(anything())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_url() {
+        let dir = create_storage_dir("signed-url");
+        let storage = LocalStorage::new(dir.clone());
+        storage
+            .put("reports/q1 2024.txt", Bytes::from("report"), "text/plain")
+            .await
+            .unwrap();
+        let url = storage
+            .signed_url("reports/q1 2024.txt", Duration::from_secs(60))
+            .unwrap();
+        assert_that!(url, starts_with("/storage/reports/q1%202024.txt?expires="));
+
+        let response = get(router(&dir), &url).await;
+        assert_that!(response.status(), eq(StatusCode::OK));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_that!(body, eq(&Bytes::from("report")));
+
+        let tampered = url.replace("q1%202024", "q2%202024");
+        let response = get(router(&dir), &tampered).await;
+        assert_that!(response.status(), eq(StatusCode::FORBIDDEN));
+        let response = get(router(&dir), "/storage/reports/q1%202024.txt").await;
+        assert_that!(response.status(), eq(StatusCode::FORBIDDEN));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expired_signed_url() {
+        let dir = create_storage_dir("expired");
+        let expires = unix_now() - 1;
+        let url = format!(
+            "/storage/report.txt?expires={}&signature={}",
+            expires,
+            sign("report.txt", expires)
+        );
+
+        let response = get(router(&dir), &url).await;
+
+        assert_that!(response.status(), eq(StatusCode::FORBIDDEN));
+    }
+}
@@ -0,0 +1,82 @@
+use axum::{async_trait, body::Bytes};
+use {{crate_name}}_config::{StorageBackend, StorageConfig};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stores files on the local disk and serves them at signed URLs.
+pub mod local;
+/// Stores files in Amazon S3 or an S3-compatible service.
+pub mod s3;
+
+/// A store for files, e.g. uploaded files (see [`crate::uploads::Upload`]).
+///
+/// Files are identified by their key, a relative path such as `users/42/avatar/8fQk2x.png`. The storage that is configured in the `[storage]` section of the configuration is available in the application state, e.g.:
+///
+/// ```
+/// app_state.storage.put(&key, upload.data, &upload.content_type).await?;
+/// let url = app_state.storage.signed_url(&key, Duration::from_secs(3600))?;
+/// ```
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores a file under `key`, replacing any file stored under it before.
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), anyhow::Error>;
+
+    /// Returns the contents of the file stored under `key`, `None` if there is none.
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, anyhow::Error>;
+
+    /// Deletes the file stored under `key`; deleting a file that does not exist is not an error.
+    async fn delete(&self, key: &str) -> Result<(), anyhow::Error>;
+
+    /// Returns a URL that clients can download the file stored under `key` from without further authentication until it expires after `expires_in`.
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, anyhow::Error>;
+}
+
+/// Creates the storage for the backend configured in the `[storage.backend]` section of the configuration (see [`StorageConfig`]).
+///
+/// Returns an error if the endpoint of an S3-compatible service is not a valid URL.
+pub fn build_storage(config: &StorageConfig) -> Result<Arc<dyn Storage>, anyhow::Error> {
+    let storage: Arc<dyn Storage> = match &config.backend {
+        StorageBackend::Local { dir } => Arc::new(local::LocalStorage::new(dir.clone())),
+        StorageBackend::S3 {
+            bucket,
+            region,
+            endpoint,
+            access_key_id,
+            secret_access_key,
+            path_style,
+        } => Arc::new(s3::S3Storage::new(
+            bucket,
+            region,
+            endpoint.as_deref(),
+            access_key_id,
+            secret_access_key,
+            *path_style,
+        )?),
+    };
+
+    Ok(storage)
+}
+
+/// Encodes bytes as lowercase hex, e.g. for signatures.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Percent-encodes all characters of a key except for the unreserved ones (`A-Z`, `a-z`, `0-9`, `-`, `.`, `_`, and `~`) and `/` so that it can be used as the path of a URL.
+fn encode_key(key: &str) -> String {
+    uri_encode(key, false)
+}
+
+/// Percent-encodes all characters except for the unreserved ones, and `/` unless `encode_slash` is set.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                String::from(byte as char)
+            }
+            b'/' if !encode_slash => String::from("/"),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,166 @@
+use crate::middlewares::logging::request_id;
+use axum::extract::MatchedPath;
+use axum::http::{HeaderMap, Request, Response};
+use opentelemetry::global;
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use std::time::Duration;
+use tracing::{field::Empty, info_span, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Creates the span of an incoming request, continuing the trace of the caller if the request has a `traceparent` header.
+///
+/// The span follows OpenTelemetry's semantic conventions for HTTP servers, is named after the route that handles the request, e.g. `GET /tasks/:id`, and records the request's ID (see [`crate::middlewares::logging::REQUEST_ID_HEADER`]). It is created by the `TraceLayer` registered in [`crate::middlewares::register`] so that all spans and events that are recorded while handling a request belong to it.
+pub fn make_span<B>(request: &Request<B>) -> Span {
+    let method = request.method();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    let span = info_span!(
+        "HTTP request",
+        otel.name = %route.map_or_else(|| method.to_string(), |route| format!("{} {}", method, route)),
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.request.method = %method,
+        http.route = route,
+        http.response.status_code = Empty,
+        url.path = request.uri().path(),
+        request_id = request_id(request.headers()),
+    );
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    let _ = span.set_parent(parent);
+
+    span
+}
+
+/// Creates the span of an outgoing request and adds the `traceparent` and `tracestate` headers for it to the request so that the receiver continues the trace.
+///
+/// The span follows OpenTelemetry's semantic conventions for HTTP clients. HTTP clients create it for every request they send, perform the request in it, and record the response with [`record_response`]:
+///
+/// ```
+/// let span = telemetry::make_client_span(&mut request);
+/// let response = client.call(request).instrument(span.clone()).await?;
+/// telemetry::record_response(&response, started_at.elapsed(), &span);
+/// ```
+pub fn make_client_span<B>(request: &mut Request<B>) -> Span {
+    let method = request.method();
+    let span = info_span!(
+        "HTTP request",
+        otel.name = %method,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        http.request.method = %method,
+        http.response.status_code = Empty,
+        server.address = request.uri().host(),
+        url.full = %request.uri(),
+    );
+    let context = span.context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(request.headers_mut()))
+    });
+
+    span
+}
+
+/// Records the status of the response to a request on the request's span (see [`make_span`] and [`make_client_span`]), marking the span as failed for server errors.
+pub fn record_response<B>(response: &Response<B>, _latency: Duration, span: &Span) {
+    span.record("http.response.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+}
+
+/// Adds the `traceparent` and `tracestate` headers of the current span to `headers`, e.g. for messages sent to other services that continue the trace.
+pub fn inject_context(headers: &mut HeaderMap) {
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use googletest::prelude::*;
+    use opentelemetry::trace::{TraceContextExt, TraceId, TracerProvider};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn trace_id(span: &Span) -> TraceId {
+        span.context().span().span_context().trace_id()
+    }
+
+    fn with_tracer<R>(f: impl FnOnce() -> R) -> R {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer_provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, f)
+    }
+
+    #[test]
+    fn test_make_span_continues_trace() {
+        with_tracer(|| {
+            let request = Request::builder()
+                .uri("/tasks")
+                .header("traceparent", TRACEPARENT)
+                .body(Body::empty())
+                .unwrap();
+
+            let span = make_span(&request);
+
+            assert_that!(
+                trace_id(&span),
+                eq(TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap())
+            );
+        });
+    }
+
+    #[test]
+    fn test_make_span_starts_trace() {
+        with_tracer(|| {
+            let request = Request::builder()
+                .uri("/tasks")
+                .body(Body::empty())
+                .unwrap();
+
+            let span = make_span(&request);
+
+            assert_that!(trace_id(&span), not(eq(TraceId::INVALID)));
+        });
+    }
+
+    #[test]
+    fn test_make_client_span_propagates_trace() {
+        with_tracer(|| {
+            let incoming = Request::builder()
+                .uri("/tasks")
+                .header("traceparent", TRACEPARENT)
+                .body(Body::empty())
+                .unwrap();
+            let server_span = make_span(&incoming);
+            let _entered = server_span.enter();
+            let mut outgoing = Request::builder()
+                .uri("https://api.example.com/payments")
+                .body(Body::empty())
+                .unwrap();
+
+            make_client_span(&mut outgoing);
+
+            assert_that!(
+                outgoing.headers()["traceparent"].to_str().unwrap(),
+                starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-")
+            );
+            assert_that!(
+                outgoing.headers()["traceparent"].to_str().unwrap(),
+                not(eq(TRACEPARENT))
+            );
+        });
+    }
+}
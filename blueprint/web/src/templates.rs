@@ -0,0 +1,235 @@
+use crate::csrf::csrf_field;
+use crate::error::AppError;
+use crate::static_files::asset_path;
+use axum::{
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use minijinja::{path_loader, Environment, Value};
+use {{crate_name}}_config::TemplatesConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub use minijinja::context;
+
+/// The application's HTML templates, loaded from the directory configured in the `[templates]` section of the configuration (`web/templates` by default).
+///
+/// {% raw %}Templates use [MiniJinja](https://docs.rs/minijinja)'s Jinja2 syntax. By convention, the templates for a controller's actions are in a directory named after the controller, e.g. `tasks/index.html`, pages extend a layout from `layouts` (e.g. `{% extends "layouts/app.html" %}`), and snippets that are shared between templates are in `partials` (e.g. `{% include "partials/head.html" %}`). Templates can resolve the URLs of static files with the `asset_path` function (see [`crate::static_files::asset_path`]), e.g. `{{ asset_path("css/app.css") }}`, and render the hidden field with the CSRF token in forms with the `csrf_field` function (see [`crate::csrf::CsrfToken`]), e.g. `{{ csrf_field() }}`.{% endraw %}
+///
+/// Templates are loaded when they are first rendered and cached afterwards unless `reload` is enabled in the configuration (which it is in `development.toml`) in which case they are read from disk again whenever they are rendered.
+#[derive(Clone)]
+pub struct Templates {
+    env: Arc<Environment<'static>>,
+    dir: PathBuf,
+    reload: bool,
+}
+
+impl Templates {
+    /// Creates the templates from the configuration in the `[templates]` section.
+    pub fn from_config(config: &TemplatesConfig) -> Self {
+        Self {
+            env: Arc::new(build_environment(&config.dir)),
+            dir: config.dir.clone(),
+            reload: config.reload,
+        }
+    }
+
+    /// Returns a [`Template`] response that renders the template `name` with the `context`, typically created with the [`context!`] macro.
+    ///
+    /// Example:
+    /// ```
+    /// pub async fn index(State(app_state): State<SharedAppState>) -> Template {
+    ///     app_state
+    ///         .templates
+    ///         .render("home/index.html", context! { title => "Home" })
+    /// }
+    /// ```
+    pub fn render(&self, name: &str, context: impl Serialize) -> Template {
+        Template {
+            templates: self.clone(),
+            name: String::from(name),
+            context: Value::from_serialize(context),
+            status: StatusCode::OK,
+        }
+    }
+
+    fn render_to_string(&self, name: &str, context: &Value) -> Result<String, minijinja::Error> {
+        if self.reload {
+            build_environment(&self.dir)
+                .get_template(name)?
+                .render(context)
+        } else {
+            self.env.get_template(name)?.render(context)
+        }
+    }
+}
+
+fn build_environment(dir: &Path) -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_loader(path_loader(dir));
+    // the URLs are marked as safe so that their slashes are not escaped
+    env.add_function("asset_path", |path: &str| {
+        Value::from_safe_string(asset_path(path))
+    });
+    env.add_function("csrf_field", csrf_field);
+    env
+}
+
+/// A response that renders an HTML template (see [`Templates::render`]).
+///
+/// The template is rendered when the response is sent. Templates that fail to render, e.g. because they do not exist or contain syntax errors, result in an Internal Server Error.
+pub struct Template {
+    templates: Templates,
+    name: String,
+    context: Value,
+    status: StatusCode,
+}
+
+impl Template {
+    /// Sets the response's status code, e.g. [`StatusCode::UNPROCESSABLE_ENTITY`] for a form that is rendered again with validation errors.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl IntoResponse for Template {
+    fn into_response(self) -> Response {
+        match self.templates.render_to_string(&self.name, &self.context) {
+            Ok(html) => (self.status, Html(html)).into_response(),
+            Err(e) => {
+                tracing::error!(err.msg = %e, template = %self.name, "Failed to render template");
+                AppError::Other(anyhow::Error::new(e)).into_response()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+    use googletest::prelude::*;
+    use std::fs;
+
+    fn create_templates_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("templates-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("layouts")).unwrap();
+        fs::create_dir_all(dir.join("partials")).unwrap();
+        fs::create_dir_all(dir.join("home")).unwrap();
+        fs::write(
+            dir.join("layouts/app.html"),
+            r#"{% raw %}<html>{% include "partials/head.html" %}<body>{% block content %}{% endblock %}</body></html>{% endraw %}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("partials/head.html"),
+            r#"{% raw %}<link href="{{ asset_path('img/logo.png') }}">{% endraw %}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("home/index.html"),
+            r#"{% raw %}{% extends "layouts/app.html" %}{% block content %}<h1>{{ title }}</h1>{% endblock %}{% endraw %}"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    async fn body(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_render() {
+        let dir = create_templates_dir("render");
+        let templates = Templates::from_config(&TemplatesConfig {
+            dir: dir.clone(),
+            reload: false,
+        });
+
+        let response = templates
+            .render("home/index.html", context! { title => "<Home>" })
+            .into_response();
+
+        assert_that!(response.status(), eq(StatusCode::OK));
+        assert_that!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            eq("text/html; charset=utf-8")
+        );
+        assert_that!(
+            body(response).await,
+            eq(
+                r#"<html><link href="/static/img/logo.png"><body><h1>&lt;Home&gt;</h1></body></html>"#
+            )
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_render_status() {
+        let dir = create_templates_dir("status");
+        let templates = Templates::from_config(&TemplatesConfig {
+            dir: dir.clone(),
+            reload: false,
+        });
+
+        let response = templates
+            .render("home/index.html", context! { title => "Home" })
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .into_response();
+
+        assert_that!(response.status(), eq(StatusCode::UNPROCESSABLE_ENTITY));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_render_missing_template() {
+        let dir = create_templates_dir("missing");
+        let templates = Templates::from_config(&TemplatesConfig {
+            dir: dir.clone(),
+            reload: false,
+        });
+
+        let response = templates.render("home/missing.html", ()).into_response();
+
+        assert_that!(response.status(), eq(StatusCode::INTERNAL_SERVER_ERROR));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let dir = create_templates_dir("reload");
+        let cached = Templates::from_config(&TemplatesConfig {
+            dir: dir.clone(),
+            reload: false,
+        });
+        let reloaded = Templates::from_config(&TemplatesConfig {
+            dir: dir.clone(),
+            reload: true,
+        });
+        let response = cached.render("partials/head.html", ()).into_response();
+        assert_that!(
+            body(response).await,
+            eq(r#"<link href="/static/img/logo.png">"#)
+        );
+
+        fs::write(dir.join("partials/head.html"), "<title>Changed</title>").unwrap();
+
+        let response = cached.render("partials/head.html", ()).into_response();
+        assert_that!(
+            body(response).await,
+            eq(r#"<link href="/static/img/logo.png">"#)
+        );
+        let response = reloaded.render("partials/head.html", ()).into_response();
+        assert_that!(body(response).await, eq("<title>Changed</title>"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::AppError;
+use anyhow::{anyhow, Context};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use {{crate_name}}_config::{TenancyConfig, TenantResolver};
+use {{crate_name}}_db::{tenancy, DbPool};
+use sqlx::{Postgres, Transaction};
+use std::sync::Arc;
+
+/// The tenancy settings along with the database pool the tenants' schemas are looked up in, created from the [`TenancyConfig`].
+///
+/// Requests are resolved to tenants by the [`resolve`] middleware that is registered in [`crate::middlewares::register`]. Request handlers access the current tenant via the [`Tenant`] extractor.
+#[derive(Clone)]
+pub struct Tenancy {
+    config: Arc<TenancyConfig>,
+    db_pool: DbPool,
+}
+
+impl Tenancy {
+    /// Creates the tenancy from the `[tenancy]` section of the configuration.
+    pub fn from_config(config: &TenancyConfig, db_pool: &DbPool) -> Self {
+        Self {
+            config: Arc::new(config.clone()),
+            db_pool: db_pool.clone(),
+        }
+    }
+}
+
+/// The tenant a request was resolved to, e.g.:
+///
+/// ```
+/// pub async fn read_all(tenant: Tenant, State(app_state): State<SharedAppState>) -> Result<Json<Vec<Task>>, AppError> {
+///     let mut tx = tenant.transaction(&app_state.db_pool).await?;
+///     let tasks = tasks::load_all(&mut *tx).await?;
+///     tx.commit().await.context("Failed to commit transaction")?;
+///
+///     Ok(Json(tasks))
+/// }
+/// ```
+///
+/// Extracting the tenant fails with an internal error if tenancy is not enabled since there is no tenant then.
+#[derive(Clone, Debug)]
+pub struct Tenant {
+    /// The tenant's name, e.g. `acme`
+    pub name: String,
+    /// The name of the tenant's schema, e.g. `tenant_acme`
+    pub schema: String,
+}
+
+impl Tenant {
+    /// Starts a new database transaction in the tenant's schema (see [`{{crate_name}}_db::tenancy::transaction`]).
+    pub async fn transaction(
+        &self,
+        db_pool: &DbPool,
+    ) -> Result<Transaction<'static, Postgres>, anyhow::Error> {
+        tenancy::transaction(db_pool, &self.schema).await
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Tenant
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Tenant>().cloned().ok_or_else(|| {
+            AppError::Other(anyhow!(
+                "No tenant – tenancy is not enabled in the configuration!"
+            ))
+        })
+    }
+}
+
+/// Resolves the tenant of a request if tenancy is enabled.
+///
+/// The tenant is made available to request handlers via the [`Tenant`] extractor. Requests that do not name a tenant, name an invalid one, or one whose schema does not exist are rejected with a 404 response.
+pub async fn resolve(
+    State(tenancy): State<Tenancy>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    if !tenancy.config.enabled {
+        return Ok(next.run(req).await);
+    }
+
+    let name = tenant_name(&tenancy.config, req.headers())
+        .ok_or(AppError::NotFound)?
+        .to_ascii_lowercase();
+    let schema = tenancy.config.schema(&name).ok_or(AppError::NotFound)?;
+    let exists = tenancy::schema_exists(&tenancy.db_pool, &schema)
+        .await
+        .context("Failed to look up the tenant's schema")?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+
+    req.extensions_mut().insert(Tenant { name, schema });
+
+    Ok(next.run(req).await)
+}
+
+/// Returns the name of the tenant that a request with the given headers names according to the configured resolver.
+fn tenant_name<'a>(config: &TenancyConfig, headers: &'a HeaderMap) -> Option<&'a str> {
+    match config.resolver {
+        TenantResolver::Subdomain => {
+            let host = headers.get(header::HOST)?.to_str().ok()?;
+            let host = host.split_once(':').map_or(host, |(host, _port)| host);
+            let split = host.len().checked_sub(config.domain.len())?;
+            let subdomain = host.get(..split)?.strip_suffix('.')?;
+            (host.get(split..)?.eq_ignore_ascii_case(&config.domain)
+                && !subdomain.is_empty()
+                && !subdomain.contains('.'))
+            .then_some(subdomain)
+        }
+        TenantResolver::Header => headers
+            .get(config.header.as_str())?
+            .to_str()
+            .ok()
+            .filter(|name| !name.is_empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use googletest::prelude::*;
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        HeaderMap::from_iter([(
+            header::HeaderName::from_static(name),
+            HeaderValue::from_static(value),
+        )])
+    }
+
+    #[test]
+    fn test_tenant_name_from_subdomain() {
+        let config = TenancyConfig {
+            domain: String::from("example.com"),
+            ..TenancyConfig::default()
+        };
+
+        assert_that!(
+            tenant_name(&config, &headers("host", "acme.example.com")),
+            some(eq("acme"))
+        );
+        assert_that!(
+            tenant_name(&config, &headers("host", "acme.Example.com:3000")),
+            some(eq("acme"))
+        );
+        assert_that!(
+            tenant_name(&config, &headers("host", "example.com")),
+            none()
+        );
+        assert_that!(
+            tenant_name(&config, &headers("host", "www.acme.example.com")),
+            none()
+        );
+        assert_that!(
+            tenant_name(&config, &headers("host", "acme.example.org")),
+            none()
+        );
+        assert_that!(
+            tenant_name(&config, &headers("host", "acmeexample.com")),
+            none()
+        );
+        assert_that!(tenant_name(&config, &HeaderMap::new()), none());
+    }
+
+    #[test]
+    fn test_tenant_name_from_header() {
+        let config = TenancyConfig {
+            resolver: TenantResolver::Header,
+            ..TenancyConfig::default()
+        };
+
+        assert_that!(
+            tenant_name(&config, &headers("x-tenant", "acme")),
+            some(eq("acme"))
+        );
+        assert_that!(tenant_name(&config, &headers("x-tenant", "")), none());
+        assert_that!(
+            tenant_name(&config, &headers("host", "acme.localhost")),
+            none()
+        );
+    }
+}
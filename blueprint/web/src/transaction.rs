@@ -0,0 +1,302 @@
+use crate::error::AppError;
+use anyhow::{anyhow, Context};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    middleware::Next,
+    response::Response,
+};
+use {{crate_name}}_db::DbPool;
+use sqlx::{Connection, PgConnection, Postgres, Transaction};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// The transaction of a request that is begun when a handler first extracts [`Tx`] and completed by the [`middleware`] once the response was created.
+#[derive(Clone)]
+struct RequestTransaction {
+    db_pool: DbPool,
+    transaction: Arc<Mutex<Option<Transaction<'static, Postgres>>>>,
+}
+
+/// The request's database transaction, e.g.:
+///
+/// ```
+/// pub async fn create(
+///     mut tx: Tx,
+///     Validated(task): Validated<TaskChangeset>,
+/// ) -> Result<(StatusCode, Json<Task>), AppError> {
+///     let task = tasks::create(task, &mut *tx).await?;
+///     activities::create(ActivityChangeset::task_created(&task), &mut *tx).await?;
+///
+///     Ok((StatusCode::CREATED, Json(task)))
+/// }
+/// ```
+///
+/// The transaction is begun when `Tx` is first extracted and committed by the [`middleware`] once the handler returned a successful (2xx) or redirect (3xx) response – it is rolled back for all other responses, e.g. when the handler returned an error, as well as when the handler panicked. If committing fails, the response is replaced with an internal error. Nested transactions are created with [`Tx::begin`].
+pub struct Tx(OwnedMutexGuard<Option<Transaction<'static, Postgres>>>);
+
+impl Tx {
+    /// Begins a nested transaction within the request's transaction, i.e. a savepoint.
+    ///
+    /// Committing the nested transaction releases the savepoint while rolling it back, or dropping it without committing it, only undoes the changes made since it was begun, e.g.:
+    ///
+    /// ```
+    /// let mut savepoint = tx.begin().await?;
+    /// if let Err(e) = notifications::create(notification, &mut *savepoint).await {
+    ///     warn!(err.msg = %e, "Failed to create notification");
+    ///     savepoint.rollback().await.context("Failed to roll back savepoint")?;
+    /// } else {
+    ///     savepoint.commit().await.context("Failed to release savepoint")?;
+    /// }
+    /// ```
+    pub async fn begin(&mut self) -> Result<Transaction<'_, Postgres>, AppError> {
+        let savepoint = Connection::begin(&mut **self)
+            .await
+            .context("Failed to begin nested transaction")?;
+
+        Ok(savepoint)
+    }
+}
+
+impl Deref for Tx {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+            .as_deref()
+            .expect("The transaction is begun when Tx is extracted")
+    }
+}
+
+impl DerefMut for Tx {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+            .as_deref_mut()
+            .expect("The transaction is begun when Tx is extracted")
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Tx
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let request_transaction = parts
+            .extensions
+            .get::<RequestTransaction>()
+            .cloned()
+            .ok_or_else(|| AppError::Other(anyhow!("The transaction middleware is not registered!")))?;
+        let mut transaction = request_transaction
+            .transaction
+            .try_lock_owned()
+            .map_err(|_| {
+                AppError::Other(anyhow!(
+                    "The request's transaction is in use already – Tx can only be extracted once at a time!"
+                ))
+            })?;
+        if transaction.is_none() {
+            *transaction = Some(
+                request_transaction
+                    .db_pool
+                    .begin()
+                    .await
+                    .context("Failed to begin transaction")?,
+            );
+        }
+
+        Ok(Self(transaction))
+    }
+}
+
+/// Commits the request's transaction if the response is successful and rolls it back otherwise (see [`Tx`]).
+///
+/// Requests whose handlers do not extract [`Tx`] do not begin a transaction so that the middleware does not acquire a database connection for them.
+pub async fn middleware(
+    State(db_pool): State<DbPool>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let request_transaction = RequestTransaction {
+        db_pool,
+        transaction: Arc::new(Mutex::new(None)),
+    };
+    req.extensions_mut().insert(request_transaction.clone());
+
+    let response = next.run(req).await;
+
+    let transaction = request_transaction
+        .transaction
+        .try_lock()
+        .map_err(|_| {
+            AppError::Other(anyhow!(
+                "The request's transaction is still in use after the response was created!"
+            ))
+        })?
+        .take();
+    let Some(transaction) = transaction else {
+        return Ok(response);
+    };
+    if response.status().is_success() || response.status().is_redirection() {
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction")?;
+    } else if let Err(e) = transaction.rollback().await {
+        tracing::warn!(err.msg = %e, "Failed to roll back transaction");
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, http::StatusCode, middleware, routing::get, Router};
+    use googletest::prelude::*;
+    use {{crate_name}}_config::{load_config, Config, Environment};
+    use {{crate_name}}_db::test_helpers::{setup_db_transaction, teardown_db_transaction};
+    use tower::ServiceExt;
+
+    async fn send(app: Router, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    async fn setup() -> DbPool {
+        let config: Config = load_config(&Environment::Test).unwrap();
+        let db_pool = setup_db_transaction(&config.database).await;
+        sqlx::query("CREATE TABLE transaction_values (value TEXT NOT NULL)")
+            .execute(&db_pool)
+            .await
+            .unwrap();
+
+        db_pool
+    }
+
+    async fn insert(connection: &mut PgConnection, value: &str) {
+        sqlx::query("INSERT INTO transaction_values (value) VALUES ($1)")
+            .bind(value)
+            .execute(connection)
+            .await
+            .unwrap();
+    }
+
+    async fn values(db_pool: &DbPool) -> Vec<String> {
+        sqlx::query_scalar("SELECT value FROM transaction_values ORDER BY value")
+            .fetch_all(db_pool)
+            .await
+            .unwrap()
+    }
+
+    async fn respond_with(Path(status): Path<u16>, mut tx: Tx) -> StatusCode {
+        insert(&mut tx, &status.to_string()).await;
+
+        StatusCode::from_u16(status).unwrap()
+    }
+
+    async fn fail(mut tx: Tx) -> std::result::Result<StatusCode, AppError> {
+        insert(&mut tx, "failed").await;
+
+        Err(AppError::Other(anyhow!("The handler failed")))
+    }
+
+    async fn use_savepoints(mut tx: Tx) -> std::result::Result<StatusCode, AppError> {
+        insert(&mut tx, "outer").await;
+
+        let mut savepoint = tx.begin().await?;
+        insert(&mut savepoint, "rolled back").await;
+        savepoint.rollback().await.unwrap();
+
+        let mut savepoint = tx.begin().await?;
+        insert(&mut savepoint, "released").await;
+        savepoint.commit().await.unwrap();
+
+        Ok(StatusCode::OK)
+    }
+
+    fn app(db_pool: &DbPool) -> Router {
+        Router::new()
+            .route("/respond/:status", get(respond_with))
+            .route("/fail", get(fail))
+            .route("/savepoints", get(use_savepoints))
+            .layer(middleware::from_fn_with_state(
+                db_pool.clone(),
+                super::middleware,
+            ))
+    }
+
+    #[tokio::test]
+    async fn test_middleware_without_tx() {
+        // the pool never connects as no transaction is begun
+        let db_pool = DbPool::connect_lazy("postgresql://localhost/transactions").unwrap();
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(db_pool, super::middleware));
+
+        assert_that!(send(app, "/").await, eq(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_tx_without_middleware() {
+        let app = Router::new().route("/", get(|_tx: Tx| async { "ok" }));
+
+        assert_that!(send(app, "/").await, eq(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[tokio::test]
+    async fn test_middleware_commits_successful_responses() {
+        let db_pool = setup().await;
+
+        assert_that!(send(app(&db_pool), "/respond/200").await, eq(StatusCode::OK));
+        assert_that!(
+            send(app(&db_pool), "/respond/303").await,
+            eq(StatusCode::SEE_OTHER)
+        );
+
+        assert_that!(values(&db_pool).await, elements_are![eq("200"), eq("303")]);
+
+        teardown_db_transaction(db_pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_middleware_rolls_back_error_responses() {
+        let db_pool = setup().await;
+
+        assert_that!(
+            send(app(&db_pool), "/respond/422").await,
+            eq(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+        assert_that!(
+            send(app(&db_pool), "/respond/503").await,
+            eq(StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert_that!(
+            send(app(&db_pool), "/fail").await,
+            eq(StatusCode::INTERNAL_SERVER_ERROR)
+        );
+
+        assert_that!(values(&db_pool).await, empty());
+
+        teardown_db_transaction(db_pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_begin_rolls_back_savepoint_only() {
+        let db_pool = setup().await;
+
+        assert_that!(send(app(&db_pool), "/savepoints").await, eq(StatusCode::OK));
+
+        assert_that!(
+            values(&db_pool).await,
+            elements_are![eq("outer"), eq("released")]
+        );
+
+        teardown_db_transaction(db_pool).await;
+    }
+}
@@ -0,0 +1,321 @@
+use crate::error::AppError;
+use crate::state::SharedAppState;
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header,
+};
+use {{crate_name}}_config::StorageConfig;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The content type of uploaded files that don't declare one
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// The maximum length of the extension kept in the keys of uploaded files
+const MAX_EXTENSION_LENGTH: usize = 10;
+
+/// The limits that uploaded files must stay within, from the `[storage]` section of the configuration (see [`StorageConfig`]).
+#[derive(Clone, Debug)]
+pub struct UploadLimits {
+    /// The maximum size of uploaded files in bytes
+    pub max_size: u64,
+    /// The content types uploaded files may have, e.g. `image/png` or `image/*`, any if empty
+    pub content_types: Arc<[String]>,
+}
+
+impl UploadLimits {
+    /// Returns the limits configured in the `[storage]` section of the configuration.
+    pub fn from_config(config: &StorageConfig) -> Self {
+        Self {
+            max_size: config.max_upload_size,
+            content_types: config.allowed_content_types.clone().into(),
+        }
+    }
+}
+
+/// An extractor for a file uploaded via a `multipart/form-data` request, e.g. a form with an `<input type="file">`.
+///
+/// The first field of the form that contains a file is read into memory while other fields are ignored. Requests are rejected before the request handler runs if the file is larger than the configured `max_upload_size` (413), if its content type is not one of the configured `allowed_content_types` (415), or if the request does not contain a file (400). Handlers can restrict the content types further with [`Upload::require_content_type`] and store the file in the application's storage (see [`crate::storage::Storage`]):
+///
+/// ```
+/// pub async fn update_avatar(
+///     State(app_state): State<SharedAppState>,
+///     upload: Upload,
+/// ) -> Result<StatusCode, AppError> {
+///     upload.require_content_type(&["image/png", "image/jpeg"])?;
+///     let key = upload.key("avatars");
+///     app_state.storage.put(&key, upload.data, &upload.content_type).await?;
+/// ```
+#[derive(Debug)]
+pub struct Upload {
+    /// The name of the file on the client, e.g. `avatar.png`
+    pub file_name: String,
+    /// The content type the client declared for the file, e.g. `image/png`
+    pub content_type: String,
+    /// The contents of the file
+    pub data: Bytes,
+}
+
+impl Upload {
+    /// Reads the first file from the `multipart/form-data` body of `req`, enforcing the `limits`.
+    pub async fn from_multipart(req: Request, limits: &UploadLimits) -> Result<Self, AppError> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .unwrap_or_default();
+        let boundary = multer::parse_boundary(content_type).map_err(|_| {
+            AppError::UnsupportedMediaType(String::from(
+                "The request must be a multipart/form-data request.",
+            ))
+        })?;
+        let mut multipart = multer::Multipart::new(req.into_body().into_data_stream(), boundary);
+
+        while let Some(mut field) = multipart.next_field().await.map_err(invalid_multipart)? {
+            let Some(file_name) = field.file_name().map(String::from) else {
+                continue;
+            };
+            let content_type = field
+                .content_type()
+                .map(|content_type| String::from(content_type.essence_str()))
+                .unwrap_or_else(|| String::from(DEFAULT_CONTENT_TYPE));
+            if !allows(&limits.content_types, &content_type) {
+                return Err(unsupported_content_type(&content_type));
+            }
+
+            let mut data = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(invalid_multipart)? {
+                if (data.len() + chunk.len()) as u64 > limits.max_size {
+                    return Err(AppError::PayloadTooLarge(format!(
+                        "The file must not be larger than {} bytes.",
+                        limits.max_size
+                    )));
+                }
+                data.extend_from_slice(&chunk);
+            }
+
+            return Ok(Upload {
+                file_name,
+                content_type,
+                data: Bytes::from(data),
+            });
+        }
+
+        Err(AppError::BadRequest(String::from(
+            "The request does not contain a file.",
+        )))
+    }
+
+    /// Rejects the file with an Unsupported Media Type error unless its content type is one of `content_types`, e.g. `image/png` or `image/*`.
+    pub fn require_content_type(&self, content_types: &[&str]) -> Result<(), AppError> {
+        if content_types
+            .iter()
+            .any(|allowed| matches_content_type(allowed, &self.content_type))
+        {
+            Ok(())
+        } else {
+            Err(unsupported_content_type(&self.content_type))
+        }
+    }
+
+    /// Returns a new, unique key to store the file under in the application's storage, e.g. `avatars/x8KwQ2mZp0LrT5aV.png` for the `prefix` `avatars`.
+    ///
+    /// The key keeps the file's extension but not its name since the name is chosen by the client.
+    pub fn key(&self, prefix: &str) -> String {
+        let id: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        let extension = Path::new(&self.file_name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .filter(|extension| {
+                extension.len() <= MAX_EXTENSION_LENGTH
+                    && extension.chars().all(|c| c.is_ascii_alphanumeric())
+            })
+            .map(|extension| format!(".{}", extension.to_lowercase()))
+            .unwrap_or_default();
+
+        match prefix.trim_matches('/') {
+            "" => format!("{}{}", id, extension),
+            prefix => format!("{}/{}{}", prefix, id, extension),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequest<SharedAppState> for Upload {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &SharedAppState) -> Result<Self, Self::Rejection> {
+        Upload::from_multipart(req, &state.upload_limits).await
+    }
+}
+
+/// Returns whether `content_type` is one of `allowed`, any content type if `allowed` is empty.
+fn allows(allowed: &[String], content_type: &str) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|allowed| matches_content_type(allowed, content_type))
+}
+
+/// Returns whether `content_type` matches `pattern`, e.g. `image/png` or `image/*` for all images.
+fn matches_content_type(pattern: &str, content_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(kind) => content_type
+            .split_once('/')
+            .is_some_and(|(content_kind, _)| content_kind.eq_ignore_ascii_case(kind)),
+        None => pattern.eq_ignore_ascii_case(content_type),
+    }
+}
+
+fn unsupported_content_type(content_type: &str) -> AppError {
+    AppError::UnsupportedMediaType(format!("Files of type {} are not allowed.", content_type))
+}
+
+fn invalid_multipart(e: multer::Error) -> AppError {
+    AppError::BadRequest(format!("Invalid multipart body: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use googletest::prelude::*;
+
+    const BOUNDARY: &str = "X-BOUNDARY";
+
+    fn limits(max_size: u64, content_types: &[&str]) -> UploadLimits {
+        UploadLimits {
+            max_size,
+            content_types: content_types.iter().map(|c| String::from(*c)).collect(),
+        }
+    }
+
+    fn text_field(name: &str, value: &str) -> String {
+        format!(
+            "Content-Disposition: form-data; name=\"{}\"\r\n\r\n{}",
+            name, value
+        )
+    }
+
+    fn file_field(name: &str, file_name: &str, content_type: &str, value: &str) -> String {
+        format!(
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n{}",
+            name, file_name, content_type, value
+        )
+    }
+
+    fn multipart_request(fields: &[String]) -> Request {
+        let mut body = String::new();
+        for field in fields {
+            body.push_str(&format!("--{}\r\n{}\r\n", BOUNDARY, field));
+        }
+        body.push_str(&format!("--{}--\r\n", BOUNDARY));
+
+        Request::post("/upload")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_from_multipart() {
+        let req = multipart_request(&[
+            text_field("description", "My avatar"),
+            file_field("avatar", "me.png", "image/png", "png data"),
+        ]);
+
+        let upload = Upload::from_multipart(req, &limits(1024, &["image/*"]))
+            .await
+            .unwrap();
+
+        assert_that!(upload.file_name, eq("me.png"));
+        assert_that!(upload.content_type, eq("image/png"));
+        assert_that!(upload.data, eq(&Bytes::from("png data")));
+    }
+
+    #[tokio::test]
+    async fn test_from_multipart_too_large() {
+        let req = multipart_request(&[file_field("avatar", "me.png", "image/png", "png data")]);
+
+        let result = Upload::from_multipart(req, &limits(4, &[])).await;
+
+        assert_that!(result, err(matches_pattern!(AppError::PayloadTooLarge(_))));
+    }
+
+    #[tokio::test]
+    async fn test_from_multipart_content_type_not_allowed() {
+        let req = multipart_request(&[file_field("avatar", "me.svg", "text/html", "<script>")]);
+
+        let result = Upload::from_multipart(req, &limits(1024, &["image/*"])).await;
+
+        assert_that!(
+            result,
+            err(matches_pattern!(AppError::UnsupportedMediaType(_)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_from_multipart_invalid() {
+        let req = multipart_request(&[text_field("description", "My avatar")]);
+        let result = Upload::from_multipart(req, &limits(1024, &[])).await;
+        assert_that!(result, err(matches_pattern!(AppError::BadRequest(_))));
+
+        let req = Request::post("/upload")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let result = Upload::from_multipart(req, &limits(1024, &[])).await;
+        assert_that!(
+            result,
+            err(matches_pattern!(AppError::UnsupportedMediaType(_)))
+        );
+    }
+
+    #[test]
+    fn test_require_content_type() {
+        let upload = Upload {
+            file_name: String::from("report.pdf"),
+            content_type: String::from("application/pdf"),
+            data: Bytes::new(),
+        };
+
+        assert_that!(
+            upload.require_content_type(&["application/pdf"]),
+            ok(anything())
+        );
+        assert_that!(
+            upload.require_content_type(&["image/*"]),
+            err(matches_pattern!(AppError::UnsupportedMediaType(_)))
+        );
+    }
+
+    #[test]
+    fn test_key() {
+        let upload = |file_name: &str| Upload {
+            file_name: String::from(file_name),
+            content_type: String::from(DEFAULT_CONTENT_TYPE),
+            data: Bytes::new(),
+        };
+
+        let key = upload("Me at the Beach.JPG").key("avatars/");
+        assert_that!(key, starts_with("avatars/"));
+        assert_that!(key, ends_with(".jpg"));
+        assert_that!(key.len(), eq("avatars/".len() + 16 + ".jpg".len()));
+        assert_that!(upload("archive").key("").len(), eq(16));
+        assert_that!(
+            upload("evil.p/../hp").key("files").len(),
+            eq("files/".len() + 16)
+        );
+        assert_that!(upload("a.b").key("x"), not(eq(&upload("a.b").key("x"))));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::error::AppError;
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// The key validator uses for the errors of collections that are validated as a whole, e.g. `Vec<TaskChangeset>`
+const COLLECTION_KEY: &str = "_tmp_validator";
+
+/// An extractor that deserializes a JSON request body and validates it.
+///
+/// The payload is deserialized like with [`axum::Json`] and then validated with the rules declared via `#[derive(Validate)]` (see [`validator::Validate`]). If the payload is invalid, the request is rejected with a 422 problem details response (see [`crate::error::AppError`]) listing the messages for each invalid field (see [`messages`]) before the request handler runs:
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Unprocessable Entity",
+///   "status": 422,
+///   "detail": "The request is invalid.",
+///   "errors": { "description": ["must not be empty"] }
+/// }
+/// ```
+///
+/// Example:
+/// ```
+/// pub async fn create(
+///     State(app_state): State<SharedAppState>,
+///     Validated(task): Validated<tasks::TaskChangeset>,
+/// ) -> Result<(StatusCode, Json<tasks::Task>), AppError> {
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Validated<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Validated<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(payload) = Json::<T>::from_request(req, state).await?;
+        payload.validate()?;
+
+        Ok(Validated(payload))
+    }
+}
+
+/// Returns the messages for all invalid fields, keyed by the fields' paths.
+///
+/// Fields of nested structs are keyed by their dotted paths (e.g. `address.street`) and items of lists by their index (e.g. `tags[0]`). Messages are the messages set on the validation rules (e.g. `#[validate(length(min = 1, message = "…"))]`) or generated from the rules otherwise.
+pub fn messages(errors: &ValidationErrors) -> BTreeMap<String, Vec<String>> {
+    let mut messages = BTreeMap::new();
+    collect_messages(errors, "", &mut messages);
+    messages
+}
+
+fn collect_messages(
+    errors: &ValidationErrors,
+    prefix: &str,
+    messages: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match (prefix, *field) {
+            (prefix, COLLECTION_KEY) => String::from(prefix),
+            ("", field) => String::from(field),
+            (prefix, field) => format!("{}.{}", prefix, field),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => messages
+                .entry(path)
+                .or_default()
+                .extend(errors.iter().map(message)),
+            ValidationErrorsKind::Struct(errors) => collect_messages(errors, &path, messages),
+            ValidationErrorsKind::List(errors) => {
+                for (index, errors) in errors {
+                    collect_messages(errors, &format!("{}[{}]", path, index), messages);
+                }
+            }
+        }
+    }
+}
+
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match error.code.as_ref() {
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("must be exactly {} characters long", equal),
+            (Some(min), None, None) if min == "1" => String::from("must not be empty"),
+            (Some(min), None, None) => format!("must be at least {} characters long", min),
+            (None, Some(max), None) => format!("must be at most {} characters long", max),
+            (Some(min), Some(max), None) => {
+                format!("must be between {} and {} characters long", min, max)
+            }
+            (None, None, None) => String::from("has an invalid length"),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+            (Some(min), None) => format!("must be at least {}", min),
+            (None, Some(max)) => format!("must be at most {}", max),
+            (None, None) => String::from("is out of range"),
+        },
+        "email" => String::from("must be a valid email address"),
+        "url" => String::from("must be a valid URL"),
+        "required" => String::from("is required"),
+        "taken" => String::from("has already been taken"),
+        code => format!("is invalid ({})", code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, response::IntoResponse, routing::post, Router};
+    use googletest::prelude::*;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    #[derive(Deserialize, Validate)]
+    struct Changeset {
+        #[validate(length(min = 1))]
+        name: String,
+        #[validate(range(min = 0, max = 150))]
+        age: u8,
+        #[validate(nested)]
+        tags: Vec<Tag>,
+    }
+
+    #[derive(Deserialize, Validate)]
+    struct Tag {
+        #[validate(length(max = 3, message = "is too long"))]
+        label: String,
+    }
+
+    async fn create(Validated(changeset): Validated<Changeset>) -> String {
+        changeset.name
+    }
+
+    async fn send(body: Value) -> (StatusCode, Value) {
+        let app = Router::new().route("/", post(create));
+        let request = axum::http::Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap().into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (
+            status,
+            serde_json::from_slice(&body)
+                .unwrap_or(Value::String(String::from_utf8_lossy(&body).into_owned())),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_valid_payload() {
+        let (status, body) = send(json!({ "name": "Ann", "age": 30, "tags": [] })).await;
+
+        assert_that!(status, eq(StatusCode::OK));
+        assert_that!(body, eq(&json!("Ann")));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_payload() {
+        let (status, body) = send(json!({
+            "name": "",
+            "age": 30,
+            "tags": [{ "label": "ok" }, { "label": "too long" }]
+        }))
+        .await;
+
+        assert_that!(status, eq(StatusCode::UNPROCESSABLE_ENTITY));
+        assert_that!(
+            body,
+            eq(&json!({
+                "type": "about:blank",
+                "title": "Unprocessable Entity",
+                "status": 422,
+                "detail": "The request is invalid.",
+                "errors": {
+                    "name": ["must not be empty"],
+                    "tags[1].label": ["is too long"]
+                }
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_payload() {
+        let (status, body) = send(json!({ "name": "Ann" })).await;
+
+        assert_that!(status, eq(StatusCode::UNPROCESSABLE_ENTITY));
+        assert_that!(body["status"], eq(&json!(422)));
+        assert_that!(body["detail"], not(eq(&Value::Null)));
+    }
+
+    #[test]
+    fn test_messages_for_collections() {
+        let changesets = vec![Tag {
+            label: String::from("too long"),
+        }];
+
+        let errors = changesets.validate().unwrap_err();
+
+        assert_that!(
+            messages(&errors),
+            eq(&BTreeMap::from([(
+                String::from("[0].label"),
+                vec![String::from("is too long")]
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_generated_messages() {
+        let mut range = ValidationError::new("range");
+        range.add_param("min".into(), &0);
+        range.add_param("max".into(), &150);
+        let mut length = ValidationError::new("length");
+        length.add_param("min".into(), &8);
+
+        assert_that!(message(&range), eq("must be between 0 and 150"));
+        assert_that!(message(&length), eq("must be at least 8 characters long"));
+        assert_that!(
+            message(&ValidationError::new("taken")),
+            eq("has already been taken")
+        );
+        assert_that!(
+            message(&ValidationError::new("custom")),
+            eq("is invalid (custom)")
+        );
+    }
+}
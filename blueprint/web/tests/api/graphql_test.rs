@@ -0,0 +1,30 @@
+use axum::{http::StatusCode, Router};
+use googletest::prelude::*;
+use {{crate_name}}_test::{db_test, DbTestContext, RouterExt};
+use {{crate_name}}_web::graphql::{graphiql, GRAPHQL_PATH};
+use serde_json::json;
+
+#[db_test]
+async fn test_query(context: &DbTestContext) {
+    let response = context
+        .client
+        .post_json(GRAPHQL_PATH, &json!({ "query": "{ version }" }))
+        .await;
+
+    assert_that!(response.status(), eq(StatusCode::OK));
+
+    let body: serde_json::Value = response.json();
+    assert_that!(body["errors"], eq(&serde_json::Value::Null));
+    assert_that!(
+        body["data"]["version"],
+        eq(&json!(env!("CARGO_PKG_VERSION")))
+    );
+}
+
+#[tokio::test]
+async fn test_graphiql() {
+    let app: Router = Router::new().merge(graphiql());
+
+    let response = app.request(GRAPHQL_PATH).send().await;
+    assert_that!(response.status(), eq(StatusCode::OK));
+}
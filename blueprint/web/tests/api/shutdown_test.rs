@@ -0,0 +1,86 @@
+use axum::{routing::get, Router};
+use googletest::prelude::*;
+use {{crate_name}}_web::serve_with_shutdown;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+struct TestServer {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    server: JoinHandle<anyhow::Result<()>>,
+}
+
+async fn start_server(request_duration: Duration, timeout: Duration) -> TestServer {
+    let app = Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(request_duration).await;
+            "done"
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(
+        listener,
+        app,
+        async {
+            let _ = shutdown_signal.await;
+        },
+        timeout,
+    ));
+
+    TestServer {
+        addr,
+        shutdown,
+        server,
+    }
+}
+
+async fn get_slow(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_shutdown_finishes_requests_in_flight() {
+    let server = start_server(Duration::from_millis(500), Duration::from_secs(5)).await;
+    let request = tokio::spawn(get_slow(server.addr));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    server.shutdown.send(()).unwrap();
+
+    let response = request.await.unwrap();
+    assert_that!(response, starts_with("HTTP/1.1 200 OK"));
+    assert_that!(response, ends_with("done"));
+    let result = tokio::time::timeout(Duration::from_secs(1), server.server)
+        .await
+        .expect("The server did not shut down");
+    assert_that!(result.unwrap(), ok(anything()));
+    assert_that!(TcpStream::connect(server.addr).await, err(anything()));
+}
+
+#[tokio::test]
+async fn test_shutdown_timeout() {
+    let server = start_server(Duration::from_secs(60), Duration::from_millis(200)).await;
+    let request = tokio::spawn(get_slow(server.addr));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    server.shutdown.send(()).unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), server.server)
+        .await
+        .expect("The server did not shut down");
+    assert_that!(result.unwrap(), ok(anything()));
+    request.abort();
+}
@@ -0,0 +1,7 @@
+use crate::Registry;
+
+/// Registers all jobs with the worker.
+///
+/// The worker can only perform jobs that are registered here – jobs generated with `cargo generate job` are registered automatically.
+#[allow(unused_variables)]
+pub fn register(registry: &mut Registry) {}
@@ -0,0 +1,3 @@
+#![allow(missing_docs)]
+mod scheduler_test;
+mod worker_test;
@@ -3,14 +3,18 @@
 use anyhow::Context;
 use cargo_generate::Vcs;
 use cargo_generate::{GenerateArgs, TemplatePath};
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, Subcommand};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+#[doc(hidden)]
+mod manifest;
 #[allow(dead_code)]
 #[doc(hidden)]
 mod ui;
+#[doc(hidden)]
+mod upgrade;
 
 #[doc(hidden)]
 static VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("VERGEN_GIT_SHA"), ")");
@@ -20,7 +24,7 @@ static BLUEPRINTS_DIR: include_dir::Dir =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprint");
 
 #[doc(hidden)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Blueprint {
     Minimal,
     Default,
@@ -37,16 +41,38 @@ impl std::fmt::Display for Blueprint {
     }
 }
 
+impl std::str::FromStr for Blueprint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(Blueprint::Minimal),
+            "default" => Ok(Blueprint::Default),
+            "full" => Ok(Blueprint::Full),
+            _ => Err(anyhow::anyhow!("Unknown blueprint {}", s)),
+        }
+    }
+}
+
 #[doc(hidden)]
 #[derive(Parser)]
 #[command(author, version, about = "A backend project generator and manager.", long_about = None)]
-#[command(propagate_version = true, arg_required_else_help(true))]
+#[command(
+    propagate_version = true,
+    arg_required_else_help(true),
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     #[arg(
         index = 1,
+        required = true,
         help = "The name of the project (Cargo workspace) to generate (will be used as a prefix for the individual crates)"
     )]
-    name: String,
+    name: Option<String>,
     #[arg(
         short,
         long,
@@ -58,16 +84,30 @@ struct Cli {
         short,
         long,
         action(ArgAction::SetTrue),
-        help = "Generate a full project with example implementations of all project elements"
+        help = "Generate a full project with example implementations of all project elements, background jobs, and a mailer"
     )]
     full: bool,
     #[arg(
         short,
         long,
         action(ArgAction::SetTrue),
-        help = "Generate a minimal project without database access"
+        help = "Generate a minimal project without database access and background jobs"
     )]
     minimal: bool,
+    #[arg(
+        long,
+        action(ArgAction::SetTrue),
+        conflicts_with = "minimal",
+        help = "Add a GraphQL API (via async-graphql) to the project"
+    )]
+    graphql: bool,
+    #[arg(
+        long,
+        action(ArgAction::SetTrue),
+        conflicts_with = "minimal",
+        help = "Add a gRPC server (via tonic) to the project"
+    )]
+    grpc: bool,
 
     #[arg(long, global = true, help = "Disable VCS initialization")]
     no_vcs: bool,
@@ -79,6 +119,23 @@ struct Cli {
     debug: bool,
 }
 
+#[doc(hidden)]
+#[derive(Subcommand)]
+enum Commands {
+    #[command(
+        about = "Upgrade a project to the blueprint of this version, applying the changes that don't conflict with changes made in the project and writing .rej files for the others"
+    )]
+    Upgrade {
+        #[arg(
+            index = 1,
+            value_parser,
+            default_value = ".",
+            help = "The folder of the project to upgrade"
+        )]
+        dir: PathBuf,
+    },
+}
+
 #[doc(hidden)]
 #[tokio::main]
 async fn main() {
@@ -88,6 +145,14 @@ async fn main() {
     let mut stderr = std::io::stderr();
     let mut ui = ui::UI::new(&mut stdout, &mut stderr, !cli.no_color, cli.debug);
 
+    if let Some(Commands::Upgrade { dir }) = cli.command {
+        if let Err(e) = upgrade::upgrade(&mut ui, &dir).await {
+            ui.error("Could not upgrade project!", e);
+        }
+        return;
+    }
+    let name = cli.name.unwrap_or_default();
+
     let blueprint = if cli.full {
         Blueprint::Full
     } else if cli.minimal {
@@ -96,17 +161,22 @@ async fn main() {
         Blueprint::Default
     };
 
-    ui.info(&format!("Generating {}…", cli.name));
+    ui.info(&format!("Generating {}…", name));
     ui.indent();
 
-    match generate(&cli.name, cli.outdir, !cli.no_vcs, blueprint).await {
+    match generate(
+        &name,
+        cli.outdir,
+        !cli.no_vcs,
+        blueprint,
+        cli.graphql,
+        cli.grpc,
+    )
+    .await
+    {
         Ok(output_dir) => {
             ui.outdent();
-            ui.success(&format!(
-                "Generated {} at {}.",
-                cli.name,
-                output_dir.display()
-            ));
+            ui.success(&format!("Generated {} at {}.", name, output_dir.display()));
         }
         Err(e) => {
             ui.outdent();
@@ -121,6 +191,8 @@ async fn generate(
     output_dir: Option<PathBuf>,
     init_git_vcs: bool,
     blueprint: Blueprint,
+    graphql: bool,
+    grpc: bool,
 ) -> Result<PathBuf, anyhow::Error> {
     let output_dir = if let Some(output_dir) = output_dir {
         output_dir
@@ -130,6 +202,8 @@ async fn generate(
 
     let mut defines: Vec<String> = vec![];
     defines.push(format!("template_type={blueprint}"));
+    defines.push(format!("graphql={graphql}"));
+    defines.push(format!("grpc={grpc}"));
 
     let template_path = build_template_path().await?;
 
@@ -152,6 +226,10 @@ async fn generate(
         .current_dir(&output_dir)
         .status();
 
+    // Record how the project was generated so it can be upgraded to newer blueprints later.
+    manifest::Manifest::for_project(&output_dir, VERSION, name, blueprint, graphql, grpc)?
+        .write(&output_dir)?;
+
     Ok(output_dir)
 }
 
@@ -0,0 +1,209 @@
+use crate::Blueprint;
+use anyhow::{anyhow, Context};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The name of the manifest file in the project's root directory
+pub const MANIFEST_FILE: &str = ".gerust.toml";
+
+/// The directories that are never part of the generated files
+const IGNORED_DIRS: &[&str] = &[".git", "target"];
+
+/// The manifest records how a project was generated – the Gerust version, the name and options, and a checksum of every generated file – so that `gerust upgrade` can tell which files were changed since.
+#[derive(Debug, PartialEq)]
+pub struct Manifest {
+    pub version: String,
+    pub name: String,
+    pub blueprint: Blueprint,
+    pub graphql: bool,
+    pub grpc: bool,
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Creates a manifest for the project generated in `dir`, recording the checksums of all files in it.
+    pub fn for_project(
+        dir: &Path,
+        version: &str,
+        name: &str,
+        blueprint: Blueprint,
+        graphql: bool,
+        grpc: bool,
+    ) -> Result<Manifest, anyhow::Error> {
+        Ok(Manifest {
+            version: String::from(version),
+            name: String::from(name),
+            blueprint,
+            graphql,
+            grpc,
+            files: checksums(dir)?,
+        })
+    }
+
+    /// Reads the manifest from the project in `dir`.
+    pub fn read(dir: &Path) -> Result<Manifest, anyhow::Error> {
+        let path = dir.join(MANIFEST_FILE);
+        let contents = fs::read_to_string(&path).context(format!(
+            "Failed to read {} – projects generated before Gerust recorded a manifest cannot be upgraded automatically",
+            path.display()
+        ))?;
+        Manifest::parse(&contents).context(format!("Failed to parse {}", path.display()))
+    }
+
+    /// Writes the manifest to the project in `dir`.
+    pub fn write(&self, dir: &Path) -> Result<(), anyhow::Error> {
+        let path = dir.join(MANIFEST_FILE);
+        fs::write(&path, self.to_string()).context(format!("Failed to write {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Manifest, anyhow::Error> {
+        let mut settings: BTreeMap<String, String> = BTreeMap::new();
+        let mut files: BTreeMap<String, String> = BTreeMap::new();
+        let mut in_files = false;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[files]" {
+                in_files = true;
+                continue;
+            }
+            let (key, value) = line
+                .split_once(" = ")
+                .ok_or(anyhow!("Invalid line: {}", line))?;
+            let key = unquote(key.trim());
+            let value = unquote(value.trim());
+            if in_files {
+                files.insert(key, value);
+            } else {
+                settings.insert(key, value);
+            }
+        }
+
+        let setting = |key: &str| {
+            settings
+                .get(key)
+                .cloned()
+                .ok_or(anyhow!("Missing setting {}", key))
+        };
+        Ok(Manifest {
+            version: setting("version")?,
+            name: setting("name")?,
+            blueprint: setting("template_type")?.parse()?,
+            graphql: setting("graphql")? == "true",
+            grpc: setting("grpc")? == "true",
+            files,
+        })
+    }
+}
+
+impl std::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "# Records how the project was generated so that `gerust upgrade` can update it to newer blueprints – do not edit."
+        )?;
+        writeln!(f, "version = \"{}\"", self.version)?;
+        writeln!(f, "name = \"{}\"", self.name)?;
+        writeln!(f, "template_type = \"{}\"", self.blueprint)?;
+        writeln!(f, "graphql = {}", self.graphql)?;
+        writeln!(f, "grpc = {}", self.grpc)?;
+        writeln!(f)?;
+        writeln!(f, "[files]")?;
+        for (path, checksum) in &self.files {
+            writeln!(f, "\"{}\" = \"{}\"", path, checksum)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the checksums of all files in `dir` (except for the manifest itself and build artifacts), keyed by their paths relative to `dir` with `/` as the separator.
+pub fn checksums(dir: &Path) -> Result<BTreeMap<String, String>, anyhow::Error> {
+    let mut files = BTreeMap::new();
+    collect_checksums(dir, "", &mut files)?;
+    Ok(files)
+}
+
+fn collect_checksums(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, String>,
+) -> Result<(), anyhow::Error> {
+    for entry in fs::read_dir(dir).context(format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}{}", prefix, name);
+        if entry.file_type()?.is_dir() {
+            if !IGNORED_DIRS.contains(&name.as_str()) {
+                collect_checksums(&entry.path(), &format!("{}/", path), files)?;
+            }
+        } else if path != MANIFEST_FILE {
+            let contents = fs::read(entry.path())
+                .context(format!("Failed to read {}", entry.path().display()))?;
+            files.insert(path, checksum(&contents));
+        }
+    }
+    Ok(())
+}
+
+/// Computes a checksum of a file's contents (64-bit FNV-1a which, unlike the standard library's hasher, is stable across Rust versions).
+pub fn checksum(contents: &[u8]) -> String {
+    let hash = contents.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn unquote(value: &str) -> String {
+    String::from(
+        value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, Manifest};
+    use crate::Blueprint;
+    use insta::assert_snapshot;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(b""), "cbf29ce484222325");
+        assert_eq!(checksum(b"a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let manifest = Manifest {
+            version: String::from("0.0.3 (abcdef)"),
+            name: String::from("my-app"),
+            blueprint: Blueprint::Full,
+            graphql: false,
+            grpc: true,
+            files: BTreeMap::from([
+                (String::from("Cargo.toml"), checksum(b"[workspace]")),
+                (String::from("web/src/main.rs"), checksum(b"fn main() {}")),
+            ]),
+        };
+
+        let serialized = manifest.to_string();
+        assert_snapshot!(serialized, @r###"
+        # Records how the project was generated so that `gerust upgrade` can update it to newer blueprints – do not edit.
+        version = "0.0.3 (abcdef)"
+        name = "my-app"
+        template_type = "full"
+        graphql = false
+        grpc = true
+
+        [files]
+        "Cargo.toml" = "c8de3463ec922242"
+        "web/src/main.rs" = "aa244faa9019a10f"
+        "###);
+        assert_eq!(Manifest::parse(&serialized).unwrap(), manifest);
+    }
+}
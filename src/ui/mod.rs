@@ -4,11 +4,14 @@ use std::io::Write;
 pub struct UI<'a> {
     stdout: &'a mut dyn Write,
     errout: &'a mut dyn Write,
+    color: bool,
     debug: bool,
+    json: bool,
     indentation: usize,
     log_prefix: String,
     info_prefix: String,
     success_prefix: String,
+    warning_prefix: String,
     error_prefix: String,
 }
 
@@ -39,6 +42,11 @@ impl<'a> UI<'a> {
         } else {
             String::from("")
         };
+        let warning_prefix = if color {
+            String::from("⚠️  ")
+        } else {
+            String::from("")
+        };
         let error_prefix = if color {
             String::from("❌ ")
         } else {
@@ -53,29 +61,73 @@ impl<'a> UI<'a> {
         UI {
             stdout,
             errout,
+            color,
             debug,
+            json: false,
             indentation: 0,
             log_prefix,
             info_prefix,
             success_prefix,
+            warning_prefix,
             error_prefix,
         }
     }
 
+    /// Switches the session to JSON output.
+    ///
+    /// Every message is then printed as a JSON object on a line of its own, e.g. `{"level":"info","message":"Migrating…"}`, so that the output can be parsed by scripts – colors and indentation are not applied. Errors include the error's causes in an `error` field. Structured data is only printed in this mode (see [`UI::event`]).
+    ///
+    /// Example:
+    /// ```
+    /// let mut stdout = std::io::stdout();
+    /// let mut stderr = std::io::stderr();
+    /// let mut ui = UI::new(&mut stdout, &mut stderr, false, true).json();
+    /// ```
+    pub fn json(mut self) -> UI<'a> {
+        self.json = true;
+        self
+    }
+
+    /// Returns whether color output is enabled (see [`UI::new`]).
+    pub fn color(&self) -> bool {
+        self.color && !self.json
+    }
+
     fn indentation(&mut self) -> String {
         "  ".repeat(self.indentation)
     }
 
     /// Prints a general message.
     pub fn log(&mut self, msg: &str) {
+        if self.json {
+            self.out(&json_message("log", msg, None));
+            return;
+        }
         let indentation = self.indentation();
         self.out(&format!("{}{}{}", indentation, self.log_prefix, msg));
     }
 
+    /// Prints a debug message.
+    ///
+    /// The message is only printed if debug output is enabled (see [`UI::new`]).
+    pub fn debug(&mut self, msg: &str) {
+        if self.debug {
+            if self.json {
+                self.out(&json_message("debug", msg, None));
+            } else {
+                self.log(msg);
+            }
+        }
+    }
+
     /// Prints an info message.
     ///
     /// If color output is enabled (see [`UI::new`]), the message will be formatted.
     pub fn info(&mut self, msg: &str) {
+        if self.json {
+            self.out(&json_message("info", msg, None));
+            return;
+        }
         let indentation = self.indentation();
         self.out(&format!("{}{}{}", indentation, self.info_prefix, msg));
     }
@@ -84,14 +136,34 @@ impl<'a> UI<'a> {
     ///
     /// If color output is enabled (see [`UI::new`]), the message will be formatted.
     pub fn success(&mut self, msg: &str) {
+        if self.json {
+            self.out(&json_message("success", msg, None));
+            return;
+        }
         let indentation = self.indentation();
         self.out(&format!("{}{}{}", indentation, self.success_prefix, msg));
     }
 
+    /// Prints a warning message.
+    ///
+    /// Warnings are printed to the error output. If color output is enabled (see [`UI::new`]), the message will be formatted.
+    pub fn warning(&mut self, msg: &str) {
+        if self.json {
+            self.errout(&json_message("warning", msg, None));
+            return;
+        }
+        let indentation = self.indentation();
+        self.errout(&format!("{}{}{}", indentation, self.warning_prefix, msg));
+    }
+
     /// Prints an error message.
     ///
     /// If color output is enabled (see [`UI::new`]), the message will be formatted. If debug output is enabled (see [`UI::new`]), the error's stack trace will be printed as well.
     pub fn error(&mut self, msg: &str, e: anyhow::Error) {
+        if self.json {
+            self.errout(&json_message("error", msg, Some(&format!("{:#}", e))));
+            return;
+        }
         let indentation = self.indentation();
         self.errout(&format!("{}{}{}", indentation, self.error_prefix, msg));
         if self.debug {
@@ -99,6 +171,30 @@ impl<'a> UI<'a> {
         }
     }
 
+    /// Prints structured data about the result of an operation, e.g. the list of applied migrations.
+    ///
+    /// The data is only printed with JSON output (see [`UI::json`]) as `{"event":"<event>","data":<data>}` – `data` must be valid JSON. Otherwise, this does nothing as results are reported via messages.
+    ///
+    /// Example:
+    /// ```
+    /// let mut stdout = std::io::stdout();
+    /// let mut stderr = std::io::stderr();
+    /// let mut ui = UI::new(&mut stdout, &mut stderr, false, true).json();
+    ///
+    /// ui.event("database_created", r#"{"database":"my_app"}"#);
+    ///
+    /// // prints:
+    /// // > {"event":"database_created","data":{"database":"my_app"}}
+    /// ```
+    pub fn event(&mut self, event: &str, data: &str) {
+        if self.json {
+            self.out(&json_object(&[
+                ("event", &json_string(event)),
+                ("data", data),
+            ]));
+        }
+    }
+
     /// Increases indentation of subsequently printed messages by 2 spaces.
     ///
     /// Example:
@@ -155,6 +251,44 @@ impl<'a> UI<'a> {
     }
 }
 
+/// Formats a message as a JSON object for JSON output (see [`UI::json`]).
+fn json_message(level: &str, msg: &str, error: Option<&str>) -> String {
+    let level = json_string(level);
+    let msg = json_string(msg);
+    match error.map(json_string) {
+        Some(error) => json_object(&[("level", &level), ("message", &msg), ("error", &error)]),
+        None => json_object(&[("level", &level), ("message", &msg)]),
+    }
+}
+
+/// Formats a JSON object from keys and JSON values.
+fn json_object(fields: &[(&str, &str)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+    ["{", &fields.join(","), "}"].concat()
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 #[cfg(test)]
 mod tests {
     use super::UI;
@@ -169,6 +303,7 @@ mod tests {
         ui.log("a general message");
         ui.info("an info message");
         ui.success("a success message ✓");
+        ui.warning("a warning message");
         ui.error("an error message :(", anyhow!("oh no…"));
 
         let output = read_buffer(stdout);
@@ -180,6 +315,7 @@ mod tests {
         a success message ✓
         "###);
         assert_snapshot!(error_output, @r###"
+        a warning message
         an error message :(
         "###);
     }
@@ -192,6 +328,7 @@ mod tests {
         ui.log("a general message");
         ui.info("an info message");
         ui.success("a success message ✓");
+        ui.warning("a warning message");
         ui.error("an error message :(", anyhow!("oh no…"));
 
         let output = read_buffer(stdout);
@@ -203,10 +340,39 @@ mod tests {
         ✅ a success message ✓
         "###);
         assert_snapshot!(error_output, @r###"
+        ⚠️  a warning message
         ❌ an error message :(
         "###);
     }
 
+    #[test]
+    fn test_debug() {
+        let mut stdout = create_buffer();
+        let mut stderr = create_buffer();
+        let mut ui = UI::new(&mut stdout, &mut stderr, false, false);
+        ui.debug("a debug message");
+        ui.log("a general message");
+
+        let output = read_buffer(stdout);
+
+        assert_snapshot!(output, @r###"
+        a general message
+        "###);
+
+        let mut stdout = create_buffer();
+        let mut stderr = create_buffer();
+        let mut ui = UI::new(&mut stdout, &mut stderr, false, true);
+        ui.debug("a debug message");
+        ui.log("a general message");
+
+        let output = read_buffer(stdout);
+
+        assert_snapshot!(output, @r###"
+        a debug message
+        a general message
+        "###);
+    }
+
     #[test]
     fn test_indentation() {
         let mut stdout = create_buffer();
@@ -238,6 +404,50 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_json() {
+        let mut stdout = create_buffer();
+        let mut stderr = create_buffer();
+        let mut ui = UI::new(&mut stdout, &mut stderr, true, false).json();
+        ui.log("a general message");
+        ui.indent();
+        ui.info("an \"indented\" info message");
+        ui.debug("a debug message");
+        ui.success("a success message ✓");
+        ui.event("created", r#"{"database":"my_app"}"#);
+        ui.warning("a warning message\nwith two lines");
+        ui.error(
+            "an error message :(",
+            anyhow!("oh no…").context("it failed"),
+        );
+
+        let output = read_buffer(stdout);
+        let error_output = read_buffer(stderr);
+
+        assert_snapshot!(output, @r###"
+        {"level":"log","message":"a general message"}
+        {"level":"info","message":"an \"indented\" info message"}
+        {"level":"success","message":"a success message ✓"}
+        {"event":"created","data":{"database":"my_app"}}
+        "###);
+        assert_snapshot!(error_output, @r###"
+        {"level":"warning","message":"a warning message\nwith two lines"}
+        {"level":"error","message":"an error message :(","error":"it failed: oh no…"}
+        "###);
+    }
+
+    #[test]
+    fn test_no_json_events() {
+        let mut stdout = create_buffer();
+        let mut stderr = create_buffer();
+        let mut ui = UI::new(&mut stdout, &mut stderr, false, false);
+        ui.event("created", r#"{"database":"my_app"}"#);
+
+        let output = read_buffer(stdout);
+
+        assert_snapshot!(output, @"");
+    }
+
     fn create_buffer() -> std::io::BufWriter<Vec<u8>> {
         std::io::BufWriter::new(Vec::new())
     }
@@ -0,0 +1,181 @@
+use crate::manifest::{checksum, Manifest};
+use crate::ui::UI;
+use crate::{generate, VERSION};
+use anyhow::Context;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// What happens to a file of the project when upgrading it
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// The file is the same in the project and the new blueprint.
+    None,
+    /// The file is new in the blueprint and is added to the project.
+    Add,
+    /// The file was changed in the blueprint but not in the project and is updated.
+    Update,
+    /// The file was changed in the project but not in the blueprint and is kept.
+    Keep,
+    /// The file was deleted from the project and is not brought back.
+    Skip,
+    /// The file was changed in both the project and the blueprint – the blueprint's version is written to a `.rej` file next to it.
+    Conflict,
+}
+
+/// Determines what happens to a file when upgrading the project based on its checksum when it was generated (if it was), its checksum in the project (if it exists), and its checksum in the new blueprint.
+fn change(generated: Option<&str>, current: Option<&str>, new: &str) -> Change {
+    match (generated, current) {
+        (_, Some(current)) if current == new => Change::None,
+        (None, None) => Change::Add,
+        (Some(_), None) => Change::Skip,
+        (Some(generated), Some(current)) if generated == current => Change::Update,
+        (Some(generated), Some(_)) if generated == new => Change::Keep,
+        _ => Change::Conflict,
+    }
+}
+
+/// Upgrades the project in `project_dir` to the blueprint of this version of Gerust.
+///
+/// The project is generated anew with the name and options recorded in its manifest and every file is compared with the project's version and the one that was originally generated (via the checksums in the manifest):
+/// * files the project did not change are updated
+/// * new files are added
+/// * files that were removed from the blueprint are deleted unless the project changed them
+/// * files that were changed in both the project and the blueprint are left unchanged and the blueprint's version is written to `<file>.rej` for merging the changes manually
+///
+/// Finally, the manifest is updated so that the next upgrade starts from this version.
+pub async fn upgrade(ui: &mut UI<'_>, project_dir: &Path) -> Result<(), anyhow::Error> {
+    let manifest = Manifest::read(project_dir)?;
+    if manifest.version == VERSION {
+        ui.info(&format!(
+            "{} was generated with Gerust {} already.",
+            manifest.name, VERSION
+        ));
+        return Ok(());
+    }
+
+    ui.info(&format!(
+        "Upgrading {} from Gerust {} to {}…",
+        manifest.name, manifest.version, VERSION
+    ));
+    let temp_dir = env::temp_dir().join(format!("gerust-upgrade-{}", std::process::id()));
+    fs::create_dir_all(&temp_dir)
+        .context("Failed to create a temporary directory for the upgraded project")?;
+    let result = apply(ui, project_dir, &temp_dir, &manifest).await;
+    let _ = fs::remove_dir_all(&temp_dir);
+    result
+}
+
+async fn apply(
+    ui: &mut UI<'_>,
+    project_dir: &Path,
+    temp_dir: &Path,
+    manifest: &Manifest,
+) -> Result<(), anyhow::Error> {
+    let blueprint_dir = generate(
+        &manifest.name,
+        Some(temp_dir.to_path_buf()),
+        false,
+        manifest.blueprint,
+        manifest.graphql,
+        manifest.grpc,
+    )
+    .await?;
+    let new_manifest = Manifest::read(&blueprint_dir)?;
+
+    let mut conflicts = 0;
+    ui.indent();
+    for (path, new_checksum) in &new_manifest.files {
+        let target = project_dir.join(path);
+        let current = if target.exists() {
+            let contents =
+                fs::read(&target).context(format!("Failed to read {}", target.display()))?;
+            Some(checksum(&contents))
+        } else {
+            None
+        };
+        let generated = manifest.files.get(path).map(String::as_str);
+
+        match change(generated, current.as_deref(), new_checksum) {
+            Change::None | Change::Keep => {}
+            Change::Add => {
+                copy(&blueprint_dir.join(path), &target)?;
+                ui.log(&format!("added {}", path));
+            }
+            Change::Update => {
+                copy(&blueprint_dir.join(path), &target)?;
+                ui.log(&format!("updated {}", path));
+            }
+            Change::Skip => {
+                ui.debug(&format!("skipped {} as it was deleted", path));
+            }
+            Change::Conflict => {
+                copy(
+                    &blueprint_dir.join(path),
+                    &project_dir.join(format!("{}.rej", path)),
+                )?;
+                ui.warning(&format!(
+                    "conflict in {} – merge the changes from {}.rej",
+                    path, path
+                ));
+                conflicts += 1;
+            }
+        }
+    }
+
+    for (path, generated) in &manifest.files {
+        if new_manifest.files.contains_key(path) {
+            continue;
+        }
+        let target = project_dir.join(path);
+        if let Ok(contents) = fs::read(&target) {
+            if &checksum(&contents) == generated {
+                fs::remove_file(&target).context(format!("Failed to remove {}", path))?;
+                ui.log(&format!("removed {}", path));
+            } else {
+                ui.warning(&format!(
+                    "kept {} which is no longer part of the blueprint as it was changed",
+                    path
+                ));
+            }
+        }
+    }
+    ui.outdent();
+
+    new_manifest.write(project_dir)?;
+    if conflicts > 0 {
+        ui.warning(&format!(
+            "Upgraded {} with {} conflict(s) – merge the .rej files and delete them afterwards.",
+            manifest.name, conflicts
+        ));
+    } else {
+        ui.success(&format!("Upgraded {}.", manifest.name));
+    }
+
+    Ok(())
+}
+
+fn copy(source: &Path, destination: &Path) -> Result<(), anyhow::Error> {
+    if let Some(dir) = destination.parent() {
+        fs::create_dir_all(dir).context(format!("Failed to create {}", dir.display()))?;
+    }
+    fs::copy(source, destination).context(format!("Failed to write {}", destination.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{change, Change};
+
+    #[test]
+    fn test_change() {
+        assert_eq!(change(Some("a"), Some("b"), "b"), Change::None);
+        assert_eq!(change(None, Some("b"), "b"), Change::None);
+        assert_eq!(change(None, None, "b"), Change::Add);
+        assert_eq!(change(Some("a"), None, "b"), Change::Skip);
+        assert_eq!(change(Some("a"), Some("a"), "b"), Change::Update);
+        assert_eq!(change(Some("a"), Some("b"), "a"), Change::Keep);
+        assert_eq!(change(Some("a"), Some("b"), "c"), Change::Conflict);
+        assert_eq!(change(None, Some("b"), "c"), Change::Conflict);
+    }
+}